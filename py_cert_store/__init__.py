@@ -13,7 +13,12 @@
 # limitations under the License.
 
 from __future__ import annotations
-from typing import Union, Dict, List
+import asyncio
+import functools
+import threading
+import traceback
+from concurrent.futures import Executor, ThreadPoolExecutor
+from typing import Any, Callable, Union, Dict, List, Optional, TypeVar
 
 from .__about__ import __copyright__, __version__, __author__
 from .py_cert_store import *
@@ -27,7 +32,10 @@ __all__ = {
 def get_win_cert(
         store:str="My", user:str="CurrentUser",
         return_as_dict:bool=False, return_all:bool=False,
-        verbose:bool=True
+        verbose:bool=True, order:Optional[List[str]]=None,
+        extended_key_usage:Optional[List[str]]=None, preferred_issuers:Optional[List[str]]=None,
+        cancel:Optional[CancellationToken]=None, cache:Optional[CertificateCache]=None,
+        purpose:Optional[str]=None
     ) -> Union[bytes, Dict[str, Union[str, bytes]], List[bytes], List[Dict[str, Union[str, bytes]]]]:
     # :param user: The user to get the certificate from. Default is "CurrentUser".
     """
@@ -37,15 +45,28 @@ def get_win_cert(
     :param return_as_dict: If True, returns the certificate(s) as a dictionary, and False returns the certificate bytes only.
     :param return_all: If True, returns all vlid certificates. Default is False.
     :param verbose: If True, prints information about the certificate discovered to the console.
+    :param order: The ranking criteria used to pick the best certificate, see ``rank_certificates``.
+    :param extended_key_usage: The purposes the certificate is preferred for, e.g. ["client_auth"].
+    :param preferred_issuers: Issuer names to prefer, the first one most.
+    :param cancel: A ``CancellationToken`` that stops the search from another thread.
+    :param cache: A ``CertificateCache`` reused across calls, so the store is only searched again once it changes.
+    :param purpose: Only use certificates allowed for this purpose, e.g. "client_auth", "code_signing", "smime",
+        "smart_card_logon", "document_signing" or a dotted OID. Both the Extended Key Usage of the certificate and the
+        purposes set in its store properties must allow it. In either case only certificates whose Key Usage allows
+        digital signatures are used.
 
     :return List, Dict, Bytes: The found certificate.
         - if return_all is True, a list will be returned based on return_as_dict's value.
         - If return_as_dict is True, returns a dictionary with additional metadata about the certificate.
         - If return_as_dict is False, returns the certificate bytes only.
+        - Certificates are ranked best first, by default preferring hardware-backed keys and then the longest remaining validity.
     """
     from cryptography import x509 # There is no reason to import this in the overall module, since this is the only time the dependency is used.
 
-    certificate_list = find_windows_cert_by_extension(store=store, user=user, extension_oid=x509.OID_KEY_USAGE.dotted_string, extension_value="Digital Signature")
+    certificate_list = find_windows_cert_by_extension(store=store, user=user, extension_oid=x509.OID_KEY_USAGE.dotted_string, extension_value="Digital Signature", cancel=cancel, cache=cache, purpose=purpose)
+
+    ranking = rank_certificates(certificate_list, order=order, extended_key_usage=extended_key_usage, preferred_issuers=preferred_issuers)
+    certificate_list = [certificate_list[rank["Index"]] for rank in ranking]
 
     if return_all:
         if return_as_dict:
@@ -60,3 +81,86 @@ def get_win_cert(
     if return_as_dict:
         return certificate_list[0]
     return certificate_list[0]['cert']
+
+
+_T = TypeVar("_T")
+_executor:Optional[Executor] = None
+_executor_lock = threading.Lock()
+
+def set_async_executor(executor:Optional[Executor]) -> None:
+    """
+    Sets the executor the ``*_async`` functions run the searches on.
+
+    :param executor: The executor to use, or None to go back to the module's own thread pool.
+    """
+    global _executor
+    with _executor_lock:
+        _executor = executor
+
+def _get_executor() -> Executor:
+    global _executor
+    with _executor_lock:
+        if _executor is None:
+            _executor = ThreadPoolExecutor(max_workers=4, thread_name_prefix="py_cert_store")
+        return _executor
+
+async def _run_async(function:Callable[..., _T], *args:Any, **kwargs:Any) -> _T:
+    """
+    Runs a search on the executor, cancelling the search itself when the awaiting task is cancelled.
+
+    The searches release the GIL, so the event loop keeps running while the keys are exported.
+    """
+    cancel = kwargs.pop("cancel", None) or CancellationToken()
+    loop = asyncio.get_running_loop()
+    future = loop.run_in_executor(_get_executor(), functools.partial(function, *args, cancel=cancel, **kwargs))
+    try:
+        return await future
+    except asyncio.CancelledError:
+        # The worker thread cannot be interrupted, so stop the search before it exports another key.
+        cancel.cancel()
+        raise
+
+async def find_windows_cert_by_extension_async(*args:Any, **kwargs:Any) -> List[Dict[str, Union[str, bytes, List[str]]]]:
+    """
+    Awaitable ``find_windows_cert_by_extension``, run on a background thread. Cancelling the task stops the search.
+    """
+    return await _run_async(find_windows_cert_by_extension, *args, **kwargs)
+
+async def find_windows_cert_all_async(*args:Any, **kwargs:Any) -> List[Dict[str, Union[str, bytes, List[str]]]]:
+    """
+    Awaitable ``find_windows_cert_all``, run on a background thread. Cancelling the task stops the search.
+    """
+    return await _run_async(find_windows_cert_all, *args, **kwargs)
+
+async def find_certificates_async(*args:Any, **kwargs:Any) -> List[Dict[str, Union[str, bytes, List[str]]]]:
+    """
+    Awaitable ``find_certificates``, run on a background thread. Cancelling the task stops the search.
+    """
+    return await _run_async(find_certificates, *args, **kwargs)
+
+async def get_win_cert_async(*args:Any, **kwargs:Any) -> Union[bytes, Dict[str, Union[str, bytes]], List[bytes], List[Dict[str, Union[str, bytes]]]]:
+    """
+    Awaitable ``get_win_cert``, run on a background thread. Cancelling the task stops the search.
+    """
+    return await _run_async(get_win_cert, *args, **kwargs)
+
+def on_store_change(watch:StoreWatch, callback:Callable[[StoreEvent], Any], daemon:bool=True) -> threading.Thread:
+    """
+    Calls ``callback`` with every ``StoreEvent`` of a watch, on a background thread.
+
+    :param watch: The watch returned by ``watch_windows_store`` or ``watch_path``. Closing it stops the thread.
+    :param callback: Called with each event. Exceptions it raises are printed and the watch carries on.
+    :param daemon: Whether the thread lets the interpreter exit while it is running.
+
+    :return: The started thread.
+    """
+    def run() -> None:
+        for event in watch:
+            try:
+                callback(event)
+            except Exception:
+                traceback.print_exc()
+
+    thread = threading.Thread(target=run, name="py_cert_store-watch", daemon=daemon)
+    thread.start()
+    return thread
@@ -0,0 +1,36 @@
+# Copyright 2025 Niky H. (Unwarymold9171)
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""The py-cert-store command line tool: ``python -m py_cert_store --help``."""
+
+from __future__ import annotations
+import os
+import sys
+from typing import List, Optional
+
+from .py_cert_store import run_cli
+
+
+def main(args: Optional[List[str]] = None) -> int:
+    if args is None:
+        args = sys.argv[1:]
+    if os.path.basename(sys.argv[0]) == "__main__.py":
+        prog = "python -m py_cert_store"
+    else:
+        prog = os.path.basename(sys.argv[0]) or "py-cert-store"
+    return run_cli(args, prog=prog)
+
+
+if __name__ == "__main__":
+    sys.exit(main())
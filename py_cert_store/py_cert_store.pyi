@@ -12,10 +12,15 @@
 # See the License for the specific language governing permissions and
 # limitations under the License.
 
-from typing import Dict, Union, List
+from datetime import datetime
+import os
+from typing import Any, Callable, Dict, Union, List, Optional, Sequence, Tuple
 
 
-def find_windows_cert_by_extension(store:str="My", user:str="CurrentUser", extension_oid:str=None, extension_value:str=None) -> List[Dict[str, Union[str,bytes]]]:
+Selection = Union[str, Callable[[List["CertificateCandidate"]], Union["CertificateCandidate", int, Sequence[Union["CertificateCandidate", int]], None]], None]
+
+
+def find_windows_cert_by_extension(store:str="My", user:str="CurrentUser", extension_oid:str=None, extension_value:str=None, host:Optional[str]=None, selection:Selection=None, cancel:Optional["CancellationToken"]=None, cache:Optional["CertificateCache"]=None, purpose:Optional[str]=None) -> List[Dict[str, Union[str,bytes,List[str]]]]:
     """
     Find a certificate in the Windows certificate store by its extension.
 
@@ -27,17 +32,29 @@ def find_windows_cert_by_extension(store:str="My", user:str="CurrentUser", exten
         - It is recommended to use the dotted string from the python `cryptography` package.
             - example: `x509.OID_KEY_USAGE.dotted_string`.
     :param extension_value: The value of the extension to search for.
+    :param host: If given, only certificates valid for this DNS name or IP address are returned.
+        - example: "www.example.com", "10.0.0.1"
+    :param selection: How to pick from several matching certificates, see `select_certificates`. Default keeps all of them.
+    :param cancel: A `CancellationToken` that stops the search from another thread, raising `CertSearchCancelled`.
+    :param cache: A `CertificateCache` that keeps the results until the store changes, see `CertificateCache`.
+    :param purpose: If given, only certificates allowed for this purpose are returned, see `certificate_allows_purpose`.
+        - The purposes set in the certificate properties ("Enable only the following purposes", "Disable all
+          purposes") must allow it as well as the Extended Key Usage of the certificate.
 
     :return: The return is a list of dictionaries with the following keys:
         - "cert": The certificate bytes.
         - "FriendlyName": The friendly name of the certificate.
         - "Name": The name of the certificate.
         - "IssuerName": The issuer name of the certificate.
+        - "AltNames": The subject alternative names, in OpenSSL notation.
+        - "KeyProvider": The name of the key storage provider holding the private key.
+        - "Key": The key, as in the "Key" field of `certificate_metadata`, see `StoreCertificate.key_metadata`.
         - "EffectiveDateString": The effective date of the certificate as a string.
         - "ExpirationDateString": The expiration date of the certificate as a string.
+        - "StoreLocation": Where the certificate was found, as in the "Store" field of `certificate_metadata`.
     """
 
-def find_windows_cert_all(store:str="My", user:str="CurrentUser") -> List[Dict[str, Union[str,bytes]]]:
+def find_windows_cert_all(store:str="My", user:str="CurrentUser", host:Optional[str]=None, selection:Selection=None, cancel:Optional["CancellationToken"]=None, cache:Optional["CertificateCache"]=None, purpose:Optional[str]=None) -> List[Dict[str, Union[str,bytes,List[str]]]]:
     """
     Find all time valid certificates in the Windows certificate store.
 
@@ -45,14 +62,892 @@ def find_windows_cert_all(store:str="My", user:str="CurrentUser") -> List[Dict[s
         - example: "My", "Root", "CA", etc.
     :param user: The user to get the certificate from.
         - example: "CurrentUser", "LocalMachine".
+    :param host: If given, only certificates valid for this DNS name or IP address are returned.
+        - example: "www.example.com", "10.0.0.1"
+    :param selection: How to pick from several matching certificates, see `select_certificates`. Default keeps all of them.
+    :param cancel: A `CancellationToken` that stops the search from another thread, raising `CertSearchCancelled`.
+    :param cache: A `CertificateCache` that keeps the results until the store changes, see `CertificateCache`.
+    :param purpose: If given, only certificates allowed for this purpose are returned, see `certificate_allows_purpose`.
+        - The purposes set in the certificate properties ("Enable only the following purposes", "Disable all
+          purposes") must allow it as well as the Extended Key Usage of the certificate.
 
     :return: The return is a list of dictionaries with the following keys:
         - "cert": The certificate bytes.
         - "FriendlyName": The friendly name of the certificate.
         - "Name": The name of the certificate.
         - "IssuerName": The issuer name of the certificate.
+        - "AltNames": The subject alternative names, in OpenSSL notation.
+        - "KeyProvider": The name of the key storage provider holding the private key.
+        - "Key": The key, as in the "Key" field of `certificate_metadata`, see `StoreCertificate.key_metadata`.
         - "EffectiveDateString": The effective date of the certificate as a string.
         - "ExpirationDateString": The expiration date of the certificate as a string.
+        - "StoreLocation": Where the certificate was found, as in the "Store" field of `certificate_metadata`.
+    """
+
+def iter_windows_certs(store:str="My", user:str="CurrentUser") -> "CertificateIterator":
+    """
+    Lazily iterate over every certificate in the Windows certificate store.
+
+    Certificates are read one at a time and private keys are only exported when `export_pfx()` or `to_dict()` is
+    called, so large stores are cheap to search.
+
+    :param store: The name of the certificate store to search in.
+        - example: "My", "Root", "CA", etc.
+    :param user: The user to get the certificate from.
+        - example: "CurrentUser", "LocalMachine".
+
+    :return: An iterator of `StoreCertificate`. Use it in a `with` block, or call `close()`, to close the store early.
+    """
+
+def iter_certificates(certificates:List[bytes], password:str="") -> "CertificateIterator":
+    """
+    Lazily iterate over certificates held in memory, the same way `iter_windows_certs` iterates over a store.
+
+    :param certificates: Certificates (PEM or DER) and PKCS#12 files.
+    :param password: The password of the PKCS#12 files.
+
+    :return: An iterator of `StoreCertificate`.
+    """
+
+def find_certificates(
+    certificates:List[bytes], password:str="", host:Optional[str]=None, selection:Selection=None,
+    cancel:Optional["CancellationToken"]=None, purpose:Optional[str]=None
+) -> List[Dict[str, Union[str,bytes,List[str]]]]:
+    """
+    Search certificates held in memory the same way `find_windows_cert_all` searches a store.
+
+    Only certificates that are currently valid and whose private key can be exported are returned. The search runs
+    without holding the GIL, so other Python threads keep running while keys are exported.
+
+    :param certificates: Certificates (PEM or DER), PKCS#12 files and Java keystores.
+    :param password: The password of the PKCS#12 files and keystores.
+    :param host: Only return certificates valid for this DNS name or IP address.
+    :param selection: How to pick from several matches, see `select_certificates`.
+    :param cancel: A `CancellationToken` that stops the search from another thread, raising `CertSearchCancelled`.
+    :param purpose: Only return certificates whose Extended Key Usage allows this purpose, see `certificate_allows_purpose`.
+
+    :return: A list of dictionaries with the same keys as `find_windows_cert_all`.
+    """
+
+def find_certificates_in_path(
+    path:Union[str, "os.PathLike[str]"], password:str="", host:Optional[str]=None, selection:Selection=None,
+    cancel:Optional["CancellationToken"]=None, cache:Optional["CertificateCache"]=None, purpose:Optional[str]=None
+) -> List[Dict[str, Union[str,bytes,List[str]]]]:
+    """
+    Search a file based store the same way `find_windows_cert_all` searches a Windows store.
+
+    :param path: A certificate file, a PKCS#12 file, a Java keystore, or a directory of such files.
+        - PEM files may hold several certificates and private keys, keys are matched to their certificate.
+        - Files of a directory that cannot be read are skipped.
+    :param password: The password of the PKCS#12 files, Java keystores and encrypted keys.
+    :param host: Only return certificates valid for this DNS name or IP address.
+    :param selection: How to pick from several matches, see `select_certificates`.
+    :param cancel: A `CancellationToken` that stops the search from another thread, raising `CertSearchCancelled`.
+    :param cache: A `CertificateCache` that keeps the results until the files change.
+    :param purpose: Only return certificates whose Extended Key Usage allows this purpose, see `certificate_allows_purpose`.
+
+    :return: A list of dictionaries with the same keys as `find_windows_cert_all`. The "FriendlyName" is the file name
+        unless a PKCS#12 file names the certificate.
+    """
+
+def list_nss_databases() -> List[str]:
+    """
+    List the NSS databases (directories holding a cert9.db) of the current user: Chromium's `~/.pki/nssdb` and the
+    Firefox profiles, including those of the Firefox snap. Linux and macOS only.
+    """
+
+def read_nss_database(path:Optional[Union[str, "os.PathLike[str]"]]=None, password:Optional[str]=None) -> List[Dict[str, Any]]:
+    """
+    Describe every certificate of an NSS database, trusted CAs included. Linux and macOS only.
+
+    The databases are opened read only, so they can be read while the browser is running.
+
+    :param path: The database directory, optionally prefixed with "sql:". Defaults to the first of `list_nss_databases()`.
+    :param password: The database password. Without one, private keys are only read when no password is set.
+        A wrong password raises PermissionError.
+
+    :return: Certificate metadata as returned by `certificate_metadata`, with the NSS nickname as "FriendlyName"
+        and the certutil trust flags as "NssTrust", such as "CT,C,C" for a trusted CA or "u,u,u" for a certificate
+        with a private key.
+    """
+
+def find_nss_certificates(
+    path:Optional[Union[str, "os.PathLike[str]"]]=None, password:Optional[str]=None, host:Optional[str]=None,
+    selection:Selection=None, cancel:Optional["CancellationToken"]=None, purpose:Optional[str]=None
+) -> List[Dict[str, Union[str,bytes,List[str]]]]:
+    """
+    Search the certificates with a private key in an NSS database, the same way `find_windows_cert_all` searches a
+    Windows store. Linux and macOS only.
+
+    :param path: The database directory, see `read_nss_database`.
+    :param password: The database password, needed to read the private keys when one is set.
+    :param host: Only return certificates valid for this DNS name or IP address.
+    :param selection: How to pick from several matches, see `select_certificates`.
+    :param cancel: A `CancellationToken` that stops the search from another thread, raising `CertSearchCancelled`.
+    :param purpose: Only return certificates whose Extended Key Usage allows this purpose, see `certificate_allows_purpose`.
+
+    :return: A list of dictionaries with the same keys as `find_windows_cert_all`, plus the certutil trust flags as
+        "NssTrust". The "FriendlyName" is the NSS nickname and the "KeyProvider" is "NSS Certificate DB".
+    """
+
+def list_pkcs11_tokens(module:Union[str, "os.PathLike[str]"], parameters:Optional[str]=None) -> List[Dict[str, Union[int, str, bool]]]:
+    """
+    List the initialized tokens of a PKCS#11 module, such as a smart card middleware or HSM library.
+
+    :param module: The path of the module, such as "/usr/lib/softhsm/libsofthsm2.so" or "opensc-pkcs11.dll".
+    :param parameters: Module specific initialization parameters. Only the NSS softoken (libsoftokn3) takes any,
+        such as "configdir='sql:/path' flags=readOnly".
+
+    :return: A dictionary per token, with the keys "SlotId", "Label", "Manufacturer", "Model", "SerialNumber" and
+        "LoginRequired".
+    """
+
+def open_pkcs11_token(
+    module:Union[str, "os.PathLike[str]"], pin:Optional[str]=None, slot:Optional[int]=None, token_label:Optional[str]=None,
+    parameters:Optional[str]=None
+) -> "Pkcs11Token":
+    """
+    Open a session with a token of a PKCS#11 module and log in with the PIN.
+
+    :param module: The path of the module, see `list_pkcs11_tokens`.
+    :param pin: The user PIN. Without one, the private keys of tokens that require a login are not visible.
+        A wrong or locked PIN raises PermissionError.
+    :param slot: The slot of the token. Either this or `token_label` is needed when the module has several tokens,
+        otherwise ValueError is raised.
+    :param token_label: The label of the token.
+    :param parameters: Module specific initialization parameters, see `list_pkcs11_tokens`.
+
+    :return: A `Pkcs11Token`. Close it with `close()` or by using it in a `with` block.
+    """
+
+def watch_windows_store(store:str="My", user:str="CurrentUser") -> "StoreWatch":
+    """
+    Watch a store of the Windows certificate store for certificates being added, removed or modified.
+
+    Changes are picked up from store change notifications, so renewals by auto-enrollment or other processes are seen.
+
+    :param store: The name of the certificate store to watch.
+        - example: "My", "Root", "CA", etc.
+    :param user: The user whose store to watch.
+        - example: "CurrentUser", "LocalMachine".
+
+    :return: A `StoreWatch`. Iterate over it, `poll()` it, or pass it to `on_store_change` with a callback.
+    """
+
+def watch_path(path:Union[str, "os.PathLike[str]"], password:str="") -> "StoreWatch":
+    """
+    Watch a certificate file, PKCS#12 file or directory of certificates for changes.
+
+    Uses inotify on Linux, and checks the files twice a second elsewhere.
+
+    :param path: The file or directory, read the same way as `find_certificates_in_path`.
+    :param password: The password of the PKCS#12 files and encrypted keys.
+
+    :return: A `StoreWatch`.
+    """
+
+def run_cli(args:List[str], prog:str="py-cert-store") -> int:
+    """
+    Run the py-cert-store command line tool, as `python -m py_cert_store` does.
+
+    Output is written to `sys.stdout` and `sys.stderr`.
+
+    :param args: The command line arguments, without the program name, such as `["list", "--path", "certs"]`.
+    :param prog: The program name shown in usage and help messages.
+
+    :return: The exit code: 0 on success, 1 when the command failed or a chain did not verify, 2 for invalid arguments.
+    """
+
+class StoreWatch:
+    """
+    Watches a store, comparing its certificates by thumbprint every time it changes.
+
+    Iterating blocks until the next `StoreEvent`, and ends once the watch is closed. Close it with `close()` or by
+    using it in a `with` block.
+    """
+
+    @property
+    def thumbprints(self) -> List[str]:
+        """The thumbprints of the certificates currently in the store."""
+
+    @property
+    def closed(self) -> bool: ...
+
+    def poll(self, timeout:Optional[float]=None) -> List["StoreEvent"]:
+        """
+        Waits up to `timeout` seconds, or until the next change when None, and returns the events.
+
+        :return: The events, or an empty list when nothing changed in time or the watch was closed.
+        """
+
+    def close(self) -> None: ...
+
+    def __iter__(self) -> "StoreWatch": ...
+
+    def __next__(self) -> "StoreEvent": ...
+
+    def __enter__(self) -> "StoreWatch": ...
+
+    def __exit__(self, *args:Any) -> bool: ...
+
+class StoreEvent:
+    """
+    A certificate added to, removed from, or modified in a watched store. Removed certificates are described as they
+    were last seen.
+    """
+
+    @property
+    def kind(self) -> str:
+        """"added", "removed" or "modified" (the friendly name or the private key changed)."""
+
+    @property
+    def thumbprint(self) -> str: ...
+
+    @property
+    def friendly_name(self) -> str: ...
+
+    @property
+    def has_private_key(self) -> bool: ...
+
+    @property
+    def key_provider(self) -> Optional[str]: ...
+
+    @property
+    def subject(self) -> str: ...
+
+    @property
+    def not_after(self) -> datetime: ...
+
+    @property
+    def certificate(self) -> bytes:
+        """The DER encoded certificate."""
+
+class CertificateCache:
+    """
+    Keeps the results of store searches, including the exported keys, to reuse them on the next identical search.
+
+    Pass the same cache to every call of a search function. A cached search is stored per location, store and search
+    criteria, and is searched again once:
+        - it is older than `ttl` seconds,
+        - one of its certificates expired,
+        - the store changed (Windows store change notifications, or the size and modification time of the files).
+    """
+
+    def __init__(self, ttl:float=300.0) -> None: ...
+
+    @property
+    def ttl(self) -> float:
+        """How long, in seconds, a search stays cached."""
+
+    @property
+    def stats(self) -> Dict[str, int]:
+        """The "Hits", "Misses" and cached "Entries"."""
+
+    def invalidate(self, location:Optional[str]=None, store:Optional[str]=None) -> int:
+        """
+        Drops the cached searches of a location and/or store, or all of them when neither is given.
+
+        :param location: "CurrentUser", "LocalMachine" or "file".
+        :param store: The store name, or the path given to `find_certificates_in_path`.
+
+        :return: The number of cached searches dropped.
+        """
+
+    def clear(self) -> None: ...
+
+    def __len__(self) -> int: ...
+
+class CancellationToken:
+    """
+    Stops a search running on another thread before it exports another key.
+
+    The `*_async` functions create one for each search and cancel it when the awaiting task is cancelled.
+    """
+
+    def __init__(self) -> None: ...
+
+    @property
+    def cancelled(self) -> bool:
+        """Whether `cancel()` has been called."""
+
+    def cancel(self) -> None:
+        """Stops the searches using this token. They raise `CertSearchCancelled`."""
+
+class CertificateIterator:
+    """
+    A lazy enumeration of a certificate store, yielding `StoreCertificate` objects.
+
+    The store is closed once the enumeration is exhausted, when `close()` is called, or when the `with` block ends.
+    """
+    def __iter__(self) -> "CertificateIterator": ...
+    def __next__(self) -> "StoreCertificate": ...
+    def close(self) -> None: ...
+    def __enter__(self) -> "CertificateIterator": ...
+    def __exit__(self, *args) -> bool: ...
+
+class StoreCertificate:
+    """
+    A certificate from a store. The private key is only exported when asked for.
+    """
+    thumbprint: str
+    """The SHA-1 thumbprint as upper case hex."""
+    subject: str
+    issuer: str
+    friendly_name: str
+    not_before: datetime
+    not_after: datetime
+    alt_names: List[str]
+    """The subject alternative names, in OpenSSL notation."""
+    has_private_key: bool
+    key_provider: Optional[str]
+    """The name of the key storage provider holding the private key, when known."""
+    certificate: bytes
+    """The DER encoded certificate."""
+
+    def export_pfx(self) -> bytes:
+        """
+        Export the certificate and its private key as a PKCS#12 file without a password.
+
+        Raises `CertNotExportable` when there is no private key or it cannot be exported, and `CertKeyMismatch` when
+        the exported key does not belong to the certificate.
+        """
+    def ssh_public_key(self, comment:Optional[str]=None) -> str:
+        """
+        The public key as an OpenSSH `authorized_keys` line, see `get_ssh_public_key`.
+        """
+    def key_metadata(self) -> Dict[str, Any]:
+        """
+        Describe the key of the certificate without exporting the private key.
+
+        :return: A dictionary with the following keys:
+            - "Algorithm": "RSA", "ECDSA", "Ed25519", or the OID of another key type.
+            - "Size": The key size in bits. "Curve": The named curve of ECDSA keys.
+            - "HasPrivateKey": Whether the store holds the private key. "Provider": The provider holding it.
+            - "HardwareBacked": Whether the private key is held by a smart card, TPM or other hardware.
+            - "Exportable": Whether the export policy of the private key allows exporting it, None when the store
+              cannot tell, such as for a smart card key that cannot be opened without its PIN.
+            - "UiProtected": Whether using the private key asks for consent or a password, None when unknown.
+        """
+    def verify(self, data:bytes, signature:bytes, hash_alg:str="sha256", padding:Optional[str]=None) -> bool:
+        """
+        Verify a signature over `data` with the public key of the certificate, see `certificate_verifies_signature`.
+        """
+    def is_signed_by(self, issuer:Union["StoreCertificate", bytes]) -> bool:
+        """
+        Check that the certificate was signed by the key of `issuer`, see `certificate_issued_by`.
+        """
+    def properties(self) -> Dict[str, Any]:
+        """
+        The properties the Windows certificate store keeps with the certificate. Certificates of other stores have
+        none, so every value is None (False for "Archived").
+
+        :return: A dictionary with the following keys, None when the property is not set:
+            - "KeyProviderInfo": Where the private key lives, a dictionary with "ContainerName", "ProviderName",
+              "ProviderType", "Flags", "KeySpec", "Cng" (a CNG key storage provider rather than a CSP) and
+              "MachineKeySet".
+            - "Sha1Hash", "Md5Hash": The certificate hashes, as upper case hex.
+            - "EnhancedKeyUsage": The purposes enabled in the certificate properties, empty when all are disabled.
+            - "FriendlyName", "Description": As shown in the certificate properties.
+            - "Archived": True for archived certificates.
+            - "KeyIdentifier": The key identifier, as upper case hex.
+            - "AutoEnrollTemplate": The certificate template autoenrollment used.
+            - "RenewalHash": The SHA-1 thumbprint of the certificate that renewed this one.
+            - "NCryptKeyHandle": The NCrypt key handle cached with the certificate.
+        """
+    def to_dict(self) -> Dict[str, Union[str, bytes, List[str]]]:
+        """
+        The dictionary returned by `find_windows_cert_all`, exporting the private key.
+        """
+    def metadata(self, include_certificate:bool=False, include_private_key:bool=False) -> Dict[str, Any]:
+        """
+        The certificate metadata, see `certificate_metadata`. The key is only exported with `include_private_key`.
+        """
+    def to_json(self, include_certificate:bool=False, include_private_key:bool=False, indent:Optional[int]=None) -> str:
+        """
+        The certificate metadata as JSON, see `metadata_to_json`.
+        """
+    def to_yaml(self, include_certificate:bool=False, include_private_key:bool=False) -> str:
+        """
+        The certificate metadata as YAML, see `metadata_to_yaml`.
+        """
+    def close(self) -> None:
+        """
+        Release the certificate in the store. Its details stay readable, but the key can no longer be exported.
+        """
+    def __enter__(self) -> "StoreCertificate": ...
+    def __exit__(self, *args) -> bool: ...
+
+class Pkcs11Token:
+    """
+    A session with a PKCS#11 token, such as a smart card or HSM.
+
+    The private keys never leave the token: its certificates raise `CertNotExportable` when exported, and `sign()`
+    signs through the token instead. Certificates are paired with the private key sharing their CKA_ID.
+    """
+    module: str
+    """The path of the PKCS#11 module."""
+    slot_id: int
+    label: str
+    manufacturer: str
+    model: str
+    serial_number: str
+
+    @property
+    def closed(self) -> bool: ...
+
+    def certificates(self) -> "CertificateIterator":
+        """
+        Enumerate the certificates of the token. The "key_provider" of those with a private key is the token label.
+        """
+    def metadata(self) -> List[Dict[str, Any]]:
+        """
+        Describe every certificate of the token, see `certificate_metadata`. The "Store" has the kind "pkcs11", the
+        token label as "Store" and the module as "Path".
+        """
+    def sign(self, thumbprint:str, data:bytes) -> bytes:
+        """
+        Sign the data with the private key of a certificate, on the token.
+
+        RSA keys sign with PKCS#1 v1.5 and SHA-256, ECDSA keys with SHA-256, SHA-384 or SHA-512 to match the curve
+        (the signature is DER encoded, as in X.509), and Ed25519 keys sign the data itself.
+
+        Raises `CertNotFound` when no certificate has the thumbprint or the token holds no key for it.
+        """
+    def close(self) -> None:
+        """
+        Close the session. Certificates already enumerated stay readable.
+        """
+    def __enter__(self) -> "Pkcs11Token": ...
+    def __exit__(self, *args) -> bool: ...
+
+CertificateLike = Union["StoreCertificate", Dict[str, Any], bytes, str]
+
+def certificate_metadata(certificate:CertificateLike, include_certificate:bool=False, include_private_key:bool=False) -> Dict[str, Any]:
+    """
+    Describe a certificate for inventories, following the versioned schema returned by `metadata_schema()`.
+
+    The metadata holds the identifiers (thumbprint, SHA-256 fingerprint, serial number), subject and issuer names,
+    validity, extensions, key type and provider, and the store location. Key material is left out unless asked for.
+
+    :param certificate: A `StoreCertificate`, a dictionary returned by the search functions, or a PEM/DER certificate.
+    :param include_certificate: Add the PEM encoded certificate as "Certificate".
+    :param include_private_key: Add the PKCS#12 file with the private key, base64 encoded, as "Pkcs12". Exports
+        the key of a `StoreCertificate`.
+
+    :return: A dictionary with PascalCase keys, "SchemaVersion" first.
+    """
+
+def metadata_to_json(certificates:Union[CertificateLike, List[CertificateLike]], include_certificate:bool=False, include_private_key:bool=False, indent:Optional[int]=None) -> str:
+    """
+    Serialize certificate metadata as JSON.
+
+    A single certificate gives its metadata object. A list gives an inventory with "SchemaVersion", "GeneratedAt"
+    and "Certificates".
+
+    :param indent: Pretty print with this many spaces per level. Compact by default.
+    """
+
+def metadata_to_yaml(certificates:Union[CertificateLike, List[CertificateLike]], include_certificate:bool=False, include_private_key:bool=False) -> str:
+    """
+    Serialize certificate metadata as YAML, in the same layout as `metadata_to_json`.
+    """
+
+def metadata_schema() -> str:
+    """
+    The JSON Schema (draft 2020-12) of the certificate metadata and inventories, also found in the repository
+    as `schema/certificate-metadata-v1.json`.
+    """
+
+def snapshot_stores(
+    stores:Optional[List[Tuple[str, str]]]=None, paths:Optional[List[str]]=None, password:str="", host:Optional[str]=None,
+    nss_databases:Optional[List[str]]=None
+) -> Dict[str, Any]:
+    """
+    Record the metadata of every certificate in the given stores as an inventory, without exporting any private key.
+    Store the result, for example with `json.dump`, and compare it with a later snapshot using `diff_snapshots`.
+
+    :param stores: The Windows stores to read, as (location, store) pairs such as ("LocalMachine", "My").
+        Defaults to ("CurrentUser", "My") on Windows when no paths or NSS databases are given. Windows stores raise OSError elsewhere.
+    :param paths: PEM directories or PKCS#12 files to read.
+    :param password: The password of the PKCS#12 files and NSS databases.
+    :param host: The machine name recorded as "Host". Defaults to `socket.gethostname()`.
+    :param nss_databases: NSS database directories to read, on Linux and macOS.
+
+    :return: The inventory, with the stores that were read listed under "Stores".
+    """
+
+def diff_snapshots(previous:Union[Dict[str, Any], str, bytes], current:Union[Dict[str, Any], str, bytes], expiring_days:int=30) -> Dict[str, Any]:
+    """
+    Compare two snapshots taken by `snapshot_stores`, given as dictionaries or JSON. Certificates are matched by
+    store and thumbprint, and only the stores read for both snapshots are compared.
+
+    :param expiring_days: Report the certificates of `current` expiring within this many days of when it was taken.
+
+    :return: A dictionary with:
+        - "Previous" and "Current": when the snapshots were taken.
+        - "Added" and "Removed": certificate metadata.
+        - "Renewed": {"Previous": ..., "Current": ...} pairs of certificates with the same subject in the same store,
+          where the current one has a new serial number and a later expiry.
+        - "Expiring": certificates about to expire that have no renewal in the same store.
+    """
+
+def read_pkcs7_certificates(data:bytes) -> List[bytes]:
+    """
+    Read the certificates out of a certs-only PKCS#7 bundle (.p7b / .p7c).
+
+    :param data: The bundle, either DER encoded or PEM encoded (`-----BEGIN PKCS7-----`).
+
+    :return: The DER encoded certificates, in the order they are stored in the bundle.
+    """
+
+def write_pkcs7_certificates(certificates:List[bytes], pem:bool=False) -> bytes:
+    """
+    Write certificates into a certs-only PKCS#7 bundle (.p7b).
+
+    :param certificates: The DER encoded certificates, written in the given order.
+    :param pem: If True, the bundle is PEM encoded. Default is False (DER).
+
+    :return: The encoded bundle.
+    """
+
+def read_java_keystore(data:bytes, password:Optional[str]=None, key_password:Optional[str]=None) -> List[Dict[str, Any]]:
+    """
+    Read the entries of a Java keystore (.jks or .jceks).
+
+    :param data: The keystore.
+    :param password: The keystore password. Without it the integrity of the keystore is not checked and the private
+        keys are not read, like `keytool -list`.
+    :param key_password: The password of the private keys, when it differs from the keystore password.
+
+    :return: A dictionary per entry with the keys "Alias", "EntryType" (`"PrivateKeyEntry"` or `"trustedCertEntry"`),
+        "Created", "Certificate" (DER), "Chain" (DER, leaf first) and "HasPrivateKey". Entries with a private key also
+        have "cert", the chain and key as a PKCS#12 file without a password.
+
+    :raises PermissionError: The keystore or key password is wrong.
+    :raises ValueError: The data is not a keystore, or holds secret key entries.
+    """
+
+def write_java_keystore(
+        certificates:List[CertificateLike], password:str, format:str="jks", key_password:Optional[str]=None,
+        include_private_keys:bool=True
+) -> bytes:
+    """
+    Write certificates into a Java keystore (.jks or .jceks), for example a truststore of the Windows `Root` store.
+
+    :param certificates: `StoreCertificate` objects, dictionaries returned by the search functions, encoded
+        certificates or PKCS#12 files without a password.
+    :param password: The keystore password.
+    :param format: `"jks"` or `"jceks"`. JCEKS protects the private keys with 3DES, and needs ASCII passwords.
+    :param key_password: The password of the private keys. Default is the keystore password.
+    :param include_private_keys: If False, every certificate becomes a trusted certificate entry.
+
+    :return: The keystore. Certificates with a private key become private key entries, the others trusted
+        certificate entries, named after their friendly name or thumbprint.
+    """
+
+def generate_csr(
+        subject:str, key:Optional[str]=None, key_type:str="rsa2048",
+        alt_names:Optional[List[str]]=None, key_usage:Optional[List[str]]=None, extended_key_usage:Optional[List[str]]=None
+    ) -> Tuple[str, str]:
+    """
+    Generate a PKCS#10 certificate signing request.
+
+    :param subject: The RFC 4514 subject of the request.
+        - example: "CN=client.example.com,O=Example,C=US"
+    :param key: An existing unencrypted PEM private key (PKCS#8, PKCS#1 or SEC1) to sign the request with. If None, a new key is generated.
+    :param key_type: The type of key to generate when `key` is None.
+        - example: "rsa2048", "rsa3072", "rsa4096", "p256", "p384", "p521", "ed25519".
+    :param alt_names: The subject alternative names, in OpenSSL notation.
+        - example: ["DNS:client.example.com", "IP:10.0.0.1", "email:ops@example.com", "URI:https://example.com"]
+    :param key_usage: The key usage bits to request.
+        - example: ["digital_signature", "key_encipherment"]
+    :param extended_key_usage: The extended key usage purposes to request, by name or dotted OID.
+        - example: ["client_auth", "server_auth", "1.3.6.1.5.5.7.3.3"]
+
+    :return: A tuple of the PEM encoded request and the PEM encoded (PKCS#8) private key.
+    """
+
+def parse_csr(data:bytes) -> Dict[str, Union[str, bool, List[str]]]:
+    """
+    Parse a PKCS#10 certificate signing request for review.
+
+    :param data: The request, either DER or PEM encoded.
+
+    :return: A dictionary with the following keys:
+        - "Subject": The requested subject in RFC 4514 notation.
+        - "KeyAlgorithm": The type of the key being certified, e.g. "RSA 2048" or "ECDSA P-256".
+        - "AltNames": The requested subject alternative names, in OpenSSL notation.
+        - "KeyUsage": The requested key usage bits.
+        - "ExtendedKeyUsage": The requested extended key usage purposes.
+        - "SignatureAlgorithm": The dotted OID of the signature algorithm.
+        - "SignatureValid": True if the request is signed by the key it asks to certify.
+    """
+
+def generate_certificate(
+        subject:str, key:Optional[str]=None, key_type:str="rsa2048",
+        issuer_cert:Optional[bytes]=None, issuer_key:Optional[str]=None, valid_days:int=365,
+        alt_names:Optional[List[str]]=None, key_usage:Optional[List[str]]=None, extended_key_usage:Optional[List[str]]=None,
+        is_ca:bool=False, path_length:Optional[int]=None, friendly_name:Optional[str]=None, password:str=""
+    ) -> Dict[str, Union[str, bytes]]:
+    """
+    Generate a self-signed certificate, or a certificate issued by a CA, for tests and development environments.
+
+    :param subject: The RFC 4514 subject of the certificate.
+        - example: "CN=Test Exportable Cert,O=Py_Cert_Store Examples,C=US"
+    :param key: An existing unencrypted PEM private key (PKCS#8, PKCS#1 or SEC1) to certify. If None, a new key is generated.
+    :param key_type: The type of key to generate when `key` is None.
+        - example: "rsa2048", "rsa3072", "rsa4096", "p256", "p384", "p521", "ed25519".
+    :param issuer_cert: The issuing CA certificate, DER or PEM encoded. If None, the certificate is self-signed.
+    :param issuer_key: The unencrypted PEM private key of `issuer_cert`. Required together with `issuer_cert`.
+    :param valid_days: The number of days the certificate is valid for, starting now. Default is 365.
+    :param alt_names: The subject alternative names, in OpenSSL notation.
+        - example: ["DNS:localhost", "IP:127.0.0.1"]
+    :param key_usage: The key usage bits.
+        - example: ["digital_signature", "key_encipherment"]
+    :param extended_key_usage: The extended key usage purposes, by name or dotted OID.
+        - example: ["server_auth", "client_auth"]
+    :param is_ca: If True, the certificate can issue other certificates (basic constraints CA:TRUE).
+    :param path_length: The maximum number of intermediate CAs below this certificate.
+    :param friendly_name: The friendly name stored in the PKCS#12 file, shown by Windows after import.
+    :param password: The password protecting the PKCS#12 file. Default is "" (empty).
+
+    :return: A dictionary with the following keys:
+        - "FriendlyName": The friendly name of the certificate.
+        - "Certificate": The PEM encoded certificate.
+        - "PrivateKey": The PEM encoded (PKCS#8) private key.
+        - "cert": The certificate, its private key and any issuer certificate as a PKCS#12 (.pfx) file.
+    """
+
+def generate_private_key(key_type:str="rsa2048", format:str="pkcs8", password:Optional[str]=None) -> str:
+    """
+    Generate a new private key.
+
+    :param key_type: The type of key to generate.
+        - example: "rsa2048", "rsa3072", "rsa4096", "p256", "p384", "p521", "ed25519".
+    :param format: The key format: "pkcs8", "pkcs1" (RSA only), "sec1" (ECDSA only) or "openssh". Default is "pkcs8".
+    :param password: If given, the key is encrypted as PKCS#8 using PBES2 (PBKDF2-SHA256, AES-256-CBC), or like
+        `ssh-keygen` (bcrypt-pbkdf, AES-256-CTR) for the "openssh" format.
+
+    :return: The PEM encoded private key.
+    """
+
+def convert_private_key(
+        data:bytes, password:Optional[str]=None, format:str="pkcs8", pem:bool=True, new_password:Optional[str]=None
+    ) -> bytes:
+    """
+    Convert a private key to another format or encoding.
+
+    :param data: The private key as PKCS#8 (optionally encrypted), PKCS#1 or SEC1, PEM or DER encoded, or an OpenSSH
+        private key.
+    :param password: The password of an encrypted PKCS#8 key, or the passphrase of an OpenSSH key.
+    :param format: The output format: "pkcs8", "pkcs1" (RSA only), "sec1" (ECDSA only) or "openssh". Default is "pkcs8".
+    :param pem: If True, the output is PEM encoded. Default is True; False gives DER. OpenSSH keys are always text.
+    :param new_password: If given, the output is encrypted as PKCS#8 using PBES2, or with the OpenSSH passphrase.
+
+    :return: The converted private key.
+    """
+
+def get_public_key(data:bytes, password:Optional[str]=None) -> str:
+    """
+    Extract the public key from a private key or a certificate.
+
+    :param data: A certificate or a private key, PEM or DER encoded.
+    :param password: The password of an encrypted PKCS#8 key.
+
+    :return: The PEM encoded public key (-----BEGIN PUBLIC KEY-----).
+    """
+
+def get_ssh_public_key(data:bytes, password:Optional[str]=None, comment:Optional[str]=None) -> str:
+    """
+    Format the public key of a certificate or private key for an OpenSSH `authorized_keys` file.
+
+    :param data: A certificate or a private key, PEM or DER encoded.
+    :param password: The password of an encrypted key.
+    :param comment: Appended after the key, such as "alice@laptop".
+
+    :return: The key line, such as "ssh-rsa AAAAB3NzaC1yc2E... alice@laptop".
+    """
+
+def parse_ssh_certificate(data:bytes) -> Dict[str, Any]:
+    """
+    Describe an OpenSSH user or host certificate, as `ssh-keygen -L` does.
+
+    :param data: The `*-cert.pub` file written by `ssh-keygen -s`, or the decoded certificate blob.
+
+    :return: A dictionary with "KeyType", "Type" (`"user"` or `"host"`), "KeyId", "Serial", "Principals",
+        "ValidAfter" and "ValidBefore" (RFC 3339, or None when unbounded), "CriticalOptions" and "Extensions",
+        the certified "Key" (like the certificate metadata "Key"), its "Fingerprint" (`SHA256:...`) and "PublicKey",
+        the CA's "SigningKey" and "SigningKeyFingerprint", the "SignatureAlgorithm" and the "Comment".
+    """
+
+def private_key_matches_certificate(key:bytes, certificate:bytes, password:Optional[str]=None, probe:bool=False) -> bool:
+    """
+    Check whether a private key belongs to a certificate, by comparing their public keys.
+
+    :param key: The private key, PEM or DER encoded.
+    :param certificate: The certificate, PEM or DER encoded.
+    :param password: The password of an encrypted PKCS#8 key.
+    :param probe: If True, a test message is also signed with the key and verified with the certificate.
+
+    :return: True if the key is the certificate's private key.
+    """
+
+def get_alt_names(certificate:bytes) -> List[str]:
+    """
+    List the subject alternative names of a certificate.
+
+    :param certificate: The certificate, PEM or DER encoded.
+
+    :return: The alternative names in OpenSSL notation.
+        - example: ["DNS:www.example.com", "IP:10.0.0.1", "email:ops@example.com", "URI:https://example.com/"]
+    """
+
+def certificate_matches_host(certificate:bytes, host:str) -> bool:
+    """
+    Check whether a certificate is valid for a host, following RFC 6125.
+
+    DNS names are compared case-insensitively and a wildcard (`*.example.com`) matches exactly one left-most label.
+    The common name is only used when the certificate has no DNS alternative names, and IP addresses only match IP
+    alternative names.
+
+    :param certificate: The certificate, PEM or DER encoded.
+    :param host: The DNS name or IP address the client connects to.
+        - example: "www.example.com", "10.0.0.1", "[2001:db8::1]"
+
+    :return: True if the certificate covers the host.
+    """
+
+def certificate_matches_email(certificate:bytes, email:str) -> bool:
+    """
+    Check whether a certificate is valid for an email address.
+
+    The domain is compared case-insensitively. The subject emailAddress is only used when the certificate has no
+    email alternative names.
+
+    :param certificate: The certificate, PEM or DER encoded.
+    :param email: The email address.
+
+    :return: True if the certificate covers the email address.
+    """
+
+def certificate_allows_purpose(certificate:bytes, purpose:str) -> bool:
+    """
+    Check whether the Extended Key Usage of a certificate allows a purpose.
+
+    Certificates without the extension, or with anyExtendedKeyUsage, allow every purpose. The search functions also
+    honor the purposes a Windows store assigned to the certificate, which this function cannot see.
+
+    :param certificate: The certificate, PEM or DER encoded.
+    :param purpose: The purpose name or a dotted OID.
+        - example: "server_auth", "client_auth", "code_signing", "email_protection" (or "smime"), "time_stamping",
+          "ocsp_signing", "smart_card_logon", "document_signing".
+
+    :return: True if the certificate may be used for the purpose.
+    """
+
+def certificate_verifies_signature(certificate:bytes, data:bytes, signature:bytes, hash_alg:str="sha256", padding:Optional[str]=None) -> bool:
+    """
+    Verify a signature over `data` with the public key of a certificate, as made by `openssl dgst -sign`.
+
+    :param certificate: The certificate, PEM or DER encoded.
+    :param data: The signed data. It is hashed with `hash_alg` before verifying, except for Ed25519 keys.
+    :param signature: The signature. ECDSA signatures may be DER encoded or the fixed size `r || s` form.
+    :param hash_alg: The digest the signature was made with: "sha256", "sha384" or "sha512".
+    :param padding: The padding of RSA signatures, "pkcs1v15" (the default) or "pss". The salt length of RSA-PSS
+        signatures is read from the signature, so any salt length is accepted.
+
+    :return: True if the signature is valid.
+
+    :raises ValueError: The hash or padding is not supported, or a padding is given for a key that is not RSA.
+    """
+
+def certificate_issued_by(certificate:bytes, issuer:bytes) -> bool:
+    """
+    Check whether a certificate was signed by the key of another certificate.
+
+    Only the signature is checked, with RSA PKCS#1 v1.5, RSA-PSS, ECDSA and Ed25519 signature algorithms. The
+    `verify-chain` command also checks names, validity and CA constraints.
+
+    :param certificate: The certificate, PEM or DER encoded.
+    :param issuer: The certificate of the expected issuer, PEM or DER encoded.
+
+    :return: True if the issuer's key signed the certificate.
+
+    :raises ValueError: The signature algorithm is not supported, or does not match the issuer's key type.
+    """
+
+def rank_certificates(
+        certificates:List[Union[bytes, Dict[str, Union[str, bytes, List[str]]]]], order:Optional[List[str]]=None,
+        extended_key_usage:Optional[List[str]]=None, preferred_issuers:Optional[List[str]]=None
+    ) -> List[Dict[str, Union[int, str, List[str], Dict[str, int]]]]:
+    """
+    Rank certificates best first, so that picking one is deterministic and explainable.
+
+    Certificates are compared on the first criterion of `order`, and the next criteria break ties. Certificates that
+    still tie are ordered by thumbprint. Every criterion scores a certificate, where higher is better:
+        - "has_private_key": 1 when the private key is available.
+        - "matching_eku": 2 when every purpose of `extended_key_usage` is allowed, 1 when the purposes are not restricted.
+        - "preferred_issuer": Higher the earlier the issuer appears in `preferred_issuers`, 0 when it is not listed.
+        - "hardware_backed": 1 when the key is held by a smart card or TPM.
+        - "remaining_validity": The seconds until the certificate expires, negative once expired.
+        - "newest": The start of the validity period, in seconds since 1970.
+
+    :param certificates: Certificates (PEM or DER), PKCS#12 files without a password, or the dictionaries returned by
+        the search functions.
+    :param order: The criteria to compare on.
+        - Default: ["has_private_key", "matching_eku", "preferred_issuer", "hardware_backed", "remaining_validity", "newest"]
+    :param extended_key_usage: The purposes the certificate is wanted for, by name or dotted OID.
+        - example: ["client_auth"]
+    :param preferred_issuers: Issuers to prefer, the first one most. Each is matched against the issuer's common name or
+        its full name.
+        - example: ["Contoso Issuing CA 2", "Contoso Issuing CA 1"]
+
+    :return: A dictionary per certificate, best first, with the following keys:
+        - "Index": The position of the certificate in `certificates`.
+        - "Thumbprint": The SHA-1 thumbprint of the certificate.
+        - "Subject": The subject of the certificate.
+        - "Scores": The score of each criterion.
+        - "Order": The criteria in the order they were compared.
+    """
+
+class CertificateCandidate:
+    """
+    A certificate matching a search, handed to a custom selection callable.
+    """
+    index: int
+    """The position of the certificate in the list of matches."""
+    thumbprint: str
+    """The SHA-1 thumbprint as upper case hex."""
+    subject: str
+    issuer: str
+    friendly_name: str
+    not_before: datetime
+    not_after: datetime
+    alt_names: List[str]
+    """The subject alternative names, in OpenSSL notation."""
+    has_private_key: bool
+    key_provider: Optional[str]
+    """The name of the key storage provider holding the private key, when known."""
+    certificate: bytes
+    """The DER encoded certificate."""
+
+def select_certificates(certificates:List[Any], selection:Selection) -> List[Any]:
+    """
+    Pick from several matching certificates without user interaction.
+
+    :param certificates: Certificates (PEM or DER), PKCS#12 files without a password, or the dictionaries returned by
+        the search functions.
+    :param selection: A strategy name, or a callable receiving the list of `CertificateCandidate`.
+        - "all": Keep every certificate.
+        - "first": The first certificate.
+        - "newest": The certificate whose validity started last.
+        - "longest_valid": The certificate that expires last.
+        - "raise_if_ambiguous": The only certificate, raising `CertAmbiguous` if there are several.
+        - A callable returns the chosen candidate(s) or their `index`, or None to choose nothing.
+            - example: `lambda candidates: max(candidates, key=lambda c: c.not_after)`
+        - Ties between certificates with the same dates go to the lowest thumbprint.
+
+    :return: The chosen items of `certificates`, in their original order.
     """
 
 class CertNotExportable(Exception):
@@ -64,3 +959,18 @@ class CertNotFound(Exception):
     """
     Raised when there is no certificate found with the given parameters.
     """
+
+class CertKeyMismatch(Exception):
+    """
+    Raised when the private key exported with a certificate does not belong to that certificate.
+    """
+
+class CertAmbiguous(Exception):
+    """
+    Raised by the "raise_if_ambiguous" selection when more than one certificate matches.
+    """
+
+class CertSearchCancelled(Exception):
+    """
+    Raised by a search whose `CancellationToken` was cancelled.
+    """
@@ -0,0 +1,306 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::collections::HashMap;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use crate::store::export::ExportedCertificate;
+use crate::store::path::PathFingerprint;
+
+
+/// What a cached search is stored under.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// Where the store lives, such as `currentuser`, `localmachine` or `file`.
+    pub location: String,
+    /// The store name, or the path of a file based store.
+    pub store: String,
+    /// The search criteria, so different searches of the same store are cached separately.
+    pub query: String,
+}
+
+impl CacheKey {
+    pub fn new(location: &str, store: &str, query: &str) -> CacheKey {
+        CacheKey { location: location.to_string(), store: store.to_string(), query: query.to_string() }
+    }
+}
+
+/// Tells whether a store changed since the detector was created, so cached searches of it can be dropped.
+pub trait ChangeDetector: Send {
+    fn has_changed(&mut self) -> bool;
+}
+
+/// Detects changes to a file based store by comparing the size and modification time of its files.
+pub struct PathChangeDetector {
+    path: PathBuf,
+    fingerprint: Result<PathFingerprint>,
+}
+
+impl PathChangeDetector {
+    pub fn new<P: AsRef<Path>>(path: P) -> PathChangeDetector {
+        let path = path.as_ref().to_path_buf();
+        let fingerprint = PathFingerprint::of(&path);
+        PathChangeDetector { path, fingerprint }
+    }
+}
+
+impl ChangeDetector for PathChangeDetector {
+    fn has_changed(&mut self) -> bool {
+        // A store that could not be read is treated as changed, so the next search reads it again.
+        match (&self.fingerprint, PathFingerprint::of(&self.path)) {
+            (Ok(cached), Ok(current)) => *cached != current,
+            _ => true,
+        }
+    }
+}
+
+/// How often the cache was used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+struct CachedSearch {
+    certificates: Vec<ExportedCertificate>,
+    cached_at: SystemTime,
+    detector: Option<Box<dyn ChangeDetector>>,
+}
+
+impl CachedSearch {
+    fn is_fresh(&mut self, ttl: Duration, now: SystemTime) -> bool {
+        let within_ttl = now.duration_since(self.cached_at).map(|age| age < ttl).unwrap_or(true);
+        let all_valid = self.certificates.iter().all(|exported| now < exported.certificate.not_after());
+        let unchanged = self.detector.as_mut().is_none_or(|detector| !detector.has_changed());
+        within_ttl && all_valid && unchanged
+    }
+}
+
+#[derive(Default)]
+struct CacheState {
+    searches: HashMap<CacheKey, CachedSearch>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Keeps the certificates (and exported keys) found by store searches, so repeated searches skip the store.
+///
+/// A cached search is dropped once it is older than the TTL, once one of its certificates expires, or once the store
+/// it came from changed. Failed searches are never cached.
+pub struct QueryCache {
+    ttl: Duration,
+    state: Mutex<CacheState>,
+}
+
+impl QueryCache {
+    pub fn new(ttl: Duration) -> QueryCache {
+        QueryCache { ttl, state: Mutex::new(CacheState::default()) }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// The cached result of a search, if it is still fresh.
+    pub fn get(&self, key: &CacheKey) -> Option<Vec<ExportedCertificate>> {
+        self.get_at(key, SystemTime::now())
+    }
+
+    fn get_at(&self, key: &CacheKey, now: SystemTime) -> Option<Vec<ExportedCertificate>> {
+        let mut state = self.state();
+        let fresh = state.searches.get_mut(key).map(|search| search.is_fresh(self.ttl, now));
+
+        match fresh {
+            Some(true) => {
+                state.hits += 1;
+                state.searches.get(key).map(|search| search.certificates.clone())
+            },
+            Some(false) => {
+                state.searches.remove(key);
+                state.misses += 1;
+                None
+            },
+            None => {
+                state.misses += 1;
+                None
+            },
+        }
+    }
+
+    /// Caches the result of a search. The detector should have been created before the search ran, so changes made
+    /// while searching are not missed.
+    pub fn insert(&self, key: CacheKey, certificates: Vec<ExportedCertificate>, detector: Option<Box<dyn ChangeDetector>>) {
+        let search = CachedSearch { certificates, cached_at: SystemTime::now(), detector };
+        self.state().searches.insert(key, search);
+    }
+
+    /// Returns the cached result of a search, or runs it and caches the result when it succeeds.
+    ///
+    /// `detector` is only called on a cache miss, right before the search.
+    pub fn get_or_search<D, S, E>(&self, key: CacheKey, detector: D, search: S) -> std::result::Result<Vec<ExportedCertificate>, E>
+    where
+        D: FnOnce() -> Option<Box<dyn ChangeDetector>>,
+        S: FnOnce() -> std::result::Result<Vec<ExportedCertificate>, E>,
+    {
+        if let Some(certificates) = self.get(&key) {
+            return Ok(certificates);
+        }
+
+        let detector = detector();
+        let certificates = search()?;
+        self.insert(key, certificates.clone(), detector);
+        Ok(certificates)
+    }
+
+    /// Drops the cached search, returning whether there was one.
+    pub fn invalidate(&self, key: &CacheKey) -> bool {
+        self.state().searches.remove(key).is_some()
+    }
+
+    /// Drops every cached search of a location and/or a store, or every cached search when neither is given.
+    /// Returns how many were dropped.
+    pub fn invalidate_store(&self, location: Option<&str>, store: Option<&str>) -> usize {
+        let mut state = self.state();
+        let before = state.searches.len();
+        state.searches.retain(|key, _| {
+            let location_matches = location.is_none_or(|location| key.location.eq_ignore_ascii_case(location));
+            let store_matches = store.is_none_or(|store| key.store.eq_ignore_ascii_case(store));
+            !(location_matches && store_matches)
+        });
+        before - state.searches.len()
+    }
+
+    pub fn clear(&self) {
+        self.state().searches.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let state = self.state();
+        CacheStats { hits: state.hits, misses: state.misses, entries: state.searches.len() }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        // The cache only holds copies of search results, so it stays usable after a panic elsewhere.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Error, ErrorKind};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use crate::cert_builder::test_support::self_signed;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn exported(days: u32) -> ExportedCertificate {
+        let now = SystemTime::now();
+        let certificate = self_signed("CN=cached.example.com", now - DAY, now + DAY * days, &[]).certificate;
+        ExportedCertificate { certificate, friendly_name: "Cached".to_string(), key_provider: None, key_storage: None, pfx: vec![1, 2, 3] }
+    }
+
+    /// A detector whose store changes when the test says so.
+    struct Flag(Arc<AtomicBool>);
+
+    impl ChangeDetector for Flag {
+        fn has_changed(&mut self) -> bool {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn caches_until_ttl_or_expiry() {
+        let cache = QueryCache::new(Duration::from_secs(60));
+        let key = CacheKey::new("currentuser", "My", "all");
+        let searches = AtomicUsize::new(0);
+        let search = || {
+            searches.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, Error>(vec![exported(30)])
+        };
+
+        let first = cache.get_or_search(key.clone(), || None, search).unwrap();
+        let second = cache.get_or_search(key.clone(), || None, search).unwrap();
+        assert_eq!(first[0].certificate, second[0].certificate);
+        assert_eq!(searches.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1, entries: 1 });
+
+        // Other searches of the same store are cached separately.
+        assert!(cache.get(&CacheKey::new("currentuser", "My", "host=example.com")).is_none());
+
+        let now = SystemTime::now();
+        assert!(cache.get_at(&key, now + Duration::from_secs(30)).is_some());
+        assert!(cache.get_at(&key, now + Duration::from_secs(61)).is_none());
+        assert_eq!(cache.stats().entries, 0);
+
+        // A certificate expiring before the TTL ends the cached search early.
+        let cache = QueryCache::new(DAY * 365);
+        cache.insert(key.clone(), vec![exported(30), exported(2)], None);
+        assert!(cache.get_at(&key, now + DAY).is_some());
+        assert!(cache.get_at(&key, now + DAY * 3).is_none());
+    }
+
+    #[test]
+    fn invalidates_on_request_and_on_change() {
+        let cache = QueryCache::new(Duration::from_secs(60));
+        let changed = Arc::new(AtomicBool::new(false));
+        let mine = CacheKey::new("currentuser", "My", "all");
+        let root = CacheKey::new("localmachine", "Root", "all");
+
+        cache.insert(mine.clone(), vec![exported(30)], Some(Box::new(Flag(changed.clone()))));
+        cache.insert(root.clone(), vec![exported(30)], None);
+        assert!(cache.get(&mine).is_some());
+
+        changed.store(true, Ordering::SeqCst);
+        assert!(cache.get(&mine).is_none());
+        assert!(cache.get(&root).is_some());
+
+        cache.insert(mine.clone(), vec![exported(30)], None);
+        assert_eq!(cache.invalidate_store(Some("CurrentUser"), None), 1);
+        assert_eq!(cache.invalidate_store(None, Some("root")), 1);
+        assert!(!cache.invalidate(&root));
+
+        // Failed searches are not cached.
+        let err = cache.get_or_search(mine.clone(), || None, || Err(Error::new(ErrorKind::NotFound, "none"))).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn detects_file_changes() {
+        let path = std::env::temp_dir().join(format!("py_cert_store-cache-{}.pem", std::process::id()));
+        fs::write(&path, "first").unwrap();
+
+        let mut detector = PathChangeDetector::new(&path);
+        assert!(!detector.has_changed());
+
+        fs::write(&path, "second version").unwrap();
+        assert!(detector.has_changed());
+
+        fs::remove_file(&path).unwrap();
+        assert!(detector.has_changed());
+        assert!(!PathChangeDetector::new(&path).has_changed());
+    }
+}
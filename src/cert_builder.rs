@@ -0,0 +1,373 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use der::Encode;
+use der::asn1::{BitString, GeneralizedTime, ObjectIdentifier, OctetString, UtcTime};
+use rand_core::{OsRng, RngCore};
+use sha1::{Digest, Sha1};
+use spki::SubjectPublicKeyInfoOwned;
+use x509_cert::TbsCertificate;
+use x509_cert::certificate::Version;
+use x509_cert::ext::pkix::{AuthorityKeyIdentifier, BasicConstraints, KeyUsage, SubjectKeyIdentifier};
+use x509_cert::name::Name;
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::time::{Time, Validity};
+
+use crate::certificate::{Certificate, invalid_data};
+use crate::certificate::extensions::{AltName, UsageExtensions, encode_extension, find_extension};
+use crate::file_store::pkcs12_store::Pkcs12Store;
+use crate::keys::PrivateKey;
+
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Collects the contents of an X.509 v3 certificate before it is signed.
+///
+/// Certificates are valid for a year from the moment the builder is created and get a random 128-bit serial
+/// number unless told otherwise. A subject and authority key identifier are always added.
+#[derive(Clone, Debug)]
+pub struct CertificateBuilder {
+    subject: Name,
+    serial_number: Option<Vec<u8>>,
+    not_before: SystemTime,
+    not_after: SystemTime,
+    usage: UsageExtensions,
+    basic_constraints: Option<BasicConstraints>,
+    friendly_name: Option<String>,
+}
+
+impl CertificateBuilder {
+    /// Starts a certificate for the given RFC 4514 subject, e.g. `"CN=localhost,O=Example,C=US"`.
+    pub fn new(subject: &str) -> Result<CertificateBuilder> {
+        let subject = Name::from_str(subject).map_err(|err| {
+            Error::new(ErrorKind::InvalidInput, format!("Invalid subject \"{}\": {}", subject, err))
+        })?;
+        let now = SystemTime::now();
+        Ok(CertificateBuilder {
+            subject,
+            serial_number: None,
+            not_before: now,
+            not_after: now + Duration::from_secs(365 * SECONDS_PER_DAY),
+            usage: UsageExtensions::default(),
+            basic_constraints: None,
+            friendly_name: None,
+        })
+    }
+
+    pub fn validity(mut self, not_before: SystemTime, not_after: SystemTime) -> CertificateBuilder {
+        self.not_before = not_before;
+        self.not_after = not_after;
+        self
+    }
+
+    /// Makes the certificate valid from now for the given number of days.
+    pub fn valid_for_days(self, days: u32) -> CertificateBuilder {
+        let now = SystemTime::now();
+        self.validity(now, now + Duration::from_secs(u64::from(days) * SECONDS_PER_DAY))
+    }
+
+    /// Uses a fixed big-endian serial number instead of a random one.
+    pub fn serial_number(mut self, serial_number: &[u8]) -> CertificateBuilder {
+        self.serial_number = Some(serial_number.to_vec());
+        self
+    }
+
+    pub fn alt_name(mut self, name: AltName) -> CertificateBuilder {
+        self.usage.alt_names.push(name);
+        self
+    }
+
+    pub fn key_usage(mut self, key_usage: KeyUsage) -> CertificateBuilder {
+        self.usage.key_usage = Some(key_usage);
+        self
+    }
+
+    pub fn extended_key_usage(mut self, purpose: ObjectIdentifier) -> CertificateBuilder {
+        self.usage.extended_key_usage.push(purpose);
+        self
+    }
+
+    /// Adds a critical basic constraints extension, `ca` marks the certificate as able to issue others.
+    pub fn basic_constraints(mut self, ca: bool, path_len: Option<u8>) -> CertificateBuilder {
+        self.basic_constraints = Some(BasicConstraints { ca, path_len_constraint: path_len });
+        self
+    }
+
+    /// The friendly name is not part of the certificate, it is carried into the PKCS#12 file created from it.
+    pub fn friendly_name(mut self, friendly_name: &str) -> CertificateBuilder {
+        self.friendly_name = Some(friendly_name.to_string());
+        self
+    }
+
+    /// Creates a certificate signed by its own key.
+    pub fn self_signed(&self, key: &PrivateKey) -> Result<IssuedCertificate> {
+        let public_key = key.public_key_info()?;
+        let key_id = key_identifier(&public_key);
+        self.sign(key, self.subject.clone(), key_id, key)
+    }
+
+    /// Creates a certificate for `key` signed by the CA certificate `issuer` and its private key.
+    pub fn issued_by(&self, key: &PrivateKey, issuer: &Certificate, issuer_key: &PrivateKey) -> Result<IssuedCertificate> {
+        let issuer_tbs = &issuer.as_x509().tbs_certificate;
+        if issuer_key.public_key_info()? != issuer_tbs.subject_public_key_info {
+            return Err(Error::new(ErrorKind::InvalidInput, "The issuer private key does not belong to the issuer certificate"));
+        }
+
+        let issuer_extensions = issuer_tbs.extensions.as_deref().unwrap_or_default();
+        if let Some(constraints) = find_extension::<BasicConstraints>(issuer_extensions)? {
+            if !constraints.ca {
+                return Err(Error::new(ErrorKind::InvalidInput, "The issuer certificate is not a certificate authority"));
+            }
+        }
+
+        let issuer_key_id = match find_extension::<SubjectKeyIdentifier>(issuer_extensions)? {
+            Some(ski) => ski.0.into_bytes(),
+            None => key_identifier(&issuer_tbs.subject_public_key_info),
+        };
+
+        self.sign(key, issuer_tbs.subject.clone(), issuer_key_id, issuer_key)
+    }
+
+    fn sign(&self, key: &PrivateKey, issuer: Name, issuer_key_id: Vec<u8>, signing_key: &PrivateKey) -> Result<IssuedCertificate> {
+        let public_key = key.public_key_info()?;
+
+        let mut extensions = Vec::new();
+        if let Some(constraints) = &self.basic_constraints {
+            extensions.push(encode_extension(constraints, true)?);
+        }
+        extensions.extend(self.usage.to_extensions()?);
+        extensions.push(encode_extension(
+            &SubjectKeyIdentifier(OctetString::new(key_identifier(&public_key)).map_err(invalid_data)?),
+            false,
+        )?);
+        extensions.push(encode_extension(&AuthorityKeyIdentifier {
+            key_identifier: Some(OctetString::new(issuer_key_id).map_err(invalid_data)?),
+            authority_cert_issuer: None,
+            authority_cert_serial_number: None,
+        }, false)?);
+
+        let serial_number = match &self.serial_number {
+            Some(serial_number) => SerialNumber::new(serial_number),
+            None => {
+                let mut serial_number = [0u8; 16];
+                OsRng.fill_bytes(&mut serial_number);
+                // Serial numbers must be positive and should not start with a zero byte.
+                serial_number[0] = (serial_number[0] & 0x7f) | 0x01;
+                SerialNumber::new(&serial_number)
+            },
+        }.map_err(|err| Error::new(ErrorKind::InvalidInput, format!("Invalid serial number: {}", err)))?;
+
+        let tbs_certificate = TbsCertificate {
+            version: Version::V3,
+            serial_number,
+            signature: signing_key.signature_algorithm(),
+            issuer,
+            validity: Validity {
+                not_before: x509_time(self.not_before)?,
+                not_after: x509_time(self.not_after)?,
+            },
+            subject: self.subject.clone(),
+            subject_public_key_info: public_key,
+            issuer_unique_id: None,
+            subject_unique_id: None,
+            extensions: Some(extensions),
+        };
+
+        let signature = signing_key.sign(&tbs_certificate.to_der().map_err(invalid_data)?)?;
+        let certificate = Certificate::from_x509(x509_cert::Certificate {
+            tbs_certificate,
+            signature_algorithm: signing_key.signature_algorithm(),
+            signature: BitString::from_bytes(&signature).map_err(invalid_data)?,
+        })?;
+
+        Ok(IssuedCertificate {
+            certificate,
+            key: key.clone(),
+            friendly_name: self.friendly_name.clone(),
+        })
+    }
+}
+
+/// A newly created certificate together with its private key.
+#[derive(Clone, Debug)]
+pub struct IssuedCertificate {
+    pub certificate: Certificate,
+    pub key: PrivateKey,
+    pub friendly_name: Option<String>,
+}
+
+impl IssuedCertificate {
+    /// Bundles the certificate, its key and any chain certificates into a PKCS#12 file that Windows can import.
+    pub fn to_pkcs12(&self, password: &str, chain: &[Certificate]) -> Result<Vec<u8>> {
+        // The SHA-1 thumbprint pairs the key with its certificate, as OpenSSL does.
+        let local_key_id = Sha1::digest(self.certificate.as_der()).to_vec();
+
+        let mut store = Pkcs12Store::new();
+        store.add_certificate(self.certificate.clone(), self.friendly_name.as_deref(), Some(&local_key_id));
+        for cert in chain {
+            store.add_certificate(cert.clone(), None, None);
+        }
+        store.add_key(&self.key.to_pkcs8_der()?, self.friendly_name.as_deref(), Some(&local_key_id));
+
+        store.to_der(password)
+    }
+}
+
+/// The RFC 5280 method 1 key identifier: the SHA-1 hash of the public key bits.
+fn key_identifier(public_key: &SubjectPublicKeyInfoOwned) -> Vec<u8> {
+    Sha1::digest(public_key.subject_public_key.raw_bytes()).to_vec()
+}
+
+/// RFC 5280 requires UTCTime for dates through 2049 and GeneralizedTime afterwards.
+fn x509_time(time: SystemTime) -> Result<Time> {
+    let invalid_time = |err| Error::new(ErrorKind::InvalidInput, format!("Invalid validity date: {}", err));
+    match UtcTime::from_system_time(time) {
+        Ok(time) => Ok(Time::UtcTime(time)),
+        Err(_) => Ok(Time::GeneralTime(GeneralizedTime::from_system_time(time).map_err(invalid_time)?)),
+    }
+}
+
+/// Certificates with throwaway P-256 keys for the unit tests.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::time::SystemTime;
+
+    use super::{CertificateBuilder, IssuedCertificate};
+    use crate::certificate::extensions::extended_key_usage_oid;
+    use crate::keys::{KeyAlgorithm, PrivateKey};
+
+    /// Signs `builder` with a new P-256 key, issued by `issuer` or self-signed without one.
+    pub(crate) fn sign(builder: CertificateBuilder, issuer: Option<&IssuedCertificate>) -> IssuedCertificate {
+        let key = PrivateKey::generate(KeyAlgorithm::EcP256).unwrap();
+        match issuer {
+            Some(issuer) => builder.issued_by(&key, &issuer.certificate, &issuer.key).unwrap(),
+            None => builder.self_signed(&key).unwrap(),
+        }
+    }
+
+    /// A self-signed certificate valid from `not_before` to `not_after`, with an ExtendedKeyUsage of the `eku`
+    /// purposes unless there are none.
+    pub(crate) fn self_signed(subject: &str, not_before: SystemTime, not_after: SystemTime, eku: &[&str]) -> IssuedCertificate {
+        let mut builder = CertificateBuilder::new(subject).unwrap().validity(not_before, not_after);
+        for purpose in eku {
+            builder = builder.extended_key_usage(extended_key_usage_oid(purpose).unwrap());
+        }
+        sign(builder, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::KeyAlgorithm;
+    use crate::keys::verify::verify_signature;
+
+    const CA_CERT_PEM: &[u8] = include_bytes!("../test/certs/ca_certificate.crt");
+    const CA_KEY_PEM: &str = include_str!("../test/certs/ca_private_key.pem");
+    const KEY_PEM: &str = include_str!("../test/certs/private_key.pem");
+
+    fn signed_by(cert: &Certificate, issuer: &Certificate) -> bool {
+        let x509 = cert.as_x509();
+        let Some(signature) = x509.signature.as_bytes() else {
+            return false;
+        };
+        verify_signature(
+            &issuer.as_x509().tbs_certificate.subject_public_key_info,
+            &x509.signature_algorithm,
+            &x509.tbs_certificate.to_der().unwrap(),
+            signature,
+        ).unwrap()
+    }
+
+    #[test]
+    fn creates_self_signed_certificate() {
+        let key = PrivateKey::generate(KeyAlgorithm::EcP256).unwrap();
+        let issued = CertificateBuilder::new("CN=localhost,O=py_cert_store").unwrap()
+            .alt_name("DNS:localhost".parse().unwrap())
+            .alt_name("IP:127.0.0.1".parse().unwrap())
+            .valid_for_days(30)
+            .self_signed(&key)
+            .unwrap();
+
+        let tbs = &issued.certificate.as_x509().tbs_certificate;
+        assert_eq!(tbs.version, Version::V3);
+        assert_eq!(tbs.issuer, tbs.subject);
+        assert_eq!(tbs.subject.to_string(), "CN=localhost,O=py_cert_store");
+        assert!(signed_by(&issued.certificate, &issued.certificate));
+
+        let usage = UsageExtensions::from_extensions(tbs.extensions.as_deref().unwrap()).unwrap();
+        assert_eq!(usage.alt_names, vec!["DNS:localhost".parse().unwrap(), "IP:127.0.0.1".parse().unwrap()]);
+
+        let lifetime = tbs.validity.not_after.to_unix_duration() - tbs.validity.not_before.to_unix_duration();
+        assert_eq!(lifetime.as_secs(), 30 * SECONDS_PER_DAY);
+    }
+
+    #[test]
+    fn issues_certificate_from_ca() {
+        let ca = Certificate::from_pem(CA_CERT_PEM).unwrap();
+        let ca_key = PrivateKey::from_pkcs8_pem(CA_KEY_PEM).unwrap();
+        let key = PrivateKey::generate(KeyAlgorithm::Ed25519).unwrap();
+
+        let issued = CertificateBuilder::new("CN=client.example.com").unwrap()
+            .serial_number(&[0x10, 0x01])
+            .key_usage(crate::certificate::extensions::key_usage_from_names(&["digital_signature"]).unwrap())
+            .extended_key_usage(crate::certificate::extensions::extended_key_usage_oid("client_auth").unwrap())
+            .basic_constraints(false, None)
+            .issued_by(&key, &ca, &ca_key)
+            .unwrap();
+
+        let tbs = &issued.certificate.as_x509().tbs_certificate;
+        assert_eq!(tbs.issuer, ca.as_x509().tbs_certificate.subject);
+        assert_eq!(tbs.serial_number.as_bytes(), &[0x10, 0x01]);
+        assert!(signed_by(&issued.certificate, &ca));
+
+        let extensions = tbs.extensions.as_deref().unwrap();
+        let constraints = find_extension::<BasicConstraints>(extensions).unwrap().unwrap();
+        assert!(!constraints.ca);
+        assert!(extensions.iter().find(|ext| ext.extn_id == <BasicConstraints as der::oid::AssociatedOid>::OID).unwrap().critical);
+    }
+
+    #[test]
+    fn rejects_mismatched_issuer_key() {
+        let ca = Certificate::from_pem(CA_CERT_PEM).unwrap();
+        let wrong_key = PrivateKey::from_pkcs8_pem(KEY_PEM).unwrap();
+        let key = PrivateKey::generate(KeyAlgorithm::EcP256).unwrap();
+
+        let err = CertificateBuilder::new("CN=client").unwrap().issued_by(&key, &ca, &wrong_key).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn exports_to_pkcs12_with_friendly_name() {
+        let key = PrivateKey::generate(KeyAlgorithm::EcP384).unwrap();
+        let issued = CertificateBuilder::new("CN=Example Exportable Cert").unwrap()
+            .friendly_name("Example Exportable Cert")
+            .self_signed(&key)
+            .unwrap();
+
+        let store = Pkcs12Store::from_der(&issued.to_pkcs12("pass1234", &[]).unwrap(), "pass1234").unwrap();
+        let bag = &store.certificate_bags()[0];
+        assert_eq!(bag.value, issued.certificate);
+        assert_eq!(bag.friendly_name.as_deref(), Some("Example Exportable Cert"));
+        assert_eq!(store.key_bags()[0].local_key_id, bag.local_key_id);
+    }
+}
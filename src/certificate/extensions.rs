@@ -0,0 +1,272 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::fmt;
+use std::io::{Result, Error, ErrorKind};
+use std::net::IpAddr;
+use std::str::FromStr;
+use der::{Decode, Encode};
+use der::asn1::{Ia5String, ObjectIdentifier, OctetString};
+use der::flagset::FlagSet;
+use der::oid::AssociatedOid;
+use x509_cert::ext::Extension;
+use x509_cert::ext::pkix::{ExtendedKeyUsage, KeyUsage, KeyUsages, SubjectAltName};
+use x509_cert::ext::pkix::name::GeneralName;
+
+use crate::certificate::invalid_data;
+
+
+/// The KeyUsage bits, named the way they are accepted from and returned to Python.
+pub const KEY_USAGE_NAMES: [(&str, KeyUsages); 9] = [
+    ("digital_signature", KeyUsages::DigitalSignature),
+    ("non_repudiation", KeyUsages::NonRepudiation),
+    ("key_encipherment", KeyUsages::KeyEncipherment),
+    ("data_encipherment", KeyUsages::DataEncipherment),
+    ("key_agreement", KeyUsages::KeyAgreement),
+    ("key_cert_sign", KeyUsages::KeyCertSign),
+    ("crl_sign", KeyUsages::CRLSign),
+    ("encipher_only", KeyUsages::EncipherOnly),
+    ("decipher_only", KeyUsages::DecipherOnly),
+];
+
+/// The KeyUsage bits, named the way Windows shows them in the certificate details.
+const KEY_USAGE_DISPLAY_NAMES: [(KeyUsages, &str); 9] = [
+    (KeyUsages::DigitalSignature, "Digital Signature"),
+    (KeyUsages::NonRepudiation, "Non-Repudiation"),
+    (KeyUsages::KeyEncipherment, "Key Encipherment"),
+    (KeyUsages::DataEncipherment, "Data Encipherment"),
+    (KeyUsages::KeyAgreement, "Key Agreement"),
+    (KeyUsages::KeyCertSign, "Certificate Signing"),
+    (KeyUsages::CRLSign, "Off-line CRL Signing, CRL Signing"),
+    (KeyUsages::EncipherOnly, "Encipher Only"),
+    (KeyUsages::DecipherOnly, "Decipher Only"),
+];
+
+/// Well known ExtendedKeyUsage purposes.
+pub const EXTENDED_KEY_USAGE_NAMES: [(&str, ObjectIdentifier); 8] = [
+    ("server_auth", ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.1")),
+    ("client_auth", ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.2")),
+    ("code_signing", ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.3")),
+    ("email_protection", ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.4")),
+    ("time_stamping", ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.8")),
+    ("ocsp_signing", ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.9")),
+    ("smart_card_logon", ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.20.2.2")),
+    ("document_signing", ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.10.3.12")),
+];
+
+/// A single entry of a SubjectAltName extension.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AltName {
+    Dns(String),
+    Email(String),
+    Ip(IpAddr),
+    Uri(String),
+    /// Any other GeneralName choice, kept as its OpenSSL style description.
+    Other(String),
+}
+
+impl AltName {
+    pub fn to_general_name(&self) -> Result<GeneralName> {
+        let ia5 = |value: &str| Ia5String::new(value).map_err(invalid_data);
+        match self {
+            AltName::Dns(name) => Ok(GeneralName::DnsName(ia5(name)?)),
+            AltName::Email(email) => Ok(GeneralName::Rfc822Name(ia5(email)?)),
+            AltName::Ip(ip) => Ok(GeneralName::from(*ip)),
+            AltName::Uri(uri) => Ok(GeneralName::UniformResourceIdentifier(ia5(uri)?)),
+            AltName::Other(other) => Err(Error::new(ErrorKind::InvalidInput, format!("Cannot encode the alternative name \"{}\"", other))),
+        }
+    }
+
+    pub fn from_general_name(name: &GeneralName) -> AltName {
+        match name {
+            GeneralName::DnsName(dns) => AltName::Dns(dns.to_string()),
+            GeneralName::Rfc822Name(email) => AltName::Email(email.to_string()),
+            GeneralName::UniformResourceIdentifier(uri) => AltName::Uri(uri.to_string()),
+            GeneralName::IpAddress(ip) => match ip.as_bytes().len() {
+                4 => {
+                    let mut octets = [0u8; 4];
+                    octets.copy_from_slice(ip.as_bytes());
+                    AltName::Ip(IpAddr::from(octets))
+                },
+                16 => {
+                    let mut octets = [0u8; 16];
+                    octets.copy_from_slice(ip.as_bytes());
+                    AltName::Ip(IpAddr::from(octets))
+                },
+                _ => AltName::Other(format!("IP Address:<invalid length {}>", ip.as_bytes().len())),
+            },
+            GeneralName::DirectoryName(name) => AltName::Other(format!("DirName:{}", name)),
+            GeneralName::RegisteredId(oid) => AltName::Other(format!("Registered ID:{}", oid)),
+            GeneralName::OtherName(other) => AltName::Other(format!("othername:{}", other.type_id)),
+            GeneralName::EdiPartyName(_) => AltName::Other("EdiPartyName:<unsupported>".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for AltName {
+    /// Uses the OpenSSL `subjectAltName` notation, e.g. `DNS:example.com` or `IP:127.0.0.1`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AltName::Dns(name) => write!(f, "DNS:{}", name),
+            AltName::Email(email) => write!(f, "email:{}", email),
+            AltName::Ip(ip) => write!(f, "IP:{}", ip),
+            AltName::Uri(uri) => write!(f, "URI:{}", uri),
+            AltName::Other(other) => write!(f, "{}", other),
+        }
+    }
+}
+
+impl FromStr for AltName {
+    type Err = Error;
+
+    /// Parses the OpenSSL notation produced by `Display`.
+    fn from_str(s: &str) -> Result<AltName> {
+        let (kind, value) = s.split_once(':')
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Expected \"TYPE:value\", got \"{}\"", s)))?;
+        match kind.trim().to_uppercase().as_str() {
+            "DNS" => Ok(AltName::Dns(value.to_string())),
+            "EMAIL" => Ok(AltName::Email(value.to_string())),
+            "IP" => value.parse().map(AltName::Ip).map_err(|err| Error::new(ErrorKind::InvalidInput, err)),
+            "URI" => Ok(AltName::Uri(value.to_string())),
+            _ => Err(Error::new(ErrorKind::InvalidInput, format!("Unsupported alternative name type \"{}\"", kind))),
+        }
+    }
+}
+
+/// Builds a KeyUsage from its bit names (see [`KEY_USAGE_NAMES`]).
+pub fn key_usage_from_names<S: AsRef<str>>(names: &[S]) -> Result<KeyUsage> {
+    let mut flags = FlagSet::<KeyUsages>::default();
+    for name in names {
+        let name = name.as_ref();
+        let (_, flag) = KEY_USAGE_NAMES.iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unknown key usage \"{}\"", name)))?;
+        flags |= *flag;
+    }
+    Ok(KeyUsage(flags))
+}
+
+pub fn key_usage_names(key_usage: &KeyUsage) -> Vec<&'static str> {
+    KEY_USAGE_NAMES.iter()
+        .filter(|(_, flag)| key_usage.0.contains(*flag))
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Resolves an ExtendedKeyUsage purpose name (see [`EXTENDED_KEY_USAGE_NAMES`]) or a dotted OID string.
+pub fn extended_key_usage_oid(name: &str) -> Result<ObjectIdentifier> {
+    if let Some((_, oid)) = EXTENDED_KEY_USAGE_NAMES.iter().find(|(known, _)| known.eq_ignore_ascii_case(name)) {
+        return Ok(*oid);
+    }
+    ObjectIdentifier::new(name).map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Unknown extended key usage \"{}\"", name)))
+}
+
+/// The purpose name of an ExtendedKeyUsage OID, or its dotted string when the purpose is not a well known one.
+pub fn extended_key_usage_name(oid: &ObjectIdentifier) -> String {
+    EXTENDED_KEY_USAGE_NAMES.iter()
+        .find(|(_, known)| known == oid)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| oid.to_string())
+}
+
+/// Describes the KeyUsage, ExtendedKeyUsage and SubjectAltName extensions the way Windows formats them, e.g.
+/// "Digital Signature, Key Encipherment", so they can be searched like `find_windows_cert_by_extension` does.
+/// Other extensions have no description.
+pub fn describe_extension(extension: &Extension) -> Result<Option<String>> {
+    let value = extension.extn_value.as_bytes();
+    let parts: Vec<String> = if extension.extn_id == KeyUsage::OID {
+        let key_usage = KeyUsage::from_der(value).map_err(invalid_data)?;
+        KEY_USAGE_DISPLAY_NAMES.iter()
+            .filter(|(flag, _)| key_usage.0.contains(*flag))
+            .map(|(_, name)| name.to_string())
+            .collect()
+    } else if extension.extn_id == ExtendedKeyUsage::OID {
+        ExtendedKeyUsage::from_der(value).map_err(invalid_data)?.0.iter()
+            .map(|oid| format!("{} ({})", extended_key_usage_name(oid), oid))
+            .collect()
+    } else if extension.extn_id == SubjectAltName::OID {
+        SubjectAltName::from_der(value).map_err(invalid_data)?.0.iter()
+            .map(|name| AltName::from_general_name(name).to_string())
+            .collect()
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(parts.join(", ")))
+}
+
+/// Encodes an extension value into an `Extension`.
+pub fn encode_extension<T: AssociatedOid + Encode>(value: &T, critical: bool) -> Result<Extension> {
+    Ok(Extension {
+        extn_id: T::OID,
+        critical,
+        extn_value: OctetString::new(value.to_der().map_err(invalid_data)?).map_err(invalid_data)?,
+    })
+}
+
+/// Finds and decodes an extension by the OID associated with its type.
+pub fn find_extension<'a, T: AssociatedOid + Decode<'a>>(extensions: &'a [Extension]) -> Result<Option<T>> {
+    match extensions.iter().find(|ext| ext.extn_id == T::OID) {
+        Some(ext) => T::from_der(ext.extn_value.as_bytes()).map(Some).map_err(invalid_data),
+        None => Ok(None),
+    }
+}
+
+/// The SubjectAltName entries, KeyUsage bits and ExtendedKeyUsage purposes found in a list of extensions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UsageExtensions {
+    pub alt_names: Vec<AltName>,
+    pub key_usage: Option<KeyUsage>,
+    pub extended_key_usage: Vec<ObjectIdentifier>,
+}
+
+impl UsageExtensions {
+    pub fn from_extensions(extensions: &[Extension]) -> Result<UsageExtensions> {
+        let alt_names = find_extension::<SubjectAltName>(extensions)?
+            .map(|san| san.0.iter().map(AltName::from_general_name).collect())
+            .unwrap_or_default();
+        let key_usage = find_extension::<KeyUsage>(extensions)?;
+        let extended_key_usage = find_extension::<ExtendedKeyUsage>(extensions)?
+            .map(|eku| eku.0)
+            .unwrap_or_default();
+
+        Ok(UsageExtensions { alt_names, key_usage, extended_key_usage })
+    }
+
+    /// Encodes the non-empty parts as extensions. KeyUsage is marked critical as RFC 5280 recommends.
+    pub fn to_extensions(&self) -> Result<Vec<Extension>> {
+        let mut extensions = Vec::new();
+
+        if let Some(key_usage) = &self.key_usage {
+            extensions.push(encode_extension(key_usage, true)?);
+        }
+
+        if !self.extended_key_usage.is_empty() {
+            extensions.push(encode_extension(&ExtendedKeyUsage(self.extended_key_usage.clone()), false)?);
+        }
+
+        if !self.alt_names.is_empty() {
+            let names = self.alt_names.iter()
+                .map(AltName::to_general_name)
+                .collect::<Result<Vec<_>>>()?;
+            extensions.push(encode_extension(&SubjectAltName(names), false)?);
+        }
+
+        Ok(extensions)
+    }
+}
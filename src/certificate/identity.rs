@@ -0,0 +1,211 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::net::IpAddr;
+
+use crate::certificate::extensions::AltName;
+
+
+/// Parses a host that is an IP address literal, including a bracketed IPv6 address (`[::1]`).
+pub fn parse_ip_host(host: &str) -> Option<IpAddr> {
+    let host = host.strip_prefix('[').and_then(|host| host.strip_suffix(']')).unwrap_or(host);
+    host.parse().ok()
+}
+
+/// Matches a DNS name presented by a certificate against the host the client is connecting to (RFC 6125 Section 6.4).
+///
+/// Names are compared case-insensitively, ignoring a trailing dot. A wildcard is only honoured as the complete
+/// left-most label (`*.example.com`), matches exactly one label, and needs at least two labels after it. Wildcards
+/// are never matched against internationalized (`xn--`) labels.
+pub fn dns_name_matches(presented: &str, host: &str) -> bool {
+    let presented = normalize_dns_name(presented);
+    let host = normalize_dns_name(host);
+
+    if presented.is_empty() || host.is_empty() || parse_ip_host(&host).is_some() {
+        return false;
+    }
+
+    match presented.strip_prefix("*.") {
+        Some(suffix) => {
+            if suffix.contains('*') || suffix.split('.').count() < 2 {
+                return false;
+            }
+
+            match host.split_once('.') {
+                Some((label, host_suffix)) => {
+                    !label.is_empty() && !label.starts_with("xn--") && host_suffix == suffix
+                },
+                None => false,
+            }
+        },
+        None => !presented.contains('*') && presented == host,
+    }
+}
+
+/// Matches an email address presented by a certificate against the expected address (RFC 5280 Section 7.5).
+///
+/// The local part is compared exactly and the domain case-insensitively.
+pub fn email_matches(presented: &str, email: &str) -> bool {
+    match (presented.rsplit_once('@'), email.rsplit_once('@')) {
+        (Some((presented_local, presented_domain)), Some((local, domain))) => {
+            !local.is_empty() && presented_local == local && normalize_dns_name(presented_domain) == normalize_dns_name(domain)
+        },
+        _ => false,
+    }
+}
+
+/// Checks whether the alternative names (or, without a SubjectAltName extension, the subject common names)
+/// cover `host`, which is a DNS name or an IP address literal.
+///
+/// Following RFC 6125 Section 6.4.4, the common name is only used when the certificate has no DNS name
+/// alternative names, and IP addresses only ever match IP address alternative names.
+pub fn matches_host(alt_names: &[AltName], common_names: &[String], host: &str) -> bool {
+    if let Some(ip) = parse_ip_host(host) {
+        return alt_names.iter().any(|name| matches!(name, AltName::Ip(presented) if *presented == ip));
+    }
+
+    let dns_names: Vec<&String> = alt_names.iter()
+        .filter_map(|name| match name {
+            AltName::Dns(dns) => Some(dns),
+            _ => None,
+        })
+        .collect();
+
+    if dns_names.is_empty() {
+        common_names.iter().any(|name| dns_name_matches(name, host))
+    } else {
+        dns_names.into_iter().any(|name| dns_name_matches(name, host))
+    }
+}
+
+/// Checks whether the email alternative names (or, without any, the subject `emailAddress`) cover `email`.
+pub fn matches_email(alt_names: &[AltName], subject_emails: &[String], email: &str) -> bool {
+    let emails: Vec<&String> = alt_names.iter()
+        .filter_map(|name| match name {
+            AltName::Email(email) => Some(email),
+            _ => None,
+        })
+        .collect();
+
+    if emails.is_empty() {
+        subject_emails.iter().any(|presented| email_matches(presented, email))
+    } else {
+        emails.into_iter().any(|presented| email_matches(presented, email))
+    }
+}
+
+fn normalize_dns_name(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert_builder::CertificateBuilder;
+    use crate::cert_builder::test_support::sign;
+
+    #[test]
+    fn matches_dns_names() {
+        let cases = [
+            ("example.com", "example.com", true),
+            ("Example.COM", "example.com", true),
+            ("example.com.", "example.com", true),
+            ("example.com", "EXAMPLE.com.", true),
+            ("example.com", "www.example.com", false),
+            ("www.example.com", "example.com", false),
+            ("*.example.com", "www.example.com", true),
+            ("*.example.com", "WWW.Example.Com", true),
+            ("*.example.com", "example.com", false),
+            ("*.example.com", "a.b.example.com", false),
+            ("*.example.com", ".example.com", false),
+            ("*.example.com", "xn--caf-dma.example.com", false),
+            ("*.com", "example.com", false),
+            ("*", "example", false),
+            ("w*.example.com", "www.example.com", false),
+            ("www.*.com", "www.example.com", false),
+            ("*.*.example.com", "a.b.example.com", false),
+            ("xn--caf-dma.example.com", "xn--caf-dma.example.com", true),
+            ("", "example.com", false),
+            ("example.com", "", false),
+            ("127.0.0.1", "127.0.0.1", false),
+        ];
+
+        for (presented, host, expected) in cases {
+            assert_eq!(dns_name_matches(presented, host), expected, "{} against {}", presented, host);
+        }
+    }
+
+    #[test]
+    fn matches_email_addresses() {
+        let cases = [
+            ("jose@example.com", "jose@example.com", true),
+            ("jose@Example.COM", "jose@example.com", true),
+            ("Jose@example.com", "jose@example.com", false),
+            ("jose@example.com", "jose@sub.example.com", false),
+            ("jose@example.com", "example.com", false),
+            ("\"a@b\"@example.com", "\"a@b\"@example.com", true),
+            ("@example.com", "@example.com", false),
+        ];
+
+        for (presented, email, expected) in cases {
+            assert_eq!(email_matches(presented, email), expected, "{} against {}", presented, email);
+        }
+    }
+
+    #[test]
+    fn matches_certificate_identities() {
+        let with_alt_names = sign(CertificateBuilder::new("CN=legacy.example.org,O=py_cert_store").unwrap()
+            .alt_name("DNS:*.example.com".parse().unwrap())
+            .alt_name("DNS:example.net".parse().unwrap())
+            .alt_name("IP:10.0.0.1".parse().unwrap())
+            .alt_name("IP:2001:db8::1".parse().unwrap())
+            .alt_name("email:ops@example.com".parse().unwrap())
+            .alt_name("URI:https://example.com/".parse().unwrap()), None).certificate;
+        let common_name_only = sign(
+            CertificateBuilder::new("CN=legacy.example.com,1.2.840.113549.1.9.1=#1611616C696365406578616D706C652E636F6D").unwrap(),
+            None,
+        ).certificate;
+
+        assert_eq!(with_alt_names.alt_names().unwrap().len(), 6);
+        assert!(common_name_only.alt_names().unwrap().is_empty());
+
+        let hosts = [
+            (&with_alt_names, "www.example.com", true),
+            (&with_alt_names, "example.net", true),
+            (&with_alt_names, "example.com", false),
+            // The common name is ignored once DNS alternative names are present.
+            (&with_alt_names, "legacy.example.org", false),
+            (&with_alt_names, "10.0.0.1", true),
+            (&with_alt_names, "10.0.0.2", false),
+            (&with_alt_names, "[2001:db8::1]", true),
+            (&with_alt_names, "2001:0db8:0:0:0:0:0:1", true),
+            (&common_name_only, "legacy.example.com", true),
+            (&common_name_only, "other.example.com", false),
+            (&common_name_only, "10.0.0.1", false),
+        ];
+
+        for (cert, host, expected) in hosts {
+            assert_eq!(cert.matches_host(host).unwrap(), expected, "{}", host);
+        }
+
+        assert!(with_alt_names.matches_email("ops@EXAMPLE.com").unwrap());
+        assert!(!with_alt_names.matches_email("alice@example.com").unwrap());
+        assert!(common_name_only.matches_email("alice@example.com").unwrap());
+    }
+}
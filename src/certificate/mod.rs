@@ -0,0 +1,160 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+use std::time::SystemTime;
+use der::{Decode, Encode, EncodePem};
+use der::asn1::ObjectIdentifier;
+use der::pem::LineEnding;
+use sha1::{Digest, Sha1};
+use x509_cert::ext::pkix::SubjectAltName;
+
+pub mod extensions;
+pub mod identity;
+pub mod name;
+pub mod purpose;
+
+use extensions::AltName;
+use name::DistinguishedName;
+
+
+/// A parsed X.509 certificate that does not depend on any platform store.
+///
+/// The original DER encoding is kept next to the parsed structure so that the certificate can be handed
+/// back to callers (or written to another store) byte-for-byte as it was read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Certificate {
+    der: Vec<u8>,
+    inner: x509_cert::Certificate,
+}
+
+impl Certificate {
+    /// Parses a DER encoded certificate.
+    pub fn from_der(der: &[u8]) -> Result<Certificate> {
+        let inner = x509_cert::Certificate::from_der(der).map_err(invalid_data)?;
+        Ok(Certificate { der: der.to_vec(), inner })
+    }
+
+    /// Parses a single PEM encoded certificate (`-----BEGIN CERTIFICATE-----`).
+    pub fn from_pem(pem: &[u8]) -> Result<Certificate> {
+        let (label, der) = der::pem::decode_vec(pem).map_err(invalid_data)?;
+        if label != "CERTIFICATE" {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Unexpected PEM label \"{}\"", label)));
+        }
+        Certificate::from_der(&der)
+    }
+
+    /// Parses a certificate, detecting whether it is DER or PEM encoded.
+    pub fn from_bytes(data: &[u8]) -> Result<Certificate> {
+        if data.trim_ascii_start().starts_with(b"-----BEGIN") {
+            Certificate::from_pem(data.trim_ascii())
+        } else {
+            Certificate::from_der(data)
+        }
+    }
+
+    /// Wraps an already parsed certificate.
+    pub fn from_x509(inner: x509_cert::Certificate) -> Result<Certificate> {
+        let der = inner.to_der().map_err(invalid_data)?;
+        Ok(Certificate { der, inner })
+    }
+
+    /// The DER encoding of the certificate.
+    pub fn as_der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// The certificate encoded as PEM.
+    pub fn to_pem(&self) -> Result<String> {
+        self.inner.to_pem(LineEnding::LF).map_err(invalid_data)
+    }
+
+    /// The parsed certificate structure.
+    pub fn as_x509(&self) -> &x509_cert::Certificate {
+        &self.inner
+    }
+
+    /// The subject distinguished name.
+    pub fn subject(&self) -> DistinguishedName {
+        DistinguishedName::from(&self.inner.tbs_certificate.subject)
+    }
+
+    /// The issuer distinguished name.
+    pub fn issuer(&self) -> DistinguishedName {
+        DistinguishedName::from(&self.inner.tbs_certificate.issuer)
+    }
+
+    /// The SHA-1 thumbprint as upper case hex, the way Windows shows it.
+    pub fn thumbprint(&self) -> String {
+        hex(&Sha1::digest(&self.der))
+    }
+
+    /// The start of the validity period.
+    pub fn not_before(&self) -> SystemTime {
+        self.inner.tbs_certificate.validity.not_before.to_system_time()
+    }
+
+    /// The end of the validity period.
+    pub fn not_after(&self) -> SystemTime {
+        self.inner.tbs_certificate.validity.not_after.to_system_time()
+    }
+
+    /// The entries of the SubjectAltName extension, empty when the certificate has none.
+    pub fn alt_names(&self) -> Result<Vec<AltName>> {
+        let extensions = self.inner.tbs_certificate.extensions.as_deref().unwrap_or_default();
+        Ok(extensions::find_extension::<SubjectAltName>(extensions)?
+            .map(|san| san.0.iter().map(AltName::from_general_name).collect())
+            .unwrap_or_default())
+    }
+
+    /// Checks whether the certificate is valid for a DNS name or IP address, see [`identity::matches_host`].
+    pub fn matches_host(&self, host: &str) -> Result<bool> {
+        let common_names = self.subject().get_all("CN")?;
+        Ok(identity::matches_host(&self.alt_names()?, &common_names, host))
+    }
+
+    /// Checks whether the certificate has an extension and, when `value` is given, whether the extension's
+    /// description contains it (see [`extensions::describe_extension`]), like `find_windows_cert_by_extension`.
+    pub fn has_extension(&self, oid: &ObjectIdentifier, value: Option<&str>) -> Result<bool> {
+        let extensions = self.inner.tbs_certificate.extensions.as_deref().unwrap_or_default();
+        let Some(extension) = extensions.iter().find(|extension| extension.extn_id == *oid) else {
+            return Ok(false);
+        };
+        match value {
+            Some(value) => Ok(extensions::describe_extension(extension)?.is_some_and(|description| description.contains(value))),
+            None => Ok(true),
+        }
+    }
+
+    /// Checks whether the certificate is valid for an email address, see [`identity::matches_email`].
+    pub fn matches_email(&self, email: &str) -> Result<bool> {
+        let subject_emails = self.subject().get_all("emailAddress")?;
+        Ok(identity::matches_email(&self.alt_names()?, &subject_emails, email))
+    }
+}
+
+/// Upper case hex without separators, the way Windows shows thumbprints and serial numbers.
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Converts an ASN.1 error into the `std::io::Error` used throughout the crate.
+pub(crate) fn invalid_data<E: std::fmt::Display>(err: E) -> Error {
+    Error::new(ErrorKind::InvalidData, err.to_string())
+}
@@ -0,0 +1,424 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::fmt;
+use std::fmt::Write;
+use std::io::{Result, Error, ErrorKind};
+use std::str::FromStr;
+use der::{Any, Decode, Encode, Tag, Tagged};
+use der::asn1::{ObjectIdentifier, SetOfVec};
+use x509_cert::attr::AttributeTypeAndValue;
+use x509_cert::name::{Name, RelativeDistinguishedName};
+
+use crate::certificate::{hex, invalid_data};
+
+
+/// The names an attribute type is known by in each rendering.
+struct AttributeNames {
+    oid: ObjectIdentifier,
+    /// The RFC 4514 Section 3 short name, other attributes are written as a dotted OID.
+    rfc4514: Option<&'static str>,
+    /// The OpenSSL short name.
+    openssl: &'static str,
+    /// The X500 key used by `CertNameToStr`, and therefore by .NET. Others are written as `OID.<dotted>`.
+    dotnet: Option<&'static str>,
+    /// The long name from X.520 / PKCS#9.
+    long: &'static str,
+}
+
+const fn names(
+    oid: &str, rfc4514: Option<&'static str>, openssl: &'static str, dotnet: Option<&'static str>, long: &'static str
+) -> AttributeNames {
+    AttributeNames { oid: ObjectIdentifier::new_unwrap(oid), rfc4514, openssl, dotnet, long }
+}
+
+const ATTRIBUTE_NAMES: &[AttributeNames] = &[
+    names("2.5.4.3", Some("CN"), "CN", Some("CN"), "commonName"),
+    names("2.5.4.4", None, "SN", Some("SN"), "surname"),
+    names("2.5.4.5", None, "serialNumber", Some("SERIALNUMBER"), "serialNumber"),
+    names("2.5.4.6", Some("C"), "C", Some("C"), "countryName"),
+    names("2.5.4.7", Some("L"), "L", Some("L"), "localityName"),
+    names("2.5.4.8", Some("ST"), "ST", Some("S"), "stateOrProvinceName"),
+    names("2.5.4.9", Some("STREET"), "street", Some("STREET"), "streetAddress"),
+    names("2.5.4.10", Some("O"), "O", Some("O"), "organizationName"),
+    names("2.5.4.11", Some("OU"), "OU", Some("OU"), "organizationalUnitName"),
+    names("2.5.4.12", None, "title", Some("T"), "title"),
+    names("2.5.4.13", None, "description", Some("Description"), "description"),
+    names("2.5.4.15", None, "businessCategory", None, "businessCategory"),
+    names("2.5.4.17", None, "postalCode", Some("PostalCode"), "postalCode"),
+    names("2.5.4.18", None, "postOfficeBox", Some("POBox"), "postOfficeBox"),
+    names("2.5.4.20", None, "telephoneNumber", Some("Phone"), "telephoneNumber"),
+    names("2.5.4.24", None, "x121Address", Some("X21Address"), "x121Address"),
+    names("2.5.4.42", None, "GN", Some("G"), "givenName"),
+    names("2.5.4.43", None, "initials", Some("I"), "initials"),
+    names("2.5.4.46", None, "dnQualifier", Some("dnQualifier"), "dnQualifier"),
+    names("1.2.840.113549.1.9.1", None, "emailAddress", Some("E"), "emailAddress"),
+    names("1.2.840.113549.1.9.2", None, "unstructuredName", None, "unstructuredName"),
+    names("0.9.2342.19200300.100.1.1", Some("UID"), "UID", None, "userId"),
+    names("0.9.2342.19200300.100.1.25", Some("DC"), "DC", Some("DC"), "domainComponent"),
+];
+
+fn attribute_names(oid: &ObjectIdentifier) -> Option<&'static AttributeNames> {
+    ATTRIBUTE_NAMES.iter().find(|names| &names.oid == oid)
+}
+
+/// Resolves an attribute key: a dotted OID (optionally prefixed with `OID.`) or any short or long name used by
+/// RFC 4514, OpenSSL or .NET, compared case-insensitively.
+pub fn attribute_oid(key: &str) -> Result<ObjectIdentifier> {
+    let dotted = key.strip_prefix("OID.").or_else(|| key.strip_prefix("oid.")).unwrap_or(key);
+    if let Ok(oid) = ObjectIdentifier::new(dotted) {
+        return Ok(oid);
+    }
+
+    ATTRIBUTE_NAMES.iter()
+        .find(|names| {
+            [names.rfc4514, Some(names.openssl), names.dotnet, Some(names.long)].into_iter()
+                .flatten()
+                .any(|name| name.eq_ignore_ascii_case(key))
+        })
+        .map(|names| names.oid)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unknown name attribute \"{}\"", key)))
+}
+
+/// A single `type=value` pair of a distinguished name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NameAttribute {
+    pub oid: ObjectIdentifier,
+    pub value: Any,
+}
+
+impl NameAttribute {
+    /// The value as text, or `None` when it is not one of the ASN.1 string types.
+    pub fn value_str(&self) -> Option<String> {
+        let bytes = self.value.value();
+        match self.value.tag() {
+            Tag::Utf8String | Tag::PrintableString | Tag::Ia5String | Tag::VisibleString | Tag::NumericString => {
+                String::from_utf8(bytes.to_vec()).ok()
+            },
+            // T.61 is treated as Latin-1, as OpenSSL and Windows do.
+            Tag::TeletexString => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+            Tag::BmpString => {
+                let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+                String::from_utf16(&units).ok()
+            },
+            // UniversalString (UCS-4) has no variant in `der::Tag`.
+            Tag::Application { .. } | Tag::ContextSpecific { .. } | Tag::Private { .. } => None,
+            tag if u8::from(tag) == 0x1C => bytes.chunks_exact(4)
+                .map(|quad| char::from_u32(u32::from_be_bytes([quad[0], quad[1], quad[2], quad[3]])))
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// `#` followed by the hex encoding of the DER value, the RFC 4514 form for values without a string form.
+    fn value_hex(&self) -> String {
+        format!("#{}", hex(&self.value.to_der().unwrap_or_default()))
+    }
+
+    fn to_rfc4514(&self) -> String {
+        let short_name = attribute_names(&self.oid).and_then(|names| names.rfc4514);
+        match (short_name, self.value_str()) {
+            (Some(name), Some(value)) => format!("{}={}", name, escape_rfc4514(&value)),
+            // RFC 4514 Section 2.4: dotted types and non-string values use the hex form.
+            (Some(name), None) => format!("{}={}", name, self.value_hex()),
+            (None, _) => format!("{}={}", self.oid, self.value_hex()),
+        }
+    }
+
+    fn to_openssl(&self) -> String {
+        let name = attribute_names(&self.oid).map(|names| names.openssl.to_string()).unwrap_or_else(|| self.oid.to_string());
+        let value = match self.value_str() {
+            Some(value) => escape_openssl(&value),
+            None => self.value_hex(),
+        };
+        format!("{} = {}", name, value)
+    }
+
+    fn to_dotnet(&self) -> String {
+        let name = attribute_names(&self.oid)
+            .and_then(|names| names.dotnet)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("OID.{}", self.oid));
+        let value = match self.value_str() {
+            Some(value) => quote_dotnet(&value),
+            None => self.value_hex(),
+        };
+        format!("{}={}", name, value)
+    }
+}
+
+/// A distinguished name, keeping the order and grouping of its relative distinguished names (RDNs).
+///
+/// RDNs are stored in encoding order, most significant first (usually `C` or `DC`). The string renderings
+/// reverse that order where their format requires it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DistinguishedName {
+    rdns: Vec<Vec<NameAttribute>>,
+}
+
+impl DistinguishedName {
+    pub fn from_der(der: &[u8]) -> Result<DistinguishedName> {
+        Ok(DistinguishedName::from(&Name::from_der(der).map_err(invalid_data)?))
+    }
+
+    /// Converts back to the `x509-cert` structure, e.g. to encode the name into a certificate.
+    pub fn to_name(&self) -> Result<Name> {
+        let mut rdns = Vec::with_capacity(self.rdns.len());
+        for rdn in &self.rdns {
+            let mut set = SetOfVec::new();
+            for attribute in rdn {
+                set.insert(AttributeTypeAndValue { oid: attribute.oid, value: attribute.value.clone() }).map_err(invalid_data)?;
+            }
+            rdns.push(RelativeDistinguishedName::from(set));
+        }
+        Ok(rdns.into())
+    }
+
+    /// The RDNs in encoding order, each with one or more (multi-valued) attributes.
+    pub fn rdns(&self) -> &[Vec<NameAttribute>] {
+        &self.rdns
+    }
+
+    /// Every attribute in encoding order.
+    pub fn attributes(&self) -> impl Iterator<Item = &NameAttribute> {
+        self.rdns.iter().flatten()
+    }
+
+    /// The text values of every attribute matching `key` (an OID or a short or long name), in encoding order.
+    pub fn get_all(&self, key: &str) -> Result<Vec<String>> {
+        let oid = attribute_oid(key)?;
+        Ok(self.attributes().filter(|attribute| attribute.oid == oid).filter_map(NameAttribute::value_str).collect())
+    }
+
+    /// The text value of the most specific attribute matching `key`, e.g. the lowest `OU` in the hierarchy.
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.get_all(key)?.pop())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rdns.is_empty()
+    }
+
+    /// RFC 4514: reversed order, `,` between RDNs, `+` within an RDN and backslash escapes.
+    ///
+    /// Only the short names from RFC 4514 Section 3 are used, other attributes are written as dotted OIDs with a
+    /// hex encoded value.
+    pub fn to_rfc4514(&self) -> String {
+        self.render(true, ",", "+", NameAttribute::to_rfc4514)
+    }
+
+    /// The OpenSSL `-nameopt oneline` format: encoding order, `, ` between RDNs, ` + ` within an RDN and ` = `.
+    pub fn to_openssl_oneline(&self) -> String {
+        self.render(false, ", ", " + ", NameAttribute::to_openssl)
+    }
+
+    /// The format of .NET's `X509Certificate2.Subject` and `Issuer`, which come from `CertNameToStr` with
+    /// `CERT_X500_NAME_STR | CERT_NAME_STR_REVERSE_FLAG`.
+    ///
+    /// RDNs are reversed and joined with `, `, multi-valued RDNs with ` + `, attributes use the X500 keys (`S` for
+    /// the state, `E` for the email address) or `OID.<dotted>`, and values with special characters are quoted.
+    pub fn to_dotnet(&self) -> String {
+        self.render(true, ", ", " + ", NameAttribute::to_dotnet)
+    }
+
+    fn render(&self, reverse: bool, rdn_separator: &str, value_separator: &str, attribute: fn(&NameAttribute) -> String) -> String {
+        let rdns: Vec<String> = self.rdns.iter()
+            .map(|rdn| rdn.iter().map(attribute).collect::<Vec<_>>().join(value_separator))
+            .collect();
+
+        if reverse {
+            rdns.into_iter().rev().collect::<Vec<_>>().join(rdn_separator)
+        } else {
+            rdns.join(rdn_separator)
+        }
+    }
+}
+
+impl From<&Name> for DistinguishedName {
+    fn from(name: &Name) -> DistinguishedName {
+        let rdns = name.0.iter()
+            .map(|rdn| rdn.0.iter().map(|atv| NameAttribute { oid: atv.oid, value: atv.value.clone() }).collect())
+            .collect();
+        DistinguishedName { rdns }
+    }
+}
+
+impl FromStr for DistinguishedName {
+    type Err = Error;
+
+    /// Parses an RFC 4514 string, e.g. `"CN=localhost,O=Example,C=US"`.
+    fn from_str(s: &str) -> Result<DistinguishedName> {
+        let name = Name::from_str(s).map_err(|err| {
+            Error::new(ErrorKind::InvalidInput, format!("Invalid distinguished name \"{}\": {}", s, err))
+        })?;
+        Ok(DistinguishedName::from(&name))
+    }
+}
+
+impl fmt::Display for DistinguishedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_rfc4514())
+    }
+}
+
+fn escape_rfc4514(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let last = value.chars().count().saturating_sub(1);
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '#' if i == 0 => output.push_str("\\#"),
+            ' ' if i == 0 || i == last => output.push_str("\\ "),
+            '"' | '+' | ',' | ';' | '<' | '>' | '\\' => {
+                output.push('\\');
+                output.push(c);
+            },
+            '\0' => output.push_str("\\00"),
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// OpenSSL's `oneline` escaping: values with RFC 2253 special characters are quoted rather than escaped, and
+/// control and non-ASCII characters are written as `\XX` UTF-8 bytes.
+fn escape_openssl(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut quote = value.starts_with(['#', ' ']) || value.ends_with(' ');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                output.push('\\');
+                output.push(c);
+            },
+            ',' | '+' | '<' | '>' | ';' => {
+                quote = true;
+                output.push(c);
+            },
+            c if c.is_ascii_control() || !c.is_ascii() => {
+                let mut buf = [0u8; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    let _ = write!(output, "\\{:02X}", byte);
+                }
+            },
+            _ => output.push(c),
+        }
+    }
+
+    if quote {
+        format!("\"{}\"", output)
+    } else {
+        output
+    }
+}
+
+/// `CertNameToStr` quoting: values that are empty, have leading or trailing white space, or contain one of
+/// `, + = " \n < > # ;` are wrapped in double quotes, with embedded quotes doubled.
+fn quote_dotnet(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.contains([',', '+', '=', '"', '\n', '<', '>', '#', ';']);
+
+    if needs_quotes {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate::Certificate;
+
+    const COMPLEX_NAME_CERT: &[u8] = include_bytes!("../../test/certs/complex_name.crt");
+    const CA_CERT: &[u8] = include_bytes!("../../test/certs/ca_certificate.crt");
+
+    fn subject(pem: &[u8]) -> DistinguishedName {
+        Certificate::from_pem(pem).unwrap().subject()
+    }
+
+    #[test]
+    fn keeps_every_attribute_in_order() {
+        let name = subject(COMPLEX_NAME_CERT);
+        assert_eq!(name.rdns().len(), 12);
+        assert_eq!(name.rdns()[8].len(), 2);
+        assert_eq!(name.get_all("OU").unwrap(), vec!["Dev", "Ops"]);
+        assert_eq!(name.get("OU").unwrap().as_deref(), Some("Ops"));
+        assert_eq!(name.get("domainComponent").unwrap().as_deref(), Some("example"));
+        assert_eq!(name.get("E").unwrap().as_deref(), Some("jose@example.com"));
+        assert_eq!(name.get("2.5.4.3").unwrap().as_deref(), Some("José Ñ"));
+        assert_eq!(name.get("S").unwrap(), name.get("ST").unwrap());
+        assert_eq!(name.get("GN").unwrap(), None);
+        assert!(name.get("nonsense").is_err());
+    }
+
+    #[test]
+    fn renders_like_openssl() {
+        // openssl x509 -noout -subject -nameopt oneline
+        assert_eq!(
+            subject(COMPLEX_NAME_CERT).to_openssl_oneline(),
+            "DC = com, DC = example, C = US, ST = Texas, L = Austin, O = \"Example, Inc.\", OU = Dev, OU = Ops, \
+             serialNumber = 1234 + CN = Jos\\C3\\A9 \\C3\\91, emailAddress = jose@example.com, title = Engineer, \
+             street = \" #lead;\\\"q\\\"\""
+        );
+        assert_eq!(
+            subject(CA_CERT).to_openssl_oneline(),
+            "C = US, O = Py_Cert_Store Demo Examples, OU = Examples, CN = Py_Cert_Store Demo CA"
+        );
+    }
+
+    #[test]
+    fn renders_rfc4514() {
+        // Matches openssl -nameopt RFC2253, except that UTF-8 is kept and attributes without an RFC 4514 name
+        // are written in the dotted hex form.
+        assert_eq!(
+            subject(COMPLEX_NAME_CERT).to_rfc4514(),
+            "STREET=\\ #lead\\;\\\"q\\\",2.5.4.12=#0C08456E67696E656572,1.2.840.113549.1.9.1=#16106A6F7365406578616D706C652E636F6D,\
+             2.5.4.5=#130431323334+CN=José Ñ,OU=Ops,OU=Dev,O=Example\\, Inc.,L=Austin,ST=Texas,C=US,DC=example,DC=com"
+        );
+
+        let ca = subject(CA_CERT);
+        assert_eq!(ca.to_string(), "CN=Py_Cert_Store Demo CA,OU=Examples,O=Py_Cert_Store Demo Examples,C=US");
+        assert_eq!(ca.to_string().parse::<DistinguishedName>().unwrap(), ca);
+    }
+
+    #[test]
+    fn renders_like_dotnet() {
+        assert_eq!(
+            subject(COMPLEX_NAME_CERT).to_dotnet(),
+            "STREET=\" #lead;\"\"q\"\"\", T=Engineer, E=jose@example.com, SERIALNUMBER=1234 + CN=José Ñ, OU=Ops, OU=Dev, \
+             O=\"Example, Inc.\", L=Austin, S=Texas, C=US, DC=example, DC=com"
+        );
+        assert_eq!(
+            subject(CA_CERT).to_dotnet(),
+            "CN=Py_Cert_Store Demo CA, OU=Examples, O=Py_Cert_Store Demo Examples, C=US"
+        );
+
+        let unknown: DistinguishedName = "1.2.3.4=#0C06637573746F6D,CN=a=b".parse().unwrap();
+        assert_eq!(unknown.to_dotnet(), "OID.1.2.3.4=custom, CN=\"a=b\"");
+    }
+
+    #[test]
+    fn round_trips_through_x509_name() {
+        let name = subject(COMPLEX_NAME_CERT);
+        let cert = Certificate::from_pem(COMPLEX_NAME_CERT).unwrap();
+        assert_eq!(name.to_name().unwrap(), cert.as_x509().tbs_certificate.subject);
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+use der::Decode;
+use der::asn1::ObjectIdentifier;
+use x509_cert::ext::pkix::ExtendedKeyUsage;
+
+use crate::certificate::Certificate;
+use crate::certificate::extensions::{extended_key_usage_oid, find_extension};
+use crate::certificate::invalid_data;
+
+
+/// The anyExtendedKeyUsage purpose (RFC 5280 Section 4.2.1.12).
+pub const OID_ANY_EXTENDED_KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.37.0");
+
+/// Other names the purposes are known by, mapped to their name in
+/// [`EXTENDED_KEY_USAGE_NAMES`](crate::certificate::extensions::EXTENDED_KEY_USAGE_NAMES).
+const PURPOSE_ALIASES: [(&str, &str); 6] = [
+    ("tls_client", "client_auth"),
+    ("tls_server", "server_auth"),
+    ("smime", "email_protection"),
+    ("s_mime", "email_protection"),
+    ("email", "email_protection"),
+    ("timestamping", "time_stamping"),
+];
+
+/// Resolves a purpose given as an ExtendedKeyUsage name, one of its aliases (such as "smime"), or a dotted OID.
+pub fn purpose_oid(purpose: &str) -> Result<ObjectIdentifier> {
+    let name = purpose.trim().to_lowercase().replace('-', "_");
+    let name = PURPOSE_ALIASES.iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(name);
+    extended_key_usage_oid(&name)
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Unknown certificate purpose \"{}\"", purpose)))
+}
+
+/// Decodes a Windows `CERT_ENHKEY_USAGE_PROP_ID` property, a `CTL_USAGE` encoded like the ExtendedKeyUsage extension.
+pub fn decode_usage_property(der: &[u8]) -> Result<Vec<ObjectIdentifier>> {
+    // An empty sequence is how "Disable all purposes for this certificate" is stored.
+    ExtendedKeyUsage::from_der(der).map(|usage| usage.0).map_err(invalid_data)
+}
+
+/// The purposes a certificate is trusted for, combining its ExtendedKeyUsage extension with the usage a store
+/// assigned to it, like the Windows `CERT_ENHKEY_USAGE_PROP_ID` property set in the certificate properties dialog.
+///
+/// A missing extension or property does not restrict the purposes, as in RFC 5280 and CryptoAPI. When both are
+/// present the certificate must allow the purpose in both, so a store can narrow a certificate's purposes but never
+/// widen them. anyExtendedKeyUsage allows every purpose.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnhancedKeyUsage {
+    pub extension: Option<Vec<ObjectIdentifier>>,
+    pub property: Option<Vec<ObjectIdentifier>>,
+}
+
+impl EnhancedKeyUsage {
+    /// Reads the extension of a certificate, combined with the usage property of its store if it has one.
+    pub fn new(certificate: &Certificate, property: Option<Vec<ObjectIdentifier>>) -> Result<EnhancedKeyUsage> {
+        let extensions = certificate.as_x509().tbs_certificate.extensions.as_deref().unwrap_or_default();
+        Ok(EnhancedKeyUsage {
+            extension: find_extension::<ExtendedKeyUsage>(extensions)?.map(|usage| usage.0),
+            property,
+        })
+    }
+
+    /// Whether the certificate may be used for the purpose.
+    pub fn allows(&self, purpose: &ObjectIdentifier) -> bool {
+        let allowed_by = |purposes: &Option<Vec<ObjectIdentifier>>| match purposes {
+            Some(purposes) => purposes.iter().any(|allowed| allowed == purpose || *allowed == OID_ANY_EXTENDED_KEY_USAGE),
+            None => true,
+        };
+        allowed_by(&self.extension) && allowed_by(&self.property)
+    }
+
+    /// The purposes the certificate may be used for, or `None` when it is not restricted.
+    pub fn purposes(&self) -> Option<Vec<ObjectIdentifier>> {
+        let unrestricted = |purposes: &Vec<ObjectIdentifier>| purposes.contains(&OID_ANY_EXTENDED_KEY_USAGE);
+        match (&self.extension, &self.property) {
+            (Some(extension), Some(property)) if unrestricted(extension) && unrestricted(property) => None,
+            (Some(extension), Some(property)) if unrestricted(extension) => Some(property.clone()),
+            (Some(extension), Some(property)) if unrestricted(property) => Some(extension.clone()),
+            (Some(extension), Some(property)) => Some(extension.iter().filter(|purpose| property.contains(purpose)).copied().collect()),
+            (Some(purposes), None) | (None, Some(purposes)) if unrestricted(purposes) => None,
+            (Some(purposes), None) | (None, Some(purposes)) => Some(purposes.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use der::Encode;
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use crate::cert_builder::test_support::self_signed;
+
+    fn oid(name: &str) -> ObjectIdentifier {
+        purpose_oid(name).unwrap()
+    }
+
+    fn certificate(purposes: &[&str]) -> Certificate {
+        let now = SystemTime::now();
+        self_signed("CN=purpose", now, now + Duration::from_secs(24 * 60 * 60), purposes).certificate
+    }
+
+    fn property(purposes: &[&str]) -> Option<Vec<ObjectIdentifier>> {
+        let der = ExtendedKeyUsage(purposes.iter().map(|purpose| oid(purpose)).collect()).to_der().unwrap();
+        Some(decode_usage_property(&der).unwrap())
+    }
+
+    #[test]
+    fn resolves_purpose_names() {
+        assert_eq!(oid("client_auth").to_string(), "1.3.6.1.5.5.7.3.2");
+        assert_eq!(oid("Client-Auth"), oid("tls_client"));
+        assert_eq!(oid("smime"), oid("email_protection"));
+        assert_eq!(oid("smart_card_logon").to_string(), "1.3.6.1.4.1.311.20.2.2");
+        assert_eq!(oid("1.3.6.1.4.1.311.10.3.12"), oid("document_signing"));
+        assert_eq!(purpose_oid("teleportation").unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn follows_the_extension() {
+        let client = EnhancedKeyUsage::new(&certificate(&["client_auth", "email_protection"]), None).unwrap();
+        assert!(client.allows(&oid("client_auth")) && client.allows(&oid("smime")));
+        assert!(!client.allows(&oid("code_signing")));
+        assert_eq!(client.purposes(), Some(vec![oid("client_auth"), oid("email_protection")]));
+
+        let unrestricted = EnhancedKeyUsage::new(&certificate(&[]), None).unwrap();
+        assert!(unrestricted.allows(&oid("code_signing")));
+        assert_eq!(unrestricted.purposes(), None);
+
+        let any = EnhancedKeyUsage::new(&certificate(&["2.5.29.37.0"]), None).unwrap();
+        assert!(any.allows(&oid("document_signing")));
+        assert_eq!(any.purposes(), None);
+    }
+
+    #[test]
+    fn honors_store_properties() {
+        let restricted = certificate(&["client_auth", "email_protection"]);
+
+        let narrowed = EnhancedKeyUsage::new(&restricted, property(&["email_protection", "code_signing"])).unwrap();
+        assert!(narrowed.allows(&oid("email_protection")));
+        assert!(!narrowed.allows(&oid("client_auth")));
+        assert!(!narrowed.allows(&oid("code_signing")));
+        assert_eq!(narrowed.purposes(), Some(vec![oid("email_protection")]));
+
+        let disabled = EnhancedKeyUsage::new(&restricted, property(&[])).unwrap();
+        assert!(!disabled.allows(&oid("client_auth")));
+        assert_eq!(disabled.purposes(), Some(Vec::new()));
+
+        let without_extension = EnhancedKeyUsage::new(&certificate(&[]), property(&["code_signing"])).unwrap();
+        assert!(without_extension.allows(&oid("code_signing")) && !without_extension.allows(&oid("client_auth")));
+
+        assert!(decode_usage_property(b"not a usage").is_err());
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::fmt;
+use std::time::SystemTime;
+use x509_cert::ext::pkix::{BasicConstraints, KeyUsage, KeyUsages};
+
+use crate::certificate::Certificate;
+use crate::certificate::extensions::find_extension;
+use crate::keys::verify::verify_issued_by;
+
+
+/// Longest chain that is followed before giving up, which also stops issuer loops.
+const MAX_CHAIN_LENGTH: usize = 16;
+
+/// Something wrong with one certificate of a chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainProblem {
+    Expired,
+    NotYetValid,
+    /// The signature does not verify with the issuer's public key.
+    BadSignature,
+    /// The signature algorithm or the issuer's key type is not supported, so the signature was not checked.
+    UnsupportedSignature,
+    /// The issuer has no CA basic constraint, or a KeyUsage without keyCertSign.
+    IssuerNotCa,
+    /// No certificate with the issuer's name was found.
+    IssuerNotFound,
+    /// The chain ends in a self-signed certificate that is not one of the trusted roots.
+    UntrustedRoot,
+}
+
+impl fmt::Display for ChainProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            ChainProblem::Expired => "expired",
+            ChainProblem::NotYetValid => "not yet valid",
+            ChainProblem::BadSignature => "bad signature",
+            ChainProblem::UnsupportedSignature => "unsupported signature algorithm",
+            ChainProblem::IssuerNotCa => "issuer is not a CA",
+            ChainProblem::IssuerNotFound => "issuer not found",
+            ChainProblem::UntrustedRoot => "untrusted root",
+        };
+        f.write_str(text)
+    }
+}
+
+/// A certificate of a chain and the problems found with it and the link to its issuer.
+#[derive(Clone, Debug)]
+pub struct ChainLink {
+    pub certificate: Certificate,
+    pub problems: Vec<ChainProblem>,
+}
+
+/// A chain from a leaf certificate towards a trusted root, the leaf first.
+#[derive(Clone, Debug)]
+pub struct Chain {
+    pub links: Vec<ChainLink>,
+}
+
+impl Chain {
+    /// Follows the issuers of `leaf` through `intermediates` and `roots`.
+    ///
+    /// The chain ends at the first certificate that is one of `roots`, or at a self-signed certificate, or where
+    /// no issuer is found. Issuers are matched by name; when several certificates share the name, the one whose
+    /// key verifies the signature is used, preferring roots.
+    pub fn build(leaf: &Certificate, intermediates: &[Certificate], roots: &[Certificate], now: SystemTime) -> Chain {
+        let mut links: Vec<ChainLink> = Vec::new();
+        let mut current = leaf.clone();
+
+        loop {
+            let mut problems = validity_problems(&current, now);
+            let trusted = roots.iter().any(|root| root.as_der() == current.as_der());
+            let x509 = current.as_x509();
+
+            if trusted || is_self_issued(&current) {
+                if is_self_issued(&current) {
+                    problems.extend(signature_problem(&current, &current));
+                }
+                if !trusted {
+                    problems.push(ChainProblem::UntrustedRoot);
+                }
+                links.push(ChainLink { certificate: current, problems });
+                break;
+            }
+
+            let in_chain = |candidate: &Certificate| {
+                candidate.as_der() == current.as_der() || links.iter().any(|link| link.certificate.as_der() == candidate.as_der())
+            };
+            let issuers: Vec<&Certificate> = roots.iter().chain(intermediates)
+                .filter(|candidate| candidate.as_x509().tbs_certificate.subject == x509.tbs_certificate.issuer)
+                .filter(|candidate| !in_chain(candidate))
+                .collect();
+            let verified = issuers.iter().find(|candidate| signature_problem(&current, candidate).is_none());
+
+            let Some(issuer) = verified.or(issuers.first()).map(|issuer| (*issuer).clone()) else {
+                problems.push(ChainProblem::IssuerNotFound);
+                links.push(ChainLink { certificate: current, problems });
+                break;
+            };
+
+            problems.extend(signature_problem(&current, &issuer));
+            let issuer_trusted = roots.iter().any(|root| root.as_der() == issuer.as_der());
+            if !issuer_trusted && !is_ca(&issuer) {
+                problems.push(ChainProblem::IssuerNotCa);
+            }
+            links.push(ChainLink { certificate: current, problems });
+
+            if links.len() >= MAX_CHAIN_LENGTH {
+                links.push(ChainLink { certificate: issuer, problems: vec![ChainProblem::IssuerNotFound] });
+                break;
+            }
+            current = issuer;
+        }
+
+        Chain { links }
+    }
+
+    /// Whether every certificate is valid and the chain ends in a trusted root.
+    pub fn is_valid(&self) -> bool {
+        self.links.iter().all(|link| link.problems.is_empty())
+    }
+}
+
+fn is_self_issued(cert: &Certificate) -> bool {
+    let tbs = &cert.as_x509().tbs_certificate;
+    tbs.subject == tbs.issuer
+}
+
+fn validity_problems(cert: &Certificate, now: SystemTime) -> Vec<ChainProblem> {
+    if now < cert.not_before() {
+        vec![ChainProblem::NotYetValid]
+    } else if now > cert.not_after() {
+        vec![ChainProblem::Expired]
+    } else {
+        Vec::new()
+    }
+}
+
+fn signature_problem(cert: &Certificate, issuer: &Certificate) -> Option<ChainProblem> {
+    match verify_issued_by(cert, issuer) {
+        Ok(true) => None,
+        Ok(false) => Some(ChainProblem::BadSignature),
+        Err(_) => Some(ChainProblem::UnsupportedSignature),
+    }
+}
+
+fn is_ca(cert: &Certificate) -> bool {
+    let extensions = cert.as_x509().tbs_certificate.extensions.as_deref().unwrap_or_default();
+    let ca = matches!(find_extension::<BasicConstraints>(extensions), Ok(Some(constraints)) if constraints.ca);
+    let signs_certificates = match find_extension::<KeyUsage>(extensions) {
+        Ok(Some(usage)) => usage.0.contains(KeyUsages::KeyCertSign),
+        Ok(None) => true,
+        Err(_) => false,
+    };
+    ca && signs_certificates
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert_builder::{CertificateBuilder, IssuedCertificate};
+    use crate::cert_builder::test_support::sign;
+
+    fn ca(subject: &str) -> IssuedCertificate {
+        sign(CertificateBuilder::new(subject).unwrap().basic_constraints(true, None), None)
+    }
+
+    fn issue(subject: &str, issuer: &IssuedCertificate, ca: bool) -> IssuedCertificate {
+        sign(CertificateBuilder::new(subject).unwrap().basic_constraints(ca, None), Some(issuer))
+    }
+
+    #[test]
+    fn follows_issuers_to_a_trusted_root() {
+        let root = ca("CN=Root");
+        let intermediate = issue("CN=Intermediate", &root, true);
+        let leaf = issue("CN=leaf", &intermediate, false);
+
+        let chain = Chain::build(&leaf.certificate, std::slice::from_ref(&intermediate.certificate), std::slice::from_ref(&root.certificate), SystemTime::now());
+        let subjects: Vec<String> = chain.links.iter().map(|link| link.certificate.subject().to_rfc4514()).collect();
+        assert_eq!(subjects, vec!["CN=leaf", "CN=Intermediate", "CN=Root"]);
+        assert!(chain.is_valid());
+
+        let untrusted = Chain::build(&leaf.certificate, &[intermediate.certificate.clone(), root.certificate.clone()], &[], SystemTime::now());
+        assert_eq!(untrusted.links.len(), 3);
+        assert_eq!(untrusted.links[2].problems, vec![ChainProblem::UntrustedRoot]);
+        assert!(!untrusted.is_valid());
+    }
+
+    #[test]
+    fn reports_broken_links() {
+        let root = ca("CN=Root");
+        let impostor = ca("CN=Root");
+        let leaf = issue("CN=leaf", &root, false);
+
+        // Only a certificate with the right name but the wrong key is available.
+        let chain = Chain::build(&leaf.certificate, &[], std::slice::from_ref(&impostor.certificate), SystemTime::now());
+        assert_eq!(chain.links[0].problems, vec![ChainProblem::BadSignature]);
+
+        // The matching key is picked when both are available.
+        let chain = Chain::build(&leaf.certificate, &[], &[impostor.certificate.clone(), root.certificate.clone()], SystemTime::now());
+        assert!(chain.is_valid());
+        assert_eq!(chain.links[1].certificate, root.certificate);
+
+        let chain = Chain::build(&leaf.certificate, &[], &[], SystemTime::now());
+        assert_eq!(chain.links.len(), 1);
+        assert_eq!(chain.links[0].problems, vec![ChainProblem::IssuerNotFound]);
+
+        let later = SystemTime::now() + std::time::Duration::from_secs(2 * 365 * 24 * 60 * 60);
+        let chain = Chain::build(&leaf.certificate, &[], std::slice::from_ref(&root.certificate), later);
+        assert_eq!(chain.links[0].problems, vec![ChainProblem::Expired]);
+        assert_eq!(chain.links[1].problems, vec![ChainProblem::Expired]);
+    }
+}
@@ -0,0 +1,799 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+//! The `py-cert-store` command line tool, shared by the Rust binary and `python -m py_cert_store`.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Result, Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use clap::{Args, Parser, Subcommand};
+use der::asn1::ObjectIdentifier;
+
+use crate::certificate::{Certificate, hex};
+use crate::certificate::extensions::{UsageExtensions, extended_key_usage_name, extended_key_usage_oid, key_usage_names};
+use crate::certificate::purpose::{EnhancedKeyUsage, purpose_oid};
+use crate::chain::Chain;
+use crate::file_store::pkcs12_store::Pkcs12Store;
+use crate::keys::{KeyAlgorithm, PrivateKey};
+use crate::ranking::{Candidate, RankingCriterion, RankingPolicy};
+use crate::selection::SelectionStrategy;
+use crate::store::{StoreEntry, rfc3339_date_string};
+use crate::store::cancel::Cancellation;
+use crate::store::export::{ExportedCertificate, export_entries};
+use crate::store::path::open_path;
+
+pub mod output;
+
+use output::{OutputFormat, Report};
+
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Inspect, search and export certificates of a Windows store, a PEM directory or a PKCS#12 file.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// How results are written to standard output.
+    #[arg(long, short, value_enum, global = true, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Where certificates are read from. A path is used when given, otherwise a Windows system store.
+#[derive(Debug, Args)]
+struct Source {
+    /// A directory of PEM/DER files, a certificate file or a PKCS#12 file, instead of a Windows store.
+    #[arg(long)]
+    path: Option<PathBuf>,
+
+    /// The Windows system store to read.
+    #[arg(long, default_value = "My")]
+    store: String,
+
+    /// The Windows store location: CurrentUser or LocalMachine.
+    #[arg(long, default_value = "CurrentUser")]
+    user: String,
+
+    /// The password of PKCS#12 files and encrypted keys under --path.
+    #[arg(long, default_value = "")]
+    password: String,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List every certificate without exporting any key.
+    List {
+        #[command(flatten)]
+        source: Source,
+    },
+    /// Show the details of one certificate.
+    Show {
+        #[command(flatten)]
+        source: Source,
+        /// The SHA-1 thumbprint of the certificate, in hex.
+        thumbprint: String,
+    },
+    /// Search the way the library does and show which certificate would be picked.
+    Find {
+        #[command(flatten)]
+        source: Source,
+        /// Only certificates valid for this host name or IP address.
+        #[arg(long)]
+        host: Option<String>,
+        /// Only certificates allowed for this purpose (e.g. client_auth) by their Extended Key Usage and, on
+        /// Windows, their store properties.
+        #[arg(long)]
+        purpose: Option<String>,
+        /// Only certificates with this extension, as a dotted OID. Defaults to the Key Usage, like get_win_cert.
+        #[arg(long, default_value = "2.5.29.15")]
+        extension_oid: String,
+        /// Only certificates whose extension contains this value, e.g. "Digital Signature" for the Key Usage.
+        #[arg(long, default_value = "Digital Signature")]
+        extension_value: String,
+        /// Do not filter on an extension, like find_windows_cert_all.
+        #[arg(long, conflicts_with_all = ["extension_oid", "extension_value"])]
+        all: bool,
+        /// Prefer certificates allowed for this purpose (e.g. client_auth), may be repeated.
+        #[arg(long = "eku")]
+        extended_key_usage: Vec<String>,
+        /// Prefer certificates from this issuer, may be repeated; earlier issuers rank higher.
+        #[arg(long = "issuer")]
+        preferred_issuers: Vec<String>,
+        /// The ranking criteria in order of importance, separated by commas.
+        #[arg(long, value_delimiter = ',')]
+        order: Vec<String>,
+        /// How the certificate is picked from the ranked results: first, newest, longest_valid,
+        /// raise_if_ambiguous or all.
+        #[arg(long, default_value = "first")]
+        selection: String,
+        /// Rank certificates with a private key without exporting it; non-exportable keys are not ruled out.
+        #[arg(long)]
+        no_export: bool,
+    },
+    /// Write a certificate, and its private key, to a file.
+    Export {
+        #[command(flatten)]
+        source: Source,
+        /// The SHA-1 thumbprint of the certificate, in hex.
+        thumbprint: String,
+        /// The file to write.
+        #[arg(long, short)]
+        out: PathBuf,
+        /// Write PEM (certificate followed by a PKCS#8 key) instead of PKCS#12, or of DER with --no-key.
+        #[arg(long)]
+        pem: bool,
+        /// Only write the certificate.
+        #[arg(long)]
+        no_key: bool,
+        /// The password protecting the exported key.
+        #[arg(long, default_value = "")]
+        export_password: String,
+    },
+    /// Add the certificates and keys of a file to a PEM directory or a Windows store.
+    Import {
+        #[command(flatten)]
+        source: Source,
+        /// A certificate, PEM or PKCS#12 file.
+        file: PathBuf,
+        /// The password of the imported file.
+        #[arg(long, default_value = "")]
+        file_password: String,
+    },
+    /// Build the chain of a certificate and check its validity, signatures and CA constraints.
+    VerifyChain {
+        #[command(flatten)]
+        source: Source,
+        /// The SHA-1 thumbprint of the certificate, in hex.
+        thumbprint: String,
+        /// Trusted roots: a file or directory. Defaults to the Root store on Windows, and to the self-signed
+        /// certificates of --path otherwise.
+        #[arg(long)]
+        roots: Option<PathBuf>,
+    },
+    /// List the certificates that expire within a number of days.
+    Expiring {
+        #[command(flatten)]
+        source: Source,
+        /// How many days ahead to look.
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+        /// Also list certificates that have already expired.
+        #[arg(long)]
+        include_expired: bool,
+    },
+}
+
+/// Parses `args` (the program name first) and runs the command, writing results to `out` and messages to `err`.
+///
+/// Returns the process exit code: 0 on success, 1 when the command failed or a chain did not verify, and 2 for
+/// invalid arguments.
+pub fn run<I, T>(args: I, out: &mut dyn Write, err: &mut dyn Write) -> i32
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(parse_error) => {
+            let _ = match parse_error.use_stderr() {
+                true => write!(err, "{}", parse_error.render()),
+                false => write!(out, "{}", parse_error.render()),
+            };
+            return parse_error.exit_code();
+        },
+    };
+
+    match execute(cli.command) {
+        Ok((report, success)) => {
+            if write!(out, "{}", report.render(cli.format)).is_err() {
+                return 1;
+            }
+            if success { 0 } else { 1 }
+        },
+        Err(error) => {
+            let _ = writeln!(err, "error: {}", error);
+            1
+        },
+    }
+}
+
+/// Runs a command, returning its report and whether it succeeded.
+fn execute(command: Command) -> Result<(Report, bool)> {
+    let now = SystemTime::now();
+    match command {
+        Command::List { source } => Ok((list(&source.load()?), true)),
+        Command::Show { source, thumbprint } => {
+            let entries = source.load()?;
+            Ok((show(find_entry(&entries, &thumbprint)?)?, true))
+        },
+        Command::Find { source, host, purpose, extension_oid, extension_value, all, extended_key_usage, preferred_issuers, order, selection, no_export } => {
+            let filter = SearchFilter {
+                host,
+                purpose: purpose.as_deref().map(purpose_oid).transpose()?,
+                extension: match all {
+                    true => None,
+                    false => {
+                        let oid = extension_oid.parse()
+                            .map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Invalid extension OID \"{}\"", extension_oid)))?;
+                        Some((oid, extension_value))
+                    },
+                },
+            };
+            let mut policy = RankingPolicy::new();
+            if !order.is_empty() {
+                let order = order.iter().map(|name| name.parse::<RankingCriterion>()).collect::<Result<Vec<_>>>()?;
+                policy = policy.order(order)?;
+            }
+            for purpose in &extended_key_usage {
+                policy = policy.extended_key_usage(extended_key_usage_oid(purpose)?);
+            }
+            for issuer in &preferred_issuers {
+                policy = policy.preferred_issuer(issuer);
+            }
+            let selection = selection.parse::<SelectionStrategy>()?;
+            Ok((find(source.load()?, &filter, &policy, selection, no_export, now)?, true))
+        },
+        Command::Export { source, thumbprint, out, pem, no_key, export_password } => {
+            let entries = source.load()?;
+            Ok((export(find_entry(&entries, &thumbprint)?, &out, pem, no_key, &export_password)?, true))
+        },
+        Command::Import { source, file, file_password } => {
+            let entries = open_path(&file, &file_password)?.entries().map(Loaded::new).collect::<Result<Vec<_>>>()?;
+            if entries.is_empty() {
+                return Err(Error::new(ErrorKind::NotFound, format!("No certificates found in {}", file.display())));
+            }
+            Ok((source.import(&entries)?, true))
+        },
+        Command::VerifyChain { source, thumbprint, roots } => {
+            let entries = source.load()?;
+            let leaf = &find_entry(&entries, &thumbprint)?.certificate;
+            let intermediates: Vec<Certificate> = entries.iter().map(|entry| entry.certificate.clone()).collect();
+            let roots = match roots {
+                Some(path) => open_path(&path, &source.password)?.entries().map(|entry| Certificate::from_der(entry.der())).collect::<Result<Vec<_>>>()?,
+                None => source.default_roots(&intermediates)?,
+            };
+            let chain = Chain::build(leaf, &intermediates, &roots, now);
+            Ok((verify_chain(&chain), chain.is_valid()))
+        },
+        Command::Expiring { source, days, include_expired } => {
+            let horizon = now + Duration::from_secs(u64::from(days) * SECONDS_PER_DAY);
+            Ok((expiring(source.load()?, horizon, include_expired, now), true))
+        },
+    }
+}
+
+/// A store entry with its parsed certificate.
+struct Loaded {
+    entry: Box<dyn StoreEntry>,
+    certificate: Certificate,
+}
+
+impl Loaded {
+    fn new(entry: Box<dyn StoreEntry>) -> Result<Loaded> {
+        let certificate = Certificate::from_der(entry.der())?;
+        Ok(Loaded { entry, certificate })
+    }
+}
+
+impl Source {
+    /// Reads every certificate. Certificates that cannot be parsed are skipped.
+    fn load(&self) -> Result<Vec<Loaded>> {
+        let entries = match &self.path {
+            Some(path) => open_path(path, &self.password)?.entries(),
+            None => open_windows_store(&self.store, &self.user)?,
+        };
+        Ok(entries.filter_map(|entry| Loaded::new(entry).ok()).collect())
+    }
+
+    /// The roots trusted when none are given: the Root store on Windows, otherwise the self-signed certificates
+    /// read from the path.
+    fn default_roots(&self, certificates: &[Certificate]) -> Result<Vec<Certificate>> {
+        if self.path.is_none() {
+            let roots = open_windows_store("Root", &self.user)?;
+            return Ok(roots.filter_map(|entry| Certificate::from_der(entry.der()).ok()).collect());
+        }
+        Ok(certificates.iter()
+            .filter(|cert| cert.subject() == cert.issuer())
+            .cloned()
+            .collect())
+    }
+
+    fn import(&self, entries: &[Loaded]) -> Result<Report> {
+        let mut report = Report::new(&["Thumbprint", "Subject", "PrivateKey", "Destination"]);
+        for loaded in entries {
+            let key = match loaded.entry.has_private_key() {
+                true => Some(loaded.entry.export_pkcs12()?),
+                false => None,
+            };
+            let destination = match &self.path {
+                Some(directory) => import_to_directory(directory, &loaded.certificate, key.as_deref(), &self.password)?,
+                None => import_to_windows_store(&self.store, &self.user, &loaded.certificate, key.as_deref())?,
+            };
+            report.push(vec![
+                loaded.certificate.thumbprint().into(),
+                loaded.certificate.subject().to_dotnet().into(),
+                key.is_some().into(),
+                destination.into(),
+            ]);
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(windows)]
+fn open_windows_store(store: &str, user: &str) -> Result<crate::store::StoreIter> {
+    Ok(crate::windows_store::cert_store::CertStore::open_system(store, user)?.into_entries())
+}
+
+#[cfg(not(windows))]
+fn open_windows_store(_store: &str, _user: &str) -> Result<crate::store::StoreIter> {
+    Err(Error::new(ErrorKind::Unsupported, "Windows certificate stores can only be read on Windows; use --path to read a PEM directory or PKCS#12 file"))
+}
+
+/// Writes the certificate, followed by its key when there is one, to `<thumbprint>.pem` in `directory`.
+fn import_to_directory(directory: &Path, certificate: &Certificate, pfx: Option<&[u8]>, password: &str) -> Result<String> {
+    fs::create_dir_all(directory)?;
+    let mut pem = certificate.to_pem()?;
+    if let Some(pfx) = pfx {
+        pem.push_str(&key_pem(&private_key(pfx, certificate)?, password)?);
+    }
+
+    let path = directory.join(format!("{}.pem", certificate.thumbprint()));
+    write_file(&path, pem.as_bytes(), pfx.is_some())?;
+    Ok(path.display().to_string())
+}
+
+#[cfg(windows)]
+fn import_to_windows_store(store: &str, user: &str, certificate: &Certificate, pfx: Option<&[u8]>) -> Result<String> {
+    let target = crate::windows_store::cert_store::CertStore::open_system(store, user)?;
+    match pfx {
+        Some(pfx) => {
+            target.import_pkcs12(pfx, "", user.eq_ignore_ascii_case("LocalMachine"))?;
+        },
+        None => target.add_encoded_certificate(certificate.as_der())?,
+    }
+    Ok(format!("{}\\{}", user, store))
+}
+
+#[cfg(not(windows))]
+fn import_to_windows_store(store: &str, user: &str, _certificate: &Certificate, _pfx: Option<&[u8]>) -> Result<String> {
+    open_windows_store(store, user).map(|_| String::new())
+}
+
+/// Finds an entry by its thumbprint, ignoring case, spaces and colons.
+fn find_entry<'a>(entries: &'a [Loaded], thumbprint: &str) -> Result<&'a Loaded> {
+    let wanted: String = thumbprint.chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .collect::<String>()
+        .to_uppercase();
+    entries.iter()
+        .find(|loaded| loaded.certificate.thumbprint() == wanted)
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("No certificate with thumbprint {}", wanted)))
+}
+
+fn list(entries: &[Loaded]) -> Report {
+    let mut report = Report::new(&["Thumbprint", "Subject", "Issuer", "NotAfter", "PrivateKey", "FriendlyName"]);
+    for loaded in entries {
+        let certificate = &loaded.certificate;
+        report.push(vec![
+            certificate.thumbprint().into(),
+            certificate.subject().to_dotnet().into(),
+            certificate.issuer().to_dotnet().into(),
+            rfc3339_date_string(certificate.not_after()).into(),
+            loaded.entry.has_private_key().into(),
+            loaded.entry.friendly_name().into(),
+        ]);
+    }
+    report
+}
+
+fn show(loaded: &Loaded) -> Result<Report> {
+    let certificate = &loaded.certificate;
+    let tbs = &certificate.as_x509().tbs_certificate;
+    let usage = UsageExtensions::from_extensions(tbs.extensions.as_deref().unwrap_or_default())?;
+    let key_algorithm = KeyAlgorithm::from_public_key_info(&tbs.subject_public_key_info)
+        .map(|algorithm| algorithm.to_string())
+        .unwrap_or_else(|_| tbs.subject_public_key_info.algorithm.oid.to_string());
+    let serial_number = hex(tbs.serial_number.as_bytes());
+
+    Ok(Report::record(vec![
+        ("Thumbprint", certificate.thumbprint().into()),
+        ("Subject", certificate.subject().to_dotnet().into()),
+        ("Issuer", certificate.issuer().to_dotnet().into()),
+        ("SerialNumber", serial_number.into()),
+        ("NotBefore", rfc3339_date_string(certificate.not_before()).into()),
+        ("NotAfter", rfc3339_date_string(certificate.not_after()).into()),
+        ("KeyAlgorithm", key_algorithm.into()),
+        ("AltNames", usage.alt_names.iter().map(|name| name.to_string()).collect::<Vec<_>>().into()),
+        ("KeyUsage", usage.key_usage.as_ref().map(key_usage_names).unwrap_or_default().into()),
+        ("ExtendedKeyUsage", usage.extended_key_usage.iter().map(extended_key_usage_name).collect::<Vec<_>>().into()),
+        ("FriendlyName", loaded.entry.friendly_name().into()),
+        ("PrivateKey", loaded.entry.has_private_key().into()),
+        ("KeyProvider", loaded.entry.key_provider().into()),
+    ]))
+}
+
+/// The criteria of `find`, the same as the arguments of `get_win_cert` and the search functions.
+struct SearchFilter {
+    host: Option<String>,
+    purpose: Option<ObjectIdentifier>,
+    /// The extension OID and the value its description must contain.
+    extension: Option<(ObjectIdentifier, String)>,
+}
+
+impl SearchFilter {
+    fn matches(&self, loaded: &Loaded, now: SystemTime) -> Result<bool> {
+        let certificate = &loaded.certificate;
+        if now < certificate.not_before() || now > certificate.not_after() {
+            return Ok(false);
+        }
+        if let Some(host) = &self.host {
+            if !certificate.matches_host(host)? {
+                return Ok(false);
+            }
+        }
+        if let Some((oid, value)) = &self.extension {
+            if !certificate.has_extension(oid, Some(value))? {
+                return Ok(false);
+            }
+        }
+        match &self.purpose {
+            Some(purpose) => Ok(EnhancedKeyUsage::new(certificate, loaded.entry.enhanced_key_usage_property()?)?.allows(purpose)),
+            None => Ok(true),
+        }
+    }
+}
+
+/// Filters the entries the way the library searches (currently valid, matching the filter and, unless `no_export`,
+/// with an exportable key belonging to the certificate), ranks them and marks the selected ones.
+fn find(entries: Vec<Loaded>, filter: &SearchFilter, policy: &RankingPolicy, selection: SelectionStrategy, no_export: bool, now: SystemTime) -> Result<Report> {
+    let mut valid = Vec::new();
+    for loaded in entries {
+        if filter.matches(&loaded, now)? {
+            valid.push(loaded);
+        }
+    }
+
+    let candidates: Vec<Candidate> = if no_export {
+        valid.into_iter()
+            .filter(|loaded| loaded.entry.has_private_key())
+            .map(|loaded| candidate(loaded.certificate, loaded.entry.key_provider()))
+            .collect()
+    } else {
+        export_entries(valid.into_iter().map(|loaded| loaded.entry), &Cancellation::new())?
+            .into_iter()
+            .map(|exported| candidate(exported.certificate, exported.key_provider))
+            .collect()
+    };
+    if candidates.is_empty() {
+        return Err(Error::new(ErrorKind::NotFound, "No valid certificates found."));
+    }
+
+    let ranking = policy.rank(&candidates, now)?;
+    let ranked: Vec<Certificate> = ranking.iter().map(|rank| candidates[rank.index].certificate.clone()).collect();
+    let selected = selection.select(&ranked)?;
+
+    let mut report = Report::new(&["Rank", "Thumbprint", "Subject", "NotAfter", "KeyProvider", "Scores", "Selected"]);
+    for (position, rank) in ranking.iter().enumerate() {
+        let candidate = &candidates[rank.index];
+        let scores: Vec<String> = rank.scores.iter().map(|score| format!("{}={}", score.criterion, score.value)).collect();
+        report.push(vec![
+            (position + 1).into(),
+            rank.thumbprint.clone().into(),
+            candidate.certificate.subject().to_dotnet().into(),
+            rfc3339_date_string(candidate.certificate.not_after()).into(),
+            candidate.key_provider.clone().into(),
+            scores.into(),
+            selected.contains(&position).into(),
+        ]);
+    }
+    Ok(report)
+}
+
+fn candidate(certificate: Certificate, key_provider: Option<String>) -> Candidate {
+    let candidate = Candidate::new(certificate, true);
+    match key_provider {
+        Some(provider) => candidate.key_provider(&provider),
+        None => candidate,
+    }
+}
+
+fn export(loaded: &Loaded, out: &Path, pem: bool, no_key: bool, password: &str) -> Result<Report> {
+    let certificate = &loaded.certificate;
+    let (contents, format) = if no_key {
+        match pem {
+            true => (certificate.to_pem()?.into_bytes(), "pem"),
+            false => (certificate.as_der().to_vec(), "der"),
+        }
+    } else {
+        let exported = ExportedCertificate::export(loaded.entry.as_ref())?;
+        match pem {
+            true => {
+                let mut contents = certificate.to_pem()?;
+                contents.push_str(&key_pem(&private_key(&exported.pfx, certificate)?, password)?);
+                (contents.into_bytes(), "pem")
+            },
+            false if password.is_empty() => (exported.pfx, "pkcs12"),
+            false => (Pkcs12Store::from_der(&exported.pfx, "")?.to_der(password)?, "pkcs12"),
+        }
+    };
+    write_file(out, &contents, !no_key)?;
+
+    Ok(Report::record(vec![
+        ("Thumbprint", certificate.thumbprint().into()),
+        ("File", out.display().to_string().into()),
+        ("Format", format.into()),
+        ("PrivateKey", (!no_key).into()),
+    ]))
+}
+
+/// The private key of `certificate` in a PKCS#12 file exported without a password.
+fn private_key(pfx: &[u8], certificate: &Certificate) -> Result<PrivateKey> {
+    Pkcs12Store::from_der(pfx, "")?
+        .private_key_for(certificate)?
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "The certificate has no private key"))
+}
+
+/// A PKCS#8 PEM key, encrypted when a password is given.
+fn key_pem(key: &PrivateKey, password: &str) -> Result<String> {
+    match password.is_empty() {
+        true => Ok(key.to_pkcs8_pem()?.to_string()),
+        false => key.to_encrypted_pkcs8_pem(password),
+    }
+}
+
+/// Writes a file, readable only by its owner when it holds a private key.
+fn write_file(path: &Path, contents: &[u8], private: bool) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    options.open(path)?.write_all(contents)
+}
+
+fn verify_chain(chain: &Chain) -> Report {
+    let mut report = Report::new(&["Depth", "Thumbprint", "Subject", "NotAfter", "Status"]);
+    for (depth, link) in chain.links.iter().enumerate() {
+        let status = match link.problems.is_empty() {
+            true => "ok".to_string(),
+            false => link.problems.iter().map(|problem| problem.to_string()).collect::<Vec<_>>().join(", "),
+        };
+        report.push(vec![
+            depth.into(),
+            link.certificate.thumbprint().into(),
+            link.certificate.subject().to_dotnet().into(),
+            rfc3339_date_string(link.certificate.not_after()).into(),
+            status.into(),
+        ]);
+    }
+    report
+}
+
+fn expiring(entries: Vec<Loaded>, horizon: SystemTime, include_expired: bool, now: SystemTime) -> Report {
+    let mut expiring: Vec<Loaded> = entries.into_iter()
+        .filter(|loaded| loaded.certificate.not_after() <= horizon)
+        .filter(|loaded| include_expired || loaded.certificate.not_after() >= now)
+        .collect();
+    expiring.sort_by_key(|loaded| loaded.certificate.not_after());
+
+    let mut report = Report::new(&["Thumbprint", "Subject", "NotAfter", "DaysLeft", "PrivateKey", "FriendlyName"]);
+    for loaded in expiring {
+        let certificate = &loaded.certificate;
+        report.push(vec![
+            certificate.thumbprint().into(),
+            certificate.subject().to_dotnet().into(),
+            rfc3339_date_string(certificate.not_after()).into(),
+            days_between(now, certificate.not_after()).into(),
+            loaded.entry.has_private_key().into(),
+            loaded.entry.friendly_name().into(),
+        ]);
+    }
+    report
+}
+
+/// Whole days from `from` to `to`, negative when `to` is earlier.
+fn days_between(from: SystemTime, to: SystemTime) -> i64 {
+    match to.duration_since(from) {
+        Ok(ahead) => (ahead.as_secs() / SECONDS_PER_DAY) as i64,
+        Err(behind) => -((behind.duration().as_secs() / SECONDS_PER_DAY) as i64) - 1,
+    }
+}
+
+/// Runs the command line tool with the process arguments and standard streams.
+pub fn main() -> i32 {
+    run(std::env::args_os(), &mut io::stdout().lock(), &mut io::stderr().lock())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert_builder::{CertificateBuilder, IssuedCertificate};
+    use crate::cert_builder::test_support::sign;
+    use crate::certificate::extensions::key_usage_from_names;
+    use serde_json::Value;
+
+    fn cli(args: &[&str]) -> (i32, String, String) {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let code = run(["py-cert-store"].iter().chain(args), &mut out, &mut err);
+        (code, String::from_utf8(out).unwrap(), String::from_utf8(err).unwrap())
+    }
+
+    fn json(args: &[&str]) -> Value {
+        let (code, out, err) = cli(&[&["--format", "json"], args].concat());
+        assert_eq!(code, 0, "{}", err);
+        serde_json::from_str(&out).unwrap()
+    }
+
+    fn issue(subject: &str, issuer: Option<&IssuedCertificate>) -> IssuedCertificate {
+        let builder = CertificateBuilder::new(subject).unwrap().valid_for_days(30);
+        match issuer {
+            Some(_) => sign(builder.alt_name("DNS:localhost".parse().unwrap()).key_usage(key_usage_from_names(&["digital_signature"]).unwrap()), issuer),
+            None => sign(builder.basic_constraints(true, None), None),
+        }
+    }
+
+    /// A directory holding a CA and a leaf certificate issued by it, both with their keys.
+    fn store(name: &str) -> (PathBuf, IssuedCertificate, IssuedCertificate) {
+        let dir = std::env::temp_dir().join(format!("py_cert_store-cli-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let ca = issue("CN=CLI Test CA", None);
+        let leaf = issue("CN=localhost", Some(&ca));
+        for (name, issued) in [("ca", &ca), ("leaf", &leaf)] {
+            let pem = issued.certificate.to_pem().unwrap() + &issued.key.to_pkcs8_pem().unwrap();
+            fs::write(dir.join(format!("{}.pem", name)), pem).unwrap();
+        }
+        (dir, ca, leaf)
+    }
+
+    #[test]
+    fn lists_and_shows_certificates() {
+        let (dir, ca, leaf) = store("list");
+        let path = dir.to_str().unwrap();
+
+        let listed = json(&["list", "--path", path]);
+        let mut thumbprints: Vec<&str> = listed.as_array().unwrap().iter().map(|row| row["Thumbprint"].as_str().unwrap()).collect();
+        thumbprints.sort();
+        let mut expected = vec![ca.certificate.thumbprint(), leaf.certificate.thumbprint()];
+        expected.sort();
+        assert_eq!(thumbprints, expected);
+
+        let shown = json(&["show", "--path", path, &leaf.certificate.thumbprint().to_lowercase()]);
+        assert_eq!(shown["Subject"], "CN=localhost");
+        assert_eq!(shown["AltNames"][0], "DNS:localhost");
+        assert_eq!(shown["PrivateKey"], true);
+        assert_eq!(shown["FriendlyName"], "leaf");
+
+        let (code, _, err) = cli(&["show", "--path", path, "00"]);
+        assert_eq!(code, 1);
+        assert!(err.contains("No certificate with thumbprint 00"));
+
+        let (code, out, _) = cli(&["--format", "csv", "expiring", "--path", path, "--days", "31"]);
+        assert_eq!(code, 0);
+        assert_eq!(out.lines().count(), 3);
+        assert!(out.lines().nth(1).unwrap().contains(",29,"));
+        assert!(json(&["expiring", "--path", path, "--days", "7"]).as_array().unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn finds_exports_and_imports() {
+        let (dir, _, leaf) = store("export");
+        let path = dir.to_str().unwrap();
+        let thumbprint = leaf.certificate.thumbprint();
+
+        let found = json(&["find", "--path", path, "--host", "localhost"]);
+        assert_eq!(found.as_array().unwrap().len(), 1);
+        assert_eq!(found[0]["Thumbprint"], thumbprint.as_str());
+        assert_eq!(found[0]["Selected"], true);
+
+        let pfx = dir.join("out.p12");
+        let exported = json(&["export", "--path", path, &thumbprint, "--out", pfx.to_str().unwrap(), "--export-password", "secret"]);
+        assert_eq!(exported["Format"], "pkcs12");
+        let reopened = Pkcs12Store::from_der(&fs::read(&pfx).unwrap(), "secret").unwrap();
+        assert!(reopened.private_key_for(&leaf.certificate).unwrap().is_some());
+
+        let target = dir.join("imported");
+        let imported = json(&["import", "--path", target.to_str().unwrap(), pfx.to_str().unwrap(), "--file-password", "secret"]);
+        assert_eq!(imported[0]["Thumbprint"], thumbprint.as_str());
+        assert_eq!(imported[0]["PrivateKey"], true);
+
+        let listed = json(&["list", "--path", target.to_str().unwrap()]);
+        assert_eq!(listed[0]["Thumbprint"], thumbprint.as_str());
+        assert_eq!(listed[0]["PrivateKey"], true);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn finds_like_get_win_cert() {
+        let dir = std::env::temp_dir().join(format!("py_cert_store-cli-filter-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // The encryption certificate outranks the signing one on its remaining validity.
+        let usage = |days: u32, key_usage: &str, purpose: &str| {
+            let builder = CertificateBuilder::new(&format!("CN={}", key_usage)).unwrap()
+                .valid_for_days(days)
+                .key_usage(key_usage_from_names(&[key_usage]).unwrap())
+                .extended_key_usage(extended_key_usage_oid(purpose).unwrap());
+            sign(builder, None)
+        };
+        let signing = usage(30, "digital_signature", "client_auth");
+        let encryption = usage(300, "key_encipherment", "email_protection");
+        for (name, issued) in [("signing", &signing), ("encryption", &encryption)] {
+            let pem = issued.certificate.to_pem().unwrap() + &issued.key.to_pkcs8_pem().unwrap();
+            fs::write(dir.join(format!("{}.pem", name)), pem).unwrap();
+        }
+        let path = dir.to_str().unwrap();
+        let selected = |found: &Value| -> Vec<String> {
+            found.as_array().unwrap().iter()
+                .filter(|row| row["Selected"] == true)
+                .map(|row| row["Subject"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        assert_eq!(selected(&json(&["find", "--path", path])), ["CN=digital_signature"]);
+        assert_eq!(selected(&json(&["find", "--path", path, "--all"])), ["CN=key_encipherment"]);
+        assert_eq!(selected(&json(&["find", "--path", path, "--extension-value", "Key Encipherment"])), ["CN=key_encipherment"]);
+        assert_eq!(selected(&json(&["find", "--path", path, "--purpose", "client_auth"])), ["CN=digital_signature"]);
+        assert_eq!(selected(&json(&["find", "--path", path, "--all", "--purpose", "smime"])), ["CN=key_encipherment"]);
+
+        // A purpose narrows the Key Usage filter instead of replacing it.
+        let (code, _, err) = cli(&["find", "--path", path, "--purpose", "smime"]);
+        assert_eq!(code, 1);
+        assert!(err.contains("No valid certificates found."));
+
+        let (code, _, _) = cli(&["find", "--path", path, "--all", "--extension-value", "Digital Signature"]);
+        assert_eq!(code, 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn verifies_chains() {
+        let (dir, ca, leaf) = store("chain");
+        let path = dir.to_str().unwrap();
+        let thumbprint = leaf.certificate.thumbprint();
+
+        let chain = json(&["verify-chain", "--path", path, &thumbprint]);
+        assert_eq!(chain[1]["Thumbprint"], ca.certificate.thumbprint().as_str());
+        assert_eq!(chain[1]["Status"], "ok");
+
+        // Roots given with --roots replace the self-signed certificates of the directory.
+        let roots = dir.join("roots.pem");
+        fs::write(&roots, issue("CN=Other", None).certificate.to_pem().unwrap()).unwrap();
+        let (code, out, _) = cli(&["verify-chain", "--path", path, &thumbprint, "--roots", roots.to_str().unwrap()]);
+        assert_eq!(code, 1);
+        assert!(out.contains("untrusted root"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
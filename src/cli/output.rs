@@ -0,0 +1,207 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use serde_json::{Map, Value};
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns for reading in a terminal.
+    Table,
+    /// A JSON array of objects, or a single object for `show`.
+    Json,
+    /// Comma separated values with a header row.
+    Csv,
+}
+
+/// The result of a command: rows of named columns, or a single record of named fields.
+///
+/// Cells are JSON values so that JSON output keeps booleans, numbers and lists; the table and CSV output show
+/// booleans as `yes`/`no` and join lists with `; `.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Value>>,
+    record: bool,
+}
+
+impl Report {
+    pub fn new(columns: &[&'static str]) -> Report {
+        Report { columns: columns.to_vec(), rows: Vec::new(), record: false }
+    }
+
+    /// A report about a single item, shown as one field per line.
+    pub fn record(fields: Vec<(&'static str, Value)>) -> Report {
+        let (columns, row) = fields.into_iter().unzip();
+        Report { columns, rows: vec![row], record: true }
+    }
+
+    pub fn push(&mut self, row: Vec<Value>) {
+        self.rows.push(row);
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Table if self.record => self.render_fields(),
+            OutputFormat::Table => self.render_table(),
+            OutputFormat::Json => self.render_json(),
+            OutputFormat::Csv => self.render_csv(),
+        }
+    }
+
+    fn render_fields(&self) -> String {
+        let width = self.columns.iter().map(|column| column.len()).max().unwrap_or_default();
+        let mut output = String::new();
+        for row in &self.rows {
+            for (column, value) in self.columns.iter().zip(row) {
+                let text = format!("{:width$}  {}", format!("{}:", column), display(value), width = width + 1);
+                output.push_str(text.trim_end());
+                output.push('\n');
+            }
+        }
+        output
+    }
+
+    fn render_table(&self) -> String {
+        let cells: Vec<Vec<String>> = self.rows.iter()
+            .map(|row| row.iter().map(display).collect())
+            .collect();
+        let widths: Vec<usize> = self.columns.iter().enumerate()
+            .map(|(i, column)| {
+                cells.iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| cell.chars().count())
+                    .fold(column.len(), usize::max)
+            })
+            .collect();
+
+        let mut output = String::new();
+        let header: Vec<String> = self.columns.iter().map(|column| column.to_string()).collect();
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        for line in [&header, &rule].into_iter().chain(&cells) {
+            let padded: Vec<String> = line.iter().zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = *width))
+                .collect();
+            output.push_str(padded.join("  ").trim_end());
+            output.push('\n');
+        }
+        output
+    }
+
+    fn render_json(&self) -> String {
+        let objects: Vec<Value> = self.rows.iter()
+            .map(|row| {
+                let object: Map<String, Value> = self.columns.iter()
+                    .map(|column| column.to_string())
+                    .zip(row.iter().cloned())
+                    .collect();
+                Value::Object(object)
+            })
+            .collect();
+
+        let value = if self.record {
+            objects.into_iter().next().unwrap_or_default()
+        } else {
+            Value::Array(objects)
+        };
+        let mut output = serde_json::to_string_pretty(&value).unwrap_or_default();
+        output.push('\n');
+        output
+    }
+
+    fn render_csv(&self) -> String {
+        let header = self.columns.iter().map(|column| csv_field(column));
+        let mut output = header.collect::<Vec<_>>().join(",");
+        output.push('\n');
+        for row in &self.rows {
+            output.push_str(&row.iter().map(|value| csv_field(&display(value))).collect::<Vec<_>>().join(","));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(true) => "yes".to_string(),
+        Value::Bool(false) => "no".to_string(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join("; "),
+        other => other.to_string(),
+    }
+}
+
+/// Quotes a CSV field when it holds a separator, a quote or a line break (RFC 4180).
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> Report {
+        let mut report = Report::new(&["Name", "Valid", "Names"]);
+        report.push(vec!["a, b".into(), true.into(), Value::from(vec!["x", "y"])]);
+        report.push(vec!["long name".into(), false.into(), Value::Null]);
+        report
+    }
+
+    #[test]
+    fn renders_tables() {
+        assert_eq!(report().render(OutputFormat::Table), concat!(
+            "Name       Valid  Names\n",
+            "---------  -----  -----\n",
+            "a, b       yes    x; y\n",
+            "long name  no\n",
+        ));
+
+        let record = Report::record(vec![("Subject", "CN=a".into()), ("PrivateKey", true.into())]);
+        assert_eq!(record.render(OutputFormat::Table), "Subject:     CN=a\nPrivateKey:  yes\n");
+    }
+
+    #[test]
+    fn renders_json_and_csv() {
+        let json: Value = serde_json::from_str(&report().render(OutputFormat::Json)).unwrap();
+        assert_eq!(json[0]["Name"], "a, b");
+        assert_eq!(json[0]["Valid"], true);
+        assert_eq!(json[0]["Names"][1], "y");
+        assert_eq!(json[1]["Names"], Value::Null);
+
+        let record = Report::record(vec![("Subject", "CN=a".into())]);
+        let json: Value = serde_json::from_str(&record.render(OutputFormat::Json)).unwrap();
+        assert_eq!(json["Subject"], "CN=a");
+
+        assert_eq!(report().render(OutputFormat::Csv), "Name,Valid,Names\n\"a, b\",yes,x; y\nlong name,no,\n");
+    }
+}
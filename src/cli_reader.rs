@@ -0,0 +1,43 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use pyo3::prelude::*;
+
+use crate::cli;
+
+
+#[pyfunction]
+#[pyo3(signature = (args, prog="py-cert-store"))]
+/// Run the py-cert-store command line tool with `args` (without the program name) and return its exit code.
+///
+/// Output goes through `sys.stdout` and `sys.stderr`, so it can be redirected or captured from Python.
+pub fn run_cli(py: Python<'_>, args: Vec<String>, prog: &str) -> PyResult<i32> {
+    let (code, out, err) = py.detach(|| {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let code = cli::run(std::iter::once(prog.to_string()).chain(args), &mut out, &mut err);
+        (code, out, err)
+    });
+
+    let sys = py.import("sys")?;
+    for (stream, text) in [("stdout", out), ("stderr", err)] {
+        if !text.is_empty() {
+            sys.getattr(stream)?.call_method1("write", (String::from_utf8_lossy(&text),))?;
+        }
+    }
+    Ok(code)
+}
@@ -0,0 +1,255 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+use std::str::FromStr;
+use der::{Any, Decode, Encode, EncodePem};
+use der::asn1::{BitString, ObjectIdentifier, SetOfVec};
+use der::oid::AssociatedOid;
+use der::pem::LineEnding;
+use spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
+use x509_cert::attr::Attribute;
+use x509_cert::ext::pkix::KeyUsage;
+use x509_cert::name::Name;
+use x509_cert::request::{CertReq, CertReqInfo, ExtensionReq, Version};
+
+use crate::certificate::invalid_data;
+use crate::certificate::name::DistinguishedName;
+use crate::certificate::extensions::{AltName, UsageExtensions};
+use crate::keys::{KeyAlgorithm, PrivateKey};
+
+
+/// PEM labels used for PKCS#10 requests, the second one is written by older Windows and Java tooling.
+const PEM_LABELS: [&str; 2] = ["CERTIFICATE REQUEST", "NEW CERTIFICATE REQUEST"];
+
+/// Collects the contents of a PKCS#10 certificate signing request before it is signed.
+#[derive(Clone, Debug)]
+pub struct CsrBuilder {
+    subject: Name,
+    usage: UsageExtensions,
+}
+
+impl CsrBuilder {
+    /// Starts a request for the given RFC 4514 subject, e.g. `"CN=client.example.com,O=Example,C=US"`.
+    pub fn new(subject: &str) -> Result<CsrBuilder> {
+        let subject = Name::from_str(subject).map_err(|err| {
+            Error::new(ErrorKind::InvalidInput, format!("Invalid subject \"{}\": {}", subject, err))
+        })?;
+        Ok(CsrBuilder { subject, usage: UsageExtensions::default() })
+    }
+
+    pub fn alt_name(mut self, name: AltName) -> CsrBuilder {
+        self.usage.alt_names.push(name);
+        self
+    }
+
+    pub fn key_usage(mut self, key_usage: KeyUsage) -> CsrBuilder {
+        self.usage.key_usage = Some(key_usage);
+        self
+    }
+
+    pub fn extended_key_usage(mut self, purpose: ObjectIdentifier) -> CsrBuilder {
+        self.usage.extended_key_usage.push(purpose);
+        self
+    }
+
+    /// Signs the request with the key whose public half is being certified.
+    pub fn build(&self, key: &PrivateKey) -> Result<CertificateRequest> {
+        let mut attributes = SetOfVec::new();
+        let extensions = self.usage.to_extensions()?;
+        if !extensions.is_empty() {
+            let mut values = SetOfVec::new();
+            values.insert(Any::encode_from(&ExtensionReq(extensions)).map_err(invalid_data)?).map_err(invalid_data)?;
+            attributes.insert(Attribute { oid: ExtensionReq::OID, values }).map_err(invalid_data)?;
+        }
+
+        let info = CertReqInfo {
+            version: Version::V1,
+            subject: self.subject.clone(),
+            public_key: key.public_key_info()?,
+            attributes,
+        };
+
+        let signature = key.sign(&info.to_der().map_err(invalid_data)?)?;
+
+        CertificateRequest::from_x509(CertReq {
+            info,
+            algorithm: key.signature_algorithm(),
+            signature: BitString::from_bytes(&signature).map_err(invalid_data)?,
+        })
+    }
+}
+
+/// A parsed PKCS#10 certificate signing request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CertificateRequest {
+    der: Vec<u8>,
+    inner: CertReq,
+}
+
+impl CertificateRequest {
+    pub fn from_der(der: &[u8]) -> Result<CertificateRequest> {
+        let inner = CertReq::from_der(der).map_err(invalid_data)?;
+        Ok(CertificateRequest { der: der.to_vec(), inner })
+    }
+
+    /// Parses a PEM request (`-----BEGIN CERTIFICATE REQUEST-----`).
+    pub fn from_pem(pem: &[u8]) -> Result<CertificateRequest> {
+        let (label, der) = der::pem::decode_vec(pem.trim_ascii()).map_err(invalid_data)?;
+        if !PEM_LABELS.contains(&label) {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Unexpected PEM label \"{}\"", label)));
+        }
+        CertificateRequest::from_der(&der)
+    }
+
+    /// Parses a request, detecting whether it is DER or PEM encoded.
+    pub fn from_bytes(data: &[u8]) -> Result<CertificateRequest> {
+        if data.trim_ascii_start().starts_with(b"-----BEGIN") {
+            CertificateRequest::from_pem(data)
+        } else {
+            CertificateRequest::from_der(data)
+        }
+    }
+
+    fn from_x509(inner: CertReq) -> Result<CertificateRequest> {
+        let der = inner.to_der().map_err(invalid_data)?;
+        Ok(CertificateRequest { der, inner })
+    }
+
+    pub fn as_der(&self) -> &[u8] {
+        &self.der
+    }
+
+    pub fn to_pem(&self) -> Result<String> {
+        self.inner.to_pem(LineEnding::LF).map_err(invalid_data)
+    }
+
+    /// The requested subject in RFC 4514 notation.
+    pub fn subject(&self) -> String {
+        DistinguishedName::from(&self.inner.info.subject).to_rfc4514()
+    }
+
+    pub fn public_key_info(&self) -> &SubjectPublicKeyInfoOwned {
+        &self.inner.info.public_key
+    }
+
+    pub fn key_algorithm(&self) -> Result<KeyAlgorithm> {
+        KeyAlgorithm::from_public_key_info(&self.inner.info.public_key)
+    }
+
+    pub fn signature_algorithm(&self) -> &AlgorithmIdentifierOwned {
+        &self.inner.algorithm
+    }
+
+    /// The alternative names, key usage and extended key usage carried in the `extensionRequest` attribute.
+    pub fn requested_extensions(&self) -> Result<UsageExtensions> {
+        let Some(attribute) = self.inner.info.attributes.iter().find(|attr| attr.oid == ExtensionReq::OID) else {
+            return Ok(UsageExtensions::default());
+        };
+
+        let mut extensions = Vec::new();
+        for value in attribute.values.iter() {
+            let request: ExtensionReq = value.decode_as().map_err(invalid_data)?;
+            extensions.extend(request.0);
+        }
+
+        UsageExtensions::from_extensions(&extensions)
+    }
+
+    /// Checks the proof of possession: the request must be signed by the key it asks to certify.
+    pub fn verify_signature(&self) -> Result<bool> {
+        let signature = self.inner.signature.as_bytes()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The request signature has unused bits"))?;
+        crate::keys::verify::verify_signature(
+            &self.inner.info.public_key,
+            &self.inner.algorithm,
+            &self.inner.info.to_der().map_err(invalid_data)?,
+            signature,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate::extensions::{extended_key_usage_name, extended_key_usage_oid, key_usage_from_names, key_usage_names};
+
+    const RSA_REQUEST: &[u8] = include_bytes!("../test/certs/request.csr");
+    const EC_REQUEST: &[u8] = include_bytes!("../test/certs/request_ec.csr");
+
+    #[test]
+    fn parses_openssl_rsa_request() {
+        let request = CertificateRequest::from_bytes(RSA_REQUEST).unwrap();
+        assert_eq!(request.subject(), "CN=Py_Cert_Store Demo File,OU=Examples,O=Py_Cert_Store Examples,L=N/A,ST=Ohio,C=US");
+        assert_eq!(request.key_algorithm().unwrap(), KeyAlgorithm::Rsa(2048));
+        assert!(request.verify_signature().unwrap());
+
+        let extensions = request.requested_extensions().unwrap();
+        assert_eq!(
+            key_usage_names(&extensions.key_usage.unwrap()),
+            ["digital_signature", "key_encipherment", "data_encipherment", "key_cert_sign", "crl_sign"]
+        );
+        let purposes: Vec<String> = extensions.extended_key_usage.iter().map(extended_key_usage_name).collect();
+        assert_eq!(purposes, ["server_auth", "client_auth"]);
+    }
+
+    #[test]
+    fn parses_openssl_ec_request_with_alt_names() {
+        let request = CertificateRequest::from_bytes(EC_REQUEST).unwrap();
+        assert_eq!(request.key_algorithm().unwrap(), KeyAlgorithm::EcP256);
+        assert!(request.verify_signature().unwrap());
+
+        let alt_names: Vec<String> = request.requested_extensions().unwrap().alt_names.iter().map(AltName::to_string).collect();
+        assert_eq!(alt_names, ["DNS:client.example.com", "DNS:*.client.example.com", "IP:10.0.0.1", "email:ops@example.com"]);
+    }
+
+    #[test]
+    fn builds_request_that_round_trips() {
+        let key = PrivateKey::generate(KeyAlgorithm::Ed25519).unwrap();
+        let request = CsrBuilder::new("CN=device-01,O=Py_Cert_Store Examples").unwrap()
+            .alt_name("DNS:device-01.example.com".parse().unwrap())
+            .alt_name("IP:::1".parse().unwrap())
+            .key_usage(key_usage_from_names(&["digital_signature"]).unwrap())
+            .extended_key_usage(extended_key_usage_oid("client_auth").unwrap())
+            .build(&key)
+            .unwrap();
+
+        let parsed = CertificateRequest::from_pem(request.to_pem().unwrap().as_bytes()).unwrap();
+        assert_eq!(parsed, request);
+        assert_eq!(parsed.subject(), "CN=device-01,O=Py_Cert_Store Examples");
+        assert!(parsed.verify_signature().unwrap());
+        assert_eq!(parsed.public_key_info(), &key.public_key_info().unwrap());
+
+        let extensions = parsed.requested_extensions().unwrap();
+        assert_eq!(extensions.alt_names, vec![
+            AltName::Dns("device-01.example.com".to_string()),
+            AltName::Ip("::1".parse().unwrap()),
+        ]);
+        assert_eq!(key_usage_names(&extensions.key_usage.unwrap()), ["digital_signature"]);
+    }
+
+    #[test]
+    fn detects_a_tampered_request() {
+        let mut der = CertificateRequest::from_bytes(EC_REQUEST).unwrap().as_der().to_vec();
+        // Flip a byte inside the subject's common name.
+        let position = der.windows(6).position(|window| window == b"client").unwrap();
+        der[position] = b'C';
+        assert!(!CertificateRequest::from_der(&der).unwrap().verify_signature().unwrap());
+    }
+}
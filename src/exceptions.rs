@@ -19,3 +19,6 @@ use pyo3::exceptions::PyException;
 
 create_exception!(py_cert_store, CertNotExportable, PyException);
 create_exception!(py_cert_store, CertNotFound, PyException);
+create_exception!(py_cert_store, CertKeyMismatch, PyException);
+create_exception!(py_cert_store, CertAmbiguous, PyException);
+create_exception!(py_cert_store, CertSearchCancelled, PyException);
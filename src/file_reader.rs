@@ -0,0 +1,271 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::types::{PyBytes, PyDict, PyString};
+use pyo3::IntoPyObjectExt;
+
+use crate::certificate::Certificate;
+use crate::file_store::jks_store::{JksEntry, JksFormat, JksStore};
+use crate::file_store::pkcs12_store::Pkcs12Store;
+use crate::file_store::pkcs7_store::Pkcs7Store;
+use crate::keys::PrivateKey;
+use crate::store_iterator::StoreCertificate;
+
+
+#[pyfunction]
+#[pyo3(signature = (data))]
+/// Read the certificates out of a PKCS#7 (.p7b) bundle, DER or PEM encoded.
+pub fn read_pkcs7_certificates(py: Python<'_>, data: &[u8]) -> PyResult<Vec<Py<PyAny>>> {
+    let store = Pkcs7Store::from_bytes(data).map_err(|err| {
+        PyValueError::new_err(format!("Could not read the PKCS#7 bundle: {}", err))
+    })?;
+
+    Ok(store.certs().map(|cert| PyBytes::new(py, cert.as_der()).into()).collect())
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificates, pem=false))]
+/// Write DER encoded certificates into a certs-only PKCS#7 (.p7b) bundle.
+pub fn write_pkcs7_certificates(py: Python<'_>, certificates: Vec<Vec<u8>>, pem: bool) -> PyResult<Py<PyAny>> {
+    let mut store = Pkcs7Store::new();
+    for der in certificates {
+        let cert = Certificate::from_der(&der).map_err(|err| {
+            PyValueError::new_err(format!("Invalid certificate: {}", err))
+        })?;
+        store.add_certificate(cert);
+    }
+
+    let output = if pem {
+        store.to_pem().map(String::into_bytes)
+    } else {
+        store.to_der()
+    };
+
+    let output = output.map_err(|err| {
+        PyValueError::new_err(format!("Could not write the PKCS#7 bundle: {}", err))
+    })?;
+
+    Ok(PyBytes::new(py, &output).into())
+}
+
+/// Wrong passwords raise `PermissionError`, anything else a `ValueError`.
+fn keystore_error(err: std::io::Error) -> PyErr {
+    match err.kind() {
+        ErrorKind::PermissionDenied => err.into(),
+        _ => PyValueError::new_err(format!("Could not read the keystore: {}", err)),
+    }
+}
+
+/// The certificate chain of a keystore entry, with its private key, as a PKCS#12 file without a password.
+fn entry_pkcs12(entry: &JksEntry, key: &PrivateKey) -> std::io::Result<Vec<u8>> {
+    let mut pfx = Pkcs12Store::new();
+    for (index, certificate) in entry.chain.iter().enumerate() {
+        if index == 0 {
+            let local_key_id = certificate.thumbprint().into_bytes();
+            pfx.add_certificate(certificate.clone(), Some(&entry.alias), Some(&local_key_id));
+            pfx.add_key(&key.to_pkcs8_der()?, Some(&entry.alias), Some(&local_key_id));
+        } else {
+            pfx.add_certificate(certificate.clone(), None, None);
+        }
+    }
+    pfx.to_der("")
+}
+
+fn entry_to_dict(py: Python<'_>, entry: &JksEntry) -> PyResult<HashMap<String, Py<PyAny>>> {
+    let mut dict = HashMap::new();
+    dict.insert("Alias".to_string(), entry.alias.clone().into_py_any(py)?);
+    dict.insert("EntryType".to_string(), entry.kind.to_string().into_py_any(py)?);
+    dict.insert("Created".to_string(), DateTime::<Utc>::from(entry.created).into_py_any(py)?);
+    let chain: Vec<Py<PyAny>> = entry.chain.iter().map(|certificate| PyBytes::new(py, certificate.as_der()).into()).collect();
+    dict.insert("Certificate".to_string(), chain.first().map(|certificate| certificate.clone_ref(py)).into_py_any(py)?);
+    dict.insert("Chain".to_string(), chain.into_py_any(py)?);
+    dict.insert("HasPrivateKey".to_string(), entry.key.is_some().into_py_any(py)?);
+    if let Some(key) = &entry.key {
+        let pfx = entry_pkcs12(entry, key).map_err(|err| PyValueError::new_err(err.to_string()))?;
+        dict.insert("cert".to_string(), PyBytes::new(py, &pfx).into_py_any(py)?);
+    }
+    Ok(dict)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, password=None, key_password=None))]
+/// Read the entries of a Java keystore (.jks / .jceks).
+///
+/// Without a password the integrity of the keystore is not checked and the private keys are not read.
+pub fn read_java_keystore(
+    py: Python<'_>, data: &[u8], password: Option<&str>, key_password: Option<&str>
+) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+    let store = py.detach(|| JksStore::from_bytes(data, password, key_password)).map_err(keystore_error)?;
+    store.entries().iter().map(|entry| entry_to_dict(py, entry)).collect()
+}
+
+/// A keystore entry to write: its certificate chain, leaf first, and its private key.
+struct KeystoreInput {
+    name: String,
+    chain: Vec<Certificate>,
+    key: Option<PrivateKey>,
+}
+
+/// A certificate to write as taken from its Python object, decoded and exported once the GIL is released.
+enum KeystoreSource {
+    Stored(Py<StoreCertificate>),
+    Pkcs12(Vec<u8>, String),
+    Encoded(Vec<u8>),
+    Ready(Box<KeystoreInput>),
+}
+
+impl KeystoreInput {
+    /// Reads a PKCS#12 file without a password, as exported by the search functions.
+    ///
+    /// The leaf is the certificate paired with the private key, by its local key ID or its public key, or else
+    /// the one that issued none of the other certificates, as PKCS#12 files may hold the CA first.
+    fn from_pkcs12(pfx: &[u8], name: String, include_private_key: bool) -> PyResult<KeystoreInput> {
+        let store = Pkcs12Store::from_der(pfx, "").map_err(|err| PyValueError::new_err(format!("Could not read the PKCS#12 file: {}", err)))?;
+        let mut chain: Vec<Certificate> = store.certs().collect();
+        let mut key = None;
+        let mut leaf = None;
+        if include_private_key {
+            for (index, certificate) in chain.iter().enumerate() {
+                if let Some(found) = store.private_key_for(certificate).map_err(|err| PyValueError::new_err(err.to_string()))? {
+                    key = Some(found);
+                    leaf = Some(index);
+                    break;
+                }
+            }
+        }
+        let leaf = leaf
+            .or_else(|| store.certificate_bags().iter().position(|bag| {
+                bag.local_key_id.is_some() && store.key_bags().iter().any(|key| key.local_key_id == bag.local_key_id)
+            }))
+            .or_else(|| chain.iter().position(|certificate| !chain.iter().any(|other| other != certificate && issued(certificate, other))))
+            .unwrap_or(0);
+        if chain.is_empty() {
+            return Err(PyValueError::new_err("The PKCS#12 file has no certificate"));
+        }
+        let leaf = chain.remove(leaf);
+        chain.insert(0, leaf);
+        Ok(KeystoreInput { name, chain, key })
+    }
+
+    fn from_source(source: KeystoreSource, include_private_key: bool) -> PyResult<KeystoreInput> {
+        match source {
+            KeystoreSource::Stored(stored) => {
+                let exported = stored.get().export_detached()?;
+                KeystoreInput::from_pkcs12(&exported.pfx, exported.friendly_name, true)
+            },
+            KeystoreSource::Pkcs12(pfx, name) => KeystoreInput::from_pkcs12(&pfx, name, include_private_key),
+            KeystoreSource::Encoded(data) => match Certificate::from_bytes(&data) {
+                Ok(certificate) => Ok(KeystoreInput { name: String::new(), chain: vec![certificate], key: None }),
+                Err(_) => KeystoreInput::from_pkcs12(&data, String::new(), include_private_key),
+            },
+            KeystoreSource::Ready(input) => Ok(*input),
+        }
+    }
+}
+
+impl KeystoreSource {
+    fn from_py(certificate: &Bound<'_, PyAny>, include_private_key: bool) -> PyResult<KeystoreSource> {
+        if let Ok(stored) = certificate.cast::<StoreCertificate>() {
+            let certificate = stored.get();
+            if include_private_key && certificate.has_private_key {
+                return Ok(KeystoreSource::Stored(stored.clone().unbind()));
+            }
+            let input = KeystoreInput { name: certificate.friendly_name.clone(), chain: vec![certificate.certificate.clone()], key: None };
+            return Ok(KeystoreSource::Ready(Box::new(input)));
+        }
+
+        if let Ok(dict) = certificate.cast::<PyDict>() {
+            let pfx: Vec<u8> = dict.get_item("cert")?
+                .ok_or_else(|| PyValueError::new_err("The dictionary has no \"cert\" entry"))?
+                .extract()?;
+            let name = dict.get_item("FriendlyName")?.map(|name| name.extract::<String>()).transpose()?.unwrap_or_default();
+            return Ok(KeystoreSource::Pkcs12(pfx, name));
+        }
+
+        if let Ok(text) = certificate.cast::<PyString>() {
+            Ok(KeystoreSource::Encoded(text.to_str()?.as_bytes().to_vec()))
+        } else if let Ok(bytes) = certificate.cast::<PyBytes>() {
+            Ok(KeystoreSource::Encoded(bytes.as_bytes().to_vec()))
+        } else {
+            Err(PyTypeError::new_err("Expected a StoreCertificate, a search result dictionary, or certificate bytes"))
+        }
+    }
+}
+
+/// Whether `issuer` issued `certificate`, going by the names.
+fn issued(issuer: &Certificate, certificate: &Certificate) -> bool {
+    issuer.as_x509().tbs_certificate.subject == certificate.as_x509().tbs_certificate.issuer
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificates, password, format="jks", key_password=None, include_private_keys=true))]
+/// Write certificates into a Java keystore (.jks / .jceks).
+///
+/// Certificates with a private key become key entries, unless `include_private_keys` is false; the others become
+/// trusted certificate entries, as in a truststore. Entries are named after the friendly name, or the thumbprint.
+pub fn write_java_keystore(
+    py: Python<'_>, certificates: Vec<Bound<'_, PyAny>>, password: &str, format: &str, key_password: Option<&str>,
+    include_private_keys: bool
+) -> PyResult<Py<PyAny>> {
+    let format: JksFormat = format.parse().map_err(|err: std::io::Error| PyValueError::new_err(err.to_string()))?;
+    let sources = certificates.iter()
+        .map(|certificate| KeystoreSource::from_py(certificate, include_private_keys))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let output = py.detach(|| {
+        let inputs = sources.into_iter()
+            .map(|source| KeystoreInput::from_source(source, include_private_keys))
+            .collect::<PyResult<Vec<_>>>()?;
+        write_keystore(inputs, format, password, key_password)
+            .map_err(|err| PyValueError::new_err(format!("Could not write the keystore: {}", err)))
+    })?;
+
+    Ok(PyBytes::new(py, &output).into())
+}
+
+/// Writes the entries into a keystore, named after their friendly name or thumbprint and made unique.
+fn write_keystore(inputs: Vec<KeystoreInput>, format: JksFormat, password: &str, key_password: Option<&str>) -> std::io::Result<Vec<u8>> {
+    let mut store = JksStore::new(format);
+    let mut aliases: Vec<String> = Vec::new();
+    for input in inputs {
+        let KeystoreInput { name, chain, key } = input;
+        let base = match name.is_empty() {
+            true => chain[0].thumbprint().to_lowercase(),
+            false => name,
+        };
+        let mut alias = base.clone();
+        let mut count = 1;
+        while aliases.iter().any(|existing| existing.eq_ignore_ascii_case(&alias)) {
+            count += 1;
+            alias = format!("{} ({})", base, count);
+        }
+        aliases.push(alias.clone());
+
+        match key {
+            Some(key) => store.add_private_key(&alias, key, chain)?,
+            None => store.add_trusted_certificate(&alias, chain[0].clone()),
+        }
+    }
+    store.to_bytes(password, key_password)
+}
@@ -0,0 +1,635 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::fmt;
+use std::fs;
+use std::io::{Result, Error, ErrorKind};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use cbc::cipher::block_padding::Pkcs7;
+use der::{Any, Decode, Encode, Sequence};
+use der::asn1::{ObjectIdentifier, OctetString};
+use md5::Md5;
+use rand_core::{OsRng, RngCore};
+use sha1::{Digest, Sha1};
+use spki::AlgorithmIdentifierOwned;
+use zeroize::Zeroizing;
+
+use crate::certificate::{Certificate, invalid_data};
+use crate::keys::PrivateKey;
+use crate::store::memory::MemoryStore;
+
+const JKS_MAGIC: u32 = 0xFEED_FEED;
+const JCEKS_MAGIC: u32 = 0xCECE_CECE;
+const VERSION: u32 = 2;
+const TAG_PRIVATE_KEY: u32 = 1;
+const TAG_TRUSTED_CERTIFICATE: u32 = 2;
+const TAG_SECRET_KEY: u32 = 3;
+const CERTIFICATE_TYPE: &str = "X.509";
+
+/// Mixed into the keystore integrity hash, as `sun.security.provider.JavaKeyStore` does.
+const INTEGRITY_SALT: &[u8] = b"Mighty Aphrodite";
+
+/// Sun's proprietary key protection of JKS keystores, a SHA-1 keystream.
+const OID_JKS_KEY_PROTECTOR: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.42.2.17.1.1");
+/// `PBEWithMD5AndTripleDES`, the key protection of JCEKS keystores.
+const OID_PBE_MD5_TRIPLE_DES: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.42.2.19.1");
+/// The iteration count the SunJCE provider uses for `PBEWithMD5AndTripleDES`.
+const JCEKS_ITERATIONS: u32 = 200_000;
+
+/// `EncryptedPrivateKeyInfo` with the Sun algorithms, which `pkcs8::EncryptedPrivateKeyInfo` does not recognise.
+#[derive(Clone, Debug, Sequence)]
+struct EncryptedPrivateKeyInfo {
+    encryption_algorithm: AlgorithmIdentifierOwned,
+    encrypted_data: OctetString,
+}
+
+/// `PBEParameter` as defined in PKCS#5 v1.5.
+#[derive(Clone, Debug, Sequence)]
+struct PbeParameter {
+    salt: OctetString,
+    iteration_count: u32,
+}
+
+/// The two Java keystore formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JksFormat {
+    /// The `JKS` type of the SUN provider. Aliases are lower case.
+    Jks,
+    /// The `JCEKS` type of the SunJCE provider, with stronger (3DES) key protection.
+    Jceks,
+}
+
+impl fmt::Display for JksFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JksFormat::Jks => write!(f, "JKS"),
+            JksFormat::Jceks => write!(f, "JCEKS"),
+        }
+    }
+}
+
+impl FromStr for JksFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<JksFormat> {
+        match s.to_lowercase().as_str() {
+            "jks" => Ok(JksFormat::Jks),
+            "jceks" => Ok(JksFormat::Jceks),
+            _ => Err(Error::new(ErrorKind::InvalidInput, format!("Unsupported keystore format \"{}\", expected \"jks\" or \"jceks\"", s))),
+        }
+    }
+}
+
+/// The kind of a keystore entry, named as `keytool -list` shows them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JksEntryKind {
+    PrivateKeyEntry,
+    TrustedCertEntry,
+}
+
+impl fmt::Display for JksEntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JksEntryKind::PrivateKeyEntry => write!(f, "PrivateKeyEntry"),
+            JksEntryKind::TrustedCertEntry => write!(f, "trustedCertEntry"),
+        }
+    }
+}
+
+/// An entry of a keystore.
+#[derive(Clone, Debug)]
+pub struct JksEntry {
+    pub alias: String,
+    pub created: SystemTime,
+    pub kind: JksEntryKind,
+    /// The certificate of a trusted certificate entry, or the chain of a private key entry, leaf first.
+    pub chain: Vec<Certificate>,
+    /// The private key of a private key entry, when the keystore was read with a password.
+    pub key: Option<PrivateKey>,
+}
+
+/// An in-memory view of a Java keystore (.jks / .jceks).
+///
+/// Trusted certificate and private key entries are read and written; the secret key entries of JCEKS keystores
+/// are Java serialized objects, and are rejected. Keystores are written in version 2, which every Java version reads.
+#[derive(Clone, Debug)]
+pub struct JksStore {
+    format: JksFormat,
+    entries: Vec<JksEntry>,
+}
+
+impl JksStore {
+    pub fn new(format: JksFormat) -> JksStore {
+        JksStore { format, entries: Vec::new() }
+    }
+
+    /// Whether the data starts like a JKS or JCEKS keystore.
+    pub fn is_keystore(data: &[u8]) -> bool {
+        matches!(data.get(..4).map(|magic| u32::from_be_bytes([magic[0], magic[1], magic[2], magic[3]])), Some(JKS_MAGIC | JCEKS_MAGIC))
+    }
+
+    pub fn open_file<P: AsRef<Path>>(path: P, password: Option<&str>, key_password: Option<&str>) -> Result<JksStore> {
+        let data = fs::read(path)?;
+        JksStore::from_bytes(&data, password, key_password)
+    }
+
+    /// Parses a keystore and decrypts its private keys with `key_password`, or the keystore password by default.
+    ///
+    /// Without a password the integrity hash is not checked and the private keys are not read, as `keytool -list`
+    /// does. A keystore password that does not match the integrity hash, or a wrong key password, fails with
+    /// `PermissionDenied`.
+    pub fn from_bytes(data: &[u8], password: Option<&str>, key_password: Option<&str>) -> Result<JksStore> {
+        if data.len() < 20 {
+            return Err(Error::new(ErrorKind::InvalidData, "The keystore is truncated"));
+        }
+        let (body, hash) = data.split_at(data.len() - 20);
+        if let Some(password) = password {
+            if integrity_hash(password, body).as_slice() != hash {
+                return Err(Error::new(ErrorKind::PermissionDenied, "The keystore password is wrong, or the keystore was tampered with"));
+            }
+        }
+
+        let mut reader = Reader(body);
+        let format = match reader.u32()? {
+            JKS_MAGIC => JksFormat::Jks,
+            JCEKS_MAGIC => JksFormat::Jceks,
+            _ => return Err(Error::new(ErrorKind::InvalidData, "Not a JKS or JCEKS keystore")),
+        };
+        let version = reader.u32()?;
+        if version != 1 && version != 2 {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Unsupported keystore version {}", version)));
+        }
+
+        let key_password = key_password.or(password);
+        let mut entries = Vec::new();
+        for _ in 0..reader.u32()? {
+            let tag = reader.u32()?;
+            let alias = reader.utf()?;
+            let created = UNIX_EPOCH + Duration::from_millis(reader.u64()?);
+            match tag {
+                TAG_PRIVATE_KEY => {
+                    let protected = reader.bytes()?;
+                    let mut chain = Vec::new();
+                    for _ in 0..reader.u32()? {
+                        chain.push(reader.certificate(version)?);
+                    }
+                    let key = match key_password {
+                        Some(key_password) => unprotect_key(protected, key_password)?,
+                        None => None,
+                    };
+                    entries.push(JksEntry { alias, created, kind: JksEntryKind::PrivateKeyEntry, chain, key });
+                },
+                TAG_TRUSTED_CERTIFICATE => {
+                    let chain = vec![reader.certificate(version)?];
+                    entries.push(JksEntry { alias, created, kind: JksEntryKind::TrustedCertEntry, chain, key: None });
+                },
+                TAG_SECRET_KEY => return Err(Error::new(ErrorKind::Unsupported, format!("The secret key entry \"{}\" cannot be read", alias))),
+                tag => return Err(Error::new(ErrorKind::InvalidData, format!("Unknown keystore entry type {}", tag))),
+            }
+        }
+
+        Ok(JksStore { format, entries })
+    }
+
+    pub fn format(&self) -> JksFormat {
+        self.format
+    }
+
+    pub fn entries(&self) -> &[JksEntry] {
+        &self.entries
+    }
+
+    /// Adds a trusted certificate entry, replacing the entry with the same alias.
+    pub fn add_trusted_certificate(&mut self, alias: &str, certificate: Certificate) {
+        self.insert(JksEntry {
+            alias: alias.to_string(),
+            created: SystemTime::now(),
+            kind: JksEntryKind::TrustedCertEntry,
+            chain: vec![certificate],
+            key: None,
+        });
+    }
+
+    /// Adds a private key entry with its certificate chain, leaf first, replacing the entry with the same alias.
+    pub fn add_private_key(&mut self, alias: &str, key: PrivateKey, chain: Vec<Certificate>) -> Result<()> {
+        let leaf = chain.first().ok_or_else(|| Error::new(ErrorKind::InvalidInput, "A private key entry needs a certificate chain"))?;
+        if !key.matches_certificate(leaf)? {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("The private key of \"{}\" does not belong to the first certificate of the chain", alias)));
+        }
+        self.insert(JksEntry { alias: alias.to_string(), created: SystemTime::now(), kind: JksEntryKind::PrivateKeyEntry, chain, key: Some(key) });
+        Ok(())
+    }
+
+    fn insert(&mut self, mut entry: JksEntry) {
+        // JKS looks aliases up in lower case, so they are stored that way.
+        if self.format == JksFormat::Jks {
+            entry.alias = entry.alias.to_lowercase();
+        }
+        self.entries.retain(|existing| !existing.alias.eq_ignore_ascii_case(&entry.alias));
+        self.entries.push(entry);
+    }
+
+    /// Encodes the keystore, protecting the private keys with `key_password`, or the keystore password by default.
+    ///
+    /// Private key entries read without a password cannot be written back, and fail with `InvalidInput`.
+    pub fn to_bytes(&self, password: &str, key_password: Option<&str>) -> Result<Vec<u8>> {
+        let key_password = key_password.unwrap_or(password);
+        let mut out = Vec::new();
+        out.extend_from_slice(&match self.format {
+            JksFormat::Jks => JKS_MAGIC,
+            JksFormat::Jceks => JCEKS_MAGIC,
+        }.to_be_bytes());
+        out.extend_from_slice(&VERSION.to_be_bytes());
+        out.extend_from_slice(&length(self.entries.len())?.to_be_bytes());
+
+        for entry in &self.entries {
+            let created = entry.created.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+            match entry.kind {
+                JksEntryKind::PrivateKeyEntry => {
+                    let key = entry.key.as_ref().ok_or_else(|| {
+                        Error::new(ErrorKind::InvalidInput, format!("The private key of \"{}\" was not read, so it cannot be written", entry.alias))
+                    })?;
+                    out.extend_from_slice(&TAG_PRIVATE_KEY.to_be_bytes());
+                    write_utf(&mut out, &entry.alias)?;
+                    out.extend_from_slice(&created.to_be_bytes());
+                    write_bytes(&mut out, &protect_key(self.format, key, key_password)?)?;
+                    out.extend_from_slice(&length(entry.chain.len())?.to_be_bytes());
+                },
+                JksEntryKind::TrustedCertEntry => {
+                    out.extend_from_slice(&TAG_TRUSTED_CERTIFICATE.to_be_bytes());
+                    write_utf(&mut out, &entry.alias)?;
+                    out.extend_from_slice(&created.to_be_bytes());
+                },
+            }
+            for certificate in &entry.chain {
+                write_utf(&mut out, CERTIFICATE_TYPE)?;
+                write_bytes(&mut out, certificate.as_der())?;
+            }
+        }
+
+        let hash = integrity_hash(password, &out);
+        out.extend_from_slice(&hash);
+        Ok(out)
+    }
+
+    /// Copies the entries into a store that can be searched like the other stores, named after their alias.
+    pub fn to_memory_store(&self) -> MemoryStore {
+        let mut store = MemoryStore::new();
+        for entry in &self.entries {
+            let Some(leaf) = entry.chain.first() else {
+                continue;
+            };
+            match &entry.key {
+                Some(key) => store.add_certificate_with_key(leaf.clone(), key.clone(), &entry.alias, true),
+                None => store.add_certificate(leaf.clone(), &entry.alias),
+            }
+        }
+        store
+    }
+}
+
+/// Reads the big endian fields of a keystore.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8]> {
+        if self.0.len() < count {
+            return Err(Error::new(ErrorKind::InvalidData, "The keystore is truncated"));
+        }
+        let (taken, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok((u64::from(self.u32()?) << 32) | u64::from(self.u32()?))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let count = self.u32()? as usize;
+        self.take(count)
+    }
+
+    /// A string in the modified UTF-8 of `DataOutput.writeUTF`: UTF-16 code units, each encoded on 1 to 3 bytes.
+    fn utf(&mut self) -> Result<String> {
+        let count = self.u16()? as usize;
+        let bytes = self.take(count)?;
+        let mut units = Vec::with_capacity(count);
+        let mut index = 0;
+        while index < bytes.len() {
+            let byte = u16::from(bytes[index]);
+            let continuation = |offset: usize| -> Result<u16> {
+                match bytes.get(index + offset) {
+                    Some(byte) if byte & 0xC0 == 0x80 => Ok(u16::from(byte & 0x3F)),
+                    _ => Err(Error::new(ErrorKind::InvalidData, "Malformed string in the keystore")),
+                }
+            };
+            let (unit, size) = match byte {
+                0x00..=0x7F => (byte, 1),
+                0xC0..=0xDF => (((byte & 0x1F) << 6) | continuation(1)?, 2),
+                0xE0..=0xEF => (((byte & 0x0F) << 12) | (continuation(1)? << 6) | continuation(2)?, 3),
+                _ => return Err(Error::new(ErrorKind::InvalidData, "Malformed string in the keystore")),
+            };
+            units.push(unit);
+            index += size;
+        }
+        Ok(String::from_utf16_lossy(&units))
+    }
+
+    fn certificate(&mut self, version: u32) -> Result<Certificate> {
+        if version == 2 {
+            let kind = self.utf()?;
+            if kind != CERTIFICATE_TYPE {
+                return Err(Error::new(ErrorKind::Unsupported, format!("Unsupported certificate type \"{}\"", kind)));
+            }
+        }
+        Certificate::from_der(self.bytes()?)
+    }
+}
+
+fn length(count: usize) -> Result<u32> {
+    u32::try_from(count).map_err(|_| Error::new(ErrorKind::InvalidInput, "The keystore entry is too large"))
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) -> Result<()> {
+    out.extend_from_slice(&length(bytes.len())?.to_be_bytes());
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+fn write_utf(out: &mut Vec<u8>, text: &str) -> Result<()> {
+    let mut encoded = Vec::new();
+    for unit in text.encode_utf16() {
+        match unit {
+            0x01..=0x7F => encoded.push(unit as u8),
+            0x00 | 0x80..=0x7FF => encoded.extend_from_slice(&[0xC0 | (unit >> 6) as u8, 0x80 | (unit & 0x3F) as u8]),
+            _ => encoded.extend_from_slice(&[0xE0 | (unit >> 12) as u8, 0x80 | ((unit >> 6) & 0x3F) as u8, 0x80 | (unit & 0x3F) as u8]),
+        }
+    }
+    let count = u16::try_from(encoded.len()).map_err(|_| Error::new(ErrorKind::InvalidInput, "The keystore alias is too long"))?;
+    out.extend_from_slice(&count.to_be_bytes());
+    out.extend_from_slice(&encoded);
+    Ok(())
+}
+
+/// Java passwords are hashed as UTF-16 big endian code units.
+fn password_bytes(password: &str) -> Zeroizing<Vec<u8>> {
+    Zeroizing::new(password.encode_utf16().flat_map(u16::to_be_bytes).collect())
+}
+
+/// `SHA-1(password || "Mighty Aphrodite" || keystore)`, appended to every keystore.
+fn integrity_hash(password: &str, body: &[u8]) -> [u8; 20] {
+    let mut hash = Sha1::new();
+    hash.update(password_bytes(password).as_slice());
+    hash.update(INTEGRITY_SALT);
+    hash.update(body);
+    hash.finalize().into()
+}
+
+/// Decrypts a protected key. Keys of an algorithm this crate does not support are left out.
+fn unprotect_key(protected: &[u8], password: &str) -> Result<Option<PrivateKey>> {
+    let info = EncryptedPrivateKeyInfo::from_der(protected).map_err(invalid_data)?;
+    let encrypted = info.encrypted_data.as_bytes();
+    let plaintext = match info.encryption_algorithm.oid {
+        OID_JKS_KEY_PROTECTOR => jks_unprotect(encrypted, password)?,
+        OID_PBE_MD5_TRIPLE_DES => {
+            let parameters: PbeParameter = info.encryption_algorithm.parameters.as_ref()
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The PBE parameters are missing"))?
+                .decode_as()
+                .map_err(invalid_data)?;
+            let (key, iv) = jceks_key_iv(parameters.salt.as_bytes(), parameters.iteration_count, password)?;
+            Zeroizing::new(cbc::Decryptor::<des::TdesEde3>::new_from_slices(&key[..], &iv)
+                .map_err(invalid_data)?
+                .decrypt_padded_vec_mut::<Pkcs7>(encrypted)
+                .map_err(|_| wrong_key_password())?)
+        },
+        oid => return Err(Error::new(ErrorKind::Unsupported, format!("Unsupported key protection algorithm {}", oid))),
+    };
+    Ok(PrivateKey::from_pkcs8_der(&plaintext).ok())
+}
+
+fn protect_key(format: JksFormat, key: &PrivateKey, password: &str) -> Result<Vec<u8>> {
+    let plaintext = key.to_pkcs8_der()?;
+    let info = match format {
+        JksFormat::Jks => EncryptedPrivateKeyInfo {
+            encryption_algorithm: AlgorithmIdentifierOwned { oid: OID_JKS_KEY_PROTECTOR, parameters: Some(Any::null()) },
+            encrypted_data: OctetString::new(jks_protect(&plaintext, password)).map_err(invalid_data)?,
+        },
+        JksFormat::Jceks => {
+            let mut salt = [0u8; 8];
+            OsRng.fill_bytes(&mut salt);
+            let (key, iv) = jceks_key_iv(&salt, JCEKS_ITERATIONS, password)?;
+            let ciphertext = cbc::Encryptor::<des::TdesEde3>::new_from_slices(&key[..], &iv)
+                .map_err(invalid_data)?
+                .encrypt_padded_vec_mut::<Pkcs7>(&plaintext);
+            let parameters = PbeParameter { salt: OctetString::new(salt.to_vec()).map_err(invalid_data)?, iteration_count: JCEKS_ITERATIONS };
+            EncryptedPrivateKeyInfo {
+                encryption_algorithm: AlgorithmIdentifierOwned {
+                    oid: OID_PBE_MD5_TRIPLE_DES,
+                    parameters: Some(Any::encode_from(&parameters).map_err(invalid_data)?),
+                },
+                encrypted_data: OctetString::new(ciphertext).map_err(invalid_data)?,
+            }
+        },
+    };
+    info.to_der().map_err(invalid_data)
+}
+
+fn wrong_key_password() -> Error {
+    Error::new(ErrorKind::PermissionDenied, "The key password is wrong")
+}
+
+/// The SHA-1 keystream XORed with the key by `sun.security.provider.KeyProtector`.
+fn jks_keystream(salt: &[u8], password: &[u8], length: usize) -> Zeroizing<Vec<u8>> {
+    let mut stream = Zeroizing::new(Vec::with_capacity(length + 20));
+    let mut digest = salt.to_vec();
+    while stream.len() < length {
+        digest = Sha1::new().chain_update(password).chain_update(&digest).finalize().to_vec();
+        stream.extend_from_slice(&digest);
+    }
+    stream.truncate(length);
+    stream
+}
+
+/// Reverses the JKS key protection: a 20 byte salt, the XORed key, and a SHA-1 check of the password and key.
+fn jks_unprotect(encrypted: &[u8], password: &str) -> Result<Zeroizing<Vec<u8>>> {
+    if encrypted.len() < 40 {
+        return Err(Error::new(ErrorKind::InvalidData, "The protected key is truncated"));
+    }
+    let password = password_bytes(password);
+    let (salt, rest) = encrypted.split_at(20);
+    let (ciphertext, check) = rest.split_at(rest.len() - 20);
+
+    let stream = jks_keystream(salt, &password, ciphertext.len());
+    let plaintext = Zeroizing::new(ciphertext.iter().zip(stream.iter()).map(|(byte, key)| byte ^ key).collect::<Vec<u8>>());
+    let expected = Sha1::new().chain_update(password.as_slice()).chain_update(plaintext.as_slice()).finalize();
+    if expected[..] != *check {
+        return Err(wrong_key_password());
+    }
+    Ok(plaintext)
+}
+
+fn jks_protect(plaintext: &[u8], password: &str) -> Vec<u8> {
+    let password = password_bytes(password);
+    let mut salt = [0u8; 20];
+    OsRng.fill_bytes(&mut salt);
+
+    let stream = jks_keystream(&salt, &password, plaintext.len());
+    let mut protected = salt.to_vec();
+    protected.extend(plaintext.iter().zip(stream.iter()).map(|(byte, key)| byte ^ key));
+    protected.extend_from_slice(&Sha1::new().chain_update(password.as_slice()).chain_update(plaintext).finalize());
+    protected
+}
+
+/// The 3DES key and IV of SunJCE's `PBEWithMD5AndTripleDES`: each half of the salt is hashed with the password
+/// `iterations` times, after reversing the first half when both are equal.
+fn jceks_key_iv(salt: &[u8], iterations: u32, password: &str) -> Result<(Zeroizing<[u8; 24]>, [u8; 8])> {
+    if salt.len() != 8 {
+        return Err(Error::new(ErrorKind::InvalidData, "The PBE salt must be 8 bytes long"));
+    }
+    // The SunJCE provider only takes printable ASCII passwords, one byte per character.
+    if !password.bytes().all(|byte| (0x20..=0x7E).contains(&byte)) {
+        return Err(Error::new(ErrorKind::InvalidInput, "JCEKS passwords must be printable ASCII"));
+    }
+
+    let mut salt = salt.to_vec();
+    if salt[..4] == salt[4..] {
+        salt[..4].reverse();
+    }
+
+    let mut derived = Zeroizing::new([0u8; 32]);
+    for (half, output) in salt.chunks(4).zip(derived.chunks_mut(16)) {
+        let mut digest = half.to_vec();
+        for _ in 0..iterations {
+            digest = Md5::new().chain_update(&digest).chain_update(password.as_bytes()).finalize().to_vec();
+        }
+        output.copy_from_slice(&digest);
+    }
+
+    let mut key = Zeroizing::new([0u8; 24]);
+    key.copy_from_slice(&derived[..24]);
+    let mut iv = [0u8; 8];
+    iv.copy_from_slice(&derived[24..]);
+    Ok((key, iv))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JKS: &[u8] = include_bytes!("../../test/jks/keystore.jks");
+    const JCEKS: &[u8] = include_bytes!("../../test/jks/keystore.jceks");
+
+    fn summary(store: &JksStore) -> Vec<(String, JksEntryKind, usize, bool)> {
+        let mut entries: Vec<_> = store.entries().iter()
+            .map(|entry| (entry.alias.clone(), entry.kind, entry.chain.len(), entry.key.is_some()))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+
+    #[test]
+    fn reads_keytool_keystores() {
+        for (data, format) in [(JKS, JksFormat::Jks), (JCEKS, JksFormat::Jceks)] {
+            let store = JksStore::from_bytes(data, Some("changeit"), None).unwrap();
+            assert_eq!(store.format(), format);
+            assert_eq!(summary(&store), vec![
+                ("ec client".to_string(), JksEntryKind::PrivateKeyEntry, 1, true),
+                ("root".to_string(), JksEntryKind::TrustedCertEntry, 1, false),
+                ("server".to_string(), JksEntryKind::PrivateKeyEntry, 2, true),
+            ]);
+
+            let server = store.entries().iter().find(|entry| entry.alias == "server").unwrap();
+            let root = store.entries().iter().find(|entry| entry.alias == "root").unwrap();
+            assert!(server.key.as_ref().unwrap().matches_certificate(&server.chain[0]).unwrap());
+            assert_eq!(server.chain[1], root.chain[0]);
+        }
+    }
+
+    #[test]
+    fn checks_the_passwords() {
+        let without_password = JksStore::from_bytes(JKS, None, None).unwrap();
+        assert!(without_password.entries().iter().all(|entry| entry.key.is_none()));
+        assert_eq!(without_password.entries().len(), 3);
+
+        assert_eq!(JksStore::from_bytes(JKS, Some("wrong"), None).unwrap_err().kind(), ErrorKind::PermissionDenied);
+        assert_eq!(JksStore::from_bytes(JCEKS, Some("changeit"), Some("wrong")).unwrap_err().kind(), ErrorKind::PermissionDenied);
+        assert!(!JksStore::is_keystore(include_bytes!("../../test/certs/certificate.p12")));
+    }
+
+    #[test]
+    fn round_trips() {
+        for data in [JKS, JCEKS] {
+            let store = JksStore::from_bytes(data, Some("changeit"), None).unwrap();
+            let written = store.to_bytes("n3w pass", Some("key pass")).unwrap();
+            let read = JksStore::from_bytes(&written, Some("n3w pass"), Some("key pass")).unwrap();
+            assert_eq!(read.format(), store.format());
+            assert_eq!(summary(&read), summary(&store));
+            for (before, after) in store.entries().iter().zip(read.entries()) {
+                assert_eq!(before.chain, after.chain);
+                assert_eq!(before.created, after.created);
+                assert_eq!(before.key.as_ref().map(|key| key.to_pkcs8_der().unwrap()), after.key.as_ref().map(|key| key.to_pkcs8_der().unwrap()));
+            }
+        }
+    }
+
+    #[test]
+    fn builds_keystores() {
+        let certificate = Certificate::from_pem(include_bytes!("../../test/certs/certificate.crt")).unwrap();
+        let ca = Certificate::from_pem(include_bytes!("../../test/certs/ca_certificate.crt")).unwrap();
+        let key = PrivateKey::from_pkcs8_pem(include_str!("../../test/certs/private_key.pem")).unwrap();
+
+        let mut store = JksStore::new(JksFormat::Jks);
+        store.add_trusted_certificate("My CA", ca.clone());
+        store.add_trusted_certificate("my ca", ca.clone());
+        assert!(store.add_private_key("Leaf", key.clone(), vec![ca.clone()]).is_err());
+        store.add_private_key("Leaf", key, vec![certificate.clone(), ca]).unwrap();
+
+        let read = JksStore::from_bytes(&store.to_bytes("secret", None).unwrap(), Some("secret"), None).unwrap();
+        assert_eq!(summary(&read), vec![
+            ("leaf".to_string(), JksEntryKind::PrivateKeyEntry, 2, true),
+            ("my ca".to_string(), JksEntryKind::TrustedCertEntry, 1, false),
+        ]);
+
+        let memory = read.to_memory_store();
+        let entries: Vec<_> = memory.entries().collect();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().any(|entry| entry.has_private_key() && entry.der() == certificate.as_der()));
+    }
+
+    #[test]
+    fn encodes_modified_utf8() {
+        let mut out = Vec::new();
+        write_utf(&mut out, "a\u{0}é€😀").unwrap();
+        assert_eq!(Reader(&out).utf().unwrap(), "a\u{0}é€😀");
+        assert_eq!(&out[..5], &[0x00, 0x0E, b'a', 0xC0, 0x80]);
+    }
+}
@@ -0,0 +1,541 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+use std::path::Path;
+use std::fs;
+use cbc::cipher::{BlockDecryptMut, InnerIvInit, KeyIvInit};
+use cbc::cipher::block_padding::Pkcs7;
+use cms::content_info::{CmsVersion, ContentInfo};
+use cms::encrypted_data::EncryptedData;
+use cms::enveloped_data::EncryptedContentInfo;
+use der::{Any, Decode, Encode, Sequence};
+use der::asn1::{BmpString, ObjectIdentifier, OctetString, SetOfVec};
+use hmac::{Mac, SimpleHmac};
+use pkcs12::cert_type::CertBag;
+use pkcs12::digest_info::DigestInfo;
+use pkcs12::kdf::{derive_key, Pkcs12KeyType};
+use pkcs12::mac_data::MacData;
+use pkcs12::pbe_params::Pkcs12PbeParams;
+use pkcs12::pfx::{Pfx, Version};
+use rand_core::{OsRng, RngCore};
+use sha1::Sha1;
+use sha2::{Sha256, Sha384, Sha512};
+use spki::AlgorithmIdentifierOwned;
+use x509_cert::attr::Attribute;
+use zeroize::Zeroizing;
+
+use crate::certificate::{Certificate, invalid_data};
+use crate::keys::{PBKDF2_ITERATIONS, PrivateKey, pbes2_parameters};
+use crate::keys::verify::check_key_pair;
+
+const OID_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+const OID_ENCRYPTED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.6");
+const OID_PBES2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.13");
+const OID_FRIENDLY_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.20");
+const OID_LOCAL_KEY_ID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.21");
+const OID_SHA1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.14.3.2.26");
+const OID_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+const OID_SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2");
+const OID_SHA512: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3");
+
+/// The iteration count of the MAC key derivation, the same as for the PBKDF2 key encryption.
+const MAC_ITERATIONS: i32 = PBKDF2_ITERATIONS as i32;
+
+/// `SafeBag` as defined in RFC 7292 Section 4.2.
+#[derive(Clone, Debug, Sequence)]
+struct SafeBag {
+    bag_id: ObjectIdentifier,
+    #[asn1(context_specific = "0", tag_mode = "EXPLICIT")]
+    bag_value: Any,
+    bag_attributes: Option<SetOfVec<Attribute>>,
+}
+
+/// `EncryptedPrivateKeyInfo` with an arbitrary algorithm, since shrouded key bags may use the PKCS#12 PBE schemes
+/// that `pkcs8::EncryptedPrivateKeyInfo` does not recognise.
+#[derive(Clone, Debug, Sequence)]
+struct ShroudedKeyBag {
+    encryption_algorithm: AlgorithmIdentifierOwned,
+    encrypted_data: OctetString,
+}
+
+/// A password in the two encodings PKCS#12 needs: UTF-8 for PBES2 and a NUL terminated BMPString for the PKCS#12 KDF.
+///
+/// Windows exports files "without a password" using an absent password, which is not the same as an empty one, so both
+/// variants are tried when reading.
+struct Password {
+    utf8: Zeroizing<Vec<u8>>,
+    bmp: Zeroizing<Vec<u8>>,
+}
+
+impl Password {
+    fn new(password: &str) -> Result<Password> {
+        let mut bmp = Zeroizing::new(BmpString::from_utf8(password).map_err(invalid_data)?.into_bytes().to_vec());
+        bmp.extend([0u8; 2]);
+        Ok(Password { utf8: Zeroizing::new(password.as_bytes().to_vec()), bmp })
+    }
+
+    fn absent() -> Password {
+        Password { utf8: Zeroizing::new(Vec::new()), bmp: Zeroizing::new(Vec::new()) }
+    }
+}
+
+/// An entry of a PKCS#12 file, along with the attributes Windows and OpenSSL use to pair keys and certificates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pkcs12Bag<T> {
+    pub value: T,
+    pub friendly_name: Option<String>,
+    pub local_key_id: Option<Vec<u8>>,
+}
+
+/// An in-memory view of a PKCS#12 (.pfx / .p12) file.
+///
+/// Reading supports the PBES2 (AES) encryption written by current OpenSSL and Windows versions as well as the
+/// legacy 3DES and RC2 schemes that `PFXExportCertStoreEx` uses by default. Writing always uses PBES2 with
+/// AES-256-CBC and an HMAC-SHA256 integrity check.
+#[derive(Clone, Debug, Default)]
+pub struct Pkcs12Store {
+    certificates: Vec<Pkcs12Bag<Certificate>>,
+    keys: Vec<Pkcs12Bag<Zeroizing<Vec<u8>>>>,
+}
+
+pub struct Pkcs12Iter<'a> {
+    store: &'a Pkcs12Store,
+    pos: usize,
+}
+
+impl <'a> Iterator for Pkcs12Iter<'a> {
+    type Item = Certificate;
+
+    fn next(&mut self) -> Option<Certificate> {
+        let next = self.store.certificates.get(self.pos)?;
+        self.pos += 1;
+        Some(next.value.clone())
+    }
+}
+
+impl Pkcs12Store {
+    pub fn new() -> Pkcs12Store {
+        Pkcs12Store::default()
+    }
+
+    pub fn open_file<P: AsRef<Path>>(path: P, password: &str) -> Result<Pkcs12Store> {
+        let data = fs::read(path)?;
+        Pkcs12Store::from_der(&data, password)
+    }
+
+    /// Parses and decrypts a DER encoded PKCS#12 file, checking its MAC when one is present.
+    pub fn from_der(der: &[u8], password: &str) -> Result<Pkcs12Store> {
+        let pfx = Pfx::from_der(der).map_err(invalid_data)?;
+        if pfx.auth_safe.content_type != OID_DATA {
+            return Err(Error::new(ErrorKind::Unsupported, "Only password integrity protected PKCS#12 files are supported"));
+        }
+        let auth_safe = pfx.auth_safe.content.decode_as::<OctetString>().map_err(invalid_data)?;
+
+        let mut candidates = vec![Password::new(password)?];
+        if password.is_empty() {
+            candidates.push(Password::absent());
+        }
+
+        let password = match &pfx.mac_data {
+            Some(mac_data) => {
+                let mut matched = None;
+                for candidate in candidates {
+                    if mac_matches(mac_data, &candidate, auth_safe.as_bytes())? {
+                        matched = Some(candidate);
+                        break;
+                    }
+                }
+                matched.ok_or_else(|| Error::new(ErrorKind::PermissionDenied, "Invalid password or corrupted PKCS#12 file"))?
+            },
+            None => candidates.remove(0),
+        };
+
+        let mut store = Pkcs12Store::new();
+        for content in Vec::<ContentInfo>::from_der(auth_safe.as_bytes()).map_err(invalid_data)? {
+            let safe_contents = match content.content_type {
+                OID_DATA => Zeroizing::new(content.content.decode_as::<OctetString>().map_err(invalid_data)?.into_bytes()),
+                OID_ENCRYPTED_DATA => {
+                    let encrypted: EncryptedData = content.content.decode_as().map_err(invalid_data)?;
+                    let ciphertext = encrypted.enc_content_info.encrypted_content
+                        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The encrypted PKCS#12 content is empty"))?;
+                    decrypt(&encrypted.enc_content_info.content_enc_alg, &password, ciphertext.as_bytes())?
+                },
+                // Public-key privacy mode (enveloped data) is not used by Windows or OpenSSL exports.
+                _ => continue,
+            };
+
+            for bag in Vec::<SafeBag>::from_der(&safe_contents).map_err(invalid_data)? {
+                store.add_bag(bag, &password)?;
+            }
+        }
+
+        Ok(store)
+    }
+
+    fn add_bag(&mut self, bag: SafeBag, password: &Password) -> Result<()> {
+        let mut friendly_name = None;
+        let mut local_key_id = None;
+        for attribute in bag.bag_attributes.iter().flat_map(|attrs| attrs.iter()) {
+            let Some(value) = attribute.values.get(0) else {
+                continue;
+            };
+            match attribute.oid {
+                OID_FRIENDLY_NAME => friendly_name = Some(value.decode_as::<BmpString>().map_err(invalid_data)?.to_string()),
+                OID_LOCAL_KEY_ID => local_key_id = Some(value.decode_as::<OctetString>().map_err(invalid_data)?.into_bytes()),
+                _ => {},
+            }
+        }
+
+        match bag.bag_id {
+            pkcs12::PKCS_12_CERT_BAG_OID => {
+                let cert_bag: CertBag = bag.bag_value.decode_as().map_err(invalid_data)?;
+                if cert_bag.cert_id == pkcs12::PKCS_12_X509_CERT_OID {
+                    let value = Certificate::from_der(cert_bag.cert_value.as_bytes())?;
+                    self.certificates.push(Pkcs12Bag { value, friendly_name, local_key_id });
+                }
+            },
+            pkcs12::PKCS_12_PKCS8_KEY_BAG_OID => {
+                let encrypted: ShroudedKeyBag = bag.bag_value.decode_as().map_err(invalid_data)?;
+                let value = decrypt(&encrypted.encryption_algorithm, password, encrypted.encrypted_data.as_bytes())?;
+                self.keys.push(Pkcs12Bag { value, friendly_name, local_key_id });
+            },
+            pkcs12::PKCS_12_KEY_BAG_OID => {
+                let value = Zeroizing::new(bag.bag_value.to_der().map_err(invalid_data)?);
+                self.keys.push(Pkcs12Bag { value, friendly_name, local_key_id });
+            },
+            // CRL, secret and nested safe contents bags carry nothing this crate uses.
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    pub fn add_certificate(&mut self, cert: Certificate, friendly_name: Option<&str>, local_key_id: Option<&[u8]>) {
+        self.certificates.push(Pkcs12Bag {
+            value: cert,
+            friendly_name: friendly_name.map(str::to_string),
+            local_key_id: local_key_id.map(<[u8]>::to_vec),
+        });
+    }
+
+    /// Adds an unencrypted PKCS#8 `PrivateKeyInfo`; it is encrypted when the store is written.
+    pub fn add_key(&mut self, pkcs8_der: &[u8], friendly_name: Option<&str>, local_key_id: Option<&[u8]>) {
+        self.keys.push(Pkcs12Bag {
+            value: Zeroizing::new(pkcs8_der.to_vec()),
+            friendly_name: friendly_name.map(str::to_string),
+            local_key_id: local_key_id.map(<[u8]>::to_vec),
+        });
+    }
+
+    pub fn certs<'a>(&'a self) -> Pkcs12Iter<'a> {
+        Pkcs12Iter {
+            store: self,
+            pos: 0
+        }
+    }
+
+    pub fn certificate_bags(&self) -> &[Pkcs12Bag<Certificate>] {
+        &self.certificates
+    }
+
+    /// The private keys as unencrypted PKCS#8 `PrivateKeyInfo` structures.
+    pub fn key_bags(&self) -> &[Pkcs12Bag<Zeroizing<Vec<u8>>>] {
+        &self.keys
+    }
+
+    /// The private key stored for `cert`.
+    ///
+    /// Keys are paired through the localKeyId attribute. Files without it (or with a single key) fall back to the
+    /// only key present, and otherwise to the key whose public half matches the certificate.
+    pub fn private_key_for(&self, cert: &Certificate) -> Result<Option<PrivateKey>> {
+        let local_key_id = self.certificates.iter()
+            .find(|bag| &bag.value == cert)
+            .and_then(|bag| bag.local_key_id.as_ref());
+
+        if let Some(local_key_id) = local_key_id {
+            if let Some(bag) = self.keys.iter().find(|bag| bag.local_key_id.as_ref() == Some(local_key_id)) {
+                return PrivateKey::from_pkcs8_der(&bag.value).map(Some);
+            }
+        }
+
+        if let [bag] = self.keys.as_slice() {
+            return PrivateKey::from_pkcs8_der(&bag.value).map(Some);
+        }
+
+        for bag in &self.keys {
+            let key = PrivateKey::from_pkcs8_der(&bag.value)?;
+            if key.matches_certificate(cert)? {
+                return Ok(Some(key));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Checks that the file holds the private key of `cert`, see [`check_key_pair`] for what `probe` adds.
+    pub fn check_key_pair(&self, cert: &Certificate, probe: bool) -> Result<()> {
+        let key = self.private_key_for(cert)?
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "The PKCS#12 file does not contain a private key for the certificate"))?;
+        check_key_pair(&key, cert, probe)
+    }
+
+    /// Encrypts and encodes the store with the given password.
+    pub fn to_der(&self, password: &str) -> Result<Vec<u8>> {
+        let password = Password::new(password)?;
+
+        let mut cert_bags = Vec::new();
+        for cert in &self.certificates {
+            let cert_bag = CertBag {
+                cert_id: pkcs12::PKCS_12_X509_CERT_OID,
+                cert_value: OctetString::new(cert.value.as_der()).map_err(invalid_data)?,
+            };
+            cert_bags.push(safe_bag(pkcs12::PKCS_12_CERT_BAG_OID, Any::encode_from(&cert_bag).map_err(invalid_data)?, cert)?);
+        }
+
+        let mut key_bags = Vec::new();
+        for key in &self.keys {
+            let info = pkcs8::PrivateKeyInfo::from_der(&key.value).map_err(invalid_data)?;
+            let encrypted = info.encrypt_with_params(pbes2_parameters(&mut [0u8; 16], &mut [0u8; 16])?, password.utf8.as_slice())
+                .map_err(invalid_data)?;
+            key_bags.push(safe_bag(pkcs12::PKCS_12_PKCS8_KEY_BAG_OID, Any::from_der(encrypted.as_bytes()).map_err(invalid_data)?, key)?);
+        }
+
+        let mut salt = [0u8; 16];
+        let mut iv = [0u8; 16];
+        let scheme = pbes2_parameters(&mut salt, &mut iv)?;
+        let ciphertext = scheme.encrypt(password.utf8.as_slice(), &cert_bags.to_der().map_err(invalid_data)?).map_err(invalid_data)?;
+        let encrypted_certs = EncryptedData {
+            version: CmsVersion::V0,
+            enc_content_info: EncryptedContentInfo {
+                content_type: OID_DATA,
+                content_enc_alg: AlgorithmIdentifierOwned::from_der(&pkcs5::EncryptionScheme::from(scheme).to_der().map_err(invalid_data)?)
+                    .map_err(invalid_data)?,
+                encrypted_content: Some(OctetString::new(ciphertext).map_err(invalid_data)?),
+            },
+            unprotected_attrs: None,
+        };
+
+        let auth_safe = vec![
+            ContentInfo {
+                content_type: OID_ENCRYPTED_DATA,
+                content: Any::encode_from(&encrypted_certs).map_err(invalid_data)?,
+            },
+            ContentInfo {
+                content_type: OID_DATA,
+                content: Any::encode_from(&OctetString::new(key_bags.to_der().map_err(invalid_data)?).map_err(invalid_data)?)
+                    .map_err(invalid_data)?,
+            },
+        ].to_der().map_err(invalid_data)?;
+
+        let mut mac_salt = [0u8; 16];
+        OsRng.fill_bytes(&mut mac_salt);
+        let mac_key = Zeroizing::new(derive_key::<Sha256>(&password.bmp, &mac_salt, Pkcs12KeyType::Mac, MAC_ITERATIONS, 32));
+        let mut mac = <SimpleHmac<Sha256> as Mac>::new_from_slice(&mac_key).map_err(invalid_data)?;
+        mac.update(&auth_safe);
+
+        let pfx = Pfx {
+            version: Version::V3,
+            auth_safe: ContentInfo {
+                content_type: OID_DATA,
+                content: Any::encode_from(&OctetString::new(auth_safe).map_err(invalid_data)?).map_err(invalid_data)?,
+            },
+            mac_data: Some(MacData {
+                mac: DigestInfo {
+                    algorithm: AlgorithmIdentifierOwned { oid: OID_SHA256, parameters: Some(Any::null()) },
+                    digest: OctetString::new(mac.finalize().into_bytes().to_vec()).map_err(invalid_data)?,
+                },
+                mac_salt: OctetString::new(mac_salt.to_vec()).map_err(invalid_data)?,
+                iterations: MAC_ITERATIONS,
+            }),
+        };
+
+        pfx.to_der().map_err(invalid_data)
+    }
+}
+
+fn safe_bag<T>(bag_id: ObjectIdentifier, bag_value: Any, source: &Pkcs12Bag<T>) -> Result<SafeBag> {
+    let mut attributes = SetOfVec::new();
+
+    if let Some(name) = &source.friendly_name {
+        let mut values = SetOfVec::new();
+        values.insert(Any::encode_from(&BmpString::from_utf8(name).map_err(invalid_data)?).map_err(invalid_data)?).map_err(invalid_data)?;
+        attributes.insert(Attribute { oid: OID_FRIENDLY_NAME, values }).map_err(invalid_data)?;
+    }
+
+    if let Some(key_id) = &source.local_key_id {
+        let mut values = SetOfVec::new();
+        values.insert(Any::encode_from(&OctetString::new(key_id.as_slice()).map_err(invalid_data)?).map_err(invalid_data)?).map_err(invalid_data)?;
+        attributes.insert(Attribute { oid: OID_LOCAL_KEY_ID, values }).map_err(invalid_data)?;
+    }
+
+    Ok(SafeBag {
+        bag_id,
+        bag_value,
+        bag_attributes: if attributes.is_empty() { None } else { Some(attributes) },
+    })
+}
+
+fn mac_matches(mac_data: &MacData, password: &Password, data: &[u8]) -> Result<bool> {
+    let salt = mac_data.mac_salt.as_bytes();
+    let expected = mac_data.mac.digest.as_bytes();
+    let iterations = mac_data.iterations;
+
+    Ok(match mac_data.mac.algorithm.oid {
+        OID_SHA1 => hmac_matches::<Sha1>(password, salt, iterations, data, expected),
+        OID_SHA256 => hmac_matches::<Sha256>(password, salt, iterations, data, expected),
+        OID_SHA384 => hmac_matches::<Sha384>(password, salt, iterations, data, expected),
+        OID_SHA512 => hmac_matches::<Sha512>(password, salt, iterations, data, expected),
+        oid => return Err(Error::new(ErrorKind::Unsupported, format!("Unsupported PKCS#12 MAC algorithm {}", oid))),
+    })
+}
+
+fn hmac_matches<D>(password: &Password, salt: &[u8], iterations: i32, data: &[u8], expected: &[u8]) -> bool
+where
+    D: sha2::Digest + sha2::digest::FixedOutputReset + sha2::digest::core_api::BlockSizeUser,
+{
+    let key = Zeroizing::new(derive_key::<D>(&password.bmp, salt, Pkcs12KeyType::Mac, iterations, <D as sha2::Digest>::output_size()));
+    let Ok(mut mac) = <SimpleHmac<D> as Mac>::new_from_slice(&key) else {
+        return false;
+    };
+    mac.update(data);
+    mac.verify_slice(expected).is_ok()
+}
+
+fn decrypt(algorithm: &AlgorithmIdentifierOwned, password: &Password, ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let pkcs12_params = || -> Result<Pkcs12PbeParams> {
+        algorithm.parameters.as_ref()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The PKCS#12 PBE parameters are missing"))?
+            .decode_as()
+            .map_err(invalid_data)
+    };
+
+    let plaintext = match algorithm.oid {
+        OID_PBES2 => {
+            let algorithm_der = algorithm.to_der().map_err(invalid_data)?;
+            let scheme = pkcs5::EncryptionScheme::from_der(&algorithm_der).map_err(invalid_data)?;
+            scheme.decrypt(password.utf8.as_slice(), ciphertext).map_err(decrypt_error)?
+        },
+        pkcs12::PKCS_12_PBE_WITH_SHAAND3_KEY_TRIPLE_DES_CBC => {
+            let params = pkcs12_params()?;
+            let (key, iv) = pkcs12_key_iv(password, &params, 24, 8);
+            cbc::Decryptor::<des::TdesEde3>::new_from_slices(&key, &iv)
+                .map_err(invalid_data)?
+                .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+                .map_err(decrypt_error)?
+        },
+        pkcs12::PKCS_12_PBEWITH_SHAAND40_BIT_RC2_CBC | pkcs12::PKCS_12_PBE_WITH_SHAAND128_BIT_RC2_CBC => {
+            let params = pkcs12_params()?;
+            let key_len = if algorithm.oid == pkcs12::PKCS_12_PBEWITH_SHAAND40_BIT_RC2_CBC { 5 } else { 16 };
+            let (key, iv) = pkcs12_key_iv(password, &params, key_len, 8);
+            let cipher = rc2::Rc2::new_with_eff_key_len(&key, key_len * 8);
+            cbc::Decryptor::<rc2::Rc2>::inner_iv_slice_init(cipher, &iv)
+                .map_err(invalid_data)?
+                .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+                .map_err(decrypt_error)?
+        },
+        oid => return Err(Error::new(ErrorKind::Unsupported, format!("Unsupported PKCS#12 encryption algorithm {}", oid))),
+    };
+
+    Ok(Zeroizing::new(plaintext))
+}
+
+/// Wrong passwords surface as padding errors, which are reported as such rather than as corrupt data.
+fn decrypt_error<E>(_: E) -> Error {
+    Error::new(ErrorKind::PermissionDenied, "Could not decrypt the PKCS#12 contents")
+}
+
+fn pkcs12_key_iv(password: &Password, params: &Pkcs12PbeParams, key_len: usize, iv_len: usize) -> (Zeroizing<Vec<u8>>, Vec<u8>) {
+    let salt = params.salt.as_bytes();
+    let key = derive_key::<Sha1>(&password.bmp, salt, Pkcs12KeyType::EncryptionKey, params.iterations, key_len);
+    let iv = derive_key::<Sha1>(&password.bmp, salt, Pkcs12KeyType::Iv, params.iterations, iv_len);
+    (Zeroizing::new(key), iv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::verify::KeyMismatch;
+
+    const MODERN_P12: &[u8] = include_bytes!("../../test/certs/certificate.p12");
+    const LEGACY_P12: &[u8] = include_bytes!("../../test/certs/certificate_legacy.p12");
+    const MISMATCHED_P12: &[u8] = include_bytes!("../../test/certs/certificate_mismatched_key.p12");
+    const CERT_PEM: &[u8] = include_bytes!("../../test/certs/certificate.crt");
+    const KEY_PEM: &str = include_str!("../../test/certs/private_key.pem");
+
+    fn assert_matches_fixtures(store: &Pkcs12Store) {
+        assert_eq!(store.certs().collect::<Vec<_>>(), vec![Certificate::from_pem(CERT_PEM).unwrap()]);
+        assert_eq!(store.key_bags().len(), 1);
+        let key = PrivateKey::from_pkcs8_der(&store.key_bags()[0].value).unwrap();
+        assert_eq!(key.to_pkcs8_pem().unwrap().trim(), KEY_PEM.trim());
+    }
+
+    #[test]
+    fn reads_openssl_pbes2_file() {
+        let store = Pkcs12Store::from_der(MODERN_P12, "pass1234").unwrap();
+        assert_matches_fixtures(&store);
+        assert_eq!(store.certificate_bags()[0].friendly_name.as_deref(), Some("Rust Test Cert"));
+    }
+
+    #[test]
+    fn reads_openssl_legacy_file() {
+        let store = Pkcs12Store::from_der(LEGACY_P12, "pass1234").unwrap();
+        assert_matches_fixtures(&store);
+        assert_eq!(store.key_bags()[0].local_key_id, store.certificate_bags()[0].local_key_id);
+    }
+
+    #[test]
+    fn rejects_wrong_password() {
+        let err = Pkcs12Store::from_der(MODERN_P12, "wrong").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn written_file_round_trips() {
+        let key = PrivateKey::from_pkcs8_pem(KEY_PEM).unwrap();
+        let mut store = Pkcs12Store::new();
+        store.add_certificate(Certificate::from_pem(CERT_PEM).unwrap(), Some("Example Exportable Cert"), Some(&[1, 2, 3]));
+        store.add_key(&key.to_pkcs8_der().unwrap(), Some("Example Exportable Cert"), Some(&[1, 2, 3]));
+
+        for password in ["pass1234", ""] {
+            let der = store.to_der(password).unwrap();
+            let read = Pkcs12Store::from_der(&der, password).unwrap();
+            assert_matches_fixtures(&read);
+            assert_eq!(read.certificate_bags()[0].friendly_name.as_deref(), Some("Example Exportable Cert"));
+            assert_eq!(read.key_bags()[0].local_key_id.as_deref(), Some(&[1u8, 2, 3][..]));
+        }
+    }
+
+    #[test]
+    fn checks_key_pairs() {
+        let cert = Certificate::from_pem(CERT_PEM).unwrap();
+        for p12 in [MODERN_P12, LEGACY_P12] {
+            Pkcs12Store::from_der(p12, "pass1234").unwrap().check_key_pair(&cert, true).unwrap();
+        }
+
+        let store = Pkcs12Store::from_der(MISMATCHED_P12, "pass1234").unwrap();
+        let err = store.check_key_pair(&cert, true).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(KeyMismatch::from_error(&err), Some(KeyMismatch::PublicKey));
+
+        let mut store = Pkcs12Store::new();
+        store.add_certificate(cert.clone(), None, None);
+        assert_eq!(store.check_key_pair(&cert, false).unwrap_err().kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+use std::path::Path;
+use std::fs;
+use der::{Any, Decode, Encode, Sequence, Tag, Tagged};
+use der::asn1::ObjectIdentifier;
+use der::pem::LineEnding;
+
+use crate::certificate::{Certificate, invalid_data};
+
+const OID_PKCS7_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+const OID_PKCS7_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+
+/// PEM labels that are used in the wild for certs-only PKCS#7 bundles.
+const PEM_LABELS: [&str; 3] = ["PKCS7", "PKCS #7 SIGNED DATA", "CMS"];
+
+/// `ContentInfo` as defined in RFC 5652 Section 3.
+#[derive(Clone, Debug, Sequence)]
+struct ContentInfo {
+    content_type: ObjectIdentifier,
+    #[asn1(context_specific = "0", tag_mode = "EXPLICIT")]
+    content: Any,
+}
+
+/// `SignedData` as defined in RFC 5652 Section 5.1.
+///
+/// The `cms` crate models the certificate set as a DER `SET OF`, which re-sorts the certificates and rejects duplicates.
+/// Bundles are expected to keep the order they were written in (leaf first, then the chain), so the
+/// certificate set is decoded as an ordered list instead.
+#[derive(Clone, Debug, Sequence)]
+struct SignedData {
+    version: u8,
+    digest_algorithms: Any,
+    encap_content_info: EncapsulatedContentInfo,
+    #[asn1(context_specific = "0", tag_mode = "IMPLICIT", constructed = "true", optional = "true")]
+    certificates: Option<Vec<Any>>,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", constructed = "true", optional = "true")]
+    crls: Option<Vec<Any>>,
+    signer_infos: Any,
+}
+
+#[derive(Clone, Debug, Sequence)]
+struct EncapsulatedContentInfo {
+    econtent_type: ObjectIdentifier,
+    #[asn1(context_specific = "0", tag_mode = "EXPLICIT", optional = "true")]
+    econtent: Option<Any>,
+}
+
+/// An in-memory certificate store backed by a degenerate (certs-only) PKCS#7 `SignedData` bundle.
+///
+/// These are the `.p7b`/`.p7c` files produced by `openssl crl2pkcs7` and by the Windows certificate export wizard.
+#[derive(Clone, Debug, Default)]
+pub struct Pkcs7Store {
+    certificates: Vec<Certificate>,
+}
+
+pub struct Pkcs7Iter<'a> {
+    store: &'a Pkcs7Store,
+    pos: usize,
+}
+
+impl <'a> Iterator for Pkcs7Iter<'a> {
+    type Item = Certificate;
+
+    fn next(&mut self) -> Option<Certificate> {
+        let next = self.store.certificates.get(self.pos)?;
+        self.pos += 1;
+        Some(next.clone())
+    }
+}
+
+impl Pkcs7Store {
+    /// Creates an empty store, used to build a new bundle.
+    pub fn new() -> Pkcs7Store {
+        Pkcs7Store::default()
+    }
+
+    /// Opens a bundle from disk, accepting either the DER or the PEM encoding.
+    pub fn open_file<P: AsRef<Path>>(path: P) -> Result<Pkcs7Store> {
+        let data = fs::read(path)?;
+        Pkcs7Store::from_bytes(&data)
+    }
+
+    /// Parses a bundle, detecting whether it is DER or PEM encoded.
+    pub fn from_bytes(data: &[u8]) -> Result<Pkcs7Store> {
+        if data.trim_ascii_start().starts_with(b"-----BEGIN") {
+            Pkcs7Store::from_pem(data)
+        } else {
+            Pkcs7Store::from_der(data)
+        }
+    }
+
+    /// Parses a PEM encoded bundle (`-----BEGIN PKCS7-----`).
+    pub fn from_pem(pem: &[u8]) -> Result<Pkcs7Store> {
+        let (label, der) = der::pem::decode_vec(pem.trim_ascii()).map_err(invalid_data)?;
+        if !PEM_LABELS.contains(&label) {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Unexpected PEM label \"{}\"", label)));
+        }
+        Pkcs7Store::from_der(&der)
+    }
+
+    /// Parses a DER encoded bundle.
+    pub fn from_der(der: &[u8]) -> Result<Pkcs7Store> {
+        let content_info = ContentInfo::from_der(der).map_err(invalid_data)?;
+        if content_info.content_type != OID_PKCS7_SIGNED_DATA {
+            return Err(Error::new(ErrorKind::InvalidData, "The PKCS#7 content is not SignedData"));
+        }
+
+        let signed_data: SignedData = content_info.content.decode_as().map_err(invalid_data)?;
+
+        let mut certificates = Vec::new();
+        for choice in signed_data.certificates.unwrap_or_default() {
+            // Only the plain `Certificate` choice is a SEQUENCE, the obsolete extended and attribute certificate choices are skipped.
+            if choice.tag() != Tag::Sequence {
+                continue;
+            }
+            certificates.push(Certificate::from_der(&choice.to_der().map_err(invalid_data)?)?);
+        }
+
+        Ok(Pkcs7Store { certificates })
+    }
+
+    /// Appends a certificate to the end of the bundle.
+    pub fn add_certificate(&mut self, cert: Certificate) {
+        self.certificates.push(cert);
+    }
+
+    pub fn len(&self) -> usize {
+        self.certificates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.certificates.is_empty()
+    }
+
+    pub fn certs<'a>(&'a self) -> Pkcs7Iter<'a> {
+        Pkcs7Iter {
+            store: self,
+            pos: 0
+        }
+    }
+
+    /// Encodes the store as a DER certs-only bundle, keeping the certificates in insertion order.
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        let certificates = self.certificates.iter()
+            .map(|cert| Any::from_der(cert.as_der()))
+            .collect::<der::Result<Vec<Any>>>()
+            .map_err(invalid_data)?;
+
+        let signed_data = SignedData {
+            version: 1,
+            digest_algorithms: Any::new(Tag::Set, Vec::new()).map_err(invalid_data)?,
+            encap_content_info: EncapsulatedContentInfo {
+                econtent_type: OID_PKCS7_DATA,
+                econtent: None,
+            },
+            certificates: Some(certificates),
+            crls: None,
+            signer_infos: Any::new(Tag::Set, Vec::new()).map_err(invalid_data)?,
+        };
+
+        let content_info = ContentInfo {
+            content_type: OID_PKCS7_SIGNED_DATA,
+            content: Any::encode_from(&signed_data).map_err(invalid_data)?,
+        };
+
+        content_info.to_der().map_err(invalid_data)
+    }
+
+    /// Encodes the store as a PEM certs-only bundle, using the `PKCS7` label written by OpenSSL.
+    pub fn to_pem(&self) -> Result<String> {
+        der::pem::encode_string(PEM_LABELS[0], LineEnding::LF, &self.to_der()?).map_err(invalid_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUNDLE_DER: &[u8] = include_bytes!("../../test/certs/bundle.p7b");
+    const BUNDLE_PEM: &[u8] = include_bytes!("../../test/certs/bundle.p7b.pem");
+    const LEAF_PEM: &[u8] = include_bytes!("../../test/certs/certificate.crt");
+    const CA_PEM: &[u8] = include_bytes!("../../test/certs/ca_certificate.crt");
+
+    fn fixtures() -> Vec<Certificate> {
+        vec![
+            Certificate::from_pem(LEAF_PEM).unwrap(),
+            Certificate::from_pem(CA_PEM).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn reads_openssl_der_bundle_in_order() {
+        let store = Pkcs7Store::from_bytes(BUNDLE_DER).unwrap();
+        assert_eq!(store.certs().collect::<Vec<_>>(), fixtures());
+    }
+
+    #[test]
+    fn reads_openssl_pem_bundle() {
+        let store = Pkcs7Store::from_bytes(BUNDLE_PEM).unwrap();
+        assert_eq!(store.certs().collect::<Vec<_>>(), fixtures());
+    }
+
+    #[test]
+    fn writes_the_same_encoding_as_openssl() {
+        let mut store = Pkcs7Store::new();
+        for cert in fixtures() {
+            store.add_certificate(cert);
+        }
+        assert_eq!(store.to_der().unwrap(), BUNDLE_DER);
+        assert_eq!(Pkcs7Store::from_pem(store.to_pem().unwrap().as_bytes()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn rejects_non_signed_data() {
+        let leaf = Certificate::from_pem(LEAF_PEM).unwrap();
+        assert!(Pkcs7Store::from_der(leaf.as_der()).is_err());
+        assert!(Pkcs7Store::from_bytes(LEAF_PEM).is_err());
+    }
+}
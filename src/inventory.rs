@@ -0,0 +1,301 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Error, ErrorKind, Result};
+use std::time::{Duration, SystemTime};
+use serde::{Deserialize, Serialize};
+
+use crate::certificate::{Certificate, invalid_data};
+use crate::metadata::{CertificateInventory, CertificateMetadata, SCHEMA_VERSION, StoreKind, StoreLocation, parse_rfc3339};
+use crate::pkcs11_store::{Pkcs11Module, Pkcs11Token};
+use crate::store::StoreIter;
+use crate::store::path::open_path;
+
+
+/// Records the metadata of every certificate of the given stores, without exporting any key.
+///
+/// Certificates that cannot be parsed are left out. The stores are listed in the snapshot even when empty, so that
+/// a later diff can tell an emptied store from one that was not read.
+pub fn take_snapshot(sources: Vec<(StoreLocation, StoreIter)>, host: Option<&str>) -> CertificateInventory {
+    let mut stores = Vec::with_capacity(sources.len());
+    let mut certificates = Vec::new();
+
+    for (location, entries) in sources {
+        for entry in entries {
+            let Ok(metadata) = Certificate::from_der(entry.der()).and_then(|cert| CertificateMetadata::new(&cert)) else {
+                continue;
+            };
+            let metadata = match entry.key_storage() {
+                Ok(Some(storage)) => metadata.key_storage(&storage),
+                Ok(None) => metadata,
+                // The key could not be described, its provider is still worth recording.
+                Err(_) => metadata.private_key(entry.key_provider()),
+            };
+            certificates.push(metadata.friendly_name(&entry.friendly_name()).store(location.clone()));
+        }
+        stores.push(location);
+    }
+
+    let mut snapshot = CertificateInventory::new(certificates);
+    snapshot.host = host.map(String::from);
+    snapshot.stores = stores;
+    snapshot
+}
+
+/// Opens a Windows system store, the PEM directory or PKCS#12 file of a file location, an NSS database, or a
+/// PKCS#11 token, logging in with the password as PIN.
+pub fn open_store(location: &StoreLocation, password: &str) -> Result<StoreIter> {
+    match (&location.kind, &location.location, &location.store, &location.path) {
+        (StoreKind::Windows, Some(user), Some(store), _) => open_windows_store(store, user),
+        (StoreKind::File, _, _, Some(path)) => Ok(open_path(path, password)
+            .map_err(|err| Error::new(err.kind(), format!("Could not read {}: {}", path, err)))?
+            .entries()),
+        (StoreKind::Nss, _, _, Some(directory)) => open_nss_database(directory, password),
+        (StoreKind::Pkcs11, _, Some(token), Some(module)) => {
+            let pin = Some(password).filter(|password| !password.is_empty());
+            Ok(Pkcs11Token::open(Pkcs11Module::load(module, None)?, None, Some(token), pin)?.entries())
+        },
+        _ => Err(Error::new(ErrorKind::InvalidInput, "Only Windows system stores, file paths, NSS databases and PKCS#11 tokens can be read for a snapshot")),
+    }
+}
+
+#[cfg(unix)]
+fn open_nss_database(directory: &str, password: &str) -> Result<StoreIter> {
+    let password = Some(password).filter(|password| !password.is_empty());
+    Ok(crate::nss_store::NssDatabase::open(directory, password)?.to_memory_store().entries())
+}
+
+#[cfg(not(unix))]
+fn open_nss_database(_directory: &str, _password: &str) -> Result<StoreIter> {
+    Err(Error::new(ErrorKind::Unsupported, "NSS databases can only be read on Linux and macOS"))
+}
+
+#[cfg(windows)]
+fn open_windows_store(store: &str, user: &str) -> Result<StoreIter> {
+    Ok(crate::windows_store::cert_store::CertStore::open_system(store, user)?.into_entries())
+}
+
+#[cfg(not(windows))]
+fn open_windows_store(_store: &str, _user: &str) -> Result<StoreIter> {
+    Err(Error::new(ErrorKind::Unsupported, "Windows certificate stores can only be read on Windows"))
+}
+
+/// A certificate replaced by one with the same subject and a new serial number in the same store.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Renewal {
+    pub previous: CertificateMetadata,
+    pub current: CertificateMetadata,
+}
+
+/// The changes between two snapshots, compared store by store.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct InventoryDiff {
+    pub schema_version: u32,
+    /// When the previous snapshot was taken.
+    pub previous: String,
+    /// When the current snapshot was taken.
+    pub current: String,
+    pub added: Vec<CertificateMetadata>,
+    pub removed: Vec<CertificateMetadata>,
+    pub renewed: Vec<Renewal>,
+    /// Certificates of the current snapshot that expire within the window and have not been renewed.
+    pub expiring: Vec<CertificateMetadata>,
+}
+
+impl InventoryDiff {
+    /// Compares two snapshots. Certificates are matched by store and thumbprint.
+    ///
+    /// An added certificate is a renewal when the previous snapshot had a certificate in the same store with the
+    /// same subject, a different serial number and an earlier expiry; a removed one is preferred as the previous
+    /// certificate. When both snapshots list the stores they read, only the stores read both times are compared.
+    pub fn between(previous: &CertificateInventory, current: &CertificateInventory, expiring_within: Duration) -> Result<InventoryDiff> {
+        let compared = |location: &Option<StoreLocation>| {
+            if previous.stores.is_empty() || current.stores.is_empty() {
+                return true;
+            }
+            location.as_ref().is_some_and(|location| previous.stores.contains(location) && current.stores.contains(location))
+        };
+        let before: Vec<&CertificateMetadata> = previous.certificates.iter().filter(|cert| compared(&cert.store)).collect();
+        let after: Vec<&CertificateMetadata> = current.certificates.iter().filter(|cert| compared(&cert.store)).collect();
+
+        let mut removed: Vec<&CertificateMetadata> = before.iter().copied().filter(|cert| !contains(&after, cert)).collect();
+        let mut added: Vec<&CertificateMetadata> = Vec::new();
+        let mut renewed = Vec::new();
+
+        for cert in after.iter().copied().filter(|cert| !contains(&before, cert)) {
+            let renews = |old: &&CertificateMetadata| {
+                old.store == cert.store && old.subject.rfc4514 == cert.subject.rfc4514
+                    && old.serial_number != cert.serial_number && expires_before(old, cert)
+            };
+
+            let replaced = match removed.iter().position(renews) {
+                Some(position) => Some(removed.remove(position)),
+                None => latest(before.iter().copied().filter(renews)),
+            };
+            match replaced {
+                Some(old) => renewed.push(Renewal { previous: old.clone(), current: cert.clone() }),
+                None => added.push(cert),
+            }
+        }
+
+        let now = parse_rfc3339(&current.generated_at)?;
+        let horizon = now + expiring_within;
+        let mut expiring = Vec::new();
+        for cert in &current.certificates {
+            let not_after = cert.not_after_time()?;
+            let superseded = current.certificates.iter().any(|other| {
+                other.store == cert.store && other.subject.rfc4514 == cert.subject.rfc4514
+                    && other.not_after_time().is_ok_and(|other_not_after| other_not_after > horizon)
+            });
+            if now <= not_after && not_after <= horizon && !superseded {
+                expiring.push(cert.clone());
+            }
+        }
+        expiring.sort_by(|a, b| a.not_after.cmp(&b.not_after));
+
+        Ok(InventoryDiff {
+            schema_version: SCHEMA_VERSION,
+            previous: previous.generated_at.clone(),
+            current: current.generated_at.clone(),
+            added: added.into_iter().cloned().collect(),
+            removed: removed.into_iter().cloned().collect(),
+            renewed,
+            expiring,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(invalid_data)
+    }
+
+    pub fn to_json_pretty(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(invalid_data)
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).map_err(invalid_data)
+    }
+
+    /// Whether nothing was added, removed or renewed. Expiring certificates are not a change.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renewed.is_empty()
+    }
+}
+
+fn contains(certificates: &[&CertificateMetadata], cert: &CertificateMetadata) -> bool {
+    certificates.iter().any(|other| other.store == cert.store && other.thumbprint == cert.thumbprint)
+}
+
+fn expires_before(old: &CertificateMetadata, new: &CertificateMetadata) -> bool {
+    match (old.not_after_time(), new.not_after_time()) {
+        (Ok(old), Ok(new)) => old < new,
+        _ => false,
+    }
+}
+
+fn latest<'a>(certificates: impl Iterator<Item = &'a CertificateMetadata>) -> Option<&'a CertificateMetadata> {
+    certificates.max_by_key(|cert| cert.not_after_time().unwrap_or(SystemTime::UNIX_EPOCH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use crate::cert_builder::CertificateBuilder;
+    use crate::cert_builder::test_support::sign;
+    use crate::metadata::SCHEMA;
+    use crate::metadata::test_support::assert_matches;
+    use crate::store::memory::MemoryStore;
+
+    const PREVIOUS: &str = include_str!("../test/snapshots/previous.json");
+    const CURRENT: &str = include_str!("../test/snapshots/current.json");
+    const MONTH: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+    fn common_names(certificates: &[CertificateMetadata]) -> Vec<&str> {
+        certificates.iter().filter_map(|cert| cert.subject.common_name.as_deref()).collect()
+    }
+
+    #[test]
+    fn snapshots_stores() {
+        let issued = sign(CertificateBuilder::new("CN=snapshot").unwrap(), None);
+        let mut store = MemoryStore::new();
+        store.add_certificate_with_key(issued.certificate.clone(), issued.key, "snapshot", false);
+
+        let location = StoreLocation::file("/etc/ssl/private");
+        let sources = vec![(location.clone(), store.entries()), (StoreLocation::file("/empty"), MemoryStore::new().entries())];
+        let snapshot = take_snapshot(sources, Some("build01"));
+
+        assert_eq!(snapshot.host.as_deref(), Some("build01"));
+        assert_eq!(snapshot.stores, vec![location.clone(), StoreLocation::file("/empty")]);
+        assert_eq!(snapshot.certificates.len(), 1);
+        let metadata = &snapshot.certificates[0];
+        assert_eq!(metadata.thumbprint, issued.certificate.thumbprint());
+        assert_eq!(metadata.friendly_name.as_deref(), Some("snapshot"));
+        assert_eq!(metadata.store, Some(location));
+        assert!(metadata.key.has_private_key && metadata.pkcs12.is_none());
+
+        let again = InventoryDiff::between(&snapshot, &snapshot, MONTH).unwrap();
+        assert!(again.is_empty() && again.expiring.is_empty());
+    }
+
+    #[test]
+    fn diffs_fixture_snapshots() {
+        let previous = CertificateInventory::from_json(PREVIOUS).unwrap();
+        let current = CertificateInventory::from_json(CURRENT).unwrap();
+        let diff = InventoryDiff::between(&previous, &current, MONTH).unwrap();
+
+        assert_eq!(diff.previous, "2026-01-01T00:00:00Z");
+        assert_eq!(diff.current, "2026-06-01T00:00:00Z");
+        assert_eq!(common_names(&diff.added), vec!["new.example.com"]);
+        // The root store was only read for the previous snapshot, so its certificate is not reported as removed.
+        assert_eq!(common_names(&diff.removed), vec!["old.example.com"]);
+        assert_eq!(diff.renewed.len(), 1);
+        assert_eq!(diff.renewed[0].previous.subject, diff.renewed[0].current.subject);
+        assert_ne!(diff.renewed[0].previous.serial_number, diff.renewed[0].current.serial_number);
+        assert_eq!(common_names(&diff.expiring), vec!["api.example.com"]);
+        assert!(!diff.is_empty());
+
+        let shorter = InventoryDiff::between(&previous, &current, Duration::from_secs(24 * 60 * 60)).unwrap();
+        assert!(shorter.expiring.is_empty());
+
+        // Without a renewal the previous certificate is only removed.
+        let backwards = InventoryDiff::between(&current, &previous, MONTH).unwrap();
+        assert!(backwards.renewed.is_empty());
+        assert_eq!(common_names(&backwards.removed), vec!["web.example.com", "new.example.com"]);
+        assert_eq!(common_names(&backwards.added), vec!["web.example.com", "old.example.com"]);
+    }
+
+    #[test]
+    fn matches_the_schema() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        let previous = CertificateInventory::from_json(PREVIOUS).unwrap();
+        let current = CertificateInventory::from_json(CURRENT).unwrap();
+        let diff = InventoryDiff::between(&previous, &current, MONTH).unwrap();
+
+        let snapshot: Value = serde_json::from_str(CURRENT).unwrap();
+        assert_matches(&snapshot, &schema["$defs"]["CertificateInventory"], &schema);
+        let value: Value = serde_json::from_str(&diff.to_json().unwrap()).unwrap();
+        assert_matches(&value, &schema["$defs"]["InventoryDiff"], &schema);
+        for renewal in value["Renewed"].as_array().unwrap() {
+            assert_matches(renewal, &schema["$defs"]["InventoryDiff"]["properties"]["Renewed"]["items"], &schema);
+        }
+        assert_eq!(serde_json::from_str::<InventoryDiff>(&diff.to_json_pretty().unwrap()).unwrap(), diff);
+    }
+}
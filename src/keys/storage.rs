@@ -0,0 +1,175 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+
+use crate::ranking::is_hardware_provider;
+
+
+/// `NCRYPT_ALLOW_EXPORT_FLAG`, the key can be exported.
+pub const ALLOW_EXPORT: u32 = 0x1;
+/// `NCRYPT_ALLOW_PLAINTEXT_EXPORT_FLAG`, the key can be exported without encrypting it.
+pub const ALLOW_PLAINTEXT_EXPORT: u32 = 0x2;
+/// `NCRYPT_UI_PROTECT_KEY_FLAG`, using the key shows a dialog, and a password if one was set.
+pub const UI_PROTECT_KEY: u32 = 0x1;
+/// `NCRYPT_UI_FORCE_HIGH_PROTECTION_FLAG`, using the key always asks for its password.
+pub const UI_FORCE_HIGH_PROTECTION: u32 = 0x2;
+/// `NCRYPT_IMPL_HARDWARE_FLAG`, the provider keeps its keys in hardware.
+pub const IMPL_HARDWARE: u32 = 0x1;
+/// `NCRYPT_IMPL_SOFTWARE_FLAG`
+pub const IMPL_SOFTWARE: u32 = 0x2;
+/// `NCRYPT_IMPL_REMOVABLE_FLAG`, the keys are on removable media such as a smart card.
+pub const IMPL_REMOVABLE: u32 = 0x8;
+/// `CRYPT_EXPORT`, the `KP_PERMISSIONS` bit of a legacy CSP key that can be exported.
+pub const CSP_EXPORT: u32 = 0x4;
+
+/// What the store holding a private key reports about it, read without exporting the key.
+///
+/// The flags follow CNG: the `Export Policy`, `UI Policy` and `Impl Type` properties of an NCrypt key. Legacy CSP
+/// keys are described with the same flags, see [`KeyStorage::from_csp`]. A flag set is `None` when the store could
+/// not tell, such as for a smart card key that cannot be opened without its PIN.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyStorage {
+    /// The key storage provider, CSP or token holding the key.
+    pub provider: Option<String>,
+    pub export_policy: Option<u32>,
+    pub ui_policy: Option<u32>,
+    pub implementation: Option<u32>,
+}
+
+impl KeyStorage {
+    /// A key only the provider name is known of.
+    pub fn provider(provider: Option<String>) -> KeyStorage {
+        KeyStorage { provider, ..KeyStorage::default() }
+    }
+
+    /// A key held in memory, which is exportable unless the store forbids it.
+    pub fn software(provider: Option<String>, exportable: bool) -> KeyStorage {
+        KeyStorage {
+            provider,
+            export_policy: Some(if exportable { ALLOW_EXPORT | ALLOW_PLAINTEXT_EXPORT } else { 0 }),
+            ui_policy: Some(0),
+            implementation: Some(IMPL_SOFTWARE),
+        }
+    }
+
+    /// A key held by a PKCS#11 token, which never leaves it.
+    pub fn token(provider: Option<String>) -> KeyStorage {
+        KeyStorage { provider, export_policy: Some(0), ui_policy: None, implementation: Some(IMPL_HARDWARE) }
+    }
+
+    /// Describes a legacy CSP key from its `KP_PERMISSIONS` and the `PP_IMPTYPE` of its provider. CSP keys that can
+    /// be exported can always be exported in plain text.
+    pub fn from_csp(provider: Option<String>, permissions: Option<u32>, implementation: Option<u32>) -> KeyStorage {
+        KeyStorage {
+            provider,
+            export_policy: permissions.map(|permissions| {
+                if permissions & CSP_EXPORT != 0 { ALLOW_EXPORT | ALLOW_PLAINTEXT_EXPORT } else { 0 }
+            }),
+            ui_policy: None,
+            implementation,
+        }
+    }
+
+    /// Whether the key can be exported, or `None` when the export policy is unknown.
+    pub fn exportable(&self) -> Option<bool> {
+        self.export_policy.map(|policy| policy & ALLOW_EXPORT != 0)
+    }
+
+    /// Whether using the key asks the user for consent or a password, or `None` when the UI policy is unknown.
+    pub fn ui_protected(&self) -> Option<bool> {
+        self.ui_policy.map(|policy| policy & (UI_PROTECT_KEY | UI_FORCE_HIGH_PROTECTION) != 0)
+    }
+
+    /// Whether the key is held by a smart card, TPM or other hardware, from the provider implementation type or,
+    /// when it is unknown, the provider name.
+    pub fn hardware_backed(&self) -> bool {
+        match self.implementation {
+            Some(implementation) => implementation & (IMPL_HARDWARE | IMPL_REMOVABLE) != 0,
+            None => self.provider.as_deref().is_some_and(is_hardware_provider),
+        }
+    }
+}
+
+/// Decodes a DWORD key property such as `Export Policy` or `Impl Type`.
+pub fn decode_dword(blob: &[u8]) -> Result<u32> {
+    let bytes: [u8; 4] = blob.try_into()
+        .map_err(|_| Error::new(ErrorKind::InvalidData, format!("Expected a 4 byte key property, found {} bytes", blob.len())))?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Decodes the flags of an `NCRYPT_UI_POLICY`, which starts with its version and flags.
+pub fn decode_ui_policy(blob: &[u8]) -> Result<u32> {
+    match blob.get(4..8) {
+        Some(flags) => decode_dword(flags),
+        None => Err(Error::new(ErrorKind::InvalidData, "The key UI policy is truncated")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_cng_policies() {
+        let storage = KeyStorage {
+            provider: Some("Microsoft Software Key Storage Provider".to_string()),
+            export_policy: Some(decode_dword(&[0x03, 0, 0, 0]).unwrap()),
+            // NCRYPT_UI_POLICY { dwVersion: 1, dwFlags: NCRYPT_UI_PROTECT_KEY_FLAG, .. } on x64.
+            ui_policy: Some(decode_ui_policy(&[1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap()),
+            implementation: Some(decode_dword(&[0x02, 0, 0, 0]).unwrap()),
+        };
+        assert_eq!(storage.exportable(), Some(true));
+        assert_eq!(storage.ui_protected(), Some(true));
+        assert!(!storage.hardware_backed());
+
+        assert!(decode_dword(&[1, 0]).is_err());
+        assert!(decode_ui_policy(&[1, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn recognizes_hardware_keys() {
+        let smart_card = KeyStorage {
+            provider: Some("Contoso Smart Card KSP".to_string()),
+            export_policy: Some(0),
+            ui_policy: Some(0),
+            implementation: Some(IMPL_HARDWARE | IMPL_REMOVABLE),
+        };
+        assert!(smart_card.hardware_backed());
+        assert_eq!(smart_card.exportable(), Some(false));
+
+        // A key that could not be opened silently is judged by its provider.
+        let tpm = KeyStorage::provider(Some("Microsoft Platform Crypto Provider".to_string()));
+        assert!(tpm.hardware_backed());
+        assert_eq!((tpm.exportable(), tpm.ui_protected()), (None, None));
+    }
+
+    #[test]
+    fn maps_csp_permissions() {
+        let exportable = KeyStorage::from_csp(None, Some(0x3F), Some(2));
+        assert_eq!(exportable.exportable(), Some(true));
+        assert_eq!(exportable.export_policy, Some(ALLOW_EXPORT | ALLOW_PLAINTEXT_EXPORT));
+        assert_eq!(exportable.ui_protected(), None);
+
+        let locked = KeyStorage::from_csp(None, Some(0x3B), Some(1));
+        assert_eq!(locked.exportable(), Some(false));
+        assert!(locked.hardware_backed());
+
+        assert_eq!(KeyStorage::software(None, false).exportable(), Some(false));
+    }
+}
@@ -0,0 +1,483 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::fmt;
+use std::io::{Result, Error, ErrorKind};
+use std::str::FromStr;
+use der::{Decode, Encode};
+use der::asn1::ObjectIdentifier;
+use rsa::{Pkcs1v15Sign, Pss};
+use rsa::pkcs1::RsaPssParams;
+use rsa::traits::PublicKeyParts;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use signature::hazmat::PrehashVerifier;
+use signature::Verifier;
+use spki::{AlgorithmIdentifierOwned, DecodePublicKey, SubjectPublicKeyInfoOwned};
+
+use crate::certificate::{Certificate, invalid_data};
+use crate::keys::{
+    OID_ECDSA_WITH_SHA256, OID_ECDSA_WITH_SHA384, OID_ECDSA_WITH_SHA512, OID_EC_PUBLIC_KEY, OID_ED25519,
+    OID_RSA_ENCRYPTION, OID_SECP256R1, OID_SECP384R1, OID_SECP521R1, OID_SHA256_WITH_RSA, OID_SHA384_WITH_RSA,
+    OID_SHA512_WITH_RSA, PrivateKey, curve_oid,
+};
+
+
+pub(crate) const OID_RSASSA_PSS: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.10");
+
+const OID_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+const OID_SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2");
+const OID_SHA512: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3");
+
+/// The message digests used by the supported signature algorithms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    fn from_oid(oid: ObjectIdentifier) -> Result<HashAlgorithm> {
+        match oid {
+            OID_SHA256 => Ok(HashAlgorithm::Sha256),
+            OID_SHA384 => Ok(HashAlgorithm::Sha384),
+            OID_SHA512 => Ok(HashAlgorithm::Sha512),
+            oid => Err(Error::new(ErrorKind::Unsupported, format!("Unsupported hash algorithm {}", oid))),
+        }
+    }
+
+    fn digest(&self, msg: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::digest(msg).to_vec(),
+            HashAlgorithm::Sha384 => Sha384::digest(msg).to_vec(),
+            HashAlgorithm::Sha512 => Sha512::digest(msg).to_vec(),
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 32,
+            HashAlgorithm::Sha384 => 48,
+            HashAlgorithm::Sha512 => 64,
+        }
+    }
+
+    fn pkcs1v15(&self) -> Pkcs1v15Sign {
+        match self {
+            HashAlgorithm::Sha256 => Pkcs1v15Sign::new::<Sha256>(),
+            HashAlgorithm::Sha384 => Pkcs1v15Sign::new::<Sha384>(),
+            HashAlgorithm::Sha512 => Pkcs1v15Sign::new::<Sha512>(),
+        }
+    }
+
+    /// The MGF1 mask of `len` bytes generated from `seed`.
+    fn mgf1(&self, seed: &[u8], len: usize) -> Vec<u8> {
+        let mut mask = Vec::with_capacity(len + self.output_size());
+        let mut counter = 0u32;
+        while mask.len() < len {
+            mask.extend(self.digest(&[seed, &counter.to_be_bytes()].concat()));
+            counter += 1;
+        }
+        mask.truncate(len);
+        mask
+    }
+
+    fn pss(&self, salt_len: usize) -> Pss {
+        match self {
+            HashAlgorithm::Sha256 => Pss::new_with_salt::<Sha256>(salt_len),
+            HashAlgorithm::Sha384 => Pss::new_with_salt::<Sha384>(salt_len),
+            HashAlgorithm::Sha512 => Pss::new_with_salt::<Sha512>(salt_len),
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<HashAlgorithm> {
+        match s.to_lowercase().replace(['-', '_'], "").as_str() {
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha384" => Ok(HashAlgorithm::Sha384),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            _ => Err(Error::new(ErrorKind::InvalidInput, format!("Unsupported hash algorithm \"{}\"", s))),
+        }
+    }
+}
+
+/// The padding of an RSA signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RsaPadding {
+    Pkcs1v15,
+    Pss,
+}
+
+impl FromStr for RsaPadding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<RsaPadding> {
+        match s.to_lowercase().replace(['-', '_', '#', '.', ' '], "").as_str() {
+            "pkcs1v15" | "pkcs1" => Ok(RsaPadding::Pkcs1v15),
+            "pss" | "rsapss" => Ok(RsaPadding::Pss),
+            _ => Err(Error::new(ErrorKind::InvalidInput, format!("Unsupported RSA padding \"{}\"", s))),
+        }
+    }
+}
+
+/// How a signature was made, which together with the key type selects the verification.
+#[derive(Clone, Copy, Debug)]
+enum Scheme {
+    Pkcs1v15(HashAlgorithm),
+    /// RSA-PSS with MGF1 over the same digest. Without a salt length it is read from the signature, like
+    /// OpenSSL's `RSA_PSS_SALTLEN_AUTO`.
+    Pss(HashAlgorithm, Option<usize>),
+    Ecdsa(HashAlgorithm),
+    Ed25519,
+}
+
+impl Scheme {
+    /// The scheme of an X.509 signature algorithm identifier.
+    fn from_algorithm(algorithm: &AlgorithmIdentifierOwned) -> Result<Scheme> {
+        match algorithm.oid {
+            OID_SHA256_WITH_RSA => Ok(Scheme::Pkcs1v15(HashAlgorithm::Sha256)),
+            OID_SHA384_WITH_RSA => Ok(Scheme::Pkcs1v15(HashAlgorithm::Sha384)),
+            OID_SHA512_WITH_RSA => Ok(Scheme::Pkcs1v15(HashAlgorithm::Sha512)),
+            OID_ECDSA_WITH_SHA256 => Ok(Scheme::Ecdsa(HashAlgorithm::Sha256)),
+            OID_ECDSA_WITH_SHA384 => Ok(Scheme::Ecdsa(HashAlgorithm::Sha384)),
+            OID_ECDSA_WITH_SHA512 => Ok(Scheme::Ecdsa(HashAlgorithm::Sha512)),
+            OID_ED25519 => Ok(Scheme::Ed25519),
+            OID_RSASSA_PSS => {
+                let parameters = algorithm.parameters.as_ref()
+                    .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The RSA-PSS parameters are missing"))?
+                    .to_der()
+                    .map_err(invalid_data)?;
+                let parameters = RsaPssParams::from_der(&parameters).map_err(invalid_data)?;
+                let hash = HashAlgorithm::from_oid(parameters.hash.oid)?;
+                let mgf_hash = parameters.mask_gen.parameters.map(|mgf_hash| mgf_hash.oid);
+                if mgf_hash != Some(parameters.hash.oid) {
+                    return Err(Error::new(ErrorKind::Unsupported, "RSA-PSS with a different MGF1 digest is not supported"));
+                }
+                Ok(Scheme::Pss(hash, Some(usize::from(parameters.salt_len))))
+            },
+            oid => Err(Error::new(ErrorKind::Unsupported, format!("Unsupported signature algorithm {}", oid))),
+        }
+    }
+
+    fn accepts_key(&self, key_oid: ObjectIdentifier) -> bool {
+        match self {
+            Scheme::Pkcs1v15(_) | Scheme::Pss(..) => key_oid == OID_RSA_ENCRYPTION,
+            Scheme::Ecdsa(_) => key_oid == OID_EC_PUBLIC_KEY,
+            Scheme::Ed25519 => key_oid == OID_ED25519,
+        }
+    }
+}
+
+/// Verifies an X.509 style signature (as found in certificates and CSRs) with the given public key.
+///
+/// Returns `Ok(false)` when the signature does not match, and an error when the key or signature algorithm is not supported.
+pub fn verify_signature(public_key: &SubjectPublicKeyInfoOwned, algorithm: &AlgorithmIdentifierOwned, msg: &[u8], signature: &[u8]) -> Result<bool> {
+    let scheme = Scheme::from_algorithm(algorithm)?;
+    if !scheme.accepts_key(public_key.algorithm.oid) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Signature algorithm {} cannot be used with a {} key", algorithm.oid, public_key.algorithm.oid)
+        ));
+    }
+    verify_scheme(public_key, scheme, msg, signature, false)
+}
+
+/// Verifies a signature over `msg` made with the private key of `public_key`, as made by `openssl dgst -sign`.
+///
+/// RSA signatures use PKCS#1 v1.5 padding unless `padding` says otherwise. ECDSA signatures may be DER encoded or
+/// the fixed size `r || s` form, and Ed25519 hashes the message itself so `hash` is ignored. Returns `Ok(false)`
+/// when the signature does not match, and an `InvalidInput` error when the padding does not apply to the key.
+pub fn verify_data(public_key: &SubjectPublicKeyInfoOwned, msg: &[u8], signature: &[u8], hash: HashAlgorithm, padding: Option<RsaPadding>) -> Result<bool> {
+    let scheme = match (public_key.algorithm.oid, padding) {
+        (OID_RSA_ENCRYPTION, None | Some(RsaPadding::Pkcs1v15)) => Scheme::Pkcs1v15(hash),
+        (OID_RSA_ENCRYPTION, Some(RsaPadding::Pss)) => Scheme::Pss(hash, None),
+        (_, Some(_)) => return Err(Error::new(ErrorKind::InvalidInput, "A padding can only be given for RSA keys")),
+        (OID_EC_PUBLIC_KEY, None) => Scheme::Ecdsa(hash),
+        (OID_ED25519, None) => Scheme::Ed25519,
+        (oid, None) => return Err(Error::new(ErrorKind::Unsupported, format!("Unsupported key algorithm {}", oid))),
+    };
+    verify_scheme(public_key, scheme, msg, signature, true)
+}
+
+/// Checks that `issuer` signed `cert`, using the issuer's public key and the certificate's signature algorithm.
+///
+/// Only the signature is checked; the names, validity and CA constraints are left to [`crate::chain::Chain`].
+pub fn verify_issued_by(cert: &Certificate, issuer: &Certificate) -> Result<bool> {
+    let x509 = cert.as_x509();
+    let Some(signature) = x509.signature.as_bytes() else {
+        return Ok(false);
+    };
+    let tbs = x509.tbs_certificate.to_der().map_err(invalid_data)?;
+    verify_signature(&issuer.as_x509().tbs_certificate.subject_public_key_info, &x509.signature_algorithm, &tbs, signature)
+}
+
+fn verify_scheme(public_key: &SubjectPublicKeyInfoOwned, scheme: Scheme, msg: &[u8], signature: &[u8], fixed_ecdsa: bool) -> Result<bool> {
+    let spki_der = public_key.to_der().map_err(invalid_data)?;
+
+    match scheme {
+        Scheme::Pkcs1v15(hash) => {
+            let key = rsa::RsaPublicKey::from_public_key_der(&spki_der).map_err(invalid_data)?;
+            Ok(key.verify(hash.pkcs1v15(), &hash.digest(msg), signature).is_ok())
+        },
+        Scheme::Pss(hash, salt_len) => {
+            let key = rsa::RsaPublicKey::from_public_key_der(&spki_der).map_err(invalid_data)?;
+            let Some(salt_len) = salt_len.or_else(|| pss_salt_len(&key, hash, signature)) else {
+                return Ok(false);
+            };
+            Ok(key.verify(hash.pss(salt_len), &hash.digest(msg), signature).is_ok())
+        },
+        Scheme::Ecdsa(hash) => verify_ecdsa(public_key, &spki_der, &hash.digest(msg), signature, fixed_ecdsa),
+        Scheme::Ed25519 => {
+            let key = ed25519_dalek::VerifyingKey::from_public_key_der(&spki_der).map_err(invalid_data)?;
+            let Ok(signature) = ed25519_dalek::Signature::from_slice(signature) else {
+                return Ok(false);
+            };
+            Ok(key.verify(msg, &signature).is_ok())
+        },
+    }
+}
+
+/// Reads the salt length of an RSA-PSS signature from its encoded message (RFC 8017, section 9.1.2), which lets
+/// signatures made with any salt length be verified. Returns `None` when the signature is not PSS encoded.
+fn pss_salt_len(key: &rsa::RsaPublicKey, hash: HashAlgorithm, signature: &[u8]) -> Option<usize> {
+    let signature = rsa::BigUint::from_bytes_be(signature);
+    if &signature >= key.n() {
+        return None;
+    }
+    let encoded = signature.modpow(key.e(), key.n()).to_bytes_be();
+
+    let em_bits = key.n().bits() - 1;
+    let em_len = em_bits.div_ceil(8);
+    let h_len = hash.output_size();
+    if encoded.len() > em_len || em_len < h_len + 2 || encoded.last() != Some(&0xbc) {
+        return None;
+    }
+    let mut em = vec![0u8; em_len - encoded.len()];
+    em.extend(encoded);
+
+    let (masked_db, h) = em[..em_len - 1].split_at(em_len - h_len - 1);
+    let mut db: Vec<u8> = masked_db.iter().zip(hash.mgf1(h, masked_db.len())).map(|(byte, mask)| byte ^ mask).collect();
+    db[0] &= 0xff >> (8 * em_len - em_bits);
+
+    // DB is the padding of zeros, a 0x01 separator and the salt.
+    let separator = db.iter().position(|byte| *byte != 0)?;
+    (db[separator] == 0x01).then(|| db.len() - separator - 1)
+}
+
+/// Verifies an ECDSA signature over a digest. X.509 signatures are DER encoded, `fixed` also accepts `r || s`.
+fn verify_ecdsa(public_key: &SubjectPublicKeyInfoOwned, spki_der: &[u8], prehash: &[u8], signature: &[u8], fixed: bool) -> Result<bool> {
+    match curve_oid(&public_key.algorithm)? {
+        OID_SECP256R1 => {
+            let key = p256::ecdsa::VerifyingKey::from_public_key_der(spki_der).map_err(invalid_data)?;
+            let signature = match p256::ecdsa::Signature::from_der(signature) {
+                Ok(signature) => signature,
+                Err(_) if fixed => match p256::ecdsa::Signature::from_slice(signature) {
+                    Ok(signature) => signature,
+                    Err(_) => return Ok(false),
+                },
+                Err(_) => return Ok(false),
+            };
+            Ok(key.verify_prehash(prehash, &signature).is_ok())
+        },
+        OID_SECP384R1 => {
+            let key = p384::ecdsa::VerifyingKey::from_public_key_der(spki_der).map_err(invalid_data)?;
+            let signature = match p384::ecdsa::Signature::from_der(signature) {
+                Ok(signature) => signature,
+                Err(_) if fixed => match p384::ecdsa::Signature::from_slice(signature) {
+                    Ok(signature) => signature,
+                    Err(_) => return Ok(false),
+                },
+                Err(_) => return Ok(false),
+            };
+            Ok(key.verify_prehash(prehash, &signature).is_ok())
+        },
+        OID_SECP521R1 => {
+            let key = p521::ecdsa::VerifyingKey::from_sec1_bytes(public_key.subject_public_key.raw_bytes()).map_err(invalid_data)?;
+            let signature = match p521::ecdsa::Signature::from_der(signature) {
+                Ok(signature) => signature,
+                Err(_) if fixed => match p521::ecdsa::Signature::from_slice(signature) {
+                    Ok(signature) => signature,
+                    Err(_) => return Ok(false),
+                },
+                Err(_) => return Ok(false),
+            };
+            Ok(key.verify_prehash(prehash, &signature).is_ok())
+        },
+        curve => Err(Error::new(ErrorKind::Unsupported, format!("Unsupported elliptic curve {}", curve))),
+    }
+}
+
+/// Why a private key was found not to belong to a certificate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyMismatch {
+    /// The public key derived from the private key differs from the certificate's SubjectPublicKeyInfo.
+    PublicKey,
+    /// The public keys agree, but a signature made with the private key does not verify against the certificate.
+    Signature,
+}
+
+impl fmt::Display for KeyMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyMismatch::PublicKey => write!(f, "The private key does not match the certificate's public key"),
+            KeyMismatch::Signature => write!(f, "A signature made with the private key does not verify against the certificate"),
+        }
+    }
+}
+
+impl std::error::Error for KeyMismatch {}
+
+impl KeyMismatch {
+    /// Returns the mismatch carried by an error from [`check_key_pair`], if that is why it failed.
+    pub fn from_error(err: &Error) -> Option<KeyMismatch> {
+        err.get_ref()?.downcast_ref::<KeyMismatch>().copied()
+    }
+}
+
+/// Checks that `key` is the private key of `cert`.
+///
+/// The public key derived from the key material is compared with the certificate's SubjectPublicKeyInfo. With
+/// `probe` set, a test message is also signed with the key and verified with the certificate's public key, which
+/// catches key material that is damaged in ways the public half does not show. Mismatches are reported as an
+/// `InvalidData` error wrapping a [`KeyMismatch`].
+pub fn check_key_pair(key: &PrivateKey, cert: &Certificate, probe: bool) -> Result<()> {
+    if !key.matches_certificate(cert)? {
+        return Err(Error::new(ErrorKind::InvalidData, KeyMismatch::PublicKey));
+    }
+
+    if probe {
+        let msg = b"py_cert_store key pair probe";
+        let signature = key.sign(msg)?;
+        let public_key = &cert.as_x509().tbs_certificate.subject_public_key_info;
+        if !verify_signature(public_key, &key.signature_algorithm(), msg, &signature)? {
+            return Err(Error::new(ErrorKind::InvalidData, KeyMismatch::Signature));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &[u8] = include_bytes!("../../test/signatures/message.txt");
+    const RSA_CERT: &[u8] = include_bytes!("../../test/signatures/rsa.crt");
+    const EC_PSS_CERT: &[u8] = include_bytes!("../../test/signatures/ec_pss.crt");
+    const ED25519_CERT: &[u8] = include_bytes!("../../test/signatures/ed25519.crt");
+    const RSA_PKCS1_SHA256: &[u8] = include_bytes!("../../test/signatures/rsa_pkcs1_sha256.sig");
+    const RSA_PSS_SHA256: &[u8] = include_bytes!("../../test/signatures/rsa_pss_sha256.sig");
+    const RSA_PSS_MAX_SHA512: &[u8] = include_bytes!("../../test/signatures/rsa_pss_max_sha512.sig");
+    const RSA_PSS_SALT20_SHA256: &[u8] = include_bytes!("../../test/signatures/rsa_pss_salt20_sha256.sig");
+    const ECDSA_SHA384: &[u8] = include_bytes!("../../test/signatures/ecdsa_sha384.sig");
+    const ED25519: &[u8] = include_bytes!("../../test/signatures/ed25519.sig");
+    const KEY_PEM: &str = include_str!("../../test/certs/private_key.pem");
+    const KEY_CERT: &[u8] = include_bytes!("../../test/certs/certificate.crt");
+    const CA_CERT: &[u8] = include_bytes!("../../test/certs/ca_certificate.crt");
+
+    fn public_key(pem: &[u8]) -> SubjectPublicKeyInfoOwned {
+        Certificate::from_pem(pem).unwrap().as_x509().tbs_certificate.subject_public_key_info.clone()
+    }
+
+    #[test]
+    fn verifies_openssl_signatures() {
+        let rsa = public_key(RSA_CERT);
+        let ec = public_key(EC_PSS_CERT);
+        let ed25519 = public_key(ED25519_CERT);
+
+        assert!(verify_data(&rsa, MESSAGE, RSA_PKCS1_SHA256, HashAlgorithm::Sha256, None).unwrap());
+        assert!(verify_data(&rsa, MESSAGE, RSA_PKCS1_SHA256, HashAlgorithm::Sha256, Some(RsaPadding::Pkcs1v15)).unwrap());
+        assert!(verify_data(&rsa, MESSAGE, RSA_PSS_SHA256, HashAlgorithm::Sha256, Some(RsaPadding::Pss)).unwrap());
+        assert!(verify_data(&rsa, MESSAGE, RSA_PSS_MAX_SHA512, HashAlgorithm::Sha512, Some(RsaPadding::Pss)).unwrap());
+        assert!(verify_data(&ec, MESSAGE, ECDSA_SHA384, HashAlgorithm::Sha384, None).unwrap());
+        assert!(verify_data(&ed25519, MESSAGE, ED25519, HashAlgorithm::Sha256, None).unwrap());
+
+        let fixed = p256::ecdsa::Signature::from_der(ECDSA_SHA384).unwrap().to_bytes();
+        assert!(verify_data(&ec, MESSAGE, &fixed, HashAlgorithm::Sha384, None).unwrap());
+    }
+
+    #[test]
+    fn rejects_wrong_signatures() {
+        let rsa = public_key(RSA_CERT);
+        let ec = public_key(EC_PSS_CERT);
+
+        assert!(!verify_data(&rsa, b"tampered", RSA_PKCS1_SHA256, HashAlgorithm::Sha256, None).unwrap());
+        assert!(!verify_data(&rsa, MESSAGE, RSA_PSS_SHA256, HashAlgorithm::Sha256, None).unwrap());
+        assert!(!verify_data(&rsa, MESSAGE, RSA_PKCS1_SHA256, HashAlgorithm::Sha384, None).unwrap());
+        assert!(!verify_data(&ec, MESSAGE, ECDSA_SHA384, HashAlgorithm::Sha256, None).unwrap());
+        assert!(!verify_data(&ec, MESSAGE, b"not a signature", HashAlgorithm::Sha384, None).unwrap());
+        assert!(!verify_data(&public_key(ED25519_CERT), b"tampered", ED25519, HashAlgorithm::Sha256, None).unwrap());
+
+        let err = verify_data(&ec, MESSAGE, ECDSA_SHA384, HashAlgorithm::Sha384, Some(RsaPadding::Pss)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn detects_pss_salt_lengths() {
+        let rsa = public_key(RSA_CERT);
+        let key = rsa::RsaPublicKey::from_public_key_der(&rsa.to_der().unwrap()).unwrap();
+
+        assert_eq!(pss_salt_len(&key, HashAlgorithm::Sha256, RSA_PSS_SHA256), Some(32));
+        assert_eq!(pss_salt_len(&key, HashAlgorithm::Sha256, RSA_PSS_SALT20_SHA256), Some(20));
+        assert_eq!(pss_salt_len(&key, HashAlgorithm::Sha512, RSA_PSS_MAX_SHA512), Some(256 - 64 - 2));
+        assert_eq!(pss_salt_len(&key, HashAlgorithm::Sha256, RSA_PKCS1_SHA256), None);
+
+        assert!(verify_data(&rsa, MESSAGE, RSA_PSS_SALT20_SHA256, HashAlgorithm::Sha256, Some(RsaPadding::Pss)).unwrap());
+        assert!(!verify_data(&rsa, b"tampered", RSA_PSS_SALT20_SHA256, HashAlgorithm::Sha256, Some(RsaPadding::Pss)).unwrap());
+        assert!(!verify_data(&rsa, MESSAGE, RSA_PKCS1_SHA256, HashAlgorithm::Sha256, Some(RsaPadding::Pss)).unwrap());
+    }
+
+    #[test]
+    fn verifies_issuing_certificates() {
+        let ca = Certificate::from_pem(RSA_CERT).unwrap();
+        let leaf = Certificate::from_pem(EC_PSS_CERT).unwrap();
+        let ed25519 = Certificate::from_pem(ED25519_CERT).unwrap();
+
+        assert_eq!(leaf.as_x509().signature_algorithm.oid, OID_RSASSA_PSS);
+        assert!(verify_issued_by(&leaf, &ca).unwrap());
+        assert!(verify_issued_by(&ca, &ca).unwrap());
+        assert!(verify_issued_by(&ed25519, &ed25519).unwrap());
+        assert!(!verify_issued_by(&ca, &Certificate::from_pem(CA_CERT).unwrap()).unwrap());
+        assert_eq!(verify_issued_by(&leaf, &ed25519).unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn parses_hash_and_padding_names() {
+        assert_eq!("SHA-384".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha384);
+        assert_eq!("sha512".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha512);
+        assert_eq!("md5".parse::<HashAlgorithm>().unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!("PKCS1v1.5".parse::<RsaPadding>().unwrap(), RsaPadding::Pkcs1v15);
+        assert_eq!("oaep".parse::<RsaPadding>().unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!("pkcs1v15".parse::<RsaPadding>().unwrap(), RsaPadding::Pkcs1v15);
+        assert_eq!("RSA-PSS".parse::<RsaPadding>().unwrap(), RsaPadding::Pss);
+    }
+
+    #[test]
+    fn checks_key_pair_consistency() {
+        let key = PrivateKey::from_pkcs8_pem(KEY_PEM).unwrap();
+        check_key_pair(&key, &Certificate::from_pem(KEY_CERT).unwrap(), true).unwrap();
+
+        let err = check_key_pair(&key, &Certificate::from_pem(CA_CERT).unwrap(), true).unwrap_err();
+        assert_eq!(KeyMismatch::from_error(&err), Some(KeyMismatch::PublicKey));
+        assert_eq!(KeyMismatch::from_error(&Error::new(ErrorKind::InvalidData, "other")), None);
+    }
+}
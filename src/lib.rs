@@ -13,24 +13,111 @@
 // limitations under the License.
 
 
-#![cfg(windows)]
-
 use pyo3::prelude::*;
 use pyo3::Python;
 
+#[cfg(windows)]
 pub mod store_reader;
 pub mod windows_store;
+#[cfg(unix)]
+pub mod nss_store;
+pub mod pkcs11_store;
+pub mod certificate;
+pub mod file_store;
+pub mod file_reader;
+pub mod keys;
+pub mod csr;
+pub mod cert_builder;
+pub mod ranking;
+pub mod selection;
+pub mod chain;
+pub mod cli;
+pub mod metadata;
+pub mod inventory;
+pub mod cache;
+pub mod watch;
+pub mod store;
+pub mod store_iterator;
+pub mod watch_reader;
+pub mod cli_reader;
+pub mod metadata_reader;
+#[cfg(unix)]
+pub mod nss_reader;
+pub mod pkcs11_reader;
+pub mod pki_reader;
 pub mod exceptions;
 
 use exceptions::CertNotExportable;
 use exceptions::CertNotFound;
+use exceptions::CertKeyMismatch;
+use exceptions::CertAmbiguous;
+use exceptions::CertSearchCancelled;
 
 /// A Python module implemented in Rust.
 #[pymodule]
 fn py_cert_store(py:Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    #[cfg(windows)]
     m.add_function(wrap_pyfunction!(store_reader::find_windows_cert_by_extension, m)?)?;
+    #[cfg(windows)]
     m.add_function(wrap_pyfunction!(store_reader::find_windows_cert_all, m)?)?;
+    #[cfg(windows)]
+    m.add_function(wrap_pyfunction!(store_reader::iter_windows_certs, m)?)?;
+    #[cfg(windows)]
+    m.add_function(wrap_pyfunction!(watch_reader::watch_windows_store, m)?)?;
+    m.add_function(wrap_pyfunction!(file_reader::read_pkcs7_certificates, m)?)?;
+    m.add_function(wrap_pyfunction!(file_reader::write_pkcs7_certificates, m)?)?;
+    m.add_function(wrap_pyfunction!(file_reader::read_java_keystore, m)?)?;
+    m.add_function(wrap_pyfunction!(file_reader::write_java_keystore, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::generate_csr, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::parse_csr, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::generate_certificate, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::generate_private_key, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::convert_private_key, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::get_public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::get_ssh_public_key, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::parse_ssh_certificate, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::private_key_matches_certificate, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::get_alt_names, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::certificate_matches_host, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::certificate_matches_email, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::certificate_allows_purpose, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::certificate_verifies_signature, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::certificate_issued_by, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::rank_certificates, m)?)?;
+    m.add_function(wrap_pyfunction!(pki_reader::select_certificates, m)?)?;
+    m.add_class::<pki_reader::CertificateCandidate>()?;
+    m.add_function(wrap_pyfunction!(store_iterator::iter_certificates, m)?)?;
+    m.add_function(wrap_pyfunction!(store_iterator::find_certificates, m)?)?;
+    #[cfg(feature = "test-support")]
+    m.add_function(wrap_pyfunction!(store_iterator::find_slow_certificates, m)?)?;
+    m.add_function(wrap_pyfunction!(store_iterator::find_certificates_in_path, m)?)?;
+    m.add_class::<store_iterator::CertificateIterator>()?;
+    m.add_class::<store_iterator::StoreCertificate>()?;
+    m.add_class::<store_iterator::CancellationToken>()?;
+    m.add_class::<store_iterator::CertificateCache>()?;
+    m.add_function(wrap_pyfunction!(watch_reader::watch_path, m)?)?;
+    m.add_function(wrap_pyfunction!(cli_reader::run_cli, m)?)?;
+    m.add_function(wrap_pyfunction!(metadata_reader::certificate_metadata, m)?)?;
+    m.add_function(wrap_pyfunction!(metadata_reader::metadata_to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(metadata_reader::metadata_to_yaml, m)?)?;
+    m.add_function(wrap_pyfunction!(metadata_reader::metadata_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(metadata_reader::snapshot_stores, m)?)?;
+    m.add_function(wrap_pyfunction!(metadata_reader::diff_snapshots, m)?)?;
+    #[cfg(unix)]
+    m.add_function(wrap_pyfunction!(nss_reader::list_nss_databases, m)?)?;
+    #[cfg(unix)]
+    m.add_function(wrap_pyfunction!(nss_reader::read_nss_database, m)?)?;
+    #[cfg(unix)]
+    m.add_function(wrap_pyfunction!(nss_reader::find_nss_certificates, m)?)?;
+    m.add_function(wrap_pyfunction!(pkcs11_reader::list_pkcs11_tokens, m)?)?;
+    m.add_function(wrap_pyfunction!(pkcs11_reader::open_pkcs11_token, m)?)?;
+    m.add_class::<pkcs11_reader::Pkcs11Token>()?;
+    m.add_class::<watch_reader::StoreWatch>()?;
+    m.add_class::<watch_reader::StoreEvent>()?;
     m.add("CertNotExportable", py.get_type::<CertNotExportable>())?;
     m.add("CertNotFound", py.get_type::<CertNotFound>())?;
+    m.add("CertKeyMismatch", py.get_type::<CertKeyMismatch>())?;
+    m.add("CertAmbiguous", py.get_type::<CertAmbiguous>())?;
+    m.add("CertSearchCancelled", py.get_type::<CertSearchCancelled>())?;
     Ok(())
 }
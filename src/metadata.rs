@@ -0,0 +1,520 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+use std::time::SystemTime;
+use base64ct::{Base64, Encoding};
+use der::asn1::ObjectIdentifier;
+use spki::SubjectPublicKeyInfoOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use x509_cert::ext::pkix::{AuthorityKeyIdentifier, BasicConstraints, SubjectKeyIdentifier};
+
+use crate::certificate::{Certificate, hex, invalid_data};
+use crate::certificate::extensions::{UsageExtensions, extended_key_usage_name, find_extension, key_usage_names};
+use crate::certificate::name::DistinguishedName;
+use crate::keys::{self, KeyAlgorithm};
+use crate::keys::storage::KeyStorage;
+use crate::store::rfc3339_date_string;
+
+
+/// The version of the metadata layout, raised whenever a field is removed or changes meaning. Fields may be added
+/// without raising it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema describing [`CertificateMetadata`] and [`CertificateInventory`].
+pub const SCHEMA: &str = include_str!("../schema/certificate-metadata-v1.json");
+
+const SIGNATURE_ALGORITHM_NAMES: [(ObjectIdentifier, &str); 7] = [
+    (keys::OID_SHA256_WITH_RSA, "sha256WithRSAEncryption"),
+    (keys::OID_SHA384_WITH_RSA, "sha384WithRSAEncryption"),
+    (keys::OID_SHA512_WITH_RSA, "sha512WithRSAEncryption"),
+    (keys::OID_ECDSA_WITH_SHA256, "ecdsa-with-SHA256"),
+    (keys::OID_ECDSA_WITH_SHA384, "ecdsa-with-SHA384"),
+    (keys::OID_ECDSA_WITH_SHA512, "ecdsa-with-SHA512"),
+    (keys::OID_ED25519, "Ed25519"),
+];
+
+/// Describes a certificate for inventories, without key material unless it is explicitly added.
+///
+/// Serialized with PascalCase field names, following `schema/certificate-metadata-v1.json`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CertificateMetadata {
+    pub schema_version: u32,
+    pub thumbprint: String,
+    pub sha256_fingerprint: String,
+    pub serial_number: String,
+    pub subject: NameMetadata,
+    pub issuer: NameMetadata,
+    pub not_before: String,
+    pub not_after: String,
+    pub signature_algorithm: String,
+    pub alt_names: Vec<String>,
+    pub key_usage: Vec<String>,
+    pub extended_key_usage: Vec<String>,
+    pub basic_constraints: Option<ConstraintsMetadata>,
+    pub subject_key_identifier: Option<String>,
+    pub authority_key_identifier: Option<String>,
+    pub key: KeyMetadata,
+    pub friendly_name: Option<String>,
+    pub store: Option<StoreLocation>,
+    /// The `certutil` trust flags of certificates read from an NSS database, such as `CT,C,C`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nss_trust: Option<String>,
+    /// The PEM encoded certificate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<String>,
+    /// The certificate and its private key as a base64 encoded PKCS#12 file without a password.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pkcs12: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NameMetadata {
+    pub rfc4514: String,
+    /// The name as .NET and the Windows certificate dialogs show it.
+    pub display_name: String,
+    pub common_name: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConstraintsMetadata {
+    pub ca: bool,
+    pub path_length: Option<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct KeyMetadata {
+    /// RSA, ECDSA, Ed25519, or the OID of another key type.
+    pub algorithm: String,
+    /// The key size in bits.
+    pub size: Option<usize>,
+    /// The named curve of ECDSA keys.
+    pub curve: Option<String>,
+    pub has_private_key: bool,
+    /// The key storage provider holding the private key.
+    pub provider: Option<String>,
+    /// Whether the private key is held by a smart card, TPM or other hardware.
+    #[serde(default)]
+    pub hardware_backed: bool,
+    /// Whether the export policy of the private key allows exporting it, when the store reports it.
+    #[serde(default)]
+    pub exportable: Option<bool>,
+    /// Whether using the private key asks the user for consent or a password, when the store reports it.
+    #[serde(default)]
+    pub ui_protected: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreKind {
+    Windows,
+    File,
+    Memory,
+    /// An NSS database directory, as used by Firefox and Chromium.
+    Nss,
+    /// A PKCS#11 token, such as a smart card or HSM.
+    Pkcs11,
+}
+
+/// Where a certificate was found.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StoreLocation {
+    pub kind: StoreKind,
+    /// `CurrentUser` or `LocalMachine` for Windows stores.
+    pub location: Option<String>,
+    /// The Windows store name, such as `My`, or the label of a PKCS#11 token.
+    pub store: Option<String>,
+    /// The file or directory of a file based store, the directory of an NSS database, or the PKCS#11 module.
+    pub path: Option<String>,
+}
+
+impl StoreLocation {
+    pub fn windows(location: &str, store: &str) -> StoreLocation {
+        StoreLocation { kind: StoreKind::Windows, location: Some(location.to_string()), store: Some(store.to_string()), path: None }
+    }
+
+    pub fn file(path: &str) -> StoreLocation {
+        StoreLocation { kind: StoreKind::File, location: None, store: None, path: Some(path.to_string()) }
+    }
+
+    pub fn nss(directory: &str) -> StoreLocation {
+        StoreLocation { kind: StoreKind::Nss, location: None, store: None, path: Some(directory.to_string()) }
+    }
+
+    pub fn pkcs11(module: &str, token: &str) -> StoreLocation {
+        StoreLocation { kind: StoreKind::Pkcs11, location: None, store: Some(token.to_string()), path: Some(module.to_string()) }
+    }
+
+    pub fn memory() -> StoreLocation {
+        StoreLocation { kind: StoreKind::Memory, location: None, store: None, path: None }
+    }
+}
+
+/// A list of certificates with the time it was taken.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CertificateInventory {
+    pub schema_version: u32,
+    pub generated_at: String,
+    /// The machine the inventory was taken on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// The stores that were read, including empty ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stores: Vec<StoreLocation>,
+    pub certificates: Vec<CertificateMetadata>,
+}
+
+impl CertificateMetadata {
+    /// Describes a certificate without a private key or store.
+    pub fn new(certificate: &Certificate) -> Result<CertificateMetadata> {
+        let x509 = certificate.as_x509();
+        let tbs = &x509.tbs_certificate;
+        let extensions = tbs.extensions.as_deref().unwrap_or_default();
+        let usage = UsageExtensions::from_extensions(extensions)?;
+
+        let basic_constraints = find_extension::<BasicConstraints>(extensions)?
+            .map(|constraints| ConstraintsMetadata { ca: constraints.ca, path_length: constraints.path_len_constraint });
+        let subject_key_identifier = find_extension::<SubjectKeyIdentifier>(extensions)?
+            .map(|ski| hex(ski.0.as_bytes()));
+        let authority_key_identifier = find_extension::<AuthorityKeyIdentifier>(extensions)?
+            .and_then(|aki| aki.key_identifier)
+            .map(|key_id| hex(key_id.as_bytes()));
+
+        let signature_algorithm = SIGNATURE_ALGORITHM_NAMES.iter()
+            .find(|(oid, _)| *oid == x509.signature_algorithm.oid)
+            .map(|(_, name)| name.to_string())
+            .unwrap_or_else(|| x509.signature_algorithm.oid.to_string());
+
+        Ok(CertificateMetadata {
+            schema_version: SCHEMA_VERSION,
+            thumbprint: certificate.thumbprint(),
+            sha256_fingerprint: hex(&Sha256::digest(certificate.as_der())),
+            serial_number: hex(tbs.serial_number.as_bytes()),
+            subject: NameMetadata::new(&certificate.subject())?,
+            issuer: NameMetadata::new(&certificate.issuer())?,
+            not_before: rfc3339_date_string(certificate.not_before()),
+            not_after: rfc3339_date_string(certificate.not_after()),
+            signature_algorithm,
+            alt_names: usage.alt_names.iter().map(|name| name.to_string()).collect(),
+            key_usage: usage.key_usage.as_ref().map(key_usage_names).unwrap_or_default().into_iter().map(String::from).collect(),
+            extended_key_usage: usage.extended_key_usage.iter().map(extended_key_usage_name).collect(),
+            basic_constraints,
+            subject_key_identifier,
+            authority_key_identifier,
+            key: KeyMetadata::new(certificate),
+            friendly_name: None,
+            store: None,
+            nss_trust: None,
+            certificate: None,
+            pkcs12: None,
+        })
+    }
+
+    /// Records that the private key is available, and which provider holds it.
+    pub fn private_key(self, provider: Option<String>) -> CertificateMetadata {
+        self.key_storage(&KeyStorage::provider(provider))
+    }
+
+    /// Records that the private key is available, with what its store reports about it.
+    pub fn key_storage(mut self, storage: &KeyStorage) -> CertificateMetadata {
+        self.key = self.key.with_storage(storage);
+        self
+    }
+
+    pub fn friendly_name(mut self, friendly_name: &str) -> CertificateMetadata {
+        self.friendly_name = Some(friendly_name.to_string()).filter(|name| !name.is_empty());
+        self
+    }
+
+    pub fn store(mut self, store: StoreLocation) -> CertificateMetadata {
+        self.store = Some(store);
+        self
+    }
+
+    pub fn nss_trust(mut self, flags: &str) -> CertificateMetadata {
+        self.nss_trust = Some(flags.to_string());
+        self
+    }
+
+    /// Adds the PEM encoded certificate.
+    pub fn with_certificate(mut self, certificate: &Certificate) -> Result<CertificateMetadata> {
+        self.certificate = Some(certificate.to_pem()?);
+        Ok(self)
+    }
+
+    /// Adds a PKCS#12 file holding the private key. Only done when key material was explicitly asked for.
+    pub fn with_pkcs12(mut self, pfx: &[u8]) -> CertificateMetadata {
+        self.pkcs12 = Some(Base64::encode_string(pfx));
+        self
+    }
+
+    /// The end of the validity period.
+    pub fn not_after_time(&self) -> Result<SystemTime> {
+        parse_rfc3339(&self.not_after)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(invalid_data)
+    }
+
+    pub fn to_json_pretty(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(invalid_data)
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).map_err(invalid_data)
+    }
+
+    /// Reads metadata written by this or an earlier schema version.
+    pub fn from_json(json: &str) -> Result<CertificateMetadata> {
+        let metadata: CertificateMetadata = serde_json::from_str(json).map_err(invalid_data)?;
+        check_version(metadata.schema_version)?;
+        Ok(metadata)
+    }
+}
+
+impl NameMetadata {
+    fn new(name: &DistinguishedName) -> Result<NameMetadata> {
+        Ok(NameMetadata { rfc4514: name.to_rfc4514(), display_name: name.to_dotnet(), common_name: name.get("CN")? })
+    }
+}
+
+impl KeyMetadata {
+    /// Describes the public key of a certificate, without a private key.
+    pub fn new(certificate: &Certificate) -> KeyMetadata {
+        KeyMetadata::from_public_key(&certificate.as_x509().tbs_certificate.subject_public_key_info)
+    }
+
+    /// Adds the private key, described by its store.
+    pub fn with_storage(mut self, storage: &KeyStorage) -> KeyMetadata {
+        self.has_private_key = true;
+        self.provider = storage.provider.clone().filter(|provider| !provider.is_empty());
+        self.hardware_backed = storage.hardware_backed();
+        self.exportable = storage.exportable();
+        self.ui_protected = storage.ui_protected();
+        self
+    }
+
+    /// Describes a public key, without a private key.
+    pub(crate) fn from_public_key(spki: &SubjectPublicKeyInfoOwned) -> KeyMetadata {
+        let (algorithm, size, curve) = match KeyAlgorithm::from_public_key_info(spki) {
+            Ok(KeyAlgorithm::Rsa(bits)) => ("RSA".to_string(), Some(bits), None),
+            Ok(KeyAlgorithm::EcP256) => ("ECDSA".to_string(), Some(256), Some("P-256")),
+            Ok(KeyAlgorithm::EcP384) => ("ECDSA".to_string(), Some(384), Some("P-384")),
+            Ok(KeyAlgorithm::EcP521) => ("ECDSA".to_string(), Some(521), Some("P-521")),
+            Ok(KeyAlgorithm::Ed25519) => ("Ed25519".to_string(), Some(256), None),
+            Err(_) => (spki.algorithm.oid.to_string(), None, None),
+        };
+        KeyMetadata {
+            algorithm,
+            size,
+            curve: curve.map(String::from),
+            has_private_key: false,
+            provider: None,
+            hardware_backed: false,
+            exportable: None,
+            ui_protected: None,
+        }
+    }
+}
+
+impl CertificateInventory {
+    pub fn new(certificates: Vec<CertificateMetadata>) -> CertificateInventory {
+        CertificateInventory {
+            schema_version: SCHEMA_VERSION,
+            generated_at: rfc3339_date_string(SystemTime::now()),
+            host: None,
+            stores: Vec::new(),
+            certificates,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(invalid_data)
+    }
+
+    pub fn to_json_pretty(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(invalid_data)
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).map_err(invalid_data)
+    }
+
+    /// Reads an inventory written by this or an earlier schema version.
+    pub fn from_json(json: &str) -> Result<CertificateInventory> {
+        let inventory: CertificateInventory = serde_json::from_str(json).map_err(invalid_data)?;
+        check_version(inventory.schema_version)?;
+        for certificate in &inventory.certificates {
+            check_version(certificate.schema_version)?;
+        }
+        Ok(inventory)
+    }
+}
+
+fn check_version(version: u32) -> Result<()> {
+    if version > SCHEMA_VERSION {
+        return Err(Error::new(ErrorKind::InvalidData, format!(
+            "The metadata uses schema version {}, newer than the supported version {}", version, SCHEMA_VERSION
+        )));
+    }
+    Ok(())
+}
+
+/// Reads the timestamps written in the metadata.
+pub fn parse_rfc3339(text: &str) -> Result<SystemTime> {
+    chrono::DateTime::parse_from_rfc3339(text)
+        .map(SystemTime::from)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, format!("Invalid timestamp \"{}\": {}", text, err)))
+}
+
+
+/// Checks of serialized metadata against the schema, for the unit tests.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use serde_json::Value;
+
+    /// Checks that every field of `value` is described by `definition`, and that the required fields are present.
+    pub(crate) fn assert_matches(value: &Value, definition: &Value, schema: &Value) {
+        let definition = match definition.get("$ref").and_then(Value::as_str) {
+            Some(reference) => &schema["$defs"][reference.trim_start_matches("#/$defs/")],
+            None => definition,
+        };
+        let Some(object) = value.as_object() else {
+            return;
+        };
+        let properties = definition["properties"].as_object().unwrap();
+        for (name, field) in object {
+            let property = properties.get(name).unwrap_or_else(|| panic!("{} is not in the schema", name));
+            let nested = property.get("oneOf").and_then(|options| options.as_array()?.last().cloned()).unwrap_or(property.clone());
+            assert_matches(field, &nested, schema);
+        }
+        for name in definition["required"].as_array().unwrap() {
+            assert!(object.contains_key(name.as_str().unwrap()), "{} is required", name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use crate::cert_builder::CertificateBuilder;
+    use crate::cert_builder::test_support::sign;
+    use crate::certificate::extensions::extended_key_usage_oid;
+    use crate::keys::storage;
+    use crate::metadata::test_support::assert_matches;
+
+    fn certificate() -> Certificate {
+        let builder = CertificateBuilder::new("CN=inventory.example.com,O=py_cert_store").unwrap()
+            .alt_name("DNS:inventory.example.com".parse().unwrap())
+            .extended_key_usage(extended_key_usage_oid("client_auth").unwrap())
+            .basic_constraints(false, None);
+        sign(builder, None).certificate
+    }
+
+    #[test]
+    fn describes_certificates() {
+        let cert = certificate();
+        let metadata = CertificateMetadata::new(&cert).unwrap();
+        assert_eq!(metadata.thumbprint, cert.thumbprint());
+        assert_eq!(metadata.subject.common_name.as_deref(), Some("inventory.example.com"));
+        assert_eq!(metadata.subject.rfc4514, "CN=inventory.example.com,O=py_cert_store");
+        assert_eq!(metadata.alt_names, vec!["DNS:inventory.example.com"]);
+        assert_eq!(metadata.extended_key_usage, vec!["client_auth"]);
+        assert_eq!(metadata.basic_constraints, Some(ConstraintsMetadata { ca: false, path_length: None }));
+        assert_eq!(metadata.signature_algorithm, "ecdsa-with-SHA256");
+        assert_eq!((metadata.key.algorithm.as_str(), metadata.key.curve.as_deref()), ("ECDSA", Some("P-256")));
+        assert!(!metadata.key.has_private_key);
+
+        // Key material is left out unless it was added.
+        let json = metadata.to_json().unwrap();
+        assert!(!json.contains("Pkcs12") && !json.contains("\"Certificate\""));
+        assert_eq!(CertificateMetadata::from_json(&json).unwrap(), metadata);
+
+        let metadata = metadata.private_key(Some(String::new())).friendly_name("client").store(StoreLocation::windows("CurrentUser", "My"));
+        assert!(metadata.key.has_private_key);
+        assert_eq!(metadata.key.provider, None);
+        assert_eq!((metadata.key.hardware_backed, metadata.key.exportable), (false, None));
+        let yaml = metadata.to_yaml().unwrap();
+        assert!(yaml.contains("FriendlyName: client"));
+        assert!(yaml.contains("Kind: windows"));
+    }
+
+    #[test]
+    fn describes_private_keys() {
+        let key = KeyMetadata::new(&certificate());
+        assert_eq!((key.algorithm.as_str(), key.size), ("ECDSA", Some(256)));
+
+        let smart_card = key.clone().with_storage(&KeyStorage {
+            provider: Some("Microsoft Smart Card Key Storage Provider".to_string()),
+            export_policy: Some(0),
+            ui_policy: None,
+            implementation: Some(storage::IMPL_HARDWARE | storage::IMPL_REMOVABLE),
+        });
+        assert!(smart_card.has_private_key && smart_card.hardware_backed);
+        assert_eq!((smart_card.exportable, smart_card.ui_protected), (Some(false), None));
+
+        let software = key.with_storage(&KeyStorage::software(None, true));
+        assert!(!software.hardware_backed);
+        assert_eq!((software.exportable, software.ui_protected), (Some(true), Some(false)));
+
+        // Snapshots written before the key policies were recorded still load.
+        let mut value = serde_json::to_value(CertificateMetadata::new(&certificate()).unwrap()).unwrap();
+        for field in ["HardwareBacked", "Exportable", "UiProtected"] {
+            value["Key"].as_object_mut().unwrap().remove(field);
+        }
+        let metadata = CertificateMetadata::from_json(&value.to_string()).unwrap();
+        assert_eq!((metadata.key.hardware_backed, metadata.key.exportable), (false, None));
+    }
+
+    #[test]
+    fn matches_the_schema() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        let cert = certificate();
+        let metadata = CertificateMetadata::new(&cert).unwrap()
+            .key_storage(&KeyStorage::software(Some("Microsoft Software Key Storage Provider".to_string()), false))
+            .store(StoreLocation::file("/etc/ssl/certs"))
+            .with_certificate(&cert).unwrap()
+            .with_pkcs12(b"pfx");
+        let inventory = CertificateInventory::new(vec![metadata.clone(), CertificateMetadata::new(&cert).unwrap()]);
+
+        let value: Value = serde_json::from_str(&inventory.to_json_pretty().unwrap()).unwrap();
+        assert_matches(&value, &schema["$defs"]["CertificateInventory"], &schema);
+        for certificate in value["Certificates"].as_array().unwrap() {
+            assert_matches(certificate, &schema["$defs"]["CertificateMetadata"], &schema);
+        }
+        assert_eq!(schema["$defs"]["CertificateMetadata"]["properties"]["SchemaVersion"]["const"], SCHEMA_VERSION);
+        assert_eq!(CertificateInventory::from_json(&inventory.to_json().unwrap()).unwrap(), inventory);
+    }
+
+    #[test]
+    fn rejects_newer_schema_versions() {
+        let mut metadata = CertificateMetadata::new(&certificate()).unwrap();
+        metadata.schema_version = SCHEMA_VERSION + 1;
+        let err = CertificateMetadata::from_json(&metadata.to_json().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
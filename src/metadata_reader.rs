@@ -0,0 +1,228 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::types::{PyBytes, PyDict, PyList, PyString};
+use serde::Serialize;
+
+use crate::certificate::Certificate;
+use crate::file_store::pkcs12_store::Pkcs12Store;
+use crate::inventory::{InventoryDiff, open_store, take_snapshot};
+use crate::metadata::{CertificateInventory, CertificateMetadata, SCHEMA, StoreLocation};
+use crate::store_iterator::StoreCertificate;
+
+
+fn to_value_error(err: impl ToString) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// Converts a serializable value to the matching Python dicts, lists and scalars.
+pub(crate) fn to_python(py: Python<'_>, value: &impl Serialize) -> PyResult<Py<PyAny>> {
+    let json = serde_json::to_string(value).map_err(to_value_error)?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// Serializes to compact JSON, or to JSON indented by `indent` spaces.
+pub(crate) fn json_string(value: &impl Serialize, indent: Option<usize>) -> PyResult<String> {
+    let Some(indent) = indent else {
+        return serde_json::to_string(value).map_err(to_value_error);
+    };
+
+    let indent = " ".repeat(indent);
+    let mut output = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes()));
+    value.serialize(&mut serializer).map_err(to_value_error)?;
+    String::from_utf8(output).map_err(to_value_error)
+}
+
+pub(crate) fn yaml_string(value: &impl Serialize) -> PyResult<String> {
+    serde_yaml::to_string(value).map_err(to_value_error)
+}
+
+/// Describes a `StoreCertificate`, a dictionary returned by the search functions, or an encoded certificate.
+fn metadata_from_py(certificate: &Bound<'_, PyAny>, include_certificate: bool, include_private_key: bool) -> PyResult<CertificateMetadata> {
+    if let Ok(stored) = certificate.cast::<StoreCertificate>() {
+        return stored.get().metadata_with(certificate.py(), include_certificate, include_private_key);
+    }
+
+    if let Ok(dict) = certificate.cast::<PyDict>() {
+        return search_result_metadata(dict, include_certificate, include_private_key);
+    }
+
+    let data: Vec<u8> = if let Ok(text) = certificate.cast::<PyString>() {
+        text.to_str()?.as_bytes().to_vec()
+    } else if let Ok(bytes) = certificate.cast::<PyBytes>() {
+        bytes.as_bytes().to_vec()
+    } else {
+        return Err(PyTypeError::new_err("Expected a StoreCertificate, a search result dictionary, or certificate bytes"));
+    };
+    let cert = Certificate::from_bytes(&data).map_err(to_value_error)?;
+    let metadata = CertificateMetadata::new(&cert).map_err(to_value_error)?;
+    match include_certificate {
+        true => metadata.with_certificate(&cert).map_err(to_value_error),
+        false => Ok(metadata),
+    }
+}
+
+/// Describes a dictionary returned by the `find_*` functions, from the PKCS#12 file under "cert".
+fn search_result_metadata(dict: &Bound<'_, PyDict>, include_certificate: bool, include_private_key: bool) -> PyResult<CertificateMetadata> {
+    let pfx: Vec<u8> = dict.get_item("cert")?
+        .ok_or_else(|| PyValueError::new_err("The dictionary has no \"cert\" entry"))?
+        .extract()?;
+    let store = Pkcs12Store::from_der(&pfx, "").map_err(to_value_error)?;
+    let cert = store.certs()
+        .find(|cert| matches!(store.private_key_for(cert), Ok(Some(_))))
+        .ok_or_else(|| PyValueError::new_err("The PKCS#12 file has no certificate with a private key"))?;
+
+    let text = |key: &str| -> PyResult<String> {
+        Ok(dict.get_item(key)?.map(|value| value.extract::<String>()).transpose()?.unwrap_or_default())
+    };
+    let mut metadata = CertificateMetadata::new(&cert).map_err(to_value_error)?
+        .friendly_name(&text("FriendlyName")?)
+        .private_key(Some(text("KeyProvider")?));
+
+    if let Some(location) = dict.get_item("StoreLocation")?.filter(|location| !location.is_none()) {
+        let json: String = dict.py().import("json")?.call_method1("dumps", (location,))?.extract()?;
+        let location: StoreLocation = serde_json::from_str(&json).map_err(to_value_error)?;
+        metadata = metadata.store(location);
+    }
+    if include_certificate {
+        metadata = metadata.with_certificate(&cert).map_err(to_value_error)?;
+    }
+    if include_private_key {
+        metadata = metadata.with_pkcs12(&pfx);
+    }
+    Ok(metadata)
+}
+
+/// A single certificate becomes a metadata object, a list of certificates an inventory.
+enum Document {
+    Certificate(Box<CertificateMetadata>),
+    Inventory(CertificateInventory),
+}
+
+impl Document {
+    fn from_py(certificates: &Bound<'_, PyAny>, include_certificate: bool, include_private_key: bool) -> PyResult<Document> {
+        if let Ok(list) = certificates.cast::<PyList>() {
+            let certificates = list.iter()
+                .map(|certificate| metadata_from_py(&certificate, include_certificate, include_private_key))
+                .collect::<PyResult<Vec<_>>>()?;
+            return Ok(Document::Inventory(CertificateInventory::new(certificates)));
+        }
+        let metadata = metadata_from_py(certificates, include_certificate, include_private_key)?;
+        Ok(Document::Certificate(Box::new(metadata)))
+    }
+}
+
+impl Serialize for Document {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Document::Certificate(metadata) => metadata.serialize(serializer),
+            Document::Inventory(inventory) => inventory.serialize(serializer),
+        }
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificate, include_certificate=false, include_private_key=false))]
+/// Describe a certificate as a dictionary following the schema returned by `metadata_schema()`.
+pub fn certificate_metadata(
+    py: Python<'_>, certificate: &Bound<'_, PyAny>, include_certificate: bool, include_private_key: bool
+) -> PyResult<Py<PyAny>> {
+    to_python(py, &metadata_from_py(certificate, include_certificate, include_private_key)?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificates, include_certificate=false, include_private_key=false, indent=None))]
+/// Serialize the metadata of a certificate, or an inventory of a list of certificates, as JSON.
+pub fn metadata_to_json(
+    certificates: &Bound<'_, PyAny>, include_certificate: bool, include_private_key: bool, indent: Option<usize>
+) -> PyResult<String> {
+    json_string(&Document::from_py(certificates, include_certificate, include_private_key)?, indent)
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificates, include_certificate=false, include_private_key=false))]
+/// Serialize the metadata of a certificate, or an inventory of a list of certificates, as YAML.
+pub fn metadata_to_yaml(certificates: &Bound<'_, PyAny>, include_certificate: bool, include_private_key: bool) -> PyResult<String> {
+    yaml_string(&Document::from_py(certificates, include_certificate, include_private_key)?)
+}
+
+#[pyfunction]
+/// The JSON Schema of the certificate metadata and inventories.
+pub fn metadata_schema() -> &'static str {
+    SCHEMA
+}
+
+#[pyfunction]
+#[pyo3(signature = (stores=None, paths=None, password="", host=None, nss_databases=None))]
+/// Record the metadata of every certificate in the given Windows stores, files and NSS databases, without
+/// exporting any key.
+pub fn snapshot_stores(
+    py: Python<'_>, stores: Option<Vec<(String, String)>>, paths: Option<Vec<String>>, password: &str, host: Option<String>,
+    nss_databases: Option<Vec<String>>
+) -> PyResult<Py<PyAny>> {
+    // The personal store of the current user is read by default on Windows, and nothing elsewhere.
+    let stores = match (stores, &paths, &nss_databases) {
+        (Some(stores), _, _) => stores,
+        (None, None, None) if cfg!(windows) => vec![("CurrentUser".to_string(), "My".to_string())],
+        (None, _, _) => Vec::new(),
+    };
+    let locations: Vec<StoreLocation> = stores.iter().map(|(user, store)| StoreLocation::windows(user, store))
+        .chain(paths.iter().flatten().map(|path| StoreLocation::file(path)))
+        .chain(nss_databases.iter().flatten().map(|directory| StoreLocation::nss(directory)))
+        .collect();
+    let host = match host {
+        Some(host) => host,
+        None => py.import("socket")?.call_method0("gethostname")?.extract()?,
+    };
+
+    let snapshot = py.detach(|| -> std::io::Result<CertificateInventory> {
+        let sources = locations.into_iter()
+            .map(|location| open_store(&location, password).map(|entries| (location, entries)))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        Ok(take_snapshot(sources, Some(&host)))
+    })?;
+    to_python(py, &snapshot)
+}
+
+/// Reads a snapshot from a dictionary returned by `snapshot_stores()` or its JSON.
+fn snapshot_from_py(snapshot: &Bound<'_, PyAny>) -> PyResult<CertificateInventory> {
+    let json: String = if let Ok(text) = snapshot.cast::<PyString>() {
+        text.to_str()?.to_string()
+    } else if let Ok(bytes) = snapshot.cast::<PyBytes>() {
+        String::from_utf8(bytes.as_bytes().to_vec()).map_err(to_value_error)?
+    } else if snapshot.cast::<PyDict>().is_ok() {
+        snapshot.py().import("json")?.call_method1("dumps", (snapshot,))?.extract()?
+    } else {
+        return Err(PyTypeError::new_err("Expected a snapshot dictionary or its JSON"));
+    };
+    CertificateInventory::from_json(&json).map_err(to_value_error)
+}
+
+#[pyfunction]
+#[pyo3(signature = (previous, current, expiring_days=30))]
+/// Report the certificates added, removed, renewed and about to expire between two snapshots.
+pub fn diff_snapshots(py: Python<'_>, previous: &Bound<'_, PyAny>, current: &Bound<'_, PyAny>, expiring_days: u32) -> PyResult<Py<PyAny>> {
+    let previous = snapshot_from_py(previous)?;
+    let current = snapshot_from_py(current)?;
+    let window = std::time::Duration::from_secs(u64::from(expiring_days) * 24 * 60 * 60);
+    let diff = InventoryDiff::between(&previous, &current, window).map_err(to_value_error)?;
+    to_python(py, &diff)
+}
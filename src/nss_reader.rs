@@ -0,0 +1,84 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use pyo3::prelude::*;
+use pyo3::IntoPyObjectExt;
+
+use crate::metadata_reader::to_python;
+use crate::nss_store::NssDatabase;
+use crate::store_iterator::{CancellationToken, exported_to_dict, search_memory_store, search_purpose, select_exported};
+
+
+/// Opens the given database, or the first one of the current user when no path is given.
+///
+/// Missing databases raise `FileNotFoundError`, and a wrong password `PermissionError`.
+fn open_database(path: Option<PathBuf>, password: Option<&str>) -> std::io::Result<NssDatabase> {
+    let directory = match path {
+        Some(path) => path,
+        None => NssDatabase::default_locations().into_iter().next()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "No NSS database was found in ~/.pki/nssdb or the Firefox profiles"))?,
+    };
+    NssDatabase::open(directory, password)
+}
+
+#[pyfunction]
+/// List the NSS databases of the current user: Chromium's `~/.pki/nssdb` and the Firefox profiles.
+pub fn list_nss_databases() -> Vec<String> {
+    NssDatabase::default_locations().iter().map(|directory| directory.to_string_lossy().into_owned()).collect()
+}
+
+#[pyfunction]
+#[pyo3(signature = (path=None, password=None))]
+/// Describe every certificate of an NSS database, with its nickname and trust flags.
+pub fn read_nss_database(py: Python<'_>, path: Option<PathBuf>, password: Option<&str>) -> PyResult<Vec<Py<PyAny>>> {
+    let metadata = py.detach(|| open_database(path, password)?.metadata())?;
+    metadata.iter().map(|metadata| to_python(py, metadata)).collect()
+}
+
+#[pyfunction]
+#[pyo3(signature = (path=None, password=None, host=None, selection=None, cancel=None, purpose=None))]
+/// Search an NSS database the way `find_windows_cert_all` searches a store.
+#[allow(clippy::too_many_arguments)]
+pub fn find_nss_certificates(
+    py: Python<'_>, path: Option<PathBuf>, password: Option<&str>, host: Option<&str>, selection: Option<Bound<'_, PyAny>>,
+    cancel: Option<PyRef<'_, CancellationToken>>, purpose: Option<&str>
+) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+    let cancel = CancellationToken::cancellation(cancel.as_deref());
+    let purpose = search_purpose(purpose)?;
+    let (database, exported) = py.detach(|| -> PyResult<_> {
+        let database = open_database(path, password)?;
+        let exported = search_memory_store(&database.to_memory_store(), host, purpose.as_ref(), &cancel)?;
+        Ok((database, exported))
+    })?;
+
+    let trust: HashMap<String, String> = database.certificates().iter()
+        .map(|entry| (entry.certificate.thumbprint(), entry.trust_flags()))
+        .collect();
+    let location = database.location();
+    select_exported(py, &exported, selection.as_ref())?.into_iter()
+        .map(|index| {
+            let mut dict = exported_to_dict(py, &exported[index], &location)?;
+            let flags = trust.get(&exported[index].certificate.thumbprint()).cloned().unwrap_or_default();
+            dict.insert("NssTrust".to_string(), flags.into_py_any(py)?);
+            Ok(dict)
+        })
+        .collect()
+}
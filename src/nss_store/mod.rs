@@ -0,0 +1,463 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+//! Read-only access to the NSS databases (`cert9.db` and `key4.db`) Firefox and Chromium keep on Linux.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{Result, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use der::Decode;
+use der::asn1::ObjectIdentifier;
+use rsa::BigUint;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+
+use crate::certificate::{Certificate, hex, invalid_data};
+use crate::keys::{OID_SECP256R1, OID_SECP384R1, OID_SECP521R1, PrivateKey};
+use crate::keys::storage::KeyStorage;
+use crate::metadata::{CertificateMetadata, StoreLocation};
+use crate::store::memory::MemoryStore;
+
+mod pbe;
+use pbe::DatabaseKey;
+
+/// The name NSS gives the token of its software database, reported as the key provider.
+pub const KEY_PROVIDER: &str = "NSS Certificate DB";
+
+const CKA_CLASS: u32 = 0x0;
+const CKA_LABEL: u32 = 0x3;
+const CKA_VALUE: u32 = 0x11;
+const CKA_KEY_TYPE: u32 = 0x100;
+const CKA_ID: u32 = 0x102;
+const CKA_MODULUS: u32 = 0x120;
+const CKA_PUBLIC_EXPONENT: u32 = 0x122;
+const CKA_PRIVATE_EXPONENT: u32 = 0x123;
+const CKA_PRIME_1: u32 = 0x124;
+const CKA_PRIME_2: u32 = 0x125;
+const CKA_EC_PARAMS: u32 = 0x180;
+const CKA_TRUST_SERVER_AUTH: u32 = 0xCE53_6358;
+const CKA_TRUST_CLIENT_AUTH: u32 = 0xCE53_6359;
+const CKA_TRUST_CODE_SIGNING: u32 = 0xCE53_635A;
+const CKA_TRUST_EMAIL_PROTECTION: u32 = 0xCE53_635B;
+const CKA_CERT_SHA1_HASH: u32 = 0xCE53_63B4;
+
+const CKO_CERTIFICATE: u32 = 0x1;
+const CKO_PRIVATE_KEY: u32 = 0x3;
+const CKO_NSS_TRUST: u32 = 0xCE53_4353;
+
+const CKK_RSA: u32 = 0x0;
+const CKK_EC: u32 = 0x3;
+const CKK_EC_EDWARDS: u32 = 0x40;
+
+/// How NSS stores attributes without a value, as SQL NULL means the attribute is not set.
+const NULL_VALUE: &[u8] = &[0xa5, 0x00, 0x5a];
+
+type Attributes = HashMap<u32, Vec<u8>>;
+
+/// The trust NSS places in a certificate for one purpose.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrustLevel {
+    /// Trusted as a peer certificate.
+    Trusted,
+    /// Trusted to issue certificates, as a root CA is.
+    TrustedDelegator,
+    /// A CA that is not trusted by itself.
+    ValidDelegator,
+    MustVerify,
+    /// Explicitly distrusted.
+    NotTrusted,
+    Unknown,
+}
+
+impl TrustLevel {
+    fn from_value(value: Option<u32>) -> TrustLevel {
+        match value {
+            Some(0xCE53_4351) => TrustLevel::Trusted,
+            Some(0xCE53_4352) => TrustLevel::TrustedDelegator,
+            Some(0xCE53_4353) => TrustLevel::MustVerify,
+            Some(0xCE53_435A) => TrustLevel::NotTrusted,
+            Some(0xCE53_435B) => TrustLevel::ValidDelegator,
+            _ => TrustLevel::Unknown,
+        }
+    }
+}
+
+/// The trust settings of a certificate, as `certutil -M -t` sets them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NssTrust {
+    pub server_auth: TrustLevel,
+    pub client_auth: TrustLevel,
+    pub email_protection: TrustLevel,
+    pub code_signing: TrustLevel,
+}
+
+impl NssTrust {
+    /// The trust flags in `certutil` notation, such as `CT,C,C` for a trusted root or `u,u,u` for a client
+    /// certificate, for the SSL, email and object signing purposes.
+    pub fn flags(trust: Option<&NssTrust>, has_private_key: bool) -> String {
+        let usage = |level: Option<TrustLevel>, client_ca: bool| {
+            let mut flags = String::new();
+            match level {
+                Some(TrustLevel::ValidDelegator) => flags.push('c'),
+                Some(TrustLevel::NotTrusted) => flags.push('p'),
+                Some(TrustLevel::TrustedDelegator) => flags.push('C'),
+                _ => {},
+            }
+            if client_ca {
+                flags.push('T');
+            }
+            if level == Some(TrustLevel::Trusted) {
+                flags.push('P');
+            }
+            if has_private_key {
+                flags.push('u');
+            }
+            flags
+        };
+
+        let client_ca = trust.is_some_and(|trust| trust.client_auth == TrustLevel::TrustedDelegator);
+        [
+            usage(trust.map(|trust| trust.server_auth), client_ca),
+            usage(trust.map(|trust| trust.email_protection), false),
+            usage(trust.map(|trust| trust.code_signing), false),
+        ].join(",")
+    }
+}
+
+/// A certificate of the database, with its private key when it could be decrypted.
+#[derive(Clone)]
+pub struct NssCertificate {
+    pub certificate: Certificate,
+    /// The NSS nickname, shown as the certificate name by Firefox.
+    pub nickname: String,
+    pub trust: Option<NssTrust>,
+    pub key: Option<PrivateKey>,
+}
+
+impl NssCertificate {
+    pub fn trust_flags(&self) -> String {
+        NssTrust::flags(self.trust.as_ref(), self.key.is_some())
+    }
+}
+
+impl fmt::Debug for NssCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NssCertificate({:?}, {})", self.nickname, self.trust_flags())
+    }
+}
+
+/// The contents of an NSS database directory, read once when opened.
+#[derive(Clone, Debug)]
+pub struct NssDatabase {
+    directory: PathBuf,
+    certificates: Vec<NssCertificate>,
+}
+
+impl NssDatabase {
+    /// Reads the certificates of `cert9.db` and, when the password is right, the private keys of `key4.db`.
+    ///
+    /// `directory` may carry the `sql:` prefix NSS tools use. Without a password the keys are only read when the
+    /// database has no password set; a wrong password fails with `PermissionDenied`. The databases are opened read
+    /// only, so they can be read while the browser is running.
+    pub fn open<P: AsRef<Path>>(directory: P, password: Option<&str>) -> Result<NssDatabase> {
+        let directory = directory.as_ref();
+        let directory = match directory.to_str().and_then(|text| text.strip_prefix("sql:")) {
+            Some(stripped) => PathBuf::from(stripped),
+            None => directory.to_path_buf(),
+        };
+        let cert_db = directory.join("cert9.db");
+        if !cert_db.is_file() {
+            return Err(Error::new(ErrorKind::NotFound, format!("No NSS certificate database (cert9.db) in {}", directory.display())));
+        }
+
+        let connection = open_sqlite(&cert_db)?;
+        let trust = read_trust(&connection)?;
+        let keys = match directory.join("key4.db") {
+            key_db if key_db.is_file() => read_keys(&open_sqlite(&key_db)?, password)?,
+            _ => Vec::new(),
+        };
+
+        let mut certificates = Vec::new();
+        for object in objects(&connection, "nssPublic", CKO_CERTIFICATE, &[CKA_VALUE, CKA_LABEL, CKA_ID])? {
+            let Some(Ok(certificate)) = object.get(&CKA_VALUE).map(|der| Certificate::from_der(der)) else {
+                continue;
+            };
+            let id = object.get(&CKA_ID);
+            // Keys share the CKA_ID of their certificate, but it is only a hint, so the key pair is still checked.
+            let key = keys.iter()
+                .filter(|(key_id, _)| Some(key_id) == id)
+                .chain(keys.iter())
+                .map(|(_, key)| key)
+                .find(|key| key.matches_certificate(&certificate).unwrap_or(false))
+                .cloned();
+
+            certificates.push(NssCertificate {
+                nickname: object.get(&CKA_LABEL).map(|label| String::from_utf8_lossy(label).into_owned()).unwrap_or_default(),
+                trust: trust.get(&certificate.thumbprint()).copied(),
+                certificate,
+                key,
+            });
+        }
+
+        Ok(NssDatabase { directory, certificates })
+    }
+
+    /// The NSS databases of the current user: Chromium's `~/.pki/nssdb` and the Firefox profiles, including
+    /// those of the Firefox snap.
+    pub fn default_locations() -> Vec<PathBuf> {
+        let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+            return Vec::new();
+        };
+
+        let mut candidates = vec![home.join(".pki").join("nssdb")];
+        for profiles in [home.join(".mozilla/firefox"), home.join("snap/firefox/common/.mozilla/firefox")] {
+            let Ok(entries) = fs::read_dir(&profiles) else {
+                continue;
+            };
+            let mut profiles: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+            profiles.sort();
+            candidates.extend(profiles);
+        }
+        candidates.into_iter().filter(|directory| directory.join("cert9.db").is_file()).collect()
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn certificates(&self) -> &[NssCertificate] {
+        &self.certificates
+    }
+
+    /// The location recorded for the certificates of this database.
+    pub fn location(&self) -> StoreLocation {
+        StoreLocation::nss(&self.directory.to_string_lossy())
+    }
+
+    /// Describes every certificate of the database, with its nickname and trust flags.
+    pub fn metadata(&self) -> Result<Vec<CertificateMetadata>> {
+        self.certificates.iter()
+            .map(|entry| {
+                let metadata = CertificateMetadata::new(&entry.certificate)?;
+                let metadata = match entry.key {
+                    Some(_) => metadata.key_storage(&KeyStorage::software(Some(KEY_PROVIDER.to_string()), true)),
+                    None => metadata,
+                };
+                Ok(metadata.friendly_name(&entry.nickname).store(self.location()).nss_trust(&entry.trust_flags()))
+            })
+            .collect()
+    }
+
+    /// Copies the certificates and keys into a store that can be searched and exported like the other stores.
+    pub fn to_memory_store(&self) -> MemoryStore {
+        let mut store = MemoryStore::new();
+        for entry in &self.certificates {
+            match &entry.key {
+                Some(key) => store.add_certificate_with_key(entry.certificate.clone(), key.clone(), &entry.nickname, true),
+                None => store.add_certificate(entry.certificate.clone(), &entry.nickname),
+            }
+        }
+        store.set_key_provider(KEY_PROVIDER);
+        store
+    }
+}
+
+fn open_sqlite(path: &Path) -> Result<Connection> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(|err| Error::other(format!("Could not open {}: {}", path.display(), err)))
+}
+
+fn sqlite_error(err: rusqlite::Error) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("Could not read the NSS database: {}", err))
+}
+
+/// NSS stores `CK_ULONG` attributes as 4 big endian bytes.
+fn ulong(value: &[u8]) -> Option<u32> {
+    Some(u32::from_be_bytes(value.try_into().ok()?))
+}
+
+/// Reads the given attributes of every object of a class. Attributes that are not set are left out.
+fn objects(connection: &Connection, table: &str, class: u32, attributes: &[u32]) -> Result<Vec<Attributes>> {
+    let columns: Vec<String> = attributes.iter().map(|attribute| format!("a{:x}", attribute)).collect();
+    let mut statement = connection
+        .prepare(&format!("SELECT {} FROM {} WHERE a{:x} = ?1", columns.join(", "), table, CKA_CLASS))
+        .map_err(sqlite_error)?;
+
+    let rows = statement.query_map([class.to_be_bytes().to_vec()], |row| {
+        let mut object = Attributes::new();
+        for (index, attribute) in attributes.iter().enumerate() {
+            if let Some(value) = row.get::<_, Option<Vec<u8>>>(index)? {
+                object.insert(*attribute, if value == NULL_VALUE { Vec::new() } else { value });
+            }
+        }
+        Ok(object)
+    }).map_err(sqlite_error)?;
+    rows.collect::<std::result::Result<_, _>>().map_err(sqlite_error)
+}
+
+/// The trust objects of the certificate database, by the thumbprint of their certificate.
+fn read_trust(connection: &Connection) -> Result<HashMap<String, NssTrust>> {
+    let attributes = [CKA_CERT_SHA1_HASH, CKA_TRUST_SERVER_AUTH, CKA_TRUST_CLIENT_AUTH, CKA_TRUST_EMAIL_PROTECTION, CKA_TRUST_CODE_SIGNING];
+    let mut trust = HashMap::new();
+    for object in objects(connection, "nssPublic", CKO_NSS_TRUST, &attributes)? {
+        let Some(hash) = object.get(&CKA_CERT_SHA1_HASH) else {
+            continue;
+        };
+        let level = |attribute| TrustLevel::from_value(object.get(&attribute).and_then(|value| ulong(value)));
+        trust.insert(hex(hash), NssTrust {
+            server_auth: level(CKA_TRUST_SERVER_AUTH),
+            client_auth: level(CKA_TRUST_CLIENT_AUTH),
+            email_protection: level(CKA_TRUST_EMAIL_PROTECTION),
+            code_signing: level(CKA_TRUST_CODE_SIGNING),
+        });
+    }
+    Ok(trust)
+}
+
+/// Decrypts the private keys of the key database, with their CKA_ID.
+///
+/// Keys of unsupported types are left out rather than failing the whole database.
+fn read_keys(connection: &Connection, password: Option<&str>) -> Result<Vec<(Vec<u8>, PrivateKey)>> {
+    let check: Option<(Vec<u8>, Vec<u8>)> = connection
+        .query_row("SELECT item1, item2 FROM metaData WHERE id = 'password'", [], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()
+        .map_err(sqlite_error)?;
+    let Some((global_salt, check)) = check else {
+        return Ok(Vec::new());
+    };
+
+    let key = match password {
+        Some(password) => DatabaseKey::new(&global_salt, &check, password)?,
+        // A database without a password is encrypted with an empty one.
+        None => match DatabaseKey::new(&global_salt, &check, "") {
+            Ok(key) => key,
+            Err(err) if err.kind() == ErrorKind::PermissionDenied => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        },
+    };
+
+    let attributes = [CKA_ID, CKA_KEY_TYPE, CKA_VALUE, CKA_EC_PARAMS, CKA_MODULUS, CKA_PUBLIC_EXPONENT, CKA_PRIVATE_EXPONENT, CKA_PRIME_1, CKA_PRIME_2];
+    Ok(objects(connection, "nssPrivate", CKO_PRIVATE_KEY, &attributes)?.into_iter()
+        .filter_map(|object| {
+            let private_key = private_key(&object, &key).ok()?;
+            Some((object.get(&CKA_ID).cloned().unwrap_or_default(), private_key))
+        })
+        .collect())
+}
+
+fn private_key(object: &Attributes, key: &DatabaseKey) -> Result<PrivateKey> {
+    let attribute = |id: u32| object.get(&id)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("The NSS key is missing attribute 0x{:x}", id)));
+    let decrypted = |id: u32| key.decrypt(attribute(id)?);
+
+    match ulong(attribute(CKA_KEY_TYPE)?) {
+        Some(CKK_RSA) => {
+            let key = rsa::RsaPrivateKey::from_components(
+                BigUint::from_bytes_be(attribute(CKA_MODULUS)?),
+                BigUint::from_bytes_be(attribute(CKA_PUBLIC_EXPONENT)?),
+                BigUint::from_bytes_be(&decrypted(CKA_PRIVATE_EXPONENT)?),
+                vec![BigUint::from_bytes_be(&decrypted(CKA_PRIME_1)?), BigUint::from_bytes_be(&decrypted(CKA_PRIME_2)?)],
+            ).map_err(invalid_data)?;
+            Ok(PrivateKey::Rsa(Box::new(key)))
+        },
+        Some(CKK_EC) => {
+            let scalar = decrypted(CKA_VALUE)?;
+            match ObjectIdentifier::from_der(attribute(CKA_EC_PARAMS)?).map_err(invalid_data)? {
+                OID_SECP256R1 => Ok(PrivateKey::EcP256(p256::SecretKey::from_slice(&scalar).map_err(invalid_data)?)),
+                OID_SECP384R1 => Ok(PrivateKey::EcP384(p384::SecretKey::from_slice(&scalar).map_err(invalid_data)?)),
+                OID_SECP521R1 => Ok(PrivateKey::EcP521(p521::SecretKey::from_slice(&scalar).map_err(invalid_data)?)),
+                curve => Err(Error::new(ErrorKind::Unsupported, format!("Unsupported elliptic curve {}", curve))),
+            }
+        },
+        Some(CKK_EC_EDWARDS) => {
+            let scalar: [u8; 32] = decrypted(CKA_VALUE)?.as_slice().try_into().map_err(invalid_data)?;
+            Ok(PrivateKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(&scalar)))
+        },
+        key_type => Err(Error::new(ErrorKind::Unsupported, format!("Unsupported NSS key type {:?}", key_type))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database written by NSS itself, with the password `pass1234`.
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("test").join("nss")
+    }
+
+    fn by_nickname<'a>(database: &'a NssDatabase, nickname: &str) -> &'a NssCertificate {
+        database.certificates().iter().find(|entry| entry.nickname == nickname).unwrap()
+    }
+
+    #[test]
+    fn reads_certificates_trust_and_keys() {
+        let database = NssDatabase::open(format!("sql:{}", fixture().display()), Some("pass1234")).unwrap();
+        assert_eq!(database.directory(), fixture());
+        assert_eq!(database.certificates().len(), 4);
+
+        let ca = by_nickname(&database, "NSS Test CA");
+        assert_eq!(ca.trust_flags(), "CT,C,C");
+        assert!(ca.key.is_none());
+        assert_eq!(by_nickname(&database, "blocked peer").trust_flags(), "p,p,p");
+
+        for nickname in ["rsa client", "ec client"] {
+            let client = by_nickname(&database, nickname);
+            assert_eq!(client.trust_flags(), "u,u,u");
+            assert!(client.key.as_ref().unwrap().matches_certificate(&client.certificate).unwrap());
+        }
+        assert!(matches!(by_nickname(&database, "rsa client").key, Some(PrivateKey::Rsa(_))));
+
+        let store = database.to_memory_store();
+        let with_keys: Vec<_> = store.entries().filter(|entry| entry.has_private_key()).collect();
+        assert_eq!(with_keys.len(), 2);
+        assert_eq!(with_keys[0].key_provider().as_deref(), Some(KEY_PROVIDER));
+        assert!(with_keys[0].export_pkcs12().is_ok());
+
+        let metadata = database.metadata().unwrap();
+        let ca = metadata.iter().find(|cert| cert.friendly_name.as_deref() == Some("NSS Test CA")).unwrap();
+        assert_eq!(ca.nss_trust.as_deref(), Some("CT,C,C"));
+        assert_eq!(ca.store, Some(StoreLocation::nss(&fixture().to_string_lossy())));
+        assert_eq!(metadata.iter().filter(|cert| cert.key.has_private_key).count(), 2);
+    }
+
+    #[test]
+    fn needs_the_password_for_keys() {
+        let database = NssDatabase::open(fixture(), None).unwrap();
+        assert_eq!(database.certificates().len(), 4);
+        assert!(database.certificates().iter().all(|entry| entry.key.is_none()));
+
+        let err = NssDatabase::open(fixture(), Some("wrong")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        let err = NssDatabase::open(fixture().join("missing"), None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn formats_trust_flags() {
+        let trust = NssTrust {
+            server_auth: TrustLevel::Trusted,
+            client_auth: TrustLevel::Unknown,
+            email_protection: TrustLevel::ValidDelegator,
+            code_signing: TrustLevel::MustVerify,
+        };
+        assert_eq!(NssTrust::flags(Some(&trust), false), "P,c,");
+        assert_eq!(NssTrust::flags(None, false), ",,");
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+//! The password based encryption of the sensitive attributes in `key4.db`.
+
+use std::io::{Result, Error, ErrorKind};
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+use cbc::cipher::block_padding::Pkcs7;
+use der::{Decode, Sequence};
+use der::asn1::{ObjectIdentifier, OctetString};
+use hmac::{Mac, SimpleHmac};
+use sha1::{Digest, Sha1};
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+use spki::AlgorithmIdentifierOwned;
+use zeroize::Zeroizing;
+
+use crate::certificate::invalid_data;
+
+const OID_PBES2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.13");
+const OID_PBKDF2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.12");
+const OID_HMAC_WITH_SHA1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.7");
+const OID_HMAC_WITH_SHA224: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.8");
+const OID_HMAC_WITH_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.9");
+const OID_HMAC_WITH_SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.10");
+const OID_HMAC_WITH_SHA512: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.2.11");
+const OID_AES_128_CBC: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.1.2");
+const OID_AES_256_CBC: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.1.42");
+/// Written by NSS versions before 3.49, with its own key derivation rather than the PKCS#12 one.
+const OID_PBE_SHA1_3DES: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.12.5.1.3");
+
+/// The plaintext of the `password` entry of the `metaData` table, encrypted with the database password.
+const PASSWORD_CHECK: &[u8] = b"password-check";
+
+/// An encrypted attribute value.
+#[derive(Clone, Debug, Sequence)]
+struct EncryptedValue {
+    algorithm: AlgorithmIdentifierOwned,
+    ciphertext: OctetString,
+}
+
+#[derive(Clone, Debug, Sequence)]
+struct Pbes2Params {
+    kdf: AlgorithmIdentifierOwned,
+    encryption: AlgorithmIdentifierOwned,
+}
+
+/// `PBKDF2-params` as defined in RFC 8018. NSS leaves out the NULL parameters of the PRF, which the `pkcs5`
+/// crate requires.
+#[derive(Clone, Debug, Sequence)]
+struct Pbkdf2Params {
+    salt: OctetString,
+    iteration_count: u32,
+    key_length: Option<u16>,
+    prf: Option<AlgorithmIdentifierOwned>,
+}
+
+#[derive(Clone, Debug, Sequence)]
+struct LegacyPbeParams {
+    salt: OctetString,
+    iterations: u32,
+}
+
+/// The key material derived from the database password, used to decrypt the private key attributes.
+pub(super) struct DatabaseKey {
+    /// SHA-1 of the global salt followed by the password, which NSS uses as the password of each attribute.
+    password: Zeroizing<[u8; 20]>,
+}
+
+impl DatabaseKey {
+    /// Derives the key, and checks it against the encrypted `password-check` value of the database.
+    ///
+    /// Fails with `PermissionDenied` when the password is wrong.
+    pub(super) fn new(global_salt: &[u8], check: &[u8], password: &str) -> Result<DatabaseKey> {
+        let mut digest = Sha1::new();
+        digest.update(global_salt);
+        digest.update(password.as_bytes());
+        let key = DatabaseKey { password: Zeroizing::new(digest.finalize().into()) };
+
+        let incorrect = || Error::new(ErrorKind::PermissionDenied, "Incorrect NSS database password");
+        match key.decrypt(check) {
+            Ok(plaintext) if plaintext.as_slice() == PASSWORD_CHECK => Ok(key),
+            Ok(_) => Err(incorrect()),
+            Err(err) if err.kind() == ErrorKind::PermissionDenied => Err(incorrect()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Decrypts an attribute value.
+    pub(super) fn decrypt(&self, encrypted: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        let value = EncryptedValue::from_der(encrypted).map_err(invalid_data)?;
+        let parameters = value.algorithm.parameters.as_ref()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The NSS encryption parameters are missing"))?;
+        let ciphertext = value.ciphertext.as_bytes();
+
+        match value.algorithm.oid {
+            OID_PBES2 => {
+                let params: Pbes2Params = parameters.decode_as().map_err(invalid_data)?;
+                self.decrypt_pbes2(&params, ciphertext)
+            },
+            OID_PBE_SHA1_3DES => {
+                let params: LegacyPbeParams = parameters.decode_as().map_err(invalid_data)?;
+                self.decrypt_legacy(params.salt.as_bytes(), ciphertext)
+            },
+            oid => Err(Error::new(ErrorKind::Unsupported, format!("Unsupported NSS encryption algorithm {}", oid))),
+        }
+    }
+
+    fn decrypt_pbes2(&self, params: &Pbes2Params, ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        if params.kdf.oid != OID_PBKDF2 {
+            return Err(Error::new(ErrorKind::Unsupported, format!("Unsupported NSS key derivation {}", params.kdf.oid)));
+        }
+        let kdf: Pbkdf2Params = params.kdf.parameters.as_ref()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The PBKDF2 parameters are missing"))?
+            .decode_as()
+            .map_err(invalid_data)?;
+
+        // NSS writes the AES IV without its first two bytes, which are always those of the OCTET STRING header.
+        let iv: OctetString = params.encryption.parameters.as_ref()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The AES IV is missing"))?
+            .decode_as()
+            .map_err(invalid_data)?;
+        let iv = match iv.as_bytes() {
+            short if short.len() == 14 => [&[0x04, 0x0e], short].concat(),
+            iv => iv.to_vec(),
+        };
+
+        let key_len = match params.encryption.oid {
+            OID_AES_128_CBC => 16,
+            OID_AES_256_CBC => 32,
+            oid => return Err(Error::new(ErrorKind::Unsupported, format!("Unsupported NSS cipher {}", oid))),
+        };
+        let mut key = Zeroizing::new(vec![0u8; key_len]);
+        let (password, salt, rounds) = (self.password.as_slice(), kdf.salt.as_bytes(), kdf.iteration_count);
+        match kdf.prf.map(|prf| prf.oid).unwrap_or(OID_HMAC_WITH_SHA1) {
+            OID_HMAC_WITH_SHA1 => pbkdf2::pbkdf2_hmac::<Sha1>(password, salt, rounds, &mut key),
+            OID_HMAC_WITH_SHA224 => pbkdf2::pbkdf2_hmac::<Sha224>(password, salt, rounds, &mut key),
+            OID_HMAC_WITH_SHA256 => pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, rounds, &mut key),
+            OID_HMAC_WITH_SHA384 => pbkdf2::pbkdf2_hmac::<Sha384>(password, salt, rounds, &mut key),
+            OID_HMAC_WITH_SHA512 => pbkdf2::pbkdf2_hmac::<Sha512>(password, salt, rounds, &mut key),
+            oid => return Err(Error::new(ErrorKind::Unsupported, format!("Unsupported PBKDF2 function {}", oid))),
+        }
+
+        let plaintext = match key_len {
+            16 => cbc::Decryptor::<aes::Aes128>::new_from_slices(&key, &iv)
+                .map_err(invalid_data)?
+                .decrypt_padded_vec_mut::<Pkcs7>(ciphertext),
+            _ => cbc::Decryptor::<aes::Aes256>::new_from_slices(&key, &iv)
+                .map_err(invalid_data)?
+                .decrypt_padded_vec_mut::<Pkcs7>(ciphertext),
+        };
+        Ok(Zeroizing::new(plaintext.map_err(decrypt_error)?))
+    }
+
+    /// The triple DES encryption of older databases, keyed the way NSS derives keys for `key3.db`.
+    fn decrypt_legacy(&self, entry_salt: &[u8], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        let hashed = Sha1::new().chain_update(self.password.as_slice()).chain_update(entry_salt).finalize();
+
+        let mut padded_salt = entry_salt.to_vec();
+        padded_salt.resize(padded_salt.len().max(20), 0);
+        let hmac = |parts: &[&[u8]]| -> Result<Vec<u8>> {
+            let mut mac = <SimpleHmac<Sha1> as Mac>::new_from_slice(&hashed).map_err(invalid_data)?;
+            parts.iter().for_each(|part| mac.update(part));
+            Ok(mac.finalize().into_bytes().to_vec())
+        };
+        let first = hmac(&[&padded_salt, entry_salt])?;
+        let inner = hmac(&[&padded_salt])?;
+        let second = hmac(&[&inner, entry_salt])?;
+        let derived = Zeroizing::new([first, second].concat());
+
+        cbc::Decryptor::<des::TdesEde3>::new_from_slices(&derived[..24], &derived[derived.len() - 8..])
+            .map_err(invalid_data)?
+            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+            .map(Zeroizing::new)
+            .map_err(decrypt_error)
+    }
+}
+
+/// Wrong passwords surface as padding errors.
+fn decrypt_error<E>(_: E) -> Error {
+    Error::new(ErrorKind::PermissionDenied, "Could not decrypt the NSS key database")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `password-check` encrypted with the triple DES scheme, with the global salt `py_cert_store legacy`
+    /// and the password `legacy`.
+    const LEGACY_CHECK: &str = "303C3028060B2A864886F70D010C05010330190414656E7472792073616C7420666F7220334445532102010104102780CA03AC40B1D5AE4F52AFB4EE7D31";
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|index| u8::from_str_radix(&hex[index..index + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn checks_legacy_passwords() {
+        let check = unhex(LEGACY_CHECK);
+        assert!(DatabaseKey::new(b"py_cert_store legacy", &check, "legacy").is_ok());
+        let err = DatabaseKey::new(b"py_cert_store legacy", &check, "wrong").err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+}
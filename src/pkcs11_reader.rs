@@ -0,0 +1,164 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use pyo3::prelude::*;
+use pyo3::IntoPyObjectExt;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::types::PyBytes;
+
+use crate::exceptions::CertNotFound;
+use crate::metadata_reader::to_python;
+use crate::pkcs11_store::{Pkcs11Module, Pkcs11Token as Token};
+use crate::store_iterator::CertificateIterator;
+
+
+/// Raises `ValueError` when the token to open is ambiguous; a wrong PIN raises `PermissionError` and a missing
+/// module or token `FileNotFoundError`.
+fn token_error(err: std::io::Error) -> PyErr {
+    match err.kind() {
+        ErrorKind::InvalidInput => PyValueError::new_err(err.to_string()),
+        _ => err.into(),
+    }
+}
+
+#[pyclass(name = "Pkcs11Token", module = "py_cert_store", frozen)]
+/// A logged in session with a PKCS#11 token, such as a smart card or HSM.
+///
+/// The private keys stay on the token: the certificates report them as not exportable, and `sign()` signs through
+/// the token instead. The session ends when `close()` is called or the `with` block ends.
+pub struct Pkcs11Token {
+    token: Mutex<Option<Arc<Token>>>,
+    #[pyo3(get)]
+    module: String,
+    #[pyo3(get)]
+    slot_id: u64,
+    #[pyo3(get)]
+    label: String,
+    #[pyo3(get)]
+    manufacturer: String,
+    #[pyo3(get)]
+    model: String,
+    #[pyo3(get)]
+    serial_number: String,
+}
+
+impl Pkcs11Token {
+    fn token(&self) -> PyResult<Arc<Token>> {
+        self.token.lock()
+            .map_err(|_| PyRuntimeError::new_err("The PKCS#11 token was poisoned by an earlier panic."))?
+            .clone()
+            .ok_or_else(|| PyRuntimeError::new_err("The PKCS#11 token has been closed."))
+    }
+}
+
+#[pymethods]
+impl Pkcs11Token {
+    /// Lazily enumerates the certificates of the token. Their private keys cannot be exported.
+    fn certificates(&self) -> PyResult<CertificateIterator> {
+        let token = self.token()?;
+        Ok(CertificateIterator::new(token.entries(), token.location()))
+    }
+
+    /// Describes every certificate of the token, following the schema returned by `metadata_schema()`.
+    fn metadata(&self, py: Python<'_>) -> PyResult<Vec<Py<PyAny>>> {
+        let metadata = self.token()?.metadata()?;
+        metadata.iter().map(|metadata| to_python(py, metadata)).collect()
+    }
+
+    /// Signs the data with the private key of the certificate with the given thumbprint, on the token.
+    ///
+    /// RSA keys sign with PKCS#1 v1.5 and SHA-256, ECDSA keys with the digest matching the curve (the signature is DER
+    /// encoded), and Ed25519 keys sign the data directly.
+    fn sign<'py>(&self, py: Python<'py>, thumbprint: &str, data: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+        let token = self.token()?;
+        let certificate = token.certificate(thumbprint)
+            .ok_or_else(|| CertNotFound::new_err(format!("No certificate of the token has the thumbprint {}", thumbprint)))?;
+        if !certificate.has_private_key() {
+            return Err(CertNotFound::new_err(format!("The token holds no private key for \"{}\"", certificate.label)));
+        }
+        let signature = py.detach(|| token.sign(certificate, data))?;
+        Ok(PyBytes::new(py, &signature))
+    }
+
+    /// Logs out and closes the session. Certificates already enumerated stay usable.
+    fn close(&self) -> PyResult<()> {
+        *self.token.lock().map_err(|_| PyRuntimeError::new_err("The PKCS#11 token was poisoned by an earlier panic."))? = None;
+        Ok(())
+    }
+
+    #[getter]
+    fn closed(&self) -> PyResult<bool> {
+        Ok(self.token.lock().map_err(|_| PyRuntimeError::new_err("The PKCS#11 token was poisoned by an earlier panic."))?.is_none())
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, _args: &Bound<'_, pyo3::types::PyTuple>) -> PyResult<bool> {
+        self.close()?;
+        Ok(false)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Pkcs11Token(label={:?}, slot_id={})", self.label, self.slot_id)
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (module, parameters=None))]
+/// List the initialized tokens of a PKCS#11 module.
+pub fn list_pkcs11_tokens(py: Python<'_>, module: PathBuf, parameters: Option<&str>) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+    let tokens = py.detach(|| Pkcs11Module::load(&module, parameters)?.tokens())?;
+    tokens.into_iter()
+        .map(|token| {
+            Ok(HashMap::from([
+                ("SlotId".to_string(), token.slot_id.into_py_any(py)?),
+                ("Label".to_string(), token.label.into_py_any(py)?),
+                ("Manufacturer".to_string(), token.manufacturer.into_py_any(py)?),
+                ("Model".to_string(), token.model.into_py_any(py)?),
+                ("SerialNumber".to_string(), token.serial_number.into_py_any(py)?),
+                ("LoginRequired".to_string(), token.login_required.into_py_any(py)?),
+            ]))
+        })
+        .collect()
+}
+
+#[pyfunction]
+#[pyo3(signature = (module, pin=None, slot=None, token_label=None, parameters=None))]
+/// Open a session with a token of a PKCS#11 module, logging in with the PIN when one is given.
+pub fn open_pkcs11_token(
+    py: Python<'_>, module: PathBuf, pin: Option<&str>, slot: Option<u64>, token_label: Option<&str>, parameters: Option<&str>
+) -> PyResult<Pkcs11Token> {
+    let token = py.detach(|| Token::open(Pkcs11Module::load(&module, parameters)?, slot, token_label, pin)).map_err(token_error)?;
+    let info = token.info().clone();
+    Ok(Pkcs11Token {
+        module: token.module().path().to_string_lossy().into_owned(),
+        token: Mutex::new(Some(Arc::new(token))),
+        slot_id: info.slot_id,
+        label: info.label,
+        manufacturer: info.manufacturer,
+        model: info.model,
+        serial_number: info.serial_number,
+    })
+}
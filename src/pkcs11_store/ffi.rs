@@ -0,0 +1,256 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![allow(non_camel_case_types)]
+
+//! The subset of the PKCS#11 v2.40 C interface used to read certificates and sign with a token.
+//!
+//! Windows modules are built with 1 byte packing, as the PKCS#11 headers require on that platform.
+
+use std::ffi::{c_uchar, c_ulong, c_void};
+
+pub type CK_ULONG = c_ulong;
+pub type CK_RV = CK_ULONG;
+pub type CK_FLAGS = CK_ULONG;
+pub type CK_SLOT_ID = CK_ULONG;
+pub type CK_SESSION_HANDLE = CK_ULONG;
+pub type CK_OBJECT_HANDLE = CK_ULONG;
+pub type CK_ATTRIBUTE_TYPE = CK_ULONG;
+pub type CK_MECHANISM_TYPE = CK_ULONG;
+pub type CK_USER_TYPE = CK_ULONG;
+pub type CK_BBOOL = c_uchar;
+
+pub const CK_TRUE: CK_BBOOL = 1;
+pub const CK_UNAVAILABLE_INFORMATION: CK_ULONG = !0;
+
+pub const CKR_OK: CK_RV = 0x0;
+pub const CKR_CANCEL: CK_RV = 0x1;
+pub const CKR_HOST_MEMORY: CK_RV = 0x2;
+pub const CKR_SLOT_ID_INVALID: CK_RV = 0x3;
+pub const CKR_GENERAL_ERROR: CK_RV = 0x5;
+pub const CKR_FUNCTION_FAILED: CK_RV = 0x6;
+pub const CKR_ARGUMENTS_BAD: CK_RV = 0x7;
+pub const CKR_ATTRIBUTE_SENSITIVE: CK_RV = 0x11;
+pub const CKR_ATTRIBUTE_TYPE_INVALID: CK_RV = 0x12;
+pub const CKR_DEVICE_ERROR: CK_RV = 0x30;
+pub const CKR_DEVICE_REMOVED: CK_RV = 0x32;
+pub const CKR_KEY_HANDLE_INVALID: CK_RV = 0x60;
+pub const CKR_KEY_TYPE_INCONSISTENT: CK_RV = 0x63;
+pub const CKR_KEY_FUNCTION_NOT_PERMITTED: CK_RV = 0x68;
+pub const CKR_MECHANISM_INVALID: CK_RV = 0x70;
+pub const CKR_OBJECT_HANDLE_INVALID: CK_RV = 0x82;
+pub const CKR_PIN_INCORRECT: CK_RV = 0xA0;
+pub const CKR_PIN_INVALID: CK_RV = 0xA1;
+pub const CKR_PIN_LEN_RANGE: CK_RV = 0xA2;
+pub const CKR_PIN_EXPIRED: CK_RV = 0xA3;
+pub const CKR_PIN_LOCKED: CK_RV = 0xA4;
+pub const CKR_SESSION_HANDLE_INVALID: CK_RV = 0xB3;
+pub const CKR_TOKEN_NOT_PRESENT: CK_RV = 0xE0;
+pub const CKR_TOKEN_NOT_RECOGNIZED: CK_RV = 0xE1;
+pub const CKR_USER_ALREADY_LOGGED_IN: CK_RV = 0x100;
+pub const CKR_USER_NOT_LOGGED_IN: CK_RV = 0x101;
+pub const CKR_USER_PIN_NOT_INITIALIZED: CK_RV = 0x102;
+pub const CKR_BUFFER_TOO_SMALL: CK_RV = 0x150;
+pub const CKR_CRYPTOKI_NOT_INITIALIZED: CK_RV = 0x190;
+pub const CKR_CRYPTOKI_ALREADY_INITIALIZED: CK_RV = 0x191;
+pub const CKR_VENDOR_DEFINED: CK_RV = 0x8000_0000;
+
+pub const CKF_OS_LOCKING_OK: CK_FLAGS = 0x2;
+pub const CKF_SERIAL_SESSION: CK_FLAGS = 0x4;
+pub const CKF_LOGIN_REQUIRED: CK_FLAGS = 0x4;
+pub const CKF_TOKEN_INITIALIZED: CK_FLAGS = 0x400;
+
+pub const CKU_USER: CK_USER_TYPE = 1;
+
+pub const CKA_CLASS: CK_ATTRIBUTE_TYPE = 0x0;
+pub const CKA_LABEL: CK_ATTRIBUTE_TYPE = 0x3;
+pub const CKA_VALUE: CK_ATTRIBUTE_TYPE = 0x11;
+pub const CKA_CERTIFICATE_TYPE: CK_ATTRIBUTE_TYPE = 0x80;
+pub const CKA_ID: CK_ATTRIBUTE_TYPE = 0x102;
+
+pub const CKO_CERTIFICATE: CK_ULONG = 0x1;
+pub const CKO_PRIVATE_KEY: CK_ULONG = 0x3;
+pub const CKC_X_509: CK_ULONG = 0x0;
+
+pub const CKM_RSA_PKCS: CK_MECHANISM_TYPE = 0x1;
+pub const CKM_ECDSA: CK_MECHANISM_TYPE = 0x1041;
+pub const CKM_EDDSA: CK_MECHANISM_TYPE = 0x1057;
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CK_VERSION {
+    pub major: c_uchar,
+    pub minor: c_uchar,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_C_INITIALIZE_ARGS {
+    pub create_mutex: *mut c_void,
+    pub destroy_mutex: *mut c_void,
+    pub lock_mutex: *mut c_void,
+    pub unlock_mutex: *mut c_void,
+    pub flags: CK_FLAGS,
+    /// Reserved by the standard. NSS reads its library parameters (a C string) from here.
+    pub reserved: *mut c_void,
+    /// The reserved field of the NSS layout, which follows its library parameters.
+    pub nss_reserved: *mut c_void,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+#[derive(Clone, Copy, Default)]
+pub struct CK_TOKEN_INFO {
+    pub label: [c_uchar; 32],
+    pub manufacturer_id: [c_uchar; 32],
+    pub model: [c_uchar; 16],
+    pub serial_number: [c_uchar; 16],
+    pub flags: CK_FLAGS,
+    pub max_session_count: CK_ULONG,
+    pub session_count: CK_ULONG,
+    pub max_rw_session_count: CK_ULONG,
+    pub rw_session_count: CK_ULONG,
+    pub max_pin_len: CK_ULONG,
+    pub min_pin_len: CK_ULONG,
+    pub total_public_memory: CK_ULONG,
+    pub free_public_memory: CK_ULONG,
+    pub total_private_memory: CK_ULONG,
+    pub free_private_memory: CK_ULONG,
+    pub hardware_version: CK_VERSION,
+    pub firmware_version: CK_VERSION,
+    pub utc_time: [c_uchar; 16],
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_ATTRIBUTE {
+    pub kind: CK_ATTRIBUTE_TYPE,
+    pub value: *mut c_void,
+    pub value_len: CK_ULONG,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_MECHANISM {
+    pub mechanism: CK_MECHANISM_TYPE,
+    pub parameter: *mut c_void,
+    pub parameter_len: CK_ULONG,
+}
+
+type Unused = Option<unsafe extern "C" fn()>;
+
+/// The start of `CK_FUNCTION_LIST`, up to `C_Sign`. The module owns the list, so the entries after it can be
+/// left out.
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_FUNCTION_LIST {
+    pub version: CK_VERSION,
+    pub initialize: Option<unsafe extern "C" fn(init_args: *mut c_void) -> CK_RV>,
+    pub finalize: Option<unsafe extern "C" fn(reserved: *mut c_void) -> CK_RV>,
+    pub get_info: Unused,
+    pub get_function_list: Unused,
+    pub get_slot_list: Option<unsafe extern "C" fn(token_present: CK_BBOOL, slots: *mut CK_SLOT_ID, count: *mut CK_ULONG) -> CK_RV>,
+    pub get_slot_info: Unused,
+    pub get_token_info: Option<unsafe extern "C" fn(slot: CK_SLOT_ID, info: *mut CK_TOKEN_INFO) -> CK_RV>,
+    pub get_mechanism_list: Unused,
+    pub get_mechanism_info: Unused,
+    pub init_token: Unused,
+    pub init_pin: Unused,
+    pub set_pin: Unused,
+    pub open_session: Option<unsafe extern "C" fn(
+        slot: CK_SLOT_ID, flags: CK_FLAGS, application: *mut c_void, notify: *mut c_void, session: *mut CK_SESSION_HANDLE
+    ) -> CK_RV>,
+    pub close_session: Option<unsafe extern "C" fn(session: CK_SESSION_HANDLE) -> CK_RV>,
+    pub close_all_sessions: Unused,
+    pub get_session_info: Unused,
+    pub get_operation_state: Unused,
+    pub set_operation_state: Unused,
+    pub login: Option<unsafe extern "C" fn(session: CK_SESSION_HANDLE, user_type: CK_USER_TYPE, pin: *const c_uchar, pin_len: CK_ULONG) -> CK_RV>,
+    pub logout: Option<unsafe extern "C" fn(session: CK_SESSION_HANDLE) -> CK_RV>,
+    pub create_object: Unused,
+    pub copy_object: Unused,
+    pub destroy_object: Unused,
+    pub get_object_size: Unused,
+    pub get_attribute_value: Option<unsafe extern "C" fn(
+        session: CK_SESSION_HANDLE, object: CK_OBJECT_HANDLE, template: *mut CK_ATTRIBUTE, count: CK_ULONG
+    ) -> CK_RV>,
+    pub set_attribute_value: Unused,
+    pub find_objects_init: Option<unsafe extern "C" fn(session: CK_SESSION_HANDLE, template: *mut CK_ATTRIBUTE, count: CK_ULONG) -> CK_RV>,
+    pub find_objects: Option<unsafe extern "C" fn(
+        session: CK_SESSION_HANDLE, objects: *mut CK_OBJECT_HANDLE, max_count: CK_ULONG, count: *mut CK_ULONG
+    ) -> CK_RV>,
+    pub find_objects_final: Option<unsafe extern "C" fn(session: CK_SESSION_HANDLE) -> CK_RV>,
+    pub encrypt_init: Unused,
+    pub encrypt: Unused,
+    pub encrypt_update: Unused,
+    pub encrypt_final: Unused,
+    pub decrypt_init: Unused,
+    pub decrypt: Unused,
+    pub decrypt_update: Unused,
+    pub decrypt_final: Unused,
+    pub digest_init: Unused,
+    pub digest: Unused,
+    pub digest_update: Unused,
+    pub digest_key: Unused,
+    pub digest_final: Unused,
+    pub sign_init: Option<unsafe extern "C" fn(session: CK_SESSION_HANDLE, mechanism: *mut CK_MECHANISM, key: CK_OBJECT_HANDLE) -> CK_RV>,
+    pub sign: Option<unsafe extern "C" fn(
+        session: CK_SESSION_HANDLE, data: *const c_uchar, data_len: CK_ULONG, signature: *mut c_uchar, signature_len: *mut CK_ULONG
+    ) -> CK_RV>,
+}
+
+pub type C_GetFunctionList = unsafe extern "C" fn(list: *mut *const CK_FUNCTION_LIST) -> CK_RV;
+
+/// The name of a return value, for error messages. Codes without a name here are shown in hex, e.g.
+/// `CKR_0x00000200`, unless they are in the vendor defined range.
+pub fn rv_name(rv: CK_RV) -> String {
+    let name = match rv {
+        CKR_CANCEL => "CKR_CANCEL",
+        CKR_HOST_MEMORY => "CKR_HOST_MEMORY",
+        CKR_SLOT_ID_INVALID => "CKR_SLOT_ID_INVALID",
+        CKR_GENERAL_ERROR => "CKR_GENERAL_ERROR",
+        CKR_FUNCTION_FAILED => "CKR_FUNCTION_FAILED",
+        CKR_ARGUMENTS_BAD => "CKR_ARGUMENTS_BAD",
+        CKR_ATTRIBUTE_SENSITIVE => "CKR_ATTRIBUTE_SENSITIVE",
+        CKR_ATTRIBUTE_TYPE_INVALID => "CKR_ATTRIBUTE_TYPE_INVALID",
+        CKR_DEVICE_ERROR => "CKR_DEVICE_ERROR",
+        CKR_DEVICE_REMOVED => "CKR_DEVICE_REMOVED",
+        CKR_KEY_HANDLE_INVALID => "CKR_KEY_HANDLE_INVALID",
+        CKR_KEY_TYPE_INCONSISTENT => "CKR_KEY_TYPE_INCONSISTENT",
+        CKR_KEY_FUNCTION_NOT_PERMITTED => "CKR_KEY_FUNCTION_NOT_PERMITTED",
+        CKR_MECHANISM_INVALID => "CKR_MECHANISM_INVALID",
+        CKR_OBJECT_HANDLE_INVALID => "CKR_OBJECT_HANDLE_INVALID",
+        CKR_PIN_INCORRECT => "CKR_PIN_INCORRECT",
+        CKR_PIN_INVALID => "CKR_PIN_INVALID",
+        CKR_PIN_LEN_RANGE => "CKR_PIN_LEN_RANGE",
+        CKR_PIN_EXPIRED => "CKR_PIN_EXPIRED",
+        CKR_PIN_LOCKED => "CKR_PIN_LOCKED",
+        CKR_SESSION_HANDLE_INVALID => "CKR_SESSION_HANDLE_INVALID",
+        CKR_TOKEN_NOT_PRESENT => "CKR_TOKEN_NOT_PRESENT",
+        CKR_TOKEN_NOT_RECOGNIZED => "CKR_TOKEN_NOT_RECOGNIZED",
+        CKR_USER_ALREADY_LOGGED_IN => "CKR_USER_ALREADY_LOGGED_IN",
+        CKR_USER_NOT_LOGGED_IN => "CKR_USER_NOT_LOGGED_IN",
+        CKR_USER_PIN_NOT_INITIALIZED => "CKR_USER_PIN_NOT_INITIALIZED",
+        CKR_BUFFER_TOO_SMALL => "CKR_BUFFER_TOO_SMALL",
+        CKR_CRYPTOKI_NOT_INITIALIZED => "CKR_CRYPTOKI_NOT_INITIALIZED",
+        CKR_CRYPTOKI_ALREADY_INITIALIZED => "CKR_CRYPTOKI_ALREADY_INITIALIZED",
+        _ if rv >= CKR_VENDOR_DEFINED => "CKR_VENDOR_DEFINED",
+        _ => return format!("CKR_0x{:08X}", rv),
+    };
+    name.to_string()
+}
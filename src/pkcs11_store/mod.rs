@@ -0,0 +1,725 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+//! Certificates and private keys held by PKCS#11 tokens, such as smart cards and HSMs.
+//!
+//! The private keys never leave the token: they cannot be exported, but [`Pkcs11Token::sign`] signs through it.
+
+use std::collections::HashMap;
+use std::ffi::{CString, c_void};
+use std::fs;
+use std::io::{Result, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::{Arc, Mutex, Weak};
+use libloading::Library;
+use rsa::Pkcs1v15Sign;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::certificate::{Certificate, invalid_data};
+use crate::keys::KeyAlgorithm;
+use crate::keys::storage::KeyStorage;
+use crate::metadata::{CertificateMetadata, StoreLocation};
+use crate::store::{StoreEntry, StoreIter};
+
+mod ffi;
+use ffi::*;
+
+/// The modules loaded by this process. A module can only be initialized once, so tokens opened through the same
+/// path share it, and it is finalized when the last of them is dropped.
+static MODULES: Mutex<Vec<Weak<Pkcs11Module>>> = Mutex::new(Vec::new());
+
+/// A loaded and initialized PKCS#11 module (a `.so` or `.dll`).
+pub struct Pkcs11Module {
+    path: PathBuf,
+    functions: *const CK_FUNCTION_LIST,
+    /// Whether this module initialized the library, and so has to finalize it.
+    finalize: bool,
+    // Dropped last, after the module is finalized.
+    _library: Library,
+}
+
+// The module is initialized with CKF_OS_LOCKING_OK, so it may be called from any thread. Sessions are not shared
+// between threads without a lock.
+unsafe impl Send for Pkcs11Module {}
+unsafe impl Sync for Pkcs11Module {}
+
+impl Drop for Pkcs11Module {
+    fn drop(&mut self) {
+        if let Some(finalize) = self.functions().finalize.filter(|_| self.finalize) {
+            unsafe {
+                finalize(ptr::null_mut());
+            }
+        }
+    }
+}
+
+impl Pkcs11Module {
+    /// Loads and initializes the module at `path`, or returns the instance this process already loaded.
+    ///
+    /// `parameters` is handed to `C_Initialize` in the reserved field, where the NSS softoken expects its
+    /// configuration (such as `configdir='sql:/path' flags=readOnly`). Other modules take no parameters.
+    pub fn load<P: AsRef<Path>>(path: P, parameters: Option<&str>) -> Result<Arc<Pkcs11Module>> {
+        let path = path.as_ref();
+        // Bare library names are left to the loader's search path.
+        let path = match path.components().count() {
+            1 => path.to_path_buf(),
+            _ => fs::canonicalize(path)
+                .map_err(|err| Error::new(err.kind(), format!("Could not load the PKCS#11 module {}: {}", path.display(), err)))?,
+        };
+
+        let mut modules = MODULES.lock().map_err(|_| Error::other("The PKCS#11 module list was poisoned by an earlier panic"))?;
+        modules.retain(|module| module.strong_count() > 0);
+        if let Some(module) = modules.iter().filter_map(Weak::upgrade).find(|module| module.path == path) {
+            return Ok(module);
+        }
+
+        let library = unsafe { Library::new(&path) }
+            .map_err(|err| Error::new(ErrorKind::NotFound, format!("Could not load the PKCS#11 module {}: {}", path.display(), err)))?;
+        let mut functions: *const CK_FUNCTION_LIST = ptr::null();
+        unsafe {
+            let get_function_list = library.get::<C_GetFunctionList>(b"C_GetFunctionList\0")
+                .map_err(|err| Error::new(ErrorKind::InvalidData, format!("{} is not a PKCS#11 module: {}", path.display(), err)))?;
+            check(get_function_list(&mut functions), "C_GetFunctionList")?;
+        }
+        if functions.is_null() {
+            return Err(Error::new(ErrorKind::InvalidData, format!("{} returned no PKCS#11 function list", path.display())));
+        }
+
+        let mut module = Pkcs11Module { path, functions, finalize: false, _library: library };
+        let parameters = parameters.map(CString::new).transpose().map_err(invalid_data)?;
+        let mut args = CK_C_INITIALIZE_ARGS {
+            create_mutex: ptr::null_mut(),
+            destroy_mutex: ptr::null_mut(),
+            lock_mutex: ptr::null_mut(),
+            unlock_mutex: ptr::null_mut(),
+            flags: CKF_OS_LOCKING_OK,
+            reserved: parameters.as_ref().map_or(ptr::null_mut(), |parameters| parameters.as_ptr() as *mut c_void),
+            nss_reserved: ptr::null_mut(),
+        };
+        let initialize = module.function(module.functions().initialize, "C_Initialize")?;
+        match unsafe { initialize(&mut args as *mut CK_C_INITIALIZE_ARGS as *mut c_void) } {
+            // Initialized by another library of this process, which stays in charge of finalizing it.
+            CKR_CRYPTOKI_ALREADY_INITIALIZED => {},
+            rv => {
+                check(rv, "C_Initialize")?;
+                module.finalize = true;
+            },
+        }
+
+        let module = Arc::new(module);
+        modules.push(Arc::downgrade(&module));
+        Ok(module)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn functions(&self) -> &CK_FUNCTION_LIST {
+        // Checked to be non-null when loaded, and owned by the library kept alive with the module.
+        unsafe { &*self.functions }
+    }
+
+    fn function<F>(&self, function: Option<F>, name: &str) -> Result<F> {
+        function.ok_or_else(|| Error::new(ErrorKind::Unsupported, format!("The PKCS#11 module {} does not implement {}", self.path.display(), name)))
+    }
+
+    /// The initialized tokens in the slots of the module. Empty slots and blank tokens are left out.
+    pub fn tokens(&self) -> Result<Vec<TokenInfo>> {
+        let get_slot_list = self.function(self.functions().get_slot_list, "C_GetSlotList")?;
+        let get_token_info = self.function(self.functions().get_token_info, "C_GetTokenInfo")?;
+
+        let mut count: CK_ULONG = 0;
+        check(unsafe { get_slot_list(CK_TRUE, ptr::null_mut(), &mut count) }, "C_GetSlotList")?;
+        let mut slots: Vec<CK_SLOT_ID> = vec![0; count as usize];
+        check(unsafe { get_slot_list(CK_TRUE, slots.as_mut_ptr(), &mut count) }, "C_GetSlotList")?;
+        slots.truncate(count as usize);
+
+        let mut tokens = Vec::new();
+        for slot in slots {
+            let mut info = CK_TOKEN_INFO::default();
+            check(unsafe { get_token_info(slot, &mut info) }, "C_GetTokenInfo")?;
+            let flags = info.flags;
+            if flags & CKF_TOKEN_INITIALIZED == 0 {
+                continue;
+            }
+            tokens.push(TokenInfo {
+                slot,
+                slot_id: to_u64(slot),
+                label: padded_string(&info.label),
+                manufacturer: padded_string(&info.manufacturer_id),
+                model: padded_string(&info.model),
+                serial_number: padded_string(&info.serial_number),
+                login_required: flags & CKF_LOGIN_REQUIRED != 0,
+            });
+        }
+        Ok(tokens)
+    }
+}
+
+/// A token present in a slot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenInfo {
+    slot: CK_SLOT_ID,
+    pub slot_id: u64,
+    pub label: String,
+    pub manufacturer: String,
+    pub model: String,
+    pub serial_number: String,
+    /// Whether the private keys are only visible after logging in with the PIN.
+    pub login_required: bool,
+}
+
+/// A certificate of a token, with the handle of the private key sharing its `CKA_ID`.
+#[derive(Clone, Debug)]
+pub struct TokenCertificate {
+    pub certificate: Certificate,
+    /// The `CKA_LABEL` of the certificate object.
+    pub label: String,
+    /// The `CKA_ID` shared by the certificate and its key.
+    pub id: Vec<u8>,
+    key: Option<CK_OBJECT_HANDLE>,
+}
+
+impl TokenCertificate {
+    pub fn has_private_key(&self) -> bool {
+        self.key.is_some()
+    }
+}
+
+/// A session with a token, logged in when a PIN was given. The certificates are read once when it is opened.
+pub struct Pkcs11Token {
+    module: Arc<Pkcs11Module>,
+    info: TokenInfo,
+    session: Mutex<CK_SESSION_HANDLE>,
+    certificates: Vec<TokenCertificate>,
+}
+
+impl Drop for Pkcs11Token {
+    fn drop(&mut self) {
+        // Closing the last session of the token also logs out.
+        if let (Some(close_session), Ok(session)) = (self.module.functions().close_session, self.session.lock()) {
+            unsafe {
+                close_session(*session);
+            }
+        }
+    }
+}
+
+impl Pkcs11Token {
+    /// Opens a session with the token in `slot`, or labelled `label`, and logs in when a PIN is given.
+    ///
+    /// Without either, the module must have a single token; otherwise this fails with `InvalidInput`. A wrong or
+    /// locked PIN fails with `PermissionDenied`. Without a PIN, the certificates are listed but the private keys of
+    /// tokens requiring a login stay hidden, unless another session of this process is logged in: PKCS#11 logins are
+    /// shared by all the sessions of an application.
+    pub fn open(module: Arc<Pkcs11Module>, slot: Option<u64>, label: Option<&str>, pin: Option<&str>) -> Result<Pkcs11Token> {
+        let tokens = module.tokens()?;
+        let mut matching: Vec<TokenInfo> = tokens.iter()
+            .filter(|token| slot.is_none_or(|slot| token.slot_id == slot) && label.is_none_or(|label| token.label == label))
+            .cloned()
+            .collect();
+        let info = match matching.len() {
+            0 => return Err(Error::new(ErrorKind::NotFound, format!("No PKCS#11 token in {} matches the slot and label", module.path.display()))),
+            1 => matching.remove(0),
+            _ => {
+                let labels: Vec<String> = tokens.iter().map(|token| format!("{} (slot {})", token.label, token.slot_id)).collect();
+                return Err(Error::new(ErrorKind::InvalidInput, format!("Several PKCS#11 tokens are present, choose one of: {}", labels.join(", "))));
+            },
+        };
+
+        let open_session = module.function(module.functions().open_session, "C_OpenSession")?;
+        let mut session: CK_SESSION_HANDLE = 0;
+        check(unsafe {
+            open_session(info.slot, CKF_SERIAL_SESSION, ptr::null_mut(), ptr::null_mut(), &mut session)
+        }, "C_OpenSession")?;
+        let mut token = Pkcs11Token { module, info, session: Mutex::new(session), certificates: Vec::new() };
+
+        if let Some(pin) = pin {
+            let login = token.module.function(token.module.functions().login, "C_Login")?;
+            match unsafe { login(session, CKU_USER, pin.as_ptr(), pin.len() as CK_ULONG) } {
+                // Another session of this process already logged in.
+                CKR_USER_ALREADY_LOGGED_IN => {},
+                rv => check(rv, "C_Login")?,
+            }
+        }
+
+        token.certificates = token.read_certificates()?;
+        Ok(token)
+    }
+
+    pub fn info(&self) -> &TokenInfo {
+        &self.info
+    }
+
+    pub fn module(&self) -> &Pkcs11Module {
+        &self.module
+    }
+
+    pub fn certificates(&self) -> &[TokenCertificate] {
+        &self.certificates
+    }
+
+    /// The certificate with the given SHA-1 thumbprint.
+    pub fn certificate(&self, thumbprint: &str) -> Option<&TokenCertificate> {
+        self.certificates.iter().find(|entry| entry.certificate.thumbprint().eq_ignore_ascii_case(thumbprint))
+    }
+
+    /// The location recorded for the certificates of this token.
+    pub fn location(&self) -> StoreLocation {
+        StoreLocation::pkcs11(&self.module.path.to_string_lossy(), &self.info.label)
+    }
+
+    /// Describes every certificate of the token. The token label is reported as the key provider.
+    pub fn metadata(&self) -> Result<Vec<CertificateMetadata>> {
+        self.certificates.iter()
+            .map(|entry| {
+                let metadata = CertificateMetadata::new(&entry.certificate)?;
+                let metadata = match entry.key {
+                    Some(_) => metadata.key_storage(&KeyStorage::token(Some(self.info.label.clone()))),
+                    None => metadata,
+                };
+                Ok(metadata.friendly_name(&entry.label).store(self.location()))
+            })
+            .collect()
+    }
+
+    /// Enumerates the certificates like the other stores. Their keys cannot be exported.
+    pub fn entries(&self) -> StoreIter {
+        let provider = self.info.label.clone();
+        Box::new(self.certificates.clone().into_iter().map(move |entry| {
+            Box::new(TokenEntry { entry, provider: provider.clone() }) as Box<dyn StoreEntry>
+        }))
+    }
+
+    /// Signs the message with the private key of the certificate, the way [`crate::keys::PrivateKey::sign`] does:
+    /// PKCS#1 v1.5 with SHA-256 for RSA, DER encoded ECDSA with the digest matching the curve, and Ed25519.
+    ///
+    /// Fails with `NotFound` when the token holds no key for the certificate.
+    pub fn sign(&self, certificate: &TokenCertificate, msg: &[u8]) -> Result<Vec<u8>> {
+        let key = certificate.key.ok_or_else(|| {
+            Error::new(ErrorKind::NotFound, format!("The token holds no private key for \"{}\"", certificate.label))
+        })?;
+        let algorithm = KeyAlgorithm::from_public_key_info(&certificate.certificate.as_x509().tbs_certificate.subject_public_key_info)?;
+
+        let (mechanism, data) = match algorithm {
+            KeyAlgorithm::Rsa(_) => {
+                // CKM_RSA_PKCS only pads, so the DigestInfo is built here; CKM_SHA256_RSA_PKCS is not on every card.
+                let mut data = Pkcs1v15Sign::new::<Sha256>().prefix.to_vec();
+                data.extend_from_slice(&Sha256::digest(msg));
+                (CKM_RSA_PKCS, data)
+            },
+            KeyAlgorithm::EcP256 => (CKM_ECDSA, Sha256::digest(msg).to_vec()),
+            KeyAlgorithm::EcP384 => (CKM_ECDSA, Sha384::digest(msg).to_vec()),
+            KeyAlgorithm::EcP521 => (CKM_ECDSA, Sha512::digest(msg).to_vec()),
+            KeyAlgorithm::Ed25519 => (CKM_EDDSA, msg.to_vec()),
+        };
+        let signature = self.sign_raw(key, mechanism, &data)?;
+
+        // Tokens return ECDSA signatures as r || s.
+        match algorithm {
+            KeyAlgorithm::EcP256 => Ok(p256::ecdsa::Signature::from_slice(&signature).map_err(invalid_data)?.to_der().as_bytes().to_vec()),
+            KeyAlgorithm::EcP384 => Ok(p384::ecdsa::Signature::from_slice(&signature).map_err(invalid_data)?.to_der().as_bytes().to_vec()),
+            KeyAlgorithm::EcP521 => Ok(p521::ecdsa::Signature::from_slice(&signature).map_err(invalid_data)?.to_der().as_bytes().to_vec()),
+            _ => Ok(signature),
+        }
+    }
+
+    fn sign_raw(&self, key: CK_OBJECT_HANDLE, mechanism: CK_MECHANISM_TYPE, data: &[u8]) -> Result<Vec<u8>> {
+        let sign_init = self.module.function(self.module.functions().sign_init, "C_SignInit")?;
+        let sign = self.module.function(self.module.functions().sign, "C_Sign")?;
+        let session = self.lock_session()?;
+
+        let mut mechanism = CK_MECHANISM { mechanism, parameter: ptr::null_mut(), parameter_len: 0 };
+        check(unsafe { sign_init(*session, &mut mechanism, key) }, "C_SignInit")?;
+        // The first call only asks for the length, and leaves the operation active.
+        let mut length: CK_ULONG = 0;
+        check(unsafe { sign(*session, data.as_ptr(), data.len() as CK_ULONG, ptr::null_mut(), &mut length) }, "C_Sign")?;
+        let mut signature = vec![0u8; length as usize];
+        check(unsafe { sign(*session, data.as_ptr(), data.len() as CK_ULONG, signature.as_mut_ptr(), &mut length) }, "C_Sign")?;
+        signature.truncate(length as usize);
+        Ok(signature)
+    }
+
+    fn lock_session(&self) -> Result<std::sync::MutexGuard<'_, CK_SESSION_HANDLE>> {
+        self.session.lock().map_err(|_| Error::other("The PKCS#11 session was poisoned by an earlier panic"))
+    }
+
+    /// Reads the X.509 certificates and pairs them with the private keys sharing their `CKA_ID`.
+    fn read_certificates(&self) -> Result<Vec<TokenCertificate>> {
+        let keys: Vec<(Vec<u8>, CK_OBJECT_HANDLE)> = self.find_objects(&[(CKA_CLASS, CKO_PRIVATE_KEY)])?.into_iter()
+            .map(|handle| {
+                let mut attributes = self.attributes(handle, &[CKA_ID])?;
+                Ok((attributes.remove(&CKA_ID).unwrap_or_default(), handle))
+            })
+            .collect::<Result<_>>()?;
+
+        let mut certificates = Vec::new();
+        for handle in self.find_objects(&[(CKA_CLASS, CKO_CERTIFICATE), (CKA_CERTIFICATE_TYPE, CKC_X_509)])? {
+            let mut attributes = self.attributes(handle, &[CKA_VALUE, CKA_LABEL, CKA_ID])?;
+            let Some(Ok(certificate)) = attributes.get(&CKA_VALUE).map(|der| Certificate::from_der(der)) else {
+                continue;
+            };
+            let id = attributes.remove(&CKA_ID).unwrap_or_default();
+            let key = keys.iter().find(|(key_id, _)| !id.is_empty() && *key_id == id).map(|(_, handle)| *handle);
+            certificates.push(TokenCertificate {
+                certificate,
+                label: attributes.get(&CKA_LABEL).map(|label| String::from_utf8_lossy(label).into_owned()).unwrap_or_default(),
+                id,
+                key,
+            });
+        }
+        Ok(certificates)
+    }
+
+    /// Finds the objects whose `CK_ULONG` attributes have the given values.
+    fn find_objects(&self, template: &[(CK_ATTRIBUTE_TYPE, CK_ULONG)]) -> Result<Vec<CK_OBJECT_HANDLE>> {
+        let functions = self.module.functions();
+        let find_objects_init = self.module.function(functions.find_objects_init, "C_FindObjectsInit")?;
+        let find_objects = self.module.function(functions.find_objects, "C_FindObjects")?;
+        let find_objects_final = self.module.function(functions.find_objects_final, "C_FindObjectsFinal")?;
+        let session = self.lock_session()?;
+
+        let mut values: Vec<CK_ULONG> = template.iter().map(|(_, value)| *value).collect();
+        let mut template: Vec<CK_ATTRIBUTE> = template.iter().zip(values.iter_mut())
+            .map(|((kind, _), value)| CK_ATTRIBUTE {
+                kind: *kind,
+                value: value as *mut CK_ULONG as *mut c_void,
+                value_len: size_of::<CK_ULONG>() as CK_ULONG,
+            })
+            .collect();
+        check(unsafe { find_objects_init(*session, template.as_mut_ptr(), template.len() as CK_ULONG) }, "C_FindObjectsInit")?;
+
+        let mut handles = Vec::new();
+        let result = loop {
+            let mut batch: [CK_OBJECT_HANDLE; 32] = [0; 32];
+            let mut count: CK_ULONG = 0;
+            if let Err(err) = check(unsafe { find_objects(*session, batch.as_mut_ptr(), batch.len() as CK_ULONG, &mut count) }, "C_FindObjects") {
+                break Err(err);
+            }
+            if count == 0 {
+                break Ok(());
+            }
+            handles.extend_from_slice(&batch[..(count as usize).min(batch.len())]);
+        };
+        let finished = check(unsafe { find_objects_final(*session) }, "C_FindObjectsFinal");
+        result.and(finished).map(|_| handles)
+    }
+
+    /// Reads attributes of an object. Attributes the object does not have, or keeps secret, are left out.
+    fn attributes(&self, object: CK_OBJECT_HANDLE, kinds: &[CK_ATTRIBUTE_TYPE]) -> Result<HashMap<CK_ATTRIBUTE_TYPE, Vec<u8>>> {
+        let get_attribute_value = self.module.function(self.module.functions().get_attribute_value, "C_GetAttributeValue")?;
+        let session = self.lock_session()?;
+        let call = |template: &mut [CK_ATTRIBUTE]| -> Result<()> {
+            match unsafe { get_attribute_value(*session, object, template.as_mut_ptr(), template.len() as CK_ULONG) } {
+                // The attributes that could not be read are marked as unavailable, and the others are still read.
+                CKR_ATTRIBUTE_SENSITIVE | CKR_ATTRIBUTE_TYPE_INVALID => Ok(()),
+                rv => check(rv, "C_GetAttributeValue"),
+            }
+        };
+
+        let mut template: Vec<CK_ATTRIBUTE> = kinds.iter()
+            .map(|kind| CK_ATTRIBUTE { kind: *kind, value: ptr::null_mut(), value_len: 0 })
+            .collect();
+        call(&mut template)?;
+
+        let mut values: Vec<(CK_ATTRIBUTE_TYPE, Vec<u8>)> = template.iter()
+            .filter(|attribute| attribute.value_len != CK_UNAVAILABLE_INFORMATION)
+            .map(|attribute| (attribute.kind, vec![0u8; attribute.value_len as usize]))
+            .collect();
+        let mut template: Vec<CK_ATTRIBUTE> = values.iter_mut()
+            .map(|(kind, value)| CK_ATTRIBUTE { kind: *kind, value: value.as_mut_ptr() as *mut c_void, value_len: value.len() as CK_ULONG })
+            .collect();
+        call(&mut template)?;
+
+        let lengths: Vec<CK_ULONG> = template.iter().map(|attribute| attribute.value_len).collect();
+        Ok(values.into_iter().zip(lengths)
+            .filter(|(_, length)| *length != CK_UNAVAILABLE_INFORMATION)
+            .map(|((kind, mut value), length)| {
+                value.truncate(length as usize);
+                (kind, value)
+            })
+            .collect())
+    }
+}
+
+/// A token certificate enumerated as a store entry. The key stays on the token, so it is never exportable.
+struct TokenEntry {
+    entry: TokenCertificate,
+    provider: String,
+}
+
+impl StoreEntry for TokenEntry {
+    fn der(&self) -> &[u8] {
+        self.entry.certificate.as_der()
+    }
+
+    fn friendly_name(&self) -> String {
+        self.entry.label.clone()
+    }
+
+    fn has_private_key(&self) -> bool {
+        self.entry.has_private_key()
+    }
+
+    fn key_provider(&self) -> Option<String> {
+        self.entry.has_private_key().then(|| self.provider.clone())
+    }
+
+    fn key_storage(&self) -> Result<Option<KeyStorage>> {
+        Ok(self.entry.has_private_key().then(|| KeyStorage::token(Some(self.provider.clone()))))
+    }
+
+    fn export_pkcs12(&self) -> Result<Vec<u8>> {
+        match self.entry.key {
+            Some(_) => Err(Error::new(ErrorKind::PermissionDenied, format!("The private key of \"{}\" is held by the PKCS#11 token and cannot be exported", self.entry.label))),
+            None => Err(Error::new(ErrorKind::NotFound, "The certificate has no private key")),
+        }
+    }
+}
+
+/// Maps a PKCS#11 return value to an error: a wrong, malformed or locked PIN to `PermissionDenied`, a missing slot or token
+/// to `NotFound`, and an unsupported mechanism or key type to `Unsupported`.
+fn check(rv: CK_RV, function: &str) -> Result<()> {
+    let kind = match rv {
+        CKR_OK => return Ok(()),
+        CKR_KEY_FUNCTION_NOT_PERMITTED | CKR_PIN_INCORRECT | CKR_PIN_INVALID | CKR_PIN_LEN_RANGE | CKR_PIN_EXPIRED
+            | CKR_PIN_LOCKED | CKR_USER_NOT_LOGGED_IN | CKR_USER_PIN_NOT_INITIALIZED => ErrorKind::PermissionDenied,
+        CKR_SLOT_ID_INVALID | CKR_DEVICE_REMOVED | CKR_TOKEN_NOT_PRESENT | CKR_TOKEN_NOT_RECOGNIZED => ErrorKind::NotFound,
+        CKR_KEY_TYPE_INCONSISTENT | CKR_MECHANISM_INVALID => ErrorKind::Unsupported,
+        _ => ErrorKind::Other,
+    };
+    Err(Error::new(kind, format!("{} failed with {} (0x{:x})", function, rv_name(rv), rv)))
+}
+
+/// `CK_ULONG` is 32 bits wide on Windows and 64 bits wide elsewhere.
+#[allow(clippy::useless_conversion)]
+fn to_u64(value: CK_ULONG) -> u64 {
+    u64::from(value)
+}
+
+/// Token strings are padded with spaces to a fixed length.
+fn padded_string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).trim_end_matches([' ', '\0']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use std::sync::OnceLock;
+    use der::asn1::Any;
+    use spki::AlgorithmIdentifierOwned;
+    use crate::keys::{OID_ECDSA_WITH_SHA256, OID_SHA256_WITH_RSA};
+    use crate::keys::verify::verify_signature;
+    use crate::cert_builder::CertificateBuilder;
+    use crate::cert_builder::test_support::sign;
+
+    const SOFTOKEN_PATHS: [&str; 4] = [
+        "/usr/lib/x86_64-linux-gnu/libsoftokn3.so",
+        "/usr/lib/aarch64-linux-gnu/libsoftokn3.so",
+        "/usr/lib64/libsoftokn3.so",
+        "/usr/lib/libsoftokn3.so",
+    ];
+
+    const SOFTHSM_PATHS: [&str; 4] = [
+        "/usr/lib/softhsm/libsofthsm2.so",
+        "/usr/lib/x86_64-linux-gnu/softhsm/libsofthsm2.so",
+        "/usr/lib64/pkcs11/libsofthsm2.so",
+        "/usr/local/lib/softhsm/libsofthsm2.so",
+    ];
+
+    /// Set when the PKCS#11 tests must run, such as in CI, so a missing or broken NSS softoken or SoftHSMv2 fails
+    /// them instead of skipping them.
+    const REQUIRE_SOFTOKEN: &str = "PY_CERT_STORE_REQUIRE_SOFTOKEN";
+
+    /// The module a group of tests runs against, or `None` when it is not installed and the tests are skipped.
+    fn required<T>(module: &str, loaded: Result<T>) -> Option<T> {
+        match loaded {
+            Ok(loaded) => Some(loaded),
+            Err(err) if std::env::var_os(REQUIRE_SOFTOKEN).is_some() => panic!("{} is set, but {}: {}", REQUIRE_SOFTOKEN, module, err),
+            Err(err) => {
+                eprintln!("{}: {}, skipping its PKCS#11 tests (set {} to fail instead)", module, err, REQUIRE_SOFTOKEN);
+                None
+            },
+        }
+    }
+
+    /// The NSS softoken is itself a PKCS#11 module: pointed at a copy of `test/nss`, it serves that database as a
+    /// token with the PIN "pass1234". It is kept loaded for the whole test run, since it can only be initialized once.
+    fn softoken() -> Option<Arc<Pkcs11Module>> {
+        static SOFTOKEN: OnceLock<Option<Arc<Pkcs11Module>>> = OnceLock::new();
+        SOFTOKEN.get_or_init(|| required("NSS softoken", load_softoken())).clone()
+    }
+
+    fn load_softoken() -> Result<Arc<Pkcs11Module>> {
+        let module = SOFTOKEN_PATHS.iter().map(Path::new).find(|path| path.is_file())
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "The NSS softoken is not installed"))?;
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("test").join("nss");
+        let directory = std::env::temp_dir().join(format!("py_cert_store-pkcs11-{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        for name in ["cert9.db", "key4.db"] {
+            fs::copy(fixture.join(name), directory.join(name))?;
+        }
+        let parameters = format!("configdir='sql:{}' certPrefix='' keyPrefix='' secmod='secmod.db' flags=readOnly", directory.display());
+        Pkcs11Module::load(module, Some(&parameters))
+    }
+
+    /// A SoftHSMv2 token labelled "py_cert_store" with the PIN "1234", holding the returned "ec client" certificate
+    /// and its key. Tokens are set up with `softhsm2-util` and OpenSC's `pkcs11-tool`, as test/pkcs11.py does.
+    fn softhsm() -> Option<(Arc<Pkcs11Module>, Certificate)> {
+        static SOFTHSM: OnceLock<Option<(Arc<Pkcs11Module>, Certificate)>> = OnceLock::new();
+        SOFTHSM.get_or_init(|| required("SoftHSMv2", load_softhsm())).clone()
+    }
+
+    fn load_softhsm() -> Result<(Arc<Pkcs11Module>, Certificate)> {
+        let module = SOFTHSM_PATHS.iter().map(Path::new).find(|path| path.is_file())
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "SoftHSMv2 is not installed"))?;
+        let directory = std::env::temp_dir().join(format!("py_cert_store-softhsm-{}", std::process::id()));
+        fs::create_dir_all(directory.join("tokens"))?;
+        let config = directory.join("softhsm2.conf");
+        fs::write(&config, format!("directories.tokendir = {}\nobjectstore.backend = file\n", directory.join("tokens").display()))?;
+        // Both the tools and the loaded module only read their configuration from the environment.
+        std::env::set_var("SOFTHSM2_CONF", &config);
+
+        run(Command::new("softhsm2-util").args(["--init-token", "--free", "--label", "py_cert_store", "--pin", "1234", "--so-pin", "5678"]))?;
+        let issued = sign(CertificateBuilder::new("CN=ec client")?.valid_for_days(30), None);
+        let (key, certificate) = (directory.join("client.key"), directory.join("client.der"));
+        fs::write(&key, issued.key.to_pkcs8_pem()?.as_bytes())?;
+        fs::write(&certificate, issued.certificate.as_der())?;
+        run(Command::new("softhsm2-util").arg("--import").arg(&key)
+            .args(["--token", "py_cert_store", "--label", "ec client", "--id", "01", "--pin", "1234"]))?;
+        run(Command::new("pkcs11-tool").arg("--module").arg(module)
+            .args(["--login", "--pin", "1234", "--token-label", "py_cert_store", "--type", "cert", "--id", "01", "--label", "ec client"])
+            .arg("--write-object").arg(&certificate))?;
+
+        Ok((Pkcs11Module::load(module, None)?, issued.certificate))
+    }
+
+    fn run(command: &mut Command) -> Result<()> {
+        let output = command.output()?;
+        match output.status.success() {
+            true => Ok(()),
+            false => Err(Error::other(format!("{:?} failed: {}", command.get_program(), String::from_utf8_lossy(&output.stderr).trim()))),
+        }
+    }
+
+    /// Logins are shared by all the sessions of a process, so the tests take turns.
+    fn take_turn() -> std::sync::MutexGuard<'static, ()> {
+        static TURN: Mutex<()> = Mutex::new(());
+        TURN.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn open(pin: Option<&str>) -> Option<Pkcs11Token> {
+        let module = softoken()?;
+        Some(Pkcs11Token::open(module, None, Some("NSS Certificate DB"), pin).expect("a session with the softoken"))
+    }
+
+    #[test]
+    fn maps_return_values() {
+        assert!(check(CKR_OK, "C_Login").is_ok());
+        for rv in [CKR_PIN_INCORRECT, CKR_PIN_INVALID, CKR_PIN_LEN_RANGE, CKR_PIN_EXPIRED, CKR_PIN_LOCKED] {
+            assert_eq!(check(rv, "C_Login").map_err(|err| err.kind()).err(), Some(ErrorKind::PermissionDenied), "{}", rv_name(rv));
+        }
+        assert_eq!(check(CKR_TOKEN_NOT_PRESENT, "C_OpenSession").map_err(|err| err.kind()).err(), Some(ErrorKind::NotFound));
+
+        assert_eq!(rv_name(CKR_USER_ALREADY_LOGGED_IN), "CKR_USER_ALREADY_LOGGED_IN");
+        assert_eq!(rv_name(0x10), "CKR_0x00000010");
+        assert_eq!(rv_name(CKR_VENDOR_DEFINED + 0x42), "CKR_VENDOR_DEFINED");
+    }
+
+    #[test]
+    fn pairs_certificates_with_keys() {
+        let _turn = take_turn();
+        let Some(token) = open(Some("pass1234")) else {
+            return;
+        };
+
+        let mut labels: Vec<(&str, bool)> = token.certificates().iter()
+            .map(|entry| (entry.label.as_str(), entry.has_private_key()))
+            .collect();
+        labels.sort();
+        assert_eq!(labels, vec![("NSS Test CA", false), ("blocked peer", false), ("ec client", true), ("rsa client", true)]);
+
+        let location = token.location();
+        assert_eq!(location.store.as_deref(), Some("NSS Certificate DB"));
+        let entries: Vec<Box<dyn StoreEntry>> = token.entries().collect();
+        let with_key = entries.iter().find(|entry| entry.has_private_key()).expect("a certificate with a key");
+        assert_eq!(with_key.key_provider().as_deref(), Some("NSS Certificate DB"));
+        assert_eq!(with_key.export_pkcs12().map_err(|err| err.kind()).err(), Some(ErrorKind::PermissionDenied));
+    }
+
+    #[test]
+    fn signs_through_the_token() {
+        let _turn = take_turn();
+        let Some(token) = open(Some("pass1234")) else {
+            return;
+        };
+
+        for (label, oid, parameters) in [("rsa client", OID_SHA256_WITH_RSA, Some(Any::null())), ("ec client", OID_ECDSA_WITH_SHA256, None)] {
+            let entry = token.certificates().iter().find(|entry| entry.label == label).expect("a fixture certificate");
+            let signature = token.sign(entry, b"signed on the token").expect("a signature");
+            let algorithm = AlgorithmIdentifierOwned { oid, parameters };
+            let public_key = &entry.certificate.as_x509().tbs_certificate.subject_public_key_info;
+            assert!(verify_signature(public_key, &algorithm, b"signed on the token", &signature).expect("a supported algorithm"), "{}", label);
+        }
+
+        let ca = token.certificates().iter().find(|entry| !entry.has_private_key()).expect("a certificate without a key");
+        assert_eq!(token.sign(ca, b"data").map_err(|err| err.kind()).err(), Some(ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn needs_the_pin_for_keys() {
+        let _turn = take_turn();
+        let Some(module) = softoken() else {
+            return;
+        };
+
+        let without_pin = Pkcs11Token::open(module.clone(), None, Some("NSS Certificate DB"), None).expect("a session");
+        assert_eq!(without_pin.certificates().len(), 4);
+        assert!(without_pin.certificates().iter().all(|entry| !entry.has_private_key()));
+
+        let wrong_pin = Pkcs11Token::open(module.clone(), None, Some("NSS Certificate DB"), Some("wrong"));
+        assert_eq!(wrong_pin.map_err(|err| err.kind()).err(), Some(ErrorKind::PermissionDenied));
+        let ambiguous = Pkcs11Token::open(module, None, None, None);
+        assert_eq!(ambiguous.map_err(|err| err.kind()).err(), Some(ErrorKind::InvalidInput));
+    }
+
+    #[test]
+    fn signs_with_softhsm() {
+        let Some((module, certificate)) = softhsm() else {
+            return;
+        };
+
+        let wrong_pin = Pkcs11Token::open(module.clone(), None, Some("py_cert_store"), Some("4321"));
+        assert_eq!(wrong_pin.map_err(|err| err.kind()).err(), Some(ErrorKind::PermissionDenied));
+
+        let token = Pkcs11Token::open(module, None, Some("py_cert_store"), Some("1234")).expect("a session with SoftHSMv2");
+        let [entry] = token.certificates() else {
+            panic!("expected one certificate, found {}", token.certificates().len());
+        };
+        assert_eq!((entry.label.as_str(), entry.has_private_key()), ("ec client", true));
+        assert_eq!(entry.certificate, certificate);
+
+        let signature = token.sign(entry, b"signed by SoftHSMv2").expect("a signature");
+        let algorithm = AlgorithmIdentifierOwned { oid: OID_ECDSA_WITH_SHA256, parameters: None };
+        let public_key = &certificate.as_x509().tbs_certificate.subject_public_key_info;
+        assert!(verify_signature(public_key, &algorithm, b"signed by SoftHSMv2", &signature).expect("a supported algorithm"));
+    }
+}
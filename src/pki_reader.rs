@@ -0,0 +1,575 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::collections::HashMap;
+use std::time::SystemTime;
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::types::{PyBytes, PyDict};
+use pyo3::IntoPyObjectExt;
+
+use crate::cert_builder::CertificateBuilder;
+use crate::certificate::Certificate;
+use crate::certificate::extensions::{AltName, extended_key_usage_name, extended_key_usage_oid, key_usage_from_names, key_usage_names};
+use crate::certificate::purpose::{EnhancedKeyUsage, purpose_oid};
+use crate::csr::{CertificateRequest, CsrBuilder};
+use crate::file_store::pkcs12_store::Pkcs12Store;
+use crate::keys::{KeyAlgorithm, KeyFormat, PrivateKey, public_key_pem};
+use crate::keys::openssh::{SshCertificate, authorized_key};
+use crate::keys::verify::{HashAlgorithm, KeyMismatch, RsaPadding, check_key_pair, verify_data, verify_issued_by};
+use crate::exceptions::CertAmbiguous;
+use crate::metadata_reader::to_python;
+use crate::ranking::{Candidate, RankingCriterion, RankingPolicy};
+use crate::selection::{AmbiguousSelection, SelectionStrategy};
+
+
+#[pyfunction]
+#[pyo3(signature = (subject, key=None, key_type="rsa2048", alt_names=None, key_usage=None, extended_key_usage=None))]
+/// Generate a PKCS#10 certificate signing request, returning the PEM request and the PEM private key.
+pub fn generate_csr(
+    subject:&str, key:Option<&str>, key_type:&str,
+    alt_names:Option<Vec<String>>, key_usage:Option<Vec<String>>, extended_key_usage:Option<Vec<String>>
+) -> PyResult<(String, String)> {
+    let key = match key {
+        Some(pem) => PrivateKey::from_pem(pem, None),
+        None => key_type.parse::<KeyAlgorithm>().and_then(PrivateKey::generate),
+    }.map_err(|err| PyValueError::new_err(format!("Could not load the private key: {}", err)))?;
+
+    let mut builder = CsrBuilder::new(subject).map_err(to_value_error)?;
+
+    for name in alt_names.unwrap_or_default() {
+        builder = builder.alt_name(name.parse::<AltName>().map_err(to_value_error)?);
+    }
+
+    if let Some(key_usage) = key_usage {
+        builder = builder.key_usage(key_usage_from_names(&key_usage).map_err(to_value_error)?);
+    }
+
+    for purpose in extended_key_usage.unwrap_or_default() {
+        builder = builder.extended_key_usage(extended_key_usage_oid(&purpose).map_err(to_value_error)?);
+    }
+
+    let request = builder.build(&key).map_err(to_value_error)?;
+    let request_pem = request.to_pem().map_err(to_value_error)?;
+    let key_pem = key.to_pkcs8_pem().map_err(to_value_error)?;
+
+    Ok((request_pem, key_pem.to_string()))
+}
+
+#[pyfunction]
+#[pyo3(signature = (data))]
+/// Parse a PKCS#10 certificate signing request (DER or PEM) into a dictionary for review.
+pub fn parse_csr(py: Python<'_>, data: &[u8]) -> PyResult<HashMap<String, Py<PyAny>>> {
+    let request = CertificateRequest::from_bytes(data).map_err(to_value_error)?;
+    let extensions = request.requested_extensions().map_err(to_value_error)?;
+
+    let mut dict = HashMap::new();
+
+    dict.insert("Subject".to_string(), request.subject().into_py_any(py)?);
+
+    let key_algorithm = request.key_algorithm()
+        .map(|algorithm| algorithm.to_string())
+        .unwrap_or_else(|_| request.public_key_info().algorithm.oid.to_string());
+    dict.insert("KeyAlgorithm".to_string(), key_algorithm.into_py_any(py)?);
+
+    let alt_names: Vec<String> = extensions.alt_names.iter().map(AltName::to_string).collect();
+    dict.insert("AltNames".to_string(), alt_names.into_py_any(py)?);
+
+    let key_usage = extensions.key_usage.as_ref().map(key_usage_names).unwrap_or_default();
+    dict.insert("KeyUsage".to_string(), key_usage.into_py_any(py)?);
+
+    let extended_key_usage: Vec<String> = extensions.extended_key_usage.iter().map(extended_key_usage_name).collect();
+    dict.insert("ExtendedKeyUsage".to_string(), extended_key_usage.into_py_any(py)?);
+
+    dict.insert("SignatureAlgorithm".to_string(), request.signature_algorithm().oid.to_string().into_py_any(py)?);
+
+    let signature_valid = request.verify_signature().unwrap_or(false);
+    dict.insert("SignatureValid".to_string(), signature_valid.into_py_any(py)?);
+
+    Ok(dict)
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    subject, key=None, key_type="rsa2048", issuer_cert=None, issuer_key=None, valid_days=365, alt_names=None,
+    key_usage=None, extended_key_usage=None, is_ca=false, path_length=None, friendly_name=None, password=""
+))]
+/// Generate a self-signed certificate, or one issued by `issuer_cert`/`issuer_key`, packaged as a PKCS#12 file.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_certificate(
+    py: Python<'_>, subject:&str, key:Option<&str>, key_type:&str, issuer_cert:Option<&[u8]>, issuer_key:Option<&str>,
+    valid_days:u32, alt_names:Option<Vec<String>>, key_usage:Option<Vec<String>>, extended_key_usage:Option<Vec<String>>,
+    is_ca:bool, path_length:Option<u8>, friendly_name:Option<&str>, password:&str
+) -> PyResult<HashMap<String, Py<PyAny>>> {
+    let key = match key {
+        Some(pem) => PrivateKey::from_pem(pem, None),
+        None => key_type.parse::<KeyAlgorithm>().and_then(PrivateKey::generate),
+    }.map_err(|err| PyValueError::new_err(format!("Could not load the private key: {}", err)))?;
+
+    let mut builder = CertificateBuilder::new(subject).map_err(to_value_error)?.valid_for_days(valid_days);
+
+    for name in alt_names.unwrap_or_default() {
+        builder = builder.alt_name(name.parse::<AltName>().map_err(to_value_error)?);
+    }
+
+    if let Some(key_usage) = key_usage {
+        builder = builder.key_usage(key_usage_from_names(&key_usage).map_err(to_value_error)?);
+    }
+
+    for purpose in extended_key_usage.unwrap_or_default() {
+        builder = builder.extended_key_usage(extended_key_usage_oid(&purpose).map_err(to_value_error)?);
+    }
+
+    if is_ca || path_length.is_some() {
+        builder = builder.basic_constraints(is_ca, path_length);
+    }
+
+    if let Some(friendly_name) = friendly_name {
+        builder = builder.friendly_name(friendly_name);
+    }
+
+    let (issued, chain) = match (issuer_cert, issuer_key) {
+        (Some(issuer_cert), Some(issuer_key)) => {
+            let issuer_cert = Certificate::from_bytes(issuer_cert).map_err(to_value_error)?;
+            let issuer_key = PrivateKey::from_pem(issuer_key, None)
+                .map_err(|err| PyValueError::new_err(format!("Could not load the issuer private key: {}", err)))?;
+            (builder.issued_by(&key, &issuer_cert, &issuer_key).map_err(to_value_error)?, vec![issuer_cert])
+        },
+        (None, None) => (builder.self_signed(&key).map_err(to_value_error)?, Vec::new()),
+        _ => return Err(PyValueError::new_err("issuer_cert and issuer_key must be given together")),
+    };
+
+    let mut dict = HashMap::new();
+
+    dict.insert("FriendlyName".to_string(), friendly_name.unwrap_or_default().into_py_any(py)?);
+    dict.insert("Certificate".to_string(), issued.certificate.to_pem().map_err(to_value_error)?.into_py_any(py)?);
+    dict.insert("PrivateKey".to_string(), issued.key.to_pkcs8_pem().map_err(to_value_error)?.to_string().into_py_any(py)?);
+
+    let pfx = issued.to_pkcs12(password, &chain).map_err(to_value_error)?;
+    dict.insert("cert".to_string(), PyBytes::new(py, &pfx).into_py_any(py)?);
+
+    Ok(dict)
+}
+
+#[pyfunction]
+#[pyo3(signature = (key_type="rsa2048", format="pkcs8", password=None))]
+/// Generate a new private key, returned as PEM. A password encrypts the key as PKCS#8 with PBES2, or the OpenSSH
+/// way for the "openssh" format.
+pub fn generate_private_key(key_type:&str, format:&str, password:Option<&str>) -> PyResult<String> {
+    let key = key_type.parse::<KeyAlgorithm>().and_then(PrivateKey::generate).map_err(to_value_error)?;
+    encode_private_key(&key, format, password)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, password=None, format="pkcs8", pem=true, new_password=None))]
+/// Convert a private key between PKCS#8, PKCS#1, SEC1 and OpenSSH, PEM and DER, and encrypted and unencrypted forms.
+pub fn convert_private_key<'py>(
+    py: Python<'py>, data:&[u8], password:Option<&str>, format:&str, pem:bool, new_password:Option<&str>
+) -> PyResult<Bound<'py, PyBytes>> {
+    let key = load_private_key(data, password)?;
+
+    let output = match (pem, new_password) {
+        // OpenSSH keys only have a text form.
+        _ if is_openssh(format) => encode_private_key(&key, format, new_password)?.into_bytes(),
+        (true, _) => encode_private_key(&key, format, new_password)?.into_bytes(),
+        (false, Some(new_password)) => {
+            check_encryptable(format)?;
+            key.to_encrypted_pkcs8_der(new_password).map_err(to_value_error)?
+        },
+        (false, None) => {
+            let format = format.parse::<KeyFormat>().map_err(to_value_error)?;
+            key.to_der(format).map_err(to_value_error)?.to_vec()
+        },
+    };
+
+    Ok(PyBytes::new(py, &output))
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, password=None))]
+/// Extract the PEM public key (SubjectPublicKeyInfo) from a private key or a certificate.
+pub fn get_public_key(data:&[u8], password:Option<&str>) -> PyResult<String> {
+    let public_key = match Certificate::from_bytes(data) {
+        Ok(cert) => cert.as_x509().tbs_certificate.subject_public_key_info.clone(),
+        Err(_) => load_private_key(data, password)?.public_key_info().map_err(to_value_error)?,
+    };
+    public_key_pem(&public_key).map_err(to_value_error)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, password=None, comment=None))]
+/// Format the public key of a certificate or private key as an OpenSSH `authorized_keys` line.
+pub fn get_ssh_public_key(data:&[u8], password:Option<&str>, comment:Option<&str>) -> PyResult<String> {
+    let public_key = match Certificate::from_bytes(data) {
+        Ok(cert) => cert.as_x509().tbs_certificate.subject_public_key_info.clone(),
+        Err(_) => load_private_key(data, password)?.public_key_info().map_err(to_value_error)?,
+    };
+    authorized_key(&public_key, comment).map_err(to_value_error)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data))]
+/// Describe an OpenSSH user or host certificate (`*-cert.pub`), like `ssh-keygen -L`.
+pub fn parse_ssh_certificate(py: Python<'_>, data:&[u8]) -> PyResult<Py<PyAny>> {
+    let certificate = SshCertificate::parse(data)
+        .map_err(|err| PyValueError::new_err(format!("Could not read the OpenSSH certificate: {}", err)))?;
+    to_python(py, &certificate.metadata().map_err(to_value_error)?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (key, certificate, password=None, probe=false))]
+/// Check whether a private key belongs to a certificate, optionally also signing and verifying a test message.
+pub fn private_key_matches_certificate(key:&[u8], certificate:&[u8], password:Option<&str>, probe:bool) -> PyResult<bool> {
+    let key = load_private_key(key, password)?;
+    let cert = Certificate::from_bytes(certificate).map_err(to_value_error)?;
+    match check_key_pair(&key, &cert, probe) {
+        Ok(()) => Ok(true),
+        Err(err) if KeyMismatch::from_error(&err).is_some() => Ok(false),
+        Err(err) => Err(to_value_error(err)),
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificate))]
+/// List the SubjectAltName entries of a certificate (DER or PEM), e.g. `["DNS:example.com", "IP:10.0.0.1"]`.
+pub fn get_alt_names(certificate:&[u8]) -> PyResult<Vec<String>> {
+    let cert = Certificate::from_bytes(certificate).map_err(to_value_error)?;
+    Ok(cert.alt_names().map_err(to_value_error)?.iter().map(AltName::to_string).collect())
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificate, host))]
+/// Check whether a certificate is valid for a DNS name or IP address, using RFC 6125 wildcard matching.
+pub fn certificate_matches_host(certificate:&[u8], host:&str) -> PyResult<bool> {
+    let cert = Certificate::from_bytes(certificate).map_err(to_value_error)?;
+    cert.matches_host(host).map_err(to_value_error)
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificate, email))]
+/// Check whether a certificate is valid for an email address.
+pub fn certificate_matches_email(certificate:&[u8], email:&str) -> PyResult<bool> {
+    let cert = Certificate::from_bytes(certificate).map_err(to_value_error)?;
+    cert.matches_email(email).map_err(to_value_error)
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificate, purpose))]
+/// Check whether the ExtendedKeyUsage of a certificate allows a purpose, such as "client_auth", "code_signing",
+/// "smime", "smart_card_logon", "document_signing" or a dotted OID. Certificates without the extension allow any purpose.
+pub fn certificate_allows_purpose(certificate:&[u8], purpose:&str) -> PyResult<bool> {
+    let cert = Certificate::from_bytes(certificate).map_err(to_value_error)?;
+    let purpose = purpose_oid(purpose).map_err(to_value_error)?;
+    Ok(EnhancedKeyUsage::new(&cert, None).map_err(to_value_error)?.allows(&purpose))
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificate, data, signature, hash_alg="sha256", padding=None))]
+/// Verify a signature over `data` with the public key of a certificate. RSA signatures use "pkcs1v15" padding unless
+/// `padding="pss"` is given, ECDSA signatures may be DER or fixed size `r || s`, and Ed25519 ignores `hash_alg`.
+pub fn certificate_verifies_signature(certificate:&[u8], data:&[u8], signature:&[u8], hash_alg:&str, padding:Option<&str>) -> PyResult<bool> {
+    let cert = Certificate::from_bytes(certificate).map_err(to_value_error)?;
+    verify_with_certificate(&cert, data, signature, hash_alg, padding)
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificate, issuer))]
+/// Check whether a certificate was signed by the key of `issuer`. Only the signature is checked, the `verify-chain`
+/// command also checks names, validity and CA constraints.
+pub fn certificate_issued_by(certificate:&[u8], issuer:&[u8]) -> PyResult<bool> {
+    let cert = Certificate::from_bytes(certificate).map_err(to_value_error)?;
+    let issuer = Certificate::from_bytes(issuer).map_err(to_value_error)?;
+    verify_issued_by(&cert, &issuer).map_err(to_value_error)
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificates, order=None, extended_key_usage=None, preferred_issuers=None))]
+/// Rank certificates best first, returning the index and score breakdown of each one.
+pub fn rank_certificates(
+    py: Python<'_>, certificates:Vec<Bound<'_, PyAny>>, order:Option<Vec<String>>,
+    extended_key_usage:Option<Vec<String>>, preferred_issuers:Option<Vec<String>>
+) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+    let mut policy = RankingPolicy::new();
+
+    if let Some(order) = order {
+        let order = order.iter()
+            .map(|name| name.parse::<RankingCriterion>())
+            .collect::<std::io::Result<Vec<_>>>()
+            .map_err(to_value_error)?;
+        policy = policy.order(order).map_err(to_value_error)?;
+    }
+
+    for purpose in extended_key_usage.unwrap_or_default() {
+        policy = policy.extended_key_usage(extended_key_usage_oid(&purpose).map_err(to_value_error)?);
+    }
+
+    for issuer in preferred_issuers.unwrap_or_default() {
+        policy = policy.preferred_issuer(&issuer);
+    }
+
+    let candidates = certificates.iter().map(load_candidate).collect::<PyResult<Vec<_>>>()?;
+    let ranking = policy.rank(&candidates, SystemTime::now()).map_err(to_value_error)?;
+
+    let mut output = Vec::with_capacity(ranking.len());
+    for rank in ranking {
+        let subject = candidates[rank.index].certificate.subject().to_dotnet();
+        let scores: HashMap<String, i64> = rank.scores.iter()
+            .map(|score| (score.criterion.to_string(), score.value))
+            .collect();
+        let order: Vec<String> = rank.scores.iter().map(|score| score.criterion.to_string()).collect();
+
+        let mut dict = HashMap::new();
+        dict.insert("Index".to_string(), rank.index.into_py_any(py)?);
+        dict.insert("Thumbprint".to_string(), rank.thumbprint.into_py_any(py)?);
+        dict.insert("Subject".to_string(), subject.into_py_any(py)?);
+        dict.insert("Scores".to_string(), scores.into_py_any(py)?);
+        dict.insert("Order".to_string(), order.into_py_any(py)?);
+        output.push(dict);
+    }
+
+    Ok(output)
+}
+
+#[pyclass(name = "CertificateCandidate", module = "py_cert_store", frozen)]
+/// A certificate matching a search, handed to a custom selection callable.
+pub struct CertificateCandidate {
+    #[pyo3(get)]
+    index: usize,
+    #[pyo3(get)]
+    friendly_name: String,
+    #[pyo3(get)]
+    has_private_key: bool,
+    #[pyo3(get)]
+    key_provider: Option<String>,
+    certificate: Certificate,
+}
+
+#[pymethods]
+impl CertificateCandidate {
+    #[getter]
+    fn thumbprint(&self) -> String {
+        self.certificate.thumbprint()
+    }
+
+    #[getter]
+    fn subject(&self) -> String {
+        self.certificate.subject().to_dotnet()
+    }
+
+    #[getter]
+    fn issuer(&self) -> String {
+        self.certificate.issuer().to_dotnet()
+    }
+
+    #[getter]
+    fn not_before(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from(self.certificate.not_before())
+    }
+
+    #[getter]
+    fn not_after(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from(self.certificate.not_after())
+    }
+
+    #[getter]
+    fn alt_names(&self) -> PyResult<Vec<String>> {
+        Ok(self.certificate.alt_names().map_err(to_value_error)?.iter().map(AltName::to_string).collect())
+    }
+
+    #[getter]
+    fn certificate<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.certificate.as_der())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CertificateCandidate(index={}, subject={:?}, thumbprint={:?})", self.index, self.subject(), self.thumbprint())
+    }
+}
+
+impl CertificateCandidate {
+    pub(crate) fn new(index: usize, candidate: Candidate, friendly_name: &str) -> CertificateCandidate {
+        CertificateCandidate {
+            index,
+            friendly_name: friendly_name.to_string(),
+            has_private_key: candidate.has_private_key,
+            key_provider: candidate.key_provider,
+            certificate: candidate.certificate,
+        }
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificates, selection))]
+/// Pick from several matching certificates with a selection strategy name or a callable.
+pub fn select_certificates<'py>(
+    py: Python<'py>, certificates:Vec<Bound<'py, PyAny>>, selection:Bound<'py, PyAny>
+) -> PyResult<Vec<Bound<'py, PyAny>>> {
+    let mut candidates = Vec::with_capacity(certificates.len());
+    for (index, item) in certificates.iter().enumerate() {
+        let friendly_name: String = match item.cast::<PyDict>() {
+            Ok(dict) => dict.get_item("FriendlyName")?.map(|name| name.extract()).transpose()?.unwrap_or_default(),
+            Err(_) => String::new(),
+        };
+        candidates.push(CertificateCandidate::new(index, load_candidate(item)?, &friendly_name));
+    }
+
+    let selected = select_candidates(py, Some(&selection), candidates)?;
+    Ok(selected.into_iter().map(|index| certificates[index].clone()).collect())
+}
+
+/// Applies the `selection` argument of the search functions: `None` keeps every candidate, a string names a
+/// [`SelectionStrategy`], and a callable receives the list of candidates and returns the chosen candidate(s) or
+/// index(es), or `None` to choose nothing. Returns the `index` of each chosen candidate.
+pub(crate) fn select_candidates(
+    py: Python<'_>, selection: Option<&Bound<'_, PyAny>>, candidates: Vec<CertificateCandidate>
+) -> PyResult<Vec<usize>> {
+    let selection = match selection {
+        Some(selection) if !selection.is_none() => selection,
+        _ => return Ok((0..candidates.len()).collect()),
+    };
+
+    let known: Vec<usize> = candidates.iter().map(|candidate| candidate.index).collect();
+
+    if let Ok(name) = selection.extract::<String>() {
+        let strategy = name.parse::<SelectionStrategy>().map_err(to_value_error)?;
+        let certificates: Vec<Certificate> = candidates.into_iter().map(|candidate| candidate.certificate).collect();
+        let selected = strategy.select(&certificates).map_err(|err| match AmbiguousSelection::from_error(&err) {
+            Some(ambiguous) => CertAmbiguous::new_err(ambiguous.to_string()),
+            None => to_value_error(err),
+        })?;
+        return Ok(selected.into_iter().map(|position| known[position]).collect());
+    }
+
+    if !selection.is_callable() {
+        return Err(PyTypeError::new_err("selection must be a strategy name, a callable or None"));
+    }
+
+    let candidates = candidates.into_iter()
+        .map(|candidate| Py::new(py, candidate))
+        .collect::<PyResult<Vec<_>>>()?;
+    let chosen = selection.call1((candidates,))?;
+
+    let items = if chosen.is_none() {
+        Vec::new()
+    } else if chosen.is_instance_of::<CertificateCandidate>() || chosen.extract::<usize>().is_ok() {
+        vec![chosen]
+    } else {
+        chosen.try_iter()?.collect::<PyResult<Vec<_>>>()?
+    };
+
+    let mut indices = Vec::with_capacity(items.len());
+    for item in items {
+        let index = match item.cast::<CertificateCandidate>() {
+            Ok(candidate) => candidate.get().index,
+            Err(_) => item.extract::<usize>()
+                .map_err(|_| PyTypeError::new_err("The selection callable must return candidates, indices or None"))?,
+        };
+        if !known.contains(&index) {
+            return Err(PyValueError::new_err(format!("The selection callable returned the unknown index {}", index)));
+        }
+        if !indices.contains(&index) {
+            indices.push(index);
+        }
+    }
+
+    Ok(indices)
+}
+
+/// Reads a ranking candidate: certificate bytes, an unprotected PKCS#12 file, or a dictionary returned by the
+/// search functions (its "cert" PKCS#12 file and "KeyProvider").
+pub(crate) fn load_candidate(item: &Bound<'_, PyAny>) -> PyResult<Candidate> {
+    if let Ok(dict) = item.cast::<PyDict>() {
+        let data: Vec<u8> = dict.get_item("cert")?
+            .ok_or_else(|| PyValueError::new_err("The certificate dictionary has no \"cert\" entry"))?
+            .extract()?;
+        let mut candidate = candidate_from_bytes(&data)?;
+        if let Some(provider) = dict.get_item("KeyProvider")? {
+            let provider: String = provider.extract()?;
+            if !provider.is_empty() {
+                candidate = candidate.key_provider(&provider);
+            }
+        }
+        return Ok(candidate);
+    }
+
+    candidate_from_bytes(&item.extract::<Vec<u8>>()?)
+}
+
+fn candidate_from_bytes(data: &[u8]) -> PyResult<Candidate> {
+    if let Ok(cert) = Certificate::from_bytes(data) {
+        return Ok(Candidate::new(cert, false));
+    }
+
+    let store = Pkcs12Store::from_der(data, "")
+        .map_err(|err| PyValueError::new_err(format!("Could not read the certificate: {}", err)))?;
+    for cert in store.certs() {
+        if store.private_key_for(&cert).map_err(to_value_error)?.is_some() {
+            return Ok(Candidate::new(cert, true));
+        }
+    }
+
+    let cert = store.certs().next().ok_or_else(|| PyValueError::new_err("The PKCS#12 file holds no certificate"))?;
+    Ok(Candidate::new(cert, false))
+}
+
+/// Verifies a signature with the public key of `cert`, reading the hash and padding names.
+pub(crate) fn verify_with_certificate(cert: &Certificate, data: &[u8], signature: &[u8], hash_alg: &str, padding: Option<&str>) -> PyResult<bool> {
+    let hash = hash_alg.parse::<HashAlgorithm>().map_err(to_value_error)?;
+    let padding = padding.map(str::parse::<RsaPadding>).transpose().map_err(to_value_error)?;
+    let public_key = &cert.as_x509().tbs_certificate.subject_public_key_info;
+    verify_data(public_key, data, signature, hash, padding).map_err(to_value_error)
+}
+
+fn load_private_key(data: &[u8], password: Option<&str>) -> PyResult<PrivateKey> {
+    PrivateKey::from_bytes(data, password)
+        .map_err(|err| PyValueError::new_err(format!("Could not load the private key: {}", err)))
+}
+
+fn encode_private_key(key: &PrivateKey, format: &str, password: Option<&str>) -> PyResult<String> {
+    if is_openssh(format) {
+        return Ok(key.to_openssh("", password).map_err(to_value_error)?.to_string());
+    }
+    match password {
+        Some(password) => {
+            check_encryptable(format)?;
+            key.to_encrypted_pkcs8_pem(password).map_err(to_value_error)
+        },
+        None => {
+            let format = format.parse::<KeyFormat>().map_err(to_value_error)?;
+            Ok(key.to_pem(format).map_err(to_value_error)?.to_string())
+        },
+    }
+}
+
+/// The OpenSSH private key format, which is not one of the DER based [`KeyFormat`]s.
+fn is_openssh(format: &str) -> bool {
+    format.eq_ignore_ascii_case("openssh")
+}
+
+fn check_encryptable(format: &str) -> PyResult<()> {
+    if format.parse::<KeyFormat>().map_err(to_value_error)? != KeyFormat::Pkcs8 {
+        return Err(PyValueError::new_err("Only PKCS#8 keys can be encrypted"));
+    }
+    Ok(())
+}
+
+fn to_value_error(err: std::io::Error) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
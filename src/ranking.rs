@@ -0,0 +1,365 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{Result, Error, ErrorKind};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use der::asn1::ObjectIdentifier;
+use x509_cert::ext::pkix::ExtendedKeyUsage;
+
+use crate::certificate::Certificate;
+use crate::certificate::extensions::find_extension;
+use crate::certificate::purpose::OID_ANY_EXTENDED_KEY_USAGE;
+
+
+/// Key storage providers whose keys live in a smart card or TPM rather than in software.
+const HARDWARE_PROVIDERS: [&str; 4] = [
+    "Microsoft Smart Card Key Storage Provider",
+    "Microsoft Platform Crypto Provider",
+    "Microsoft Base Smart Card Crypto Provider",
+    "Microsoft Passport Key Storage Provider",
+];
+
+/// A property certificates are compared on. Each one scores a certificate, where higher is better.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RankingCriterion {
+    /// 1 when the private key is available, otherwise 0.
+    HasPrivateKey,
+    /// 2 when the ExtendedKeyUsage lists every requested purpose, 1 when it does not restrict the purposes
+    /// (no extension, or anyExtendedKeyUsage), otherwise 0. Always 0 when no purposes were requested.
+    MatchingEku,
+    /// Higher the earlier the issuer appears in the preferred issuers, 0 when it is not preferred.
+    PreferredIssuer,
+    /// 1 when the key is held by a smart card or TPM key storage provider, otherwise 0.
+    HardwareBacked,
+    /// The seconds left until the certificate expires, negative once it has expired.
+    RemainingValidity,
+    /// The start of the validity period in seconds since the Unix epoch.
+    Newest,
+}
+
+const CRITERION_NAMES: [(&str, RankingCriterion); 6] = [
+    ("has_private_key", RankingCriterion::HasPrivateKey),
+    ("matching_eku", RankingCriterion::MatchingEku),
+    ("preferred_issuer", RankingCriterion::PreferredIssuer),
+    ("hardware_backed", RankingCriterion::HardwareBacked),
+    ("remaining_validity", RankingCriterion::RemainingValidity),
+    ("newest", RankingCriterion::Newest),
+];
+
+/// The order used when none is configured.
+pub const DEFAULT_ORDER: [RankingCriterion; 6] = [
+    RankingCriterion::HasPrivateKey,
+    RankingCriterion::MatchingEku,
+    RankingCriterion::PreferredIssuer,
+    RankingCriterion::HardwareBacked,
+    RankingCriterion::RemainingValidity,
+    RankingCriterion::Newest,
+];
+
+impl fmt::Display for RankingCriterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = CRITERION_NAMES.iter()
+            .find(|(_, criterion)| criterion == self)
+            .map(|(name, _)| *name)
+            .unwrap_or_default();
+        f.write_str(name)
+    }
+}
+
+impl FromStr for RankingCriterion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<RankingCriterion> {
+        CRITERION_NAMES.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+            .map(|(_, criterion)| *criterion)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unknown ranking criterion \"{}\"", s)))
+    }
+}
+
+/// A certificate to rank, with what is known about its private key.
+#[derive(Clone, Debug)]
+pub struct Candidate {
+    pub certificate: Certificate,
+    pub has_private_key: bool,
+    /// The name of the key storage provider holding the private key, when known.
+    pub key_provider: Option<String>,
+}
+
+impl Candidate {
+    pub fn new(certificate: Certificate, has_private_key: bool) -> Candidate {
+        Candidate { certificate, has_private_key, key_provider: None }
+    }
+
+    pub fn key_provider(mut self, provider: &str) -> Candidate {
+        self.key_provider = Some(provider.to_string());
+        self
+    }
+
+    pub fn is_hardware_backed(&self) -> bool {
+        self.has_private_key && self.key_provider.as_deref().is_some_and(is_hardware_provider)
+    }
+}
+
+/// Whether a key storage provider keeps its keys in hardware (smart cards, TPMs and Windows Hello).
+pub fn is_hardware_provider(provider: &str) -> bool {
+    HARDWARE_PROVIDERS.iter().any(|known| known.eq_ignore_ascii_case(provider))
+}
+
+/// The score of a certificate for one criterion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Score {
+    pub criterion: RankingCriterion,
+    pub value: i64,
+}
+
+/// The place of a candidate in a ranking, with the scores that put it there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ranking {
+    /// The position of the candidate in the list that was ranked.
+    pub index: usize,
+    pub thumbprint: String,
+    /// The scores in the order of the policy, compared one after the other.
+    pub scores: Vec<Score>,
+}
+
+/// Orders certificates by a list of criteria, comparing on the first criterion and using the next ones to break
+/// ties. Remaining ties are broken by thumbprint, so the same certificates always rank the same way whatever
+/// order they were enumerated in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RankingPolicy {
+    order: Vec<RankingCriterion>,
+    extended_key_usage: Vec<ObjectIdentifier>,
+    preferred_issuers: Vec<String>,
+}
+
+impl Default for RankingPolicy {
+    fn default() -> RankingPolicy {
+        RankingPolicy { order: DEFAULT_ORDER.to_vec(), extended_key_usage: Vec::new(), preferred_issuers: Vec::new() }
+    }
+}
+
+impl RankingPolicy {
+    pub fn new() -> RankingPolicy {
+        RankingPolicy::default()
+    }
+
+    /// Replaces the order of the criteria. Criteria that are left out are not compared.
+    pub fn order(mut self, order: Vec<RankingCriterion>) -> Result<RankingPolicy> {
+        if order.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "The ranking order needs at least one criterion"));
+        }
+
+        for (i, criterion) in order.iter().enumerate() {
+            if order[..i].contains(criterion) {
+                return Err(Error::new(ErrorKind::InvalidInput, format!("The ranking criterion \"{}\" is listed twice", criterion)));
+            }
+        }
+
+        self.order = order;
+        Ok(self)
+    }
+
+    /// Adds a purpose the certificate is wanted for, scored by [`RankingCriterion::MatchingEku`].
+    pub fn extended_key_usage(mut self, purpose: ObjectIdentifier) -> RankingPolicy {
+        self.extended_key_usage.push(purpose);
+        self
+    }
+
+    /// Adds an issuer, matched case-insensitively against the issuer's common name or its full RFC 4514 or .NET
+    /// name. Issuers added first are preferred over later ones.
+    pub fn preferred_issuer(mut self, issuer: &str) -> RankingPolicy {
+        self.preferred_issuers.push(issuer.trim().to_string());
+        self
+    }
+
+    /// Scores a candidate on every criterion of the policy, in order.
+    pub fn score(&self, candidate: &Candidate, now: SystemTime) -> Result<Vec<Score>> {
+        self.order.iter()
+            .map(|criterion| Ok(Score { criterion: *criterion, value: self.score_criterion(*criterion, candidate, now)? }))
+            .collect()
+    }
+
+    /// Ranks the candidates, best first.
+    pub fn rank(&self, candidates: &[Candidate], now: SystemTime) -> Result<Vec<Ranking>> {
+        let mut rankings = candidates.iter().enumerate()
+            .map(|(index, candidate)| Ok(Ranking {
+                index,
+                thumbprint: candidate.certificate.thumbprint(),
+                scores: self.score(candidate, now)?,
+            }))
+            .collect::<Result<Vec<_>>>()?;
+
+        rankings.sort_by(compare_rankings);
+        Ok(rankings)
+    }
+
+    /// The best candidate, or `None` when there are no candidates.
+    pub fn best(&self, candidates: &[Candidate], now: SystemTime) -> Result<Option<Ranking>> {
+        Ok(self.rank(candidates, now)?.into_iter().next())
+    }
+
+    fn score_criterion(&self, criterion: RankingCriterion, candidate: &Candidate, now: SystemTime) -> Result<i64> {
+        let certificate = &candidate.certificate;
+        match criterion {
+            RankingCriterion::HasPrivateKey => Ok(i64::from(candidate.has_private_key)),
+            RankingCriterion::MatchingEku => self.score_extended_key_usage(certificate),
+            RankingCriterion::PreferredIssuer => self.score_issuer(certificate),
+            RankingCriterion::HardwareBacked => Ok(i64::from(candidate.is_hardware_backed())),
+            RankingCriterion::RemainingValidity => Ok(seconds_between(now, certificate.not_after())),
+            RankingCriterion::Newest => Ok(seconds_between(UNIX_EPOCH, certificate.not_before())),
+        }
+    }
+
+    fn score_extended_key_usage(&self, certificate: &Certificate) -> Result<i64> {
+        if self.extended_key_usage.is_empty() {
+            return Ok(0);
+        }
+
+        let extensions = certificate.as_x509().tbs_certificate.extensions.as_deref().unwrap_or_default();
+        let purposes = match find_extension::<ExtendedKeyUsage>(extensions)? {
+            Some(eku) => eku.0,
+            None => return Ok(1),
+        };
+
+        if self.extended_key_usage.iter().all(|purpose| purposes.contains(purpose)) {
+            Ok(2)
+        } else if purposes.contains(&OID_ANY_EXTENDED_KEY_USAGE) {
+            Ok(1)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn score_issuer(&self, certificate: &Certificate) -> Result<i64> {
+        let issuer = certificate.issuer();
+        let mut names = issuer.get_all("CN")?;
+        names.push(issuer.to_rfc4514());
+        names.push(issuer.to_dotnet());
+
+        let position = self.preferred_issuers.iter()
+            .position(|preferred| names.iter().any(|name| name.eq_ignore_ascii_case(preferred)));
+
+        Ok(position.map(|position| (self.preferred_issuers.len() - position) as i64).unwrap_or(0))
+    }
+}
+
+fn compare_rankings(a: &Ranking, b: &Ranking) -> Ordering {
+    for (a_score, b_score) in a.scores.iter().zip(&b.scores) {
+        match b_score.value.cmp(&a_score.value) {
+            Ordering::Equal => continue,
+            ordering => return ordering,
+        }
+    }
+    a.thumbprint.cmp(&b.thumbprint).then(a.index.cmp(&b.index))
+}
+
+/// The signed number of seconds from `from` to `to`.
+fn seconds_between(from: SystemTime, to: SystemTime) -> i64 {
+    match to.duration_since(from) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs() as i64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::cert_builder::{CertificateBuilder, IssuedCertificate};
+    use crate::cert_builder::test_support::{self_signed, sign};
+    use crate::certificate::extensions::extended_key_usage_oid;
+    use crate::keys::PrivateKey;
+
+    const CA_CERT_PEM: &[u8] = include_bytes!("../test/certs/ca_certificate.crt");
+    const CA_KEY_PEM: &str = include_str!("../test/certs/ca_private_key.pem");
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn parses_criterion_names() {
+        for criterion in DEFAULT_ORDER {
+            assert_eq!(criterion.to_string().parse::<RankingCriterion>().unwrap(), criterion);
+        }
+        assert!("fastest".parse::<RankingCriterion>().is_err());
+        assert!(RankingPolicy::new().order(vec![RankingCriterion::Newest, RankingCriterion::Newest]).is_err());
+        assert!(RankingPolicy::new().order(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn ranks_by_configured_order() {
+        let now = SystemTime::now();
+        let long_lived = self_signed("CN=long", now - DAY * 10, now + DAY * 90, &[]).certificate;
+        let newest = self_signed("CN=newest", now - DAY, now + DAY * 30, &[]).certificate;
+        let expired = self_signed("CN=expired", now - DAY * 40, now - DAY * 10, &[]).certificate;
+
+        let candidates = vec![
+            Candidate::new(expired, true),
+            Candidate::new(newest, true),
+            Candidate::new(long_lived.clone(), false),
+            Candidate::new(long_lived, true),
+        ];
+
+        let ranking = RankingPolicy::new().rank(&candidates, now).unwrap();
+        assert_eq!(ranking.iter().map(|rank| rank.index).collect::<Vec<_>>(), vec![3, 1, 0, 2]);
+        assert_eq!(ranking[0].scores[0], Score { criterion: RankingCriterion::HasPrivateKey, value: 1 });
+        assert!(ranking[2].scores[4].value < 0);
+
+        let newest_first = RankingPolicy::new().order(vec![RankingCriterion::Newest, RankingCriterion::HasPrivateKey]).unwrap();
+        let ranking = newest_first.rank(&candidates, now).unwrap();
+        assert_eq!(ranking.iter().map(|rank| rank.index).collect::<Vec<_>>(), vec![1, 3, 2, 0]);
+        assert_eq!(ranking[0].scores.len(), 2);
+    }
+
+    #[test]
+    fn ranks_by_usage_issuer_and_hardware() {
+        let now = SystemTime::now();
+        let ca = IssuedCertificate {
+            certificate: Certificate::from_pem(CA_CERT_PEM).unwrap(),
+            key: PrivateKey::from_pkcs8_pem(CA_KEY_PEM).unwrap(),
+            friendly_name: None,
+        };
+        let issued = sign(CertificateBuilder::new("CN=issued").unwrap().validity(now - DAY, now + DAY), Some(&ca)).certificate;
+
+        let candidates = vec![
+            Candidate::new(self_signed("CN=server", now - DAY, now + DAY * 365, &["server_auth"]).certificate, true),
+            Candidate::new(self_signed("CN=client", now - DAY, now + DAY * 30, &["client_auth"]).certificate, true),
+            Candidate::new(self_signed("CN=any", now - DAY, now + DAY * 60, &[]).certificate, true),
+            Candidate::new(issued, true),
+        ];
+
+        let client_auth = RankingPolicy::new().extended_key_usage(extended_key_usage_oid("client_auth").unwrap());
+        let ranking = client_auth.rank(&candidates, now).unwrap();
+        assert_eq!(ranking.iter().map(|rank| rank.index).collect::<Vec<_>>(), vec![1, 2, 3, 0]);
+
+        let preferred = RankingPolicy::new().preferred_issuer("CN=other").preferred_issuer("py_cert_store demo ca");
+        let best = preferred.best(&candidates, now).unwrap().unwrap();
+        assert_eq!(best.index, 3);
+        assert_eq!(best.scores[2], Score { criterion: RankingCriterion::PreferredIssuer, value: 1 });
+
+        let mut candidates = candidates;
+        candidates[2] = candidates[2].clone().key_provider("Microsoft Platform Crypto Provider");
+        candidates[0] = candidates[0].clone().key_provider("Microsoft Software Key Storage Provider");
+        assert_eq!(RankingPolicy::new().best(&candidates, now).unwrap().unwrap().index, 2);
+        assert!(RankingPolicy::new().best(&[], now).unwrap().is_none());
+    }
+}
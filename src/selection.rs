@@ -0,0 +1,184 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::fmt;
+use std::io::{Result, Error, ErrorKind};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use crate::certificate::Certificate;
+
+
+/// How to pick from several certificates that match a search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionStrategy {
+    /// Keep every match, in enumeration order.
+    All,
+    /// The first match in enumeration order.
+    First,
+    /// The match whose validity period started last.
+    Newest,
+    /// The match whose validity period ends last.
+    LongestValid,
+    /// The only match, failing with [`AmbiguousSelection`] when there are several.
+    RaiseIfAmbiguous,
+}
+
+const STRATEGY_NAMES: [(&str, SelectionStrategy); 5] = [
+    ("all", SelectionStrategy::All),
+    ("first", SelectionStrategy::First),
+    ("newest", SelectionStrategy::Newest),
+    ("longest_valid", SelectionStrategy::LongestValid),
+    ("raise_if_ambiguous", SelectionStrategy::RaiseIfAmbiguous),
+];
+
+impl fmt::Display for SelectionStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = STRATEGY_NAMES.iter()
+            .find(|(_, strategy)| strategy == self)
+            .map(|(name, _)| *name)
+            .unwrap_or_default();
+        f.write_str(name)
+    }
+}
+
+impl FromStr for SelectionStrategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SelectionStrategy> {
+        STRATEGY_NAMES.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+            .map(|(_, strategy)| *strategy)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unknown selection strategy \"{}\"", s)))
+    }
+}
+
+/// Why [`SelectionStrategy::RaiseIfAmbiguous`] failed: more than one certificate matched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmbiguousSelection {
+    /// The thumbprints of the matching certificates, in enumeration order.
+    pub thumbprints: Vec<String>,
+}
+
+impl fmt::Display for AmbiguousSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} certificates match: {}", self.thumbprints.len(), self.thumbprints.join(", "))
+    }
+}
+
+impl std::error::Error for AmbiguousSelection {}
+
+impl AmbiguousSelection {
+    /// Returns the ambiguity carried by an error from [`SelectionStrategy::select`], if that is why it failed.
+    pub fn from_error(err: &Error) -> Option<&AmbiguousSelection> {
+        err.get_ref()?.downcast_ref::<AmbiguousSelection>()
+    }
+}
+
+impl SelectionStrategy {
+    /// Picks from the matching certificates, returning the indices of the ones to keep.
+    ///
+    /// No matches select nothing. Ties between certificates with the same dates go to the lowest thumbprint, so the
+    /// choice does not depend on the enumeration order.
+    pub fn select(&self, certificates: &[Certificate]) -> Result<Vec<usize>> {
+        if certificates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        match self {
+            SelectionStrategy::All => Ok((0..certificates.len()).collect()),
+            SelectionStrategy::First => Ok(vec![0]),
+            SelectionStrategy::Newest => Ok(latest_by(certificates, Certificate::not_before).into_iter().collect()),
+            SelectionStrategy::LongestValid => Ok(latest_by(certificates, Certificate::not_after).into_iter().collect()),
+            SelectionStrategy::RaiseIfAmbiguous if certificates.len() == 1 => Ok(vec![0]),
+            SelectionStrategy::RaiseIfAmbiguous => {
+                let thumbprints = certificates.iter().map(Certificate::thumbprint).collect();
+                Err(Error::new(ErrorKind::InvalidInput, AmbiguousSelection { thumbprints }))
+            },
+        }
+    }
+}
+
+fn latest_by(certificates: &[Certificate], time: fn(&Certificate) -> SystemTime) -> Option<usize> {
+    let thumbprints: Vec<String> = certificates.iter().map(Certificate::thumbprint).collect();
+    (0..certificates.len()).max_by(|&a, &b| {
+        time(&certificates[a]).cmp(&time(&certificates[b]))
+            .then_with(|| thumbprints[b].cmp(&thumbprints[a]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::cert_builder::test_support::self_signed;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn parses_strategy_names() {
+        for (name, strategy) in STRATEGY_NAMES {
+            assert_eq!(name.parse::<SelectionStrategy>().unwrap(), strategy);
+            assert_eq!(strategy.to_string(), name);
+        }
+        assert_eq!("Longest_Valid".parse::<SelectionStrategy>().unwrap(), SelectionStrategy::LongestValid);
+        assert!("random".parse::<SelectionStrategy>().is_err());
+    }
+
+    #[test]
+    fn selects_from_matches() {
+        let now = SystemTime::now();
+        let certificates = vec![
+            self_signed("CN=old", now - DAY * 30, now + DAY * 370, &[]).certificate,
+            self_signed("CN=new", now - DAY, now + DAY * 89, &[]).certificate,
+            self_signed("CN=middle", now - DAY * 10, now + DAY * 20, &[]).certificate,
+        ];
+
+        let cases = [
+            (SelectionStrategy::All, vec![0, 1, 2]),
+            (SelectionStrategy::First, vec![0]),
+            (SelectionStrategy::Newest, vec![1]),
+            (SelectionStrategy::LongestValid, vec![0]),
+        ];
+
+        for (strategy, expected) in cases {
+            assert_eq!(strategy.select(&certificates).unwrap(), expected, "{}", strategy);
+            assert!(strategy.select(&[]).unwrap().is_empty());
+        }
+
+        let err = SelectionStrategy::RaiseIfAmbiguous.select(&certificates).unwrap_err();
+        let ambiguous = AmbiguousSelection::from_error(&err).unwrap();
+        assert_eq!(ambiguous.thumbprints.len(), 3);
+        assert_eq!(ambiguous.thumbprints[1], certificates[1].thumbprint());
+        assert_eq!(SelectionStrategy::RaiseIfAmbiguous.select(&certificates[2..]).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn breaks_ties_by_thumbprint() {
+        let now = SystemTime::now();
+        let a = self_signed("CN=a", now, now + DAY * 30, &[]).certificate;
+        let b = self_signed("CN=b", now, now + DAY * 30, &[]).certificate;
+        let lowest = if a.thumbprint() < b.thumbprint() { "CN=a" } else { "CN=b" };
+
+        for certificates in [vec![a.clone(), b.clone()], vec![b, a]] {
+            let selected = SelectionStrategy::Newest.select(&certificates).unwrap();
+            assert_eq!(certificates[selected[0]].subject().to_string(), lowest);
+        }
+    }
+}
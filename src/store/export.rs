@@ -0,0 +1,171 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+
+use crate::certificate::Certificate;
+use crate::file_store::pkcs12_store::Pkcs12Store;
+use crate::keys::storage::KeyStorage;
+use crate::keys::verify::KeyMismatch;
+use crate::store::StoreEntry;
+use crate::store::cancel::Cancellation;
+
+
+/// A certificate exported from a store along with its private key, ready to be handed to Python.
+///
+/// Everything is plain Rust data, so exports can run without holding the GIL and be converted in one pass.
+#[derive(Clone, Debug)]
+pub struct ExportedCertificate {
+    pub certificate: Certificate,
+    pub friendly_name: String,
+    pub key_provider: Option<String>,
+    /// What the store reported about the private key.
+    pub key_storage: Option<KeyStorage>,
+    /// The certificate and its private key as a PKCS#12 file without a password.
+    pub pfx: Vec<u8>,
+}
+
+impl ExportedCertificate {
+    /// Exports an entry and checks that the exported key belongs to the certificate.
+    ///
+    /// A key that does not match fails with an `InvalidData` error wrapping a [`KeyMismatch`].
+    pub fn export(entry: &dyn StoreEntry) -> Result<ExportedCertificate> {
+        let certificate = Certificate::from_der(entry.der())?;
+        let pfx = entry.export_pkcs12()?;
+
+        // Stores are exported without a password.
+        Pkcs12Store::from_der(&pfx, "")?.check_key_pair(&certificate, true)?;
+
+        Ok(ExportedCertificate {
+            certificate,
+            friendly_name: entry.friendly_name(),
+            key_provider: entry.key_provider(),
+            key_storage: entry.key_storage().ok().flatten(),
+            pfx,
+        })
+    }
+}
+
+/// Exports the entries that matched a search, skipping the ones that cannot be exported.
+///
+/// Fails with `NotFound` when there are no entries, and when none could be exported, with the key mismatch that
+/// was found or `PermissionDenied`. Once `cancel` is set no further key is exported and the export fails with
+/// `Interrupted`.
+pub fn export_entries<I: IntoIterator<Item = Box<dyn StoreEntry>>>(entries: I, cancel: &Cancellation) -> Result<Vec<ExportedCertificate>> {
+    let mut exported = Vec::new();
+    let mut matched = false;
+    let mut key_mismatch = None;
+
+    for entry in entries {
+        cancel.check()?;
+        matched = true;
+        match ExportedCertificate::export(entry.as_ref()) {
+            Ok(certificate) => exported.push(certificate),
+            Err(err) if KeyMismatch::from_error(&err).is_some() => key_mismatch = Some(err),
+            Err(_) => {},
+        }
+    }
+
+    cancel.check()?;
+
+    if !matched {
+        return Err(Error::new(ErrorKind::NotFound, "No valid certificates found."));
+    }
+
+    if exported.is_empty() {
+        return Err(key_mismatch.unwrap_or_else(|| Error::new(ErrorKind::PermissionDenied, "No Exportable certificates found.")));
+    }
+
+    Ok(exported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use crate::keys::PrivateKey;
+    use crate::store::memory::MemoryStore;
+    use crate::store::test_support::cancelling_entries;
+
+    const CERT_PEM: &[u8] = include_bytes!("../../test/certs/certificate.crt");
+    const KEY_PEM: &str = include_str!("../../test/certs/private_key.pem");
+    const CA_CERT_PEM: &[u8] = include_bytes!("../../test/certs/ca_certificate.crt");
+    const CA_KEY_PEM: &str = include_str!("../../test/certs/ca_private_key.pem");
+
+    fn store() -> MemoryStore {
+        let mut store = MemoryStore::new();
+        store.add_certificate(Certificate::from_pem(CA_CERT_PEM).unwrap(), "CA");
+        store.add_certificate_with_key(Certificate::from_pem(CERT_PEM).unwrap(), PrivateKey::from_pkcs8_pem(KEY_PEM).unwrap(), "Leaf", true);
+        store
+    }
+
+    #[test]
+    fn exports_matching_entries() {
+        let store = store();
+        let exported = export_entries(store.entries(), &Cancellation::new()).unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].friendly_name, "Leaf");
+        assert_eq!(exported[0].certificate, Certificate::from_pem(CERT_PEM).unwrap());
+
+        assert_eq!(export_entries(Vec::new(), &Cancellation::new()).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(export_entries(store.entries().take(1), &Cancellation::new()).unwrap_err().kind(), ErrorKind::PermissionDenied);
+
+        let mut mismatched = MemoryStore::new();
+        mismatched.add_certificate_with_key(Certificate::from_pem(CERT_PEM).unwrap(), PrivateKey::from_pkcs8_pem(CA_KEY_PEM).unwrap(), "", true);
+        let err = export_entries(mismatched.entries(), &Cancellation::new()).unwrap_err();
+        assert_eq!(KeyMismatch::from_error(&err), Some(KeyMismatch::PublicKey));
+    }
+
+    #[test]
+    fn exports_from_several_threads() {
+        let store = store();
+        let expected = export_entries(store.entries(), &Cancellation::new()).unwrap()[0].certificate.clone();
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let entries = store.entries();
+                thread::spawn(move || export_entries(entries, &Cancellation::new()))
+            })
+            .collect();
+
+        for handle in handles {
+            let exported = handle.join().unwrap().unwrap();
+            assert_eq!(exported[0].certificate, expected);
+        }
+        assert_eq!(store.export_count(), 9);
+    }
+
+    #[test]
+    fn stops_exporting_once_cancelled() {
+        let mut store = MemoryStore::new();
+        for _ in 0..20 {
+            store.add_certificate_with_key(Certificate::from_pem(CERT_PEM).unwrap(), PrivateKey::from_pkcs8_pem(KEY_PEM).unwrap(), "", true);
+        }
+
+        // The third export cancels the search, so no further key is exported.
+        let cancel = Cancellation::new();
+        let err = export_entries(cancelling_entries(store.entries(), &cancel, 3), &cancel).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Interrupted);
+        assert_eq!(store.export_count(), 3);
+
+        // A cancelled search does not export anything.
+        assert_eq!(export_entries(store.entries(), &cancel).unwrap_err().kind(), ErrorKind::Interrupted);
+        assert_eq!(store.export_count(), 3);
+    }
+}
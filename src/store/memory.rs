@@ -0,0 +1,217 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::certificate::Certificate;
+use crate::file_store::pkcs12_store::Pkcs12Store;
+use crate::keys::PrivateKey;
+use crate::keys::storage::KeyStorage;
+use crate::store::{StoreEntry, StoreIter};
+
+
+/// A certificate held by a [`MemoryStore`].
+#[derive(Clone)]
+pub struct MemoryEntry {
+    certificate: Certificate,
+    key: Option<PrivateKey>,
+    friendly_name: String,
+    key_provider: Option<String>,
+    exportable: bool,
+    exports: Arc<AtomicUsize>,
+}
+
+impl StoreEntry for MemoryEntry {
+    fn der(&self) -> &[u8] {
+        self.certificate.as_der()
+    }
+
+    fn friendly_name(&self) -> String {
+        self.friendly_name.clone()
+    }
+
+    fn has_private_key(&self) -> bool {
+        self.key.is_some()
+    }
+
+    fn key_provider(&self) -> Option<String> {
+        self.key_provider.clone()
+    }
+
+    fn key_storage(&self) -> Result<Option<KeyStorage>> {
+        Ok(self.key.as_ref().map(|_| KeyStorage::software(self.key_provider.clone(), self.exportable)))
+    }
+
+    fn export_pkcs12(&self) -> Result<Vec<u8>> {
+        let key = self.key.as_ref()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "The certificate has no private key"))?;
+        if !self.exportable {
+            return Err(Error::new(ErrorKind::PermissionDenied, "The private key is not exportable"));
+        }
+
+        self.exports.fetch_add(1, Ordering::SeqCst);
+
+        let local_key_id = self.certificate.thumbprint().into_bytes();
+        let friendly_name = Some(self.friendly_name.as_str()).filter(|name| !name.is_empty());
+        let mut pfx = Pkcs12Store::new();
+        pfx.add_certificate(self.certificate.clone(), friendly_name, Some(&local_key_id));
+        pfx.add_key(&key.to_pkcs8_der()?, friendly_name, Some(&local_key_id));
+        pfx.to_der("")
+    }
+}
+
+/// A store kept in memory, standing in for a platform store wherever one is not available (such as tests on Linux).
+///
+/// Enumerating the store hands out copies of its entries, so the store can be enumerated again or from several
+/// threads at once.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    entries: Vec<MemoryEntry>,
+    exports: Arc<AtomicUsize>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    /// Adds every certificate of a PKCS#12 file, with its private key when the file holds one.
+    pub fn from_pkcs12(pfx: &Pkcs12Store) -> Result<MemoryStore> {
+        let mut store = MemoryStore::new();
+        for bag in pfx.certificate_bags() {
+            let key = pfx.private_key_for(&bag.value)?
+                .filter(|key| key.matches_certificate(&bag.value).unwrap_or(false));
+            let friendly_name = bag.friendly_name.clone().unwrap_or_default();
+            match key {
+                Some(key) => store.add_certificate_with_key(bag.value.clone(), key, &friendly_name, true),
+                None => store.add_certificate(bag.value.clone(), &friendly_name),
+            }
+        }
+        Ok(store)
+    }
+
+    pub fn add_certificate(&mut self, certificate: Certificate, friendly_name: &str) {
+        self.entries.push(MemoryEntry {
+            certificate,
+            key: None,
+            friendly_name: friendly_name.to_string(),
+            key_provider: None,
+            exportable: false,
+            exports: self.exports.clone(),
+        });
+    }
+
+    pub fn add_certificate_with_key(&mut self, certificate: Certificate, key: PrivateKey, friendly_name: &str, exportable: bool) {
+        self.entries.push(MemoryEntry {
+            certificate,
+            key: Some(key),
+            friendly_name: friendly_name.to_string(),
+            key_provider: None,
+            exportable,
+            exports: self.exports.clone(),
+        });
+    }
+
+    /// Names the entries that do not have a friendly name yet.
+    pub fn set_default_friendly_name(&mut self, friendly_name: &str) {
+        for entry in self.entries.iter_mut().filter(|entry| entry.friendly_name.is_empty()) {
+            entry.friendly_name = friendly_name.to_string();
+        }
+    }
+
+    /// Records the provider holding the private keys of the entries that have one.
+    pub fn set_key_provider(&mut self, key_provider: &str) {
+        for entry in self.entries.iter_mut().filter(|entry| entry.key.is_some()) {
+            entry.key_provider = Some(key_provider.to_string());
+        }
+    }
+
+    /// Merges the entries of another store into this one.
+    pub fn extend(&mut self, other: MemoryStore) {
+        for mut entry in other.entries {
+            entry.exports = self.exports.clone();
+            self.entries.push(entry);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of private keys exported from the store so far.
+    pub fn export_count(&self) -> usize {
+        self.exports.load(Ordering::SeqCst)
+    }
+
+    /// Enumerates the store.
+    pub fn entries(&self) -> StoreIter {
+        Box::new(self.entries.clone().into_iter().map(|entry| Box::new(entry) as Box<dyn StoreEntry>))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CERT_PEM: &[u8] = include_bytes!("../../test/certs/certificate.crt");
+    const KEY_PEM: &str = include_str!("../../test/certs/private_key.pem");
+    const CA_CERT_PEM: &[u8] = include_bytes!("../../test/certs/ca_certificate.crt");
+
+    #[test]
+    fn defers_key_export_until_requested() {
+        let mut store = MemoryStore::new();
+        store.add_certificate(Certificate::from_pem(CA_CERT_PEM).unwrap(), "CA");
+        store.add_certificate_with_key(Certificate::from_pem(CERT_PEM).unwrap(), PrivateKey::from_pkcs8_pem(KEY_PEM).unwrap(), "Leaf", true);
+
+        let entries: Vec<_> = store.entries().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(store.export_count(), 0);
+        assert!(!entries[0].has_private_key());
+        assert_eq!(entries[0].export_pkcs12().unwrap_err().kind(), ErrorKind::NotFound);
+
+        let pfx = entries[1].export_pkcs12().unwrap();
+        assert_eq!(store.export_count(), 1);
+
+        let exported = Pkcs12Store::from_der(&pfx, "").unwrap();
+        let cert = Certificate::from_der(entries[1].der()).unwrap();
+        exported.check_key_pair(&cert, true).unwrap();
+        assert_eq!(exported.certificate_bags()[0].friendly_name.as_deref(), Some("Leaf"));
+
+        let reloaded = MemoryStore::from_pkcs12(&exported).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert!(reloaded.entries().all(|entry| entry.has_private_key()));
+    }
+
+    #[test]
+    fn refuses_non_exportable_keys() {
+        let mut store = MemoryStore::new();
+        store.add_certificate_with_key(Certificate::from_pem(CERT_PEM).unwrap(), PrivateKey::from_pkcs8_pem(KEY_PEM).unwrap(), "", false);
+
+        let entry = store.entries().next().unwrap();
+        assert!(entry.has_private_key());
+        assert_eq!(entry.export_pkcs12().unwrap_err().kind(), ErrorKind::PermissionDenied);
+        assert_eq!(store.export_count(), 0);
+    }
+}
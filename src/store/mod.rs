@@ -0,0 +1,91 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::Result;
+use std::time::SystemTime;
+use der::asn1::ObjectIdentifier;
+
+use crate::keys::storage::KeyStorage;
+
+pub mod cancel;
+pub mod export;
+pub mod memory;
+pub mod path;
+pub mod properties;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+
+use properties::{PropertyId, PropertyValue};
+
+
+/// A certificate enumerated from a store.
+///
+/// Entries are cheap to enumerate; the private key is only exported when [`StoreEntry::export_pkcs12`] is called.
+/// Dropping an entry releases whatever it holds in the underlying store.
+pub trait StoreEntry: Send {
+    /// The DER encoding of the certificate.
+    fn der(&self) -> &[u8];
+
+    fn friendly_name(&self) -> String;
+
+    fn has_private_key(&self) -> bool;
+
+    /// The name of the key storage provider holding the private key, when known.
+    fn key_provider(&self) -> Option<String>;
+
+    /// Describes the private key without exporting it, or `None` when there is no private key. Stores that cannot
+    /// tell more only report the provider.
+    fn key_storage(&self) -> Result<Option<KeyStorage>> {
+        Ok(self.has_private_key().then(|| KeyStorage::provider(self.key_provider())))
+    }
+
+    /// Exports the certificate and its private key as a PKCS#12 file without a password.
+    ///
+    /// Fails with `NotFound` when the certificate has no private key, and with `PermissionDenied` when the key
+    /// is not exportable.
+    fn export_pkcs12(&self) -> Result<Vec<u8>>;
+
+    /// A property the store keeps with the certificate, such as the Windows certificate context properties.
+    /// Stores without properties always return `None`.
+    fn property(&self, _id: PropertyId) -> Result<Option<PropertyValue>> {
+        Ok(None)
+    }
+
+    /// The purposes the store restricts the certificate to, see [`PropertyId::EnhancedKeyUsage`]. Without the
+    /// property the certificate's own ExtendedKeyUsage is in charge.
+    fn enhanced_key_usage_property(&self) -> Result<Option<Vec<ObjectIdentifier>>> {
+        match self.property(PropertyId::EnhancedKeyUsage)? {
+            Some(PropertyValue::EnhancedKeyUsage(purposes)) => Ok(Some(purposes)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// An owned, lazy enumeration of a store. Dropping it closes the store.
+pub type StoreIter = Box<dyn Iterator<Item = Box<dyn StoreEntry>> + Send>;
+
+/// Formats a time the way the .NET `X509Certificate2` date strings are shown, in local time.
+pub fn dotnet_date_string(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(time).format("%-m/%d/%Y %-I:%M:%S %p").to_string()
+}
+
+/// Formats a time as an RFC 3339 timestamp in UTC, such as `2025-04-17T01:32:47Z`.
+pub fn rfc3339_date_string(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
@@ -0,0 +1,263 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::fs;
+use std::io::{Result, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::certificate::Certificate;
+use crate::file_store::jks_store::JksStore;
+use crate::file_store::pkcs12_store::Pkcs12Store;
+use crate::keys::PrivateKey;
+use crate::store::memory::MemoryStore;
+
+
+/// Loads a file based store: a certificate file, a PKCS#12 file, a Java keystore, or a directory of such files.
+///
+/// PEM files may hold several certificates and private keys. Keys, from any file of a directory, are attached to the
+/// certificate they belong to. Certificates are named after the file they were read from unless the PKCS#12 file
+/// gives them a friendly name, or the keystore an alias. Without a password the private keys of a keystore are not
+/// read. Files in a directory that cannot be read are skipped.
+pub fn open_path<P: AsRef<Path>>(path: P, password: &str) -> Result<MemoryStore> {
+    let path = path.as_ref();
+    let mut contents = FileContents::default();
+
+    if path.is_dir() {
+        for file in directory_files(path)? {
+            let _ = contents.load(&file, password);
+        }
+    } else {
+        contents.load(path, password)?;
+    }
+
+    Ok(contents.into_store())
+}
+
+/// The state of a file or of the files of a directory, used to notice that a file based store changed.
+///
+/// Two fingerprints of the same path differ when a file was added, removed, resized or modified. A path that does
+/// not exist has an empty fingerprint.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathFingerprint(Vec<(PathBuf, u64, Option<SystemTime>)>);
+
+impl PathFingerprint {
+    pub fn of<P: AsRef<Path>>(path: P) -> Result<PathFingerprint> {
+        let path = path.as_ref();
+        let files = match fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() => directory_files(path)?,
+            Ok(_) => vec![path.to_path_buf()],
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(PathFingerprint::default()),
+            Err(err) => return Err(err),
+        };
+
+        let mut fingerprint = Vec::with_capacity(files.len());
+        for file in files {
+            // A file removed while the directory is read is left out, as if it was read a moment later.
+            if let Ok(metadata) = fs::metadata(&file) {
+                fingerprint.push((file, metadata.len(), metadata.modified().ok()));
+            }
+        }
+        Ok(PathFingerprint(fingerprint))
+    }
+
+    /// The files making up the store.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.0.iter().map(|(path, _, _)| path.as_path())
+    }
+}
+
+/// The files directly inside a directory, sorted by name.
+fn directory_files(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[derive(Default)]
+struct FileContents {
+    certificates: Vec<(Certificate, String)>,
+    keys: Vec<PrivateKey>,
+    pkcs12: MemoryStore,
+}
+
+impl FileContents {
+    fn load(&mut self, path: &Path, password: &str) -> Result<()> {
+        let data = fs::read(path)?;
+        let name = path.file_stem().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let password = Some(password).filter(|password| !password.is_empty());
+
+        if data.trim_ascii_start().starts_with(b"-----BEGIN") {
+            let text = std::str::from_utf8(&data).map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?;
+            let blocks = pem_blocks(text);
+            if blocks.is_empty() {
+                return Err(Error::new(ErrorKind::InvalidData, format!("No PEM blocks in {}", path.display())));
+            }
+
+            for (label, block) in blocks {
+                match label {
+                    "CERTIFICATE" => self.certificates.push((Certificate::from_pem(block.as_bytes())?, name.clone())),
+                    label if label.ends_with("PRIVATE KEY") => self.keys.push(PrivateKey::from_pem(block, password)?),
+                    // Requests, parameters and other blocks do not belong in a store.
+                    _ => {},
+                }
+            }
+            return Ok(());
+        }
+
+        if JksStore::is_keystore(&data) {
+            self.pkcs12.extend(JksStore::from_bytes(&data, password, None)?.to_memory_store());
+        } else if let Ok(certificate) = Certificate::from_der(&data) {
+            self.certificates.push((certificate, name));
+        } else if let Ok(pfx) = Pkcs12Store::from_der(&data, password.unwrap_or_default()) {
+            let mut store = MemoryStore::from_pkcs12(&pfx)?;
+            store.set_default_friendly_name(&name);
+            self.pkcs12.extend(store);
+        } else {
+            self.keys.push(PrivateKey::from_der(&data, password)?);
+        }
+        Ok(())
+    }
+
+    fn into_store(self) -> MemoryStore {
+        let mut store = self.pkcs12;
+        for (certificate, name) in self.certificates {
+            let key = self.keys.iter().find(|key| key.matches_certificate(&certificate).unwrap_or(false));
+            match key {
+                Some(key) => store.add_certificate_with_key(certificate, key.clone(), &name, true),
+                None => store.add_certificate(certificate, &name),
+            }
+        }
+        store
+    }
+}
+
+/// Splits a PEM file into its labelled blocks.
+fn pem_blocks(text: &str) -> Vec<(&str, &str)> {
+    let mut blocks = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("-----BEGIN ") {
+        let block = &rest[start..];
+        let label_end = match block["-----BEGIN ".len()..].find("-----") {
+            Some(end) => "-----BEGIN ".len() + end,
+            None => break,
+        };
+        let label = &block["-----BEGIN ".len()..label_end];
+        let footer = format!("-----END {}-----", label);
+        let end = match block.find(&footer) {
+            Some(end) => end + footer.len(),
+            None => break,
+        };
+
+        blocks.push((label, &block[..end]));
+        rest = &block[end..];
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const CERT_PEM: &str = include_str!("../../test/certs/certificate.crt");
+    const KEY_PEM: &str = include_str!("../../test/certs/private_key.pem");
+    const CA_CERT_PEM: &str = include_str!("../../test/certs/ca_certificate.crt");
+    const PFX: &[u8] = include_bytes!("../../test/certs/certificate.p12");
+
+    /// A directory of its own under the system temporary directory, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> TempDir {
+            static COUNT: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir()
+                .join(format!("py_cert_store-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::SeqCst)));
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn summary(store: &MemoryStore) -> Vec<(String, bool)> {
+        let mut entries: Vec<_> = store.entries().map(|entry| (entry.friendly_name(), entry.has_private_key())).collect();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn loads_directories_and_files() {
+        let dir = TempDir::new();
+        fs::write(dir.0.join("leaf.crt"), CERT_PEM).unwrap();
+        fs::write(dir.0.join("leaf.key"), KEY_PEM).unwrap();
+        fs::write(dir.0.join("bundle.pem"), format!("{}\n{}", CA_CERT_PEM, CA_CERT_PEM)).unwrap();
+        fs::write(dir.0.join("notes.txt"), "not a certificate").unwrap();
+        fs::create_dir(dir.0.join("nested")).unwrap();
+
+        let store = open_path(&dir.0, "").unwrap();
+        assert_eq!(summary(&store), vec![("bundle".to_string(), false), ("bundle".to_string(), false), ("leaf".to_string(), true)]);
+
+        let pfx = dir.0.join("nested").join("client.p12");
+        fs::write(&pfx, PFX).unwrap();
+        let store = open_path(&pfx, "pass1234").unwrap();
+        assert_eq!(store.len(), 1);
+        assert!(store.entries().next().unwrap().has_private_key());
+
+        assert!(open_path(&pfx, "wrong").is_err());
+
+        let keystore = Path::new(env!("CARGO_MANIFEST_DIR")).join("test").join("jks").join("keystore.jks");
+        let store = open_path(&keystore, "changeit").unwrap();
+        assert_eq!(summary(&store), vec![("ec client".to_string(), true), ("root".to_string(), false), ("server".to_string(), true)]);
+        assert_eq!(open_path(&keystore, "wrong").err().unwrap().kind(), ErrorKind::PermissionDenied);
+        assert!(open_path(dir.0.join("notes.txt"), "").is_err());
+        assert_eq!(open_path(dir.0.join("missing"), "").err().unwrap().kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn fingerprints_change_with_the_files() {
+        let dir = TempDir::new();
+        let empty = PathFingerprint::of(&dir.0).unwrap();
+        assert_eq!(empty, PathFingerprint::of(dir.0.join("missing")).unwrap());
+
+        fs::write(dir.0.join("a.crt"), CERT_PEM).unwrap();
+        let one = PathFingerprint::of(&dir.0).unwrap();
+        assert_ne!(one, empty);
+        assert_eq!(one, PathFingerprint::of(&dir.0).unwrap());
+        assert_eq!(one.files().count(), 1);
+
+        fs::write(dir.0.join("a.crt"), CA_CERT_PEM).unwrap();
+        assert_ne!(PathFingerprint::of(&dir.0).unwrap(), one);
+
+        fs::remove_file(dir.0.join("a.crt")).unwrap();
+        assert_eq!(PathFingerprint::of(&dir.0).unwrap(), empty);
+    }
+}
@@ -0,0 +1,382 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::fmt;
+use std::io::{Result, Error, ErrorKind};
+use std::str::FromStr;
+use der::asn1::ObjectIdentifier;
+use serde::{Deserialize, Serialize};
+
+use crate::certificate::extensions::extended_key_usage_name;
+use crate::certificate::purpose::decode_usage_property;
+use crate::certificate::hex;
+use crate::store::StoreEntry;
+
+
+/// `dwProvType` of the keys held by a CNG key storage provider rather than a legacy CSP.
+const CNG_PROVIDER_TYPE: u32 = 0;
+
+/// `CRYPT_MACHINE_KEYSET`, set when the key belongs to the computer rather than the user.
+const CRYPT_MACHINE_KEYSET: u32 = 0x20;
+
+/// The properties CryptoAPI keeps with a certificate in a store, besides the certificate itself.
+///
+/// The ids are the `CERT_*_PROP_ID` values of `wincrypt.h`, so the model and its decoders work on every platform
+/// while only Windows stores have the properties.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PropertyId {
+    KeyProviderInfo,
+    Sha1Hash,
+    Md5Hash,
+    EnhancedKeyUsage,
+    FriendlyName,
+    Description,
+    Archived,
+    KeyIdentifier,
+    AutoEnrollTemplate,
+    RenewalHash,
+    NCryptKeyHandle,
+}
+
+impl PropertyId {
+    pub const ALL: [PropertyId; 11] = [
+        PropertyId::KeyProviderInfo,
+        PropertyId::Sha1Hash,
+        PropertyId::Md5Hash,
+        PropertyId::EnhancedKeyUsage,
+        PropertyId::FriendlyName,
+        PropertyId::Description,
+        PropertyId::Archived,
+        PropertyId::KeyIdentifier,
+        PropertyId::AutoEnrollTemplate,
+        PropertyId::RenewalHash,
+        PropertyId::NCryptKeyHandle,
+    ];
+
+    /// The `CERT_*_PROP_ID` of the property.
+    pub fn prop_id(self) -> u32 {
+        match self {
+            PropertyId::KeyProviderInfo => 2,
+            PropertyId::Sha1Hash => 3,
+            PropertyId::Md5Hash => 4,
+            PropertyId::EnhancedKeyUsage => 9,
+            PropertyId::FriendlyName => 11,
+            PropertyId::Description => 13,
+            PropertyId::Archived => 19,
+            PropertyId::KeyIdentifier => 20,
+            PropertyId::AutoEnrollTemplate => 21,
+            PropertyId::RenewalHash => 64,
+            PropertyId::NCryptKeyHandle => 78,
+        }
+    }
+
+    /// The name of the property, also its key in [`ContextProperties`].
+    pub fn name(self) -> &'static str {
+        match self {
+            PropertyId::KeyProviderInfo => "KeyProviderInfo",
+            PropertyId::Sha1Hash => "Sha1Hash",
+            PropertyId::Md5Hash => "Md5Hash",
+            PropertyId::EnhancedKeyUsage => "EnhancedKeyUsage",
+            PropertyId::FriendlyName => "FriendlyName",
+            PropertyId::Description => "Description",
+            PropertyId::Archived => "Archived",
+            PropertyId::KeyIdentifier => "KeyIdentifier",
+            PropertyId::AutoEnrollTemplate => "AutoEnrollTemplate",
+            PropertyId::RenewalHash => "RenewalHash",
+            PropertyId::NCryptKeyHandle => "NCryptKeyHandle",
+        }
+    }
+}
+
+impl fmt::Display for PropertyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for PropertyId {
+    type Err = Error;
+
+    /// Accepts the property name in any case, with or without underscores, such as "friendly_name".
+    fn from_str(s: &str) -> Result<PropertyId> {
+        let wanted = s.replace('_', "").to_lowercase();
+        PropertyId::ALL.into_iter()
+            .find(|id| id.name().to_lowercase() == wanted)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unknown certificate property \"{}\"", s)))
+    }
+}
+
+/// Where the private key of a certificate lives, decoded from a `CRYPT_KEY_PROV_INFO`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct KeyProviderInfo {
+    pub container_name: String,
+    pub provider_name: String,
+    /// The CSP type, such as 24 for `PROV_RSA_AES`, or 0 for a CNG key storage provider.
+    pub provider_type: u32,
+    pub flags: u32,
+    /// `AT_KEYEXCHANGE` (1), `AT_SIGNATURE` (2), or `CERT_NCRYPT_KEY_SPEC` (0xFFFFFFFF). CNG keys may also use 0.
+    pub key_spec: u32,
+    pub cng: bool,
+    pub machine_key_set: bool,
+}
+
+impl KeyProviderInfo {
+    /// Decodes the property as `CertGetCertificateContextProperty` returns it: the structure followed by the strings
+    /// it points to. `address` is where the buffer was in memory, as the pointers are only valid there.
+    pub fn decode(blob: &[u8], address: u64) -> Result<KeyProviderInfo> {
+        // The layout follows the pointer width of the process that read the property.
+        let pointer = size_of::<usize>();
+        let params = (2 * pointer + 12).next_multiple_of(pointer);
+        let key_spec = params + pointer;
+        if blob.len() < key_spec + 4 {
+            return Err(Error::new(ErrorKind::InvalidData, "The key provider information is truncated"));
+        }
+
+        let provider_type = read_u32(blob, 2 * pointer)?;
+        let flags = read_u32(blob, 2 * pointer + 4)?;
+        Ok(KeyProviderInfo {
+            container_name: read_pointed_string(blob, address, read_uint(blob, 0, pointer)?)?,
+            provider_name: read_pointed_string(blob, address, read_uint(blob, pointer, pointer)?)?,
+            provider_type,
+            flags,
+            key_spec: read_u32(blob, key_spec)?,
+            cng: provider_type == CNG_PROVIDER_TYPE,
+            machine_key_set: flags & CRYPT_MACHINE_KEYSET != 0,
+        })
+    }
+}
+
+/// The decoded value of a certificate property.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PropertyValue {
+    KeyProviderInfo(KeyProviderInfo),
+    Sha1Hash(Vec<u8>),
+    Md5Hash(Vec<u8>),
+    /// The purposes the certificate is restricted to, empty when all of them are disabled.
+    EnhancedKeyUsage(Vec<ObjectIdentifier>),
+    FriendlyName(String),
+    Description(String),
+    /// Archived certificates are hidden from enumerations, the property has no data.
+    Archived,
+    KeyIdentifier(Vec<u8>),
+    /// The certificate template the certificate was enrolled with by autoenrollment.
+    AutoEnrollTemplate(String),
+    /// The SHA-1 hash of the certificate that renewed this one.
+    RenewalHash(Vec<u8>),
+    /// The NCrypt key handle cached with the certificate, only valid in the process that read it.
+    NCryptKeyHandle(u64),
+}
+
+impl PropertyValue {
+    /// Decodes a property blob. `address` is where the blob was read to, see [`KeyProviderInfo::decode`].
+    pub fn decode(id: PropertyId, blob: &[u8], address: u64) -> Result<PropertyValue> {
+        Ok(match id {
+            PropertyId::KeyProviderInfo => PropertyValue::KeyProviderInfo(KeyProviderInfo::decode(blob, address)?),
+            PropertyId::Sha1Hash => PropertyValue::Sha1Hash(blob.to_vec()),
+            PropertyId::Md5Hash => PropertyValue::Md5Hash(blob.to_vec()),
+            PropertyId::EnhancedKeyUsage => PropertyValue::EnhancedKeyUsage(decode_usage_property(blob)?),
+            PropertyId::FriendlyName => PropertyValue::FriendlyName(read_string(blob)?),
+            PropertyId::Description => PropertyValue::Description(read_string(blob)?),
+            PropertyId::Archived => PropertyValue::Archived,
+            PropertyId::KeyIdentifier => PropertyValue::KeyIdentifier(blob.to_vec()),
+            PropertyId::AutoEnrollTemplate => PropertyValue::AutoEnrollTemplate(read_string(blob)?),
+            PropertyId::RenewalHash => PropertyValue::RenewalHash(blob.to_vec()),
+            PropertyId::NCryptKeyHandle => PropertyValue::NCryptKeyHandle(read_uint(blob, 0, blob.len())?),
+        })
+    }
+}
+
+/// The properties of a certificate, serialized with the [`PropertyId`] names and hashes as upper case hex.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContextProperties {
+    pub key_provider_info: Option<KeyProviderInfo>,
+    pub sha1_hash: Option<String>,
+    pub md5_hash: Option<String>,
+    /// ExtendedKeyUsage names, or dotted OIDs for the purposes without a name.
+    pub enhanced_key_usage: Option<Vec<String>>,
+    pub friendly_name: Option<String>,
+    pub description: Option<String>,
+    pub archived: bool,
+    pub key_identifier: Option<String>,
+    pub auto_enroll_template: Option<String>,
+    pub renewal_hash: Option<String>,
+    #[serde(rename = "NCryptKeyHandle")]
+    pub ncrypt_key_handle: Option<u64>,
+}
+
+impl ContextProperties {
+    /// Reads every property the store has set on an entry.
+    pub fn read(entry: &dyn StoreEntry) -> Result<ContextProperties> {
+        let mut properties = ContextProperties::default();
+        for id in PropertyId::ALL {
+            if let Some(value) = entry.property(id)? {
+                properties.add(value);
+            }
+        }
+        Ok(properties)
+    }
+
+    pub fn add(&mut self, value: PropertyValue) {
+        match value {
+            PropertyValue::KeyProviderInfo(info) => self.key_provider_info = Some(info),
+            PropertyValue::Sha1Hash(hash) => self.sha1_hash = Some(hex(&hash)),
+            PropertyValue::Md5Hash(hash) => self.md5_hash = Some(hex(&hash)),
+            PropertyValue::EnhancedKeyUsage(purposes) => {
+                self.enhanced_key_usage = Some(purposes.iter().map(extended_key_usage_name).collect());
+            },
+            PropertyValue::FriendlyName(name) => self.friendly_name = Some(name),
+            PropertyValue::Description(description) => self.description = Some(description),
+            PropertyValue::Archived => self.archived = true,
+            PropertyValue::KeyIdentifier(identifier) => self.key_identifier = Some(hex(&identifier)),
+            PropertyValue::AutoEnrollTemplate(template) => self.auto_enroll_template = Some(template),
+            PropertyValue::RenewalHash(hash) => self.renewal_hash = Some(hex(&hash)),
+            PropertyValue::NCryptKeyHandle(handle) => self.ncrypt_key_handle = Some(handle),
+        }
+    }
+}
+
+impl FromIterator<PropertyValue> for ContextProperties {
+    fn from_iter<I: IntoIterator<Item = PropertyValue>>(values: I) -> ContextProperties {
+        let mut properties = ContextProperties::default();
+        values.into_iter().for_each(|value| properties.add(value));
+        properties
+    }
+}
+
+fn truncated() -> Error {
+    Error::new(ErrorKind::InvalidData, "The certificate property is truncated")
+}
+
+fn read_u32(blob: &[u8], offset: usize) -> Result<u32> {
+    read_uint(blob, offset, 4).map(|value| value as u32)
+}
+
+/// Reads a little endian integer of up to 8 bytes.
+fn read_uint(blob: &[u8], offset: usize, len: usize) -> Result<u64> {
+    if len > 8 {
+        return Err(Error::new(ErrorKind::InvalidData, "The certificate property is not an integer"));
+    }
+    let bytes = offset.checked_add(len).and_then(|end| blob.get(offset..end)).ok_or_else(truncated)?;
+    Ok(bytes.iter().rev().fold(0, |value, byte| value << 8 | u64::from(*byte)))
+}
+
+/// Reads a NUL terminated UTF-16 string, the way string properties are stored.
+fn read_string(blob: &[u8]) -> Result<String> {
+    let units: Vec<u16> = blob.chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    String::from_utf16(&units).map_err(|_| Error::new(ErrorKind::InvalidData, "The certificate property is not valid UTF-16"))
+}
+
+/// Reads the string a pointer of a structure property refers to, which must lie within the property itself.
+fn read_pointed_string(blob: &[u8], address: u64, pointer: u64) -> Result<String> {
+    if pointer == 0 {
+        return Ok(String::new());
+    }
+    let offset = pointer.checked_sub(address)
+        .and_then(|offset| usize::try_from(offset).ok())
+        .filter(|offset| *offset < blob.len())
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "The certificate property points outside of itself"))?;
+    read_string(&blob[offset..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Where the key provider blobs were read to, which their pointers are relative to.
+    const KSP_ADDRESS: u64 = 0x1D4A3C61230;
+    const CSP_ADDRESS: u64 = 0x1D4A3C68F90;
+
+    const KEY_PROV_INFO_KSP: &[u8] = include_bytes!("../../test/properties/key_prov_info_ksp.bin");
+    const KEY_PROV_INFO_CSP: &[u8] = include_bytes!("../../test/properties/key_prov_info_csp.bin");
+    const FRIENDLY_NAME: &[u8] = include_bytes!("../../test/properties/friendly_name.bin");
+    const DESCRIPTION: &[u8] = include_bytes!("../../test/properties/description.bin");
+    const AUTO_ENROLL: &[u8] = include_bytes!("../../test/properties/auto_enroll.bin");
+    const RENEWAL: &[u8] = include_bytes!("../../test/properties/renewal.bin");
+    const ENHKEY_USAGE: &[u8] = include_bytes!("../../test/properties/enhkey_usage.bin");
+    const NCRYPT_KEY_HANDLE: &[u8] = include_bytes!("../../test/properties/ncrypt_key_handle.bin");
+
+    #[test]
+    fn names_properties() {
+        assert_eq!("friendly_name".parse::<PropertyId>().unwrap(), PropertyId::FriendlyName);
+        assert_eq!("NCryptKeyHandle".parse::<PropertyId>().unwrap().prop_id(), 78);
+        assert!("color".parse::<PropertyId>().is_err());
+        for id in PropertyId::ALL {
+            assert_eq!(id.to_string().parse::<PropertyId>().unwrap(), id);
+        }
+    }
+
+    // The key provider blobs were read by a 64-bit process, their pointers only decode with the same layout.
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn decodes_key_provider_info() {
+        let ksp = KeyProviderInfo::decode(KEY_PROV_INFO_KSP, KSP_ADDRESS).unwrap();
+        assert_eq!(ksp.container_name, "te-ClientAuth-8d1f6e2a-3c47-4b0e-9a51-2f6c0d9e7b13");
+        assert_eq!(ksp.provider_name, "Microsoft Smart Card Key Storage Provider");
+        assert!(ksp.cng && !ksp.machine_key_set);
+        assert_eq!(ksp.key_spec, 0);
+
+        let csp = KeyProviderInfo::decode(KEY_PROV_INFO_CSP, CSP_ADDRESS).unwrap();
+        assert_eq!(csp.container_name, "{6F2E4C1B-93A7-4D58-B0E2-7C1D9A3F5E64}");
+        assert_eq!(csp.provider_name, "Microsoft Enhanced RSA and AES Cryptographic Provider");
+        assert_eq!((csp.provider_type, csp.key_spec), (24, 1));
+        assert!(!csp.cng && csp.machine_key_set);
+
+        // The same bytes read to another address point elsewhere.
+        assert!(KeyProviderInfo::decode(KEY_PROV_INFO_KSP, CSP_ADDRESS).is_err());
+        assert!(KeyProviderInfo::decode(&KEY_PROV_INFO_KSP[..40], KSP_ADDRESS).is_err());
+    }
+
+    #[test]
+    fn decodes_properties() {
+        let decode = |id, blob| PropertyValue::decode(id, blob, 0).unwrap();
+        let properties: ContextProperties = [
+            decode(PropertyId::FriendlyName, FRIENDLY_NAME),
+            decode(PropertyId::Description, DESCRIPTION),
+            decode(PropertyId::AutoEnrollTemplate, AUTO_ENROLL),
+            decode(PropertyId::RenewalHash, RENEWAL),
+            decode(PropertyId::EnhancedKeyUsage, ENHKEY_USAGE),
+            decode(PropertyId::NCryptKeyHandle, NCRYPT_KEY_HANDLE),
+            decode(PropertyId::Archived, &[]),
+        ].into_iter().collect();
+
+        assert_eq!(properties.friendly_name.as_deref(), Some("Client Authentication"));
+        assert_eq!(properties.description.as_deref(), Some("Issued by the lab CA"));
+        assert_eq!(properties.auto_enroll_template.as_deref(), Some("ClientAuth"));
+        assert_eq!(properties.renewal_hash.as_deref(), Some("5ED9E0C8E53A465D28867CB8F0BE1C1B05D01F63"));
+        assert_eq!(properties.enhanced_key_usage, Some(vec!["client_auth".to_string(), "email_protection".to_string()]));
+        assert_eq!(properties.ncrypt_key_handle, Some(0x1D4A3C70A20));
+        assert!(properties.archived);
+        assert_eq!(properties.sha1_hash, None);
+
+        let json = serde_json::to_value(&properties).unwrap();
+        assert_eq!(json["NCryptKeyHandle"], 0x1D4A3C70A20_u64);
+        assert_eq!(json["AutoEnrollTemplate"], "ClientAuth");
+    }
+
+    #[test]
+    fn rejects_malformed_properties() {
+        assert!(PropertyValue::decode(PropertyId::FriendlyName, &[0x00, 0xD8, 0x41, 0x00], 0).is_err());
+        assert!(PropertyValue::decode(PropertyId::EnhancedKeyUsage, b"usage", 0).is_err());
+        assert!(PropertyValue::decode(PropertyId::NCryptKeyHandle, &[0; 16], 0).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+//! Fake stores for testing. Built for the unit tests, and into the Python module with the `test-support` feature.
+
+use std::io::Result;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+use der::asn1::ObjectIdentifier;
+
+use crate::keys::storage::KeyStorage;
+use crate::store::{StoreEntry, StoreIter};
+use crate::store::cancel::Cancellation;
+use crate::store::properties::{PropertyId, PropertyValue};
+
+
+/// An entry that waits before every key export, like a smart card or a key protection prompt would.
+pub struct SlowEntry {
+    inner: Box<dyn StoreEntry>,
+    delay: Duration,
+}
+
+impl StoreEntry for SlowEntry {
+    fn der(&self) -> &[u8] {
+        self.inner.der()
+    }
+
+    fn friendly_name(&self) -> String {
+        self.inner.friendly_name()
+    }
+
+    fn has_private_key(&self) -> bool {
+        self.inner.has_private_key()
+    }
+
+    fn key_provider(&self) -> Option<String> {
+        self.inner.key_provider()
+    }
+
+    fn key_storage(&self) -> Result<Option<KeyStorage>> {
+        self.inner.key_storage()
+    }
+
+    fn export_pkcs12(&self) -> Result<Vec<u8>> {
+        thread::sleep(self.delay);
+        self.inner.export_pkcs12()
+    }
+
+    fn property(&self, id: PropertyId) -> Result<Option<PropertyValue>> {
+        self.inner.property(id)
+    }
+
+    fn enhanced_key_usage_property(&self) -> Result<Option<Vec<ObjectIdentifier>>> {
+        self.inner.enhanced_key_usage_property()
+    }
+}
+
+/// Wraps every entry in a [`SlowEntry`] waiting `delay` before each export.
+pub fn slow_entries(entries: StoreIter, delay: Duration) -> StoreIter {
+    Box::new(entries.map(move |inner| Box::new(SlowEntry { inner, delay }) as Box<dyn StoreEntry>))
+}
+
+/// An entry that cancels a search from inside its key export, once a given number of exports were attempted.
+pub struct CancellingEntry {
+    inner: Box<dyn StoreEntry>,
+    cancel: Cancellation,
+    exports: Arc<AtomicUsize>,
+    cancel_after: usize,
+}
+
+impl StoreEntry for CancellingEntry {
+    fn der(&self) -> &[u8] {
+        self.inner.der()
+    }
+
+    fn friendly_name(&self) -> String {
+        self.inner.friendly_name()
+    }
+
+    fn has_private_key(&self) -> bool {
+        self.inner.has_private_key()
+    }
+
+    fn key_provider(&self) -> Option<String> {
+        self.inner.key_provider()
+    }
+
+    fn key_storage(&self) -> Result<Option<KeyStorage>> {
+        self.inner.key_storage()
+    }
+
+    fn export_pkcs12(&self) -> Result<Vec<u8>> {
+        let exported = self.inner.export_pkcs12();
+        if self.exports.fetch_add(1, Ordering::SeqCst) + 1 == self.cancel_after {
+            self.cancel.cancel();
+        }
+        exported
+    }
+
+    fn property(&self, id: PropertyId) -> Result<Option<PropertyValue>> {
+        self.inner.property(id)
+    }
+
+    fn enhanced_key_usage_property(&self) -> Result<Option<Vec<ObjectIdentifier>>> {
+        self.inner.enhanced_key_usage_property()
+    }
+}
+
+/// Wraps every entry in a [`CancellingEntry`] setting `cancel` after the export numbered `cancel_after`.
+pub fn cancelling_entries(entries: StoreIter, cancel: &Cancellation, cancel_after: usize) -> StoreIter {
+    let (cancel, exports) = (cancel.clone(), Arc::new(AtomicUsize::new(0)));
+    Box::new(entries.map(move |inner| Box::new(CancellingEntry {
+        inner,
+        cancel: cancel.clone(),
+        exports: exports.clone(),
+        cancel_after,
+    }) as Box<dyn StoreEntry>))
+}
@@ -0,0 +1,632 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Utc};
+use der::asn1::ObjectIdentifier;
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::types::PyBytes;
+use pyo3::IntoPyObjectExt;
+use sha2::{Digest, Sha256};
+
+use crate::cache::{CacheKey, ChangeDetector, PathChangeDetector, QueryCache};
+use crate::certificate::{Certificate, hex};
+use crate::certificate::extensions::AltName;
+use crate::certificate::purpose::{EnhancedKeyUsage, purpose_oid};
+use crate::exceptions::{CertKeyMismatch, CertNotExportable, CertNotFound, CertSearchCancelled};
+use crate::file_store::jks_store::JksStore;
+use crate::file_store::pkcs12_store::Pkcs12Store;
+use crate::keys::openssh::authorized_key;
+use crate::keys::verify::{KeyMismatch, verify_issued_by};
+use crate::keys::storage::KeyStorage;
+use crate::metadata::{CertificateMetadata, KeyMetadata, StoreLocation};
+use crate::metadata_reader::{json_string, to_python, yaml_string};
+use crate::pki_reader::{CertificateCandidate, select_candidates, verify_with_certificate};
+use crate::ranking::Candidate;
+use crate::store::{StoreEntry, StoreIter, dotnet_date_string};
+use crate::store::cancel::Cancellation;
+use crate::store::export::{ExportedCertificate, export_entries};
+use crate::store::memory::MemoryStore;
+use crate::store::path::open_path;
+use crate::store::properties::ContextProperties;
+
+
+#[pyclass(name = "CertificateIterator", module = "py_cert_store")]
+/// Lazily enumerates a certificate store, yielding a `StoreCertificate` per certificate.
+///
+/// The store stays open until the enumeration is exhausted, `close()` is called or the `with` block ends.
+pub struct CertificateIterator {
+    inner: Mutex<Option<StoreIter>>,
+    location: StoreLocation,
+}
+
+impl CertificateIterator {
+    pub(crate) fn new(entries: StoreIter, location: StoreLocation) -> CertificateIterator {
+        CertificateIterator { inner: Mutex::new(Some(entries)), location }
+    }
+}
+
+#[pymethods]
+impl CertificateIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<StoreCertificate>> {
+        py.detach(|| {
+            let mut inner = lock(&self.inner)?;
+            let entry = match inner.as_mut() {
+                Some(entries) => entries.next(),
+                None => return Ok(None),
+            };
+
+            match entry {
+                Some(entry) => StoreCertificate::new(entry, self.location.clone()).map(Some),
+                None => {
+                    // Close the store as soon as the enumeration is exhausted.
+                    *inner = None;
+                    Ok(None)
+                },
+            }
+        })
+    }
+
+    /// Closes the store. Certificates already yielded stay usable.
+    fn close(&self) -> PyResult<()> {
+        *lock(&self.inner)? = None;
+        Ok(())
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, _args: &Bound<'_, pyo3::types::PyTuple>) -> PyResult<bool> {
+        self.close()?;
+        Ok(false)
+    }
+}
+
+#[pyclass(name = "StoreCertificate", module = "py_cert_store", frozen)]
+/// A certificate from a store. The private key is only exported by `export_pfx()` or `to_dict()`.
+pub struct StoreCertificate {
+    pub(crate) certificate: Certificate,
+    #[pyo3(get)]
+    pub(crate) friendly_name: String,
+    #[pyo3(get)]
+    pub(crate) has_private_key: bool,
+    #[pyo3(get)]
+    key_provider: Option<String>,
+    location: StoreLocation,
+    entry: Mutex<Option<Box<dyn StoreEntry>>>,
+}
+
+impl StoreCertificate {
+    pub(crate) fn export(&self, py: Python<'_>) -> PyResult<ExportedCertificate> {
+        py.detach(|| self.export_detached())
+    }
+
+    /// Exports the certificate and its private key, for callers that already released the GIL.
+    pub(crate) fn export_detached(&self) -> PyResult<ExportedCertificate> {
+        let entry = lock(&self.entry)?;
+        let entry = entry.as_ref().ok_or_else(|| PyRuntimeError::new_err("The certificate has been closed."))?;
+        ExportedCertificate::export(entry.as_ref()).map_err(search_error)
+    }
+
+    /// Describes the private key without exporting it. Once the certificate is closed only its provider is known.
+    fn key_storage(&self, py: Python<'_>) -> PyResult<Option<KeyStorage>> {
+        if !self.has_private_key {
+            return Ok(None);
+        }
+        py.detach(|| {
+            let entry = lock(&self.entry)?;
+            let storage = entry.as_ref().and_then(|entry| entry.key_storage().ok().flatten());
+            Ok(Some(storage.unwrap_or_else(|| KeyStorage::provider(self.key_provider.clone()))))
+        })
+    }
+
+    /// Describes the certificate, exporting the private key only when `include_private_key` is set.
+    pub(crate) fn metadata_with(&self, py: Python<'_>, include_certificate: bool, include_private_key: bool) -> PyResult<CertificateMetadata> {
+        let mut metadata = CertificateMetadata::new(&self.certificate)
+            .map_err(|err| PyValueError::new_err(err.to_string()))?
+            .friendly_name(&self.friendly_name)
+            .store(self.location.clone());
+        if let Some(storage) = self.key_storage(py)? {
+            metadata = metadata.key_storage(&storage);
+        }
+        if include_certificate {
+            metadata = metadata.with_certificate(&self.certificate).map_err(|err| PyValueError::new_err(err.to_string()))?;
+        }
+        if include_private_key {
+            metadata = metadata.with_pkcs12(&self.export(py)?.pfx);
+        }
+        Ok(metadata)
+    }
+
+    pub(crate) fn new(entry: Box<dyn StoreEntry>, location: StoreLocation) -> PyResult<StoreCertificate> {
+        let certificate = Certificate::from_der(entry.der())
+            .map_err(|err| PyValueError::new_err(format!("Could not parse the certificate: {}", err)))?;
+        Ok(StoreCertificate {
+            certificate,
+            friendly_name: entry.friendly_name(),
+            has_private_key: entry.has_private_key(),
+            key_provider: entry.key_provider(),
+            location,
+            entry: Mutex::new(Some(entry)),
+        })
+    }
+}
+
+#[pymethods]
+impl StoreCertificate {
+    #[getter]
+    fn thumbprint(&self) -> String {
+        self.certificate.thumbprint()
+    }
+
+    #[getter]
+    fn subject(&self) -> String {
+        self.certificate.subject().to_dotnet()
+    }
+
+    #[getter]
+    fn issuer(&self) -> String {
+        self.certificate.issuer().to_dotnet()
+    }
+
+    #[getter]
+    fn not_before(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from(self.certificate.not_before())
+    }
+
+    #[getter]
+    fn not_after(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from(self.certificate.not_after())
+    }
+
+    #[getter]
+    fn alt_names(&self) -> PyResult<Vec<String>> {
+        self.certificate.alt_names()
+            .map(|names| names.iter().map(AltName::to_string).collect())
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    #[getter]
+    fn certificate<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.certificate.as_der())
+    }
+
+    /// The public key as an OpenSSH `authorized_keys` line.
+    #[pyo3(signature = (comment=None))]
+    fn ssh_public_key(&self, comment: Option<&str>) -> PyResult<String> {
+        authorized_key(&self.certificate.as_x509().tbs_certificate.subject_public_key_info, comment)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// Describes the key of the certificate: its algorithm and size, and for a private key its provider, whether it
+    /// is held in hardware, and its export and UI policies. The private key is not exported.
+    fn key_metadata(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let key = match self.key_storage(py)? {
+            Some(storage) => KeyMetadata::new(&self.certificate).with_storage(&storage),
+            None => KeyMetadata::new(&self.certificate),
+        };
+        to_python(py, &key)
+    }
+
+    /// Verifies a signature over `data` with the certificate's public key, see `certificate_verifies_signature`.
+    #[pyo3(signature = (data, signature, hash_alg="sha256", padding=None))]
+    fn verify(&self, data: &[u8], signature: &[u8], hash_alg: &str, padding: Option<&str>) -> PyResult<bool> {
+        verify_with_certificate(&self.certificate, data, signature, hash_alg, padding)
+    }
+
+    /// Checks that this certificate was signed by `issuer`, a `StoreCertificate` or certificate bytes.
+    fn is_signed_by(&self, issuer: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let verified = match issuer.cast::<StoreCertificate>() {
+            Ok(issuer) => verify_issued_by(&self.certificate, &issuer.get().certificate),
+            Err(_) => {
+                let issuer = Certificate::from_bytes(&issuer.extract::<Vec<u8>>()?)
+                    .map_err(|err| PyValueError::new_err(format!("Could not parse the certificate: {}", err)))?;
+                verify_issued_by(&self.certificate, &issuer)
+            },
+        };
+        verified.map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// The properties the store keeps with the certificate, such as the key provider of a Windows certificate.
+    /// Only Windows stores have properties.
+    fn properties(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let properties = py.detach(|| {
+            let entry = lock(&self.entry)?;
+            let entry = entry.as_ref().ok_or_else(|| PyRuntimeError::new_err("The certificate has been closed."))?;
+            Ok::<_, PyErr>(ContextProperties::read(entry.as_ref())?)
+        })?;
+        to_python(py, &properties)
+    }
+
+    /// Exports the certificate and its private key as a PKCS#12 file without a password.
+    fn export_pfx<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let exported = self.export(py)?;
+        Ok(PyBytes::new(py, &exported.pfx))
+    }
+
+    /// The dictionary returned by the `find_windows_cert_*` functions, exporting the private key.
+    fn to_dict(&self, py: Python<'_>) -> PyResult<HashMap<String, Py<PyAny>>> {
+        let exported = self.export(py)?;
+        exported_to_dict(py, &exported, &self.location)
+    }
+
+    /// The certificate metadata as a dictionary, following the schema returned by `metadata_schema()`.
+    #[pyo3(signature = (include_certificate=false, include_private_key=false))]
+    fn metadata(&self, py: Python<'_>, include_certificate: bool, include_private_key: bool) -> PyResult<Py<PyAny>> {
+        to_python(py, &self.metadata_with(py, include_certificate, include_private_key)?)
+    }
+
+    /// The certificate metadata as JSON. The private key is only exported with `include_private_key=True`.
+    #[pyo3(signature = (include_certificate=false, include_private_key=false, indent=None))]
+    fn to_json(&self, py: Python<'_>, include_certificate: bool, include_private_key: bool, indent: Option<usize>) -> PyResult<String> {
+        json_string(&self.metadata_with(py, include_certificate, include_private_key)?, indent)
+    }
+
+    /// The certificate metadata as YAML. The private key is only exported with `include_private_key=True`.
+    #[pyo3(signature = (include_certificate=false, include_private_key=false))]
+    fn to_yaml(&self, py: Python<'_>, include_certificate: bool, include_private_key: bool) -> PyResult<String> {
+        yaml_string(&self.metadata_with(py, include_certificate, include_private_key)?)
+    }
+
+    /// Releases the certificate in the store. The certificate details stay readable, but the key can no
+    /// longer be exported.
+    fn close(&self) -> PyResult<()> {
+        *lock(&self.entry)? = None;
+        Ok(())
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, _args: &Bound<'_, pyo3::types::PyTuple>) -> PyResult<bool> {
+        self.close()?;
+        Ok(false)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("StoreCertificate(subject={:?}, thumbprint={:?})", self.subject(), self.thumbprint())
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificates, password=""))]
+/// Lazily iterate over certificates held in memory, such as certificate files and PKCS#12 files.
+pub fn iter_certificates(py: Python<'_>, certificates:Vec<Vec<u8>>, password:&str) -> PyResult<CertificateIterator> {
+    let store = py.detach(|| memory_store(&certificates, password))?;
+    Ok(CertificateIterator::new(store.entries(), StoreLocation::memory()))
+}
+
+#[pyclass(name = "CancellationToken", module = "py_cert_store", frozen)]
+/// Stops a store search that is running on another thread, see the `cancel` argument of the search functions.
+pub struct CancellationToken(Cancellation);
+
+impl CancellationToken {
+    /// The cancellation to hand to a search, one that is never cancelled without a token.
+    pub(crate) fn cancellation(token: Option<&CancellationToken>) -> Cancellation {
+        token.map(|token| token.0.clone()).unwrap_or_default()
+    }
+}
+
+#[pymethods]
+impl CancellationToken {
+    #[new]
+    fn new() -> CancellationToken {
+        CancellationToken(Cancellation::new())
+    }
+
+    /// Stops the searches using this token before they export another key. They raise `CertSearchCancelled`.
+    fn cancel(&self) {
+        self.0.cancel();
+    }
+
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CancellationToken(cancelled={})", if self.cancelled() { "True" } else { "False" })
+    }
+}
+
+#[pyclass(name = "CertificateCache", module = "py_cert_store", frozen)]
+/// Keeps the results of store searches, see the `cache` argument of the search functions.
+pub struct CertificateCache(QueryCache);
+
+#[pymethods]
+impl CertificateCache {
+    #[new]
+    #[pyo3(signature = (ttl=300.0))]
+    fn new(ttl: f64) -> PyResult<CertificateCache> {
+        let ttl = Duration::try_from_secs_f64(ttl).map_err(|_| PyValueError::new_err("ttl must be a positive number of seconds"))?;
+        Ok(CertificateCache(QueryCache::new(ttl)))
+    }
+
+    #[getter]
+    fn ttl(&self) -> f64 {
+        self.0.ttl().as_secs_f64()
+    }
+
+    /// The number of cache hits and misses, and of cached searches.
+    #[getter]
+    fn stats(&self) -> HashMap<String, u64> {
+        let stats = self.0.stats();
+        HashMap::from([
+            ("Hits".to_string(), stats.hits),
+            ("Misses".to_string(), stats.misses),
+            ("Entries".to_string(), stats.entries as u64),
+        ])
+    }
+
+    /// Drops the cached searches of a location (such as "CurrentUser") and/or a store, or all of them.
+    #[pyo3(signature = (location=None, store=None))]
+    fn invalidate(&self, location: Option<&str>, store: Option<&str>) -> usize {
+        self.0.invalidate_store(location, store)
+    }
+
+    fn clear(&self) {
+        self.0.clear();
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.stats().entries
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CertificateCache(ttl={}, entries={})", self.ttl(), self.__len__())
+    }
+}
+
+/// Runs a search without the GIL, through the cache when one is given.
+pub(crate) fn cached_search<D, S>(
+    py: Python<'_>, cache: Option<&CertificateCache>, key: CacheKey, detector: D, search: S
+) -> PyResult<Vec<ExportedCertificate>>
+where
+    D: FnOnce() -> Option<Box<dyn ChangeDetector>> + Send,
+    S: FnOnce() -> PyResult<Vec<ExportedCertificate>> + Send,
+{
+    py.detach(|| match cache {
+        Some(cache) => cache.0.get_or_search(key, detector, search),
+        None => search(),
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (certificates, password="", host=None, selection=None, cancel=None, purpose=None))]
+/// Search certificates held in memory the way `find_windows_cert_all` searches a store.
+pub fn find_certificates(
+    py: Python<'_>, certificates:Vec<Vec<u8>>, password:&str, host:Option<&str>, selection:Option<Bound<'_, PyAny>>,
+    cancel:Option<PyRef<'_, CancellationToken>>, purpose:Option<&str>
+) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+    let cancel = CancellationToken::cancellation(cancel.as_deref());
+    let purpose = search_purpose(purpose)?;
+
+    let exported = py.detach(|| {
+        let store = memory_store(&certificates, password)?;
+        search_memory_store(&store, host, purpose.as_ref(), &cancel)
+    })?;
+
+    finish_search(py, exported, selection.as_ref(), &StoreLocation::memory())
+}
+
+#[cfg(feature = "test-support")]
+#[pyfunction]
+#[pyo3(name = "_find_slow_certificates", signature = (certificates, password="", export_delay=0.0, selection=None, cancel=None))]
+/// `find_certificates` over entries that wait `export_delay` seconds before each key export, standing in for a slow
+/// smart card. Only built with the `test-support` feature, for test/async_lookup.py.
+pub fn find_slow_certificates(
+    py: Python<'_>, certificates:Vec<Vec<u8>>, password:&str, export_delay:f64, selection:Option<Bound<'_, PyAny>>,
+    cancel:Option<PyRef<'_, CancellationToken>>
+) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+    let export_delay = Duration::try_from_secs_f64(export_delay)
+        .map_err(|_| PyValueError::new_err("export_delay must be a positive number of seconds"))?;
+    let cancel = CancellationToken::cancellation(cancel.as_deref());
+
+    let exported = py.detach(|| {
+        let store = memory_store(&certificates, password)?;
+        search_entries(crate::store::test_support::slow_entries(store.entries(), export_delay), None, None, &cancel)
+    })?;
+
+    finish_search(py, exported, selection.as_ref(), &StoreLocation::memory())
+}
+
+#[pyfunction]
+#[pyo3(signature = (path, password="", host=None, selection=None, cancel=None, cache=None, purpose=None))]
+/// Search a certificate file, PKCS#12 file or directory of certificates the way `find_windows_cert_all` searches a store.
+#[allow(clippy::too_many_arguments)]
+pub fn find_certificates_in_path(
+    py: Python<'_>, path:PathBuf, password:&str, host:Option<&str>, selection:Option<Bound<'_, PyAny>>,
+    cancel:Option<PyRef<'_, CancellationToken>>, cache:Option<PyRef<'_, CertificateCache>>, purpose:Option<&str>
+) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+    let cancel = CancellationToken::cancellation(cancel.as_deref());
+    let purpose = search_purpose(purpose)?;
+    // The password is part of the key, as files it cannot open are left out of the results.
+    let password_digest = hex(&Sha256::digest(password.as_bytes()));
+    let query = format!("host={:?}&purpose={:?}&password={}", host, purpose.map(|purpose| purpose.to_string()), password_digest);
+    let key = CacheKey::new("file", &path.to_string_lossy(), &query);
+
+    let exported = cached_search(
+        py, cache.as_deref(), key,
+        || Some(Box::new(PathChangeDetector::new(&path)) as Box<dyn ChangeDetector>),
+        || {
+            // Raised as the matching OSError, such as FileNotFoundError.
+            let store = open_path(&path, password)
+                .map_err(|err| std::io::Error::new(err.kind(), format!("Could not read {}: {}", path.display(), err)))?;
+            search_memory_store(&store, host, purpose.as_ref(), &cancel)
+        },
+    )?;
+
+    finish_search(py, exported, selection.as_ref(), &StoreLocation::file(&path.to_string_lossy()))
+}
+
+/// Exports the currently valid certificates of a store, optionally only those valid for `host` and `purpose`.
+pub(crate) fn search_memory_store(
+    store: &MemoryStore, host: Option<&str>, purpose: Option<&ObjectIdentifier>, cancel: &Cancellation
+) -> PyResult<Vec<ExportedCertificate>> {
+    search_entries(store.entries(), host, purpose, cancel)
+}
+
+/// Exports the entries that are valid now and match the host and purpose criteria.
+fn search_entries(
+    entries: StoreIter, host: Option<&str>, purpose: Option<&ObjectIdentifier>, cancel: &Cancellation
+) -> PyResult<Vec<ExportedCertificate>> {
+    let now = SystemTime::now();
+    let matching = entries.filter(|entry| {
+        Certificate::from_der(entry.der())
+            .and_then(|cert| {
+                let time_valid = cert.not_before() <= now && now <= cert.not_after();
+                Ok(time_valid
+                    && host.map(|host| cert.matches_host(host)).transpose()?.unwrap_or(true)
+                    && allows_purpose(&cert, || entry.enhanced_key_usage_property(), purpose)?)
+            })
+            .unwrap_or(false)
+    });
+    export_entries(matching, cancel).map_err(search_error)
+}
+
+/// Resolves the `purpose` argument of the search functions, such as "client_auth" or a dotted OID.
+pub(crate) fn search_purpose(purpose: Option<&str>) -> PyResult<Option<ObjectIdentifier>> {
+    purpose.map(purpose_oid).transpose().map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// Checks the purpose criterion of the search functions against the ExtendedKeyUsage of a certificate and the
+/// usage property its store assigned to it, which is only read when there is a purpose to check.
+pub(crate) fn allows_purpose<P>(certificate: &Certificate, property: P, purpose: Option<&ObjectIdentifier>) -> std::io::Result<bool>
+where
+    P: FnOnce() -> std::io::Result<Option<Vec<ObjectIdentifier>>>,
+{
+    match purpose {
+        Some(purpose) => Ok(EnhancedKeyUsage::new(certificate, property()?)?.allows(purpose)),
+        None => Ok(true),
+    }
+}
+
+/// Applies the `selection` argument to the exported certificates and converts the chosen ones to the
+/// dictionaries returned by the search functions.
+pub(crate) fn finish_search(
+    py: Python<'_>, exported: Vec<ExportedCertificate>, selection: Option<&Bound<'_, PyAny>>, location: &StoreLocation
+) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+    select_exported(py, &exported, selection)?.into_iter()
+        .map(|index| exported_to_dict(py, &exported[index], location))
+        .collect()
+}
+
+/// Applies the `selection` argument to the exported certificates, returning the indexes of the chosen ones in
+/// their original order.
+pub(crate) fn select_exported(
+    py: Python<'_>, exported: &[ExportedCertificate], selection: Option<&Bound<'_, PyAny>>
+) -> PyResult<Vec<usize>> {
+    let selected: Vec<usize> = if selection.is_none_or(|selection| selection.is_none()) {
+        (0..exported.len()).collect()
+    } else {
+        let candidates = exported.iter().enumerate()
+            .map(|(index, exported)| {
+                let mut candidate = Candidate::new(exported.certificate.clone(), true);
+                candidate.key_provider = exported.key_provider.clone();
+                CertificateCandidate::new(index, candidate, &exported.friendly_name)
+            })
+            .collect();
+        select_candidates(py, selection, candidates)?
+    };
+
+    if selected.is_empty() {
+        return Err(CertNotFound::new_err("No certificate was selected."));
+    }
+
+    Ok((0..exported.len()).filter(|index| selected.contains(index)).collect())
+}
+
+/// Converts an exported certificate to the dictionary returned by the search functions.
+pub(crate) fn exported_to_dict(
+    py: Python<'_>, exported: &ExportedCertificate, location: &StoreLocation
+) -> PyResult<HashMap<String, Py<PyAny>>> {
+    let certificate = &exported.certificate;
+    let alt_names: Vec<String> = certificate.alt_names().unwrap_or_default().iter().map(AltName::to_string).collect();
+
+    let mut dict = HashMap::new();
+    dict.insert("FriendlyName".to_string(), exported.friendly_name.as_str().into_py_any(py)?);
+    dict.insert("Name".to_string(), certificate.subject().to_dotnet().into_py_any(py)?);
+    dict.insert("IssuerName".to_string(), certificate.issuer().to_dotnet().into_py_any(py)?);
+    dict.insert("AltNames".to_string(), alt_names.into_py_any(py)?);
+    dict.insert("KeyProvider".to_string(), exported.key_provider.as_deref().unwrap_or_default().into_py_any(py)?);
+    dict.insert("Key".to_string(), to_python(py, &exported_key(exported))?);
+    dict.insert("EffectiveDateString".to_string(), dotnet_date_string(certificate.not_before()).into_py_any(py)?);
+    dict.insert("ExpirationDateString".to_string(), dotnet_date_string(certificate.not_after()).into_py_any(py)?);
+    dict.insert("StoreLocation".to_string(), to_python(py, location)?);
+    dict.insert("cert".to_string(), PyBytes::new(py, &exported.pfx).into_py_any(py)?);
+    Ok(dict)
+}
+
+/// Describes the key of an exported certificate. Its private key was exported, so it is known to be present.
+fn exported_key(exported: &ExportedCertificate) -> KeyMetadata {
+    let storage = exported.key_storage.clone().unwrap_or_else(|| KeyStorage::provider(exported.key_provider.clone()));
+    KeyMetadata::new(&exported.certificate).with_storage(&storage)
+}
+
+/// Maps the errors of a store search to the exceptions raised from Python.
+pub(crate) fn search_error(err: std::io::Error) -> PyErr {
+    if let Some(mismatch) = KeyMismatch::from_error(&err) {
+        return CertKeyMismatch::new_err(mismatch.to_string());
+    }
+
+    match err.kind() {
+        ErrorKind::NotFound => CertNotFound::new_err(err.to_string()),
+        ErrorKind::PermissionDenied => CertNotExportable::new_err(err.to_string()),
+        ErrorKind::Interrupted => CertSearchCancelled::new_err(err.to_string()),
+        _ => PyRuntimeError::new_err(err.to_string()),
+    }
+}
+
+fn memory_store(certificates: &[Vec<u8>], password: &str) -> PyResult<MemoryStore> {
+    let mut store = MemoryStore::new();
+    for data in certificates {
+        match Certificate::from_bytes(data) {
+            Ok(cert) => store.add_certificate(cert, ""),
+            Err(_) if JksStore::is_keystore(data) => {
+                let password = Some(password).filter(|password| !password.is_empty());
+                let keystore = JksStore::from_bytes(data, password, None)
+                    .map_err(|err| PyValueError::new_err(format!("Could not read the keystore: {}", err)))?;
+                store.extend(keystore.to_memory_store());
+            },
+            Err(_) => {
+                let pfx = Pkcs12Store::from_der(data, password)
+                    .map_err(|err| PyValueError::new_err(format!("Could not read the certificate: {}", err)))?;
+                store.extend(MemoryStore::from_pkcs12(&pfx).map_err(|err| PyValueError::new_err(err.to_string()))?);
+            },
+        }
+    }
+    Ok(store)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> PyResult<MutexGuard<'_, T>> {
+    mutex.lock().map_err(|_| PyRuntimeError::new_err("The certificate store was poisoned by an earlier panic."))
+}
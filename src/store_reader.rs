@@ -19,152 +19,140 @@
 
 
 use std::collections::HashMap;
+use der::asn1::ObjectIdentifier;
 use pyo3::prelude::*; // TODO: properly import this module
 use pyo3::exceptions::{PyOSError, PyRuntimeError};
-use pyo3::types::{PyString, PyBytes};
 
 use crate::windows_store::cert_store::CertStore;
 use crate::windows_store::cert_context::CertContext;
-use crate::exceptions::{CertNotExportable, CertNotFound};
+use crate::windows_store::change_notify::StoreChangeNotifier;
+use crate::windows_store::store_entry::WindowsEntry;
+use crate::cache::{CacheKey, ChangeDetector};
+use crate::certificate::Certificate;
+use crate::store::StoreEntry;
+use crate::store::export::export_entries;
+use crate::metadata::StoreLocation;
+use crate::store_iterator::{CancellationToken, CertificateCache, CertificateIterator, allows_purpose, cached_search, finish_search, search_error, search_purpose};
 
 
 #[pyfunction]
-#[pyo3(signature = (store="My", user="CurrentUser", extension_oid=None, extension_value=None))]
+#[pyo3(signature = (store="My", user="CurrentUser", extension_oid=None, extension_value=None, host=None, selection=None, cancel=None, cache=None, purpose=None))]
 /// Find a certificate in the Windows Certificate Store by its extension OID and value.
-pub fn find_windows_cert_by_extension(store:&str, user:&str, extension_oid:Option<&str>, extension_value:Option<&str>) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+/// When `host` is given, only certificates valid for that DNS name or IP address are returned.
+/// When `purpose` is given, such as "client_auth", only certificates whose ExtendedKeyUsage and store properties
+/// allow it are returned.
+/// `selection` picks from several matches, see `select_certificates`, and `cancel` stops the search from another thread.
+/// With a `cache`, repeated searches reuse the exported certificates until the store changes.
+#[allow(clippy::too_many_arguments)]
+pub fn find_windows_cert_by_extension(
+    py: Python<'_>, store:&str, user:&str, extension_oid:Option<&str>, extension_value:Option<&str>, host:Option<&str>,
+    selection:Option<Bound<'_, PyAny>>, cancel:Option<PyRef<'_, CancellationToken>>, cache:Option<PyRef<'_, CertificateCache>>,
+    purpose:Option<&str>
+) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
     if !cfg!(windows) {
         return Err(PyOSError::new_err("The \"find_windows_cert_by_extension\" function can only be called from a Windows computer."));
     }
 
-    let certs = match get_certs_from_store(store, user) {
-        Ok(certs) => certs,
-        Err(err) => {
-            return Err(err);
-        }
-    };
+    let cancel = CancellationToken::cancellation(cancel.as_deref());
+    let purpose = search_purpose(purpose)?;
+    let query = format!(
+        "extension_oid={:?}&extension_value={:?}&host={:?}&purpose={:?}",
+        extension_oid, extension_value, host, purpose.map(|purpose| purpose.to_string())
+    );
 
-    let mut valid_certificates: Vec<CertContext> = Vec::new();
+    // The store is enumerated and exported without the GIL, the results are converted to Python objects at the end.
+    let exported = cached_search(py, cache.as_deref(), cache_key(store, user, &query), || change_detector(store, user), || {
+        let certs = get_certs_from_store(store, user)?;
 
-    for cert in certs.certs() {
+        let mut valid_certificates: Vec<Box<dyn StoreEntry>> = Vec::new();
 
-        match cert.is_time_valid() {
-            Ok(valid) => {
-                if !valid {
-                    // The certificate is not time valid, so close the certificate and continue to the next one.
-                    cert.close();
-                    continue;
-                }
-            },
-            Err(_) => {
-                // There was an error checking the time validity, so close the certificate and continue to the next one.
+        for cert in certs.certs() {
+            if cancel.is_cancelled() {
                 cert.close();
-                continue
+                break;
+            }
+
+            if !is_time_valid(&cert) || !is_valid_for_host(&cert, host) || !is_valid_for_purpose(&cert, purpose.as_ref()) {
+                cert.close();
+                continue;
             }
-        }
 
-        match extension_oid {
-            Some(oid) => {
-                match cert.has_extension_with_property(oid.as_ptr() as *const _, extension_value) { // TODO: Alter this function to take other parameters
-                    Ok(has) => {
-                        if has {
-                            valid_certificates.push(cert);
-                        } else {
-                            // The certificate does not have the requested extension, so close it and continue to the next one.
-                            cert.close();
-                        }
-                        continue;
-                    },
-                    Err(_) => {
-                        // There was an error checking the extension, so close the certificate and continue to the next one.
+            match extension_oid {
+                Some(oid) => {
+                    // TODO: Alter this function to take other parameters
+                    match cert.has_extension_with_property(oid.as_ptr() as *const _, extension_value) {
+                        Ok(true) => valid_certificates.push(Box::new(WindowsEntry::new(cert))),
+                        // The certificate does not have the requested extension, or it could not be checked.
+                        _ => cert.close(),
+                    }
+                },
+                None => {
+                    if !extension_value.is_none() {
+                        // No extension OID is provided, assume that only cares about the time validity of the certificate.
+                        valid_certificates.push(Box::new(WindowsEntry::new(cert)));
+                    } else {
                         cert.close();
-                        continue;
                     }
-                };
-            },
-            None => {
-                if !extension_value.is_none() {
-                    // No extension OID is provided, assume that only cares about the time validity of the certificate.
-                    valid_certificates.push(cert);
                 }
-            }
-        };
-    }
-
-    if valid_certificates.len() == 0 {
-        return Err(CertNotFound::new_err("No valid certificates found."));
-    }
-
-    let mut output_dicts: Vec<HashMap<String, Py<PyAny>>> = Vec::new();
-
-    for cert in valid_certificates {
-        let output_dict = build_dict_from_cert(&cert).unwrap_or_default();
-        if !output_dict.is_empty() {
-            output_dicts.push(output_dict);
+            };
         }
-        cert.close();
-    };
 
-    if output_dicts.len() == 0 {
-        return Err(CertNotExportable::new_err("No Exportable certificates found."));
-    }
+        export_entries(valid_certificates, &cancel).map_err(search_error)
+    })?;
 
-    return Ok(output_dicts);
+    finish_search(py, exported, selection.as_ref(), &StoreLocation::windows(user, store))
 }
 
 #[pyfunction]
-#[pyo3(signature = (store="My", user="CurrentUser"))]
-pub fn find_windows_cert_all(store:&str, user:&str) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
+#[pyo3(signature = (store="My", user="CurrentUser", host=None, selection=None, cancel=None, cache=None, purpose=None))]
+/// Find all valid certificates in the Windows Certificate Store, optionally only those valid for `host` and `purpose`.
+/// `selection` picks from several matches, see `select_certificates`, and `cancel` stops the search from another thread.
+/// With a `cache`, repeated searches reuse the exported certificates until the store changes.
+#[allow(clippy::too_many_arguments)]
+pub fn find_windows_cert_all(
+    py: Python<'_>, store:&str, user:&str, host:Option<&str>, selection:Option<Bound<'_, PyAny>>,
+    cancel:Option<PyRef<'_, CancellationToken>>, cache:Option<PyRef<'_, CertificateCache>>, purpose:Option<&str>
+) -> PyResult<Vec<HashMap<String, Py<PyAny>>>> {
     if !cfg!(windows) {
         return Err(PyOSError::new_err("The \"find_windows_cert_all\" function can only be called from a Windows computer."));
     }
 
-    let certs = match get_certs_from_store(store, user) {
-        Ok(certs) => certs,
-        Err(err) => {
-            return Err(err);
-        }
-    };
+    let cancel = CancellationToken::cancellation(cancel.as_deref());
+    let purpose = search_purpose(purpose)?;
+    let query = format!("all&host={:?}&purpose={:?}", host, purpose.map(|purpose| purpose.to_string()));
 
-    let mut valid_certificates: Vec<CertContext> = Vec::new();
+    // The store is enumerated and exported without the GIL, the results are converted to Python objects at the end.
+    let exported = cached_search(py, cache.as_deref(), cache_key(store, user, &query), || change_detector(store, user), || {
+        let certs = get_certs_from_store(store, user)?;
 
-    for cert in certs.certs() {
+        let mut valid_certificates: Vec<Box<dyn StoreEntry>> = Vec::new();
 
-        match cert.is_time_valid() {
-            Ok(valid) => {
-                if !valid {
-                    // The certificate is not time valid, so close the certificate and continue to the next one.
-                    cert.close();
-                    continue;
-                }
-            },
-            Err(_) => {
-                // There was an error checking the time validity, so close the certificate and continue to the next one.
+        for cert in certs.certs() {
+            if cancel.is_cancelled() {
                 cert.close();
-                continue;
+                break;
             }
-        }
-        valid_certificates.push(cert);
-    }
-
-    if valid_certificates.is_empty() {
-        return Err(CertNotFound::new_err("No valid certificates found."));
-    }
 
-    let mut output_dicts: Vec<HashMap<String, Py<PyAny>>> = Vec::new();
+            if !is_time_valid(&cert) || !is_valid_for_host(&cert, host) || !is_valid_for_purpose(&cert, purpose.as_ref()) {
+                cert.close();
+                continue;
+            }
 
-    for cert in valid_certificates {
-        let output_dict = build_dict_from_cert(&cert).unwrap_or_default();
-        if !output_dict.is_empty() {
-            output_dicts.push(output_dict);
+            valid_certificates.push(Box::new(WindowsEntry::new(cert)));
         }
-        cert.close();
-    };
 
-    if output_dicts.is_empty() {
-        return Err(CertNotExportable::new_err("No Exportable certificates found."));
-    }
+        export_entries(valid_certificates, &cancel).map_err(search_error)
+    })?;
 
-    return Ok(output_dicts);
+    finish_search(py, exported, selection.as_ref(), &StoreLocation::windows(user, store))
+}
+
+#[pyfunction]
+#[pyo3(signature = (store="My", user="CurrentUser"))]
+/// Lazily iterate over every certificate in the Windows Certificate Store, exporting keys only when asked to.
+pub fn iter_windows_certs(py: Python<'_>, store:&str, user:&str) -> PyResult<CertificateIterator> {
+    let certs = py.detach(|| get_certs_from_store(store, user))?;
+    Ok(CertificateIterator::new(certs.into_entries(), StoreLocation::windows(user, store)))
 }
 
 fn get_certs_from_store(store:&str, user:&str) -> Result<CertStore, PyErr>{
@@ -199,55 +187,37 @@ fn get_certs_from_store(store:&str, user:&str) -> Result<CertStore, PyErr>{
     return Ok(certs)
 }
 
-fn build_dict_from_cert(cert: &CertContext) -> PyResult<HashMap<String, Py<PyAny>>> {
-    let mut dict = HashMap::new();
-
-    match cert.is_exportable() {
-        Ok(exportable) => {
-            if !exportable {
-                return Err(CertNotExportable::new_err("The certificate is not exportable."));
-            }
-        },
-        Err(_) => {
-            return Err(PyRuntimeError::new_err("Unable to determine if the certificate is exportable."));
-        }
-    }
-
-    let friendly_name = cert.friendly_name().unwrap_or("".to_string());
-    dict.insert("FriendlyName".to_string(), create_python_string(&friendly_name));
-
-    let subject = cert.name().unwrap_or("".to_string());
-    dict.insert("Name".to_string(), create_python_string(&subject));
-
-    let issuer = cert.issuer().unwrap_or("".to_string());
-    dict.insert("IssuerName".to_string(), create_python_string(&issuer));
-
-    let valid_from = cert.valid_from().unwrap_or("".to_string());
-    dict.insert("EffectiveDateString".to_string(), create_python_string(&valid_from));
-
-    let valid_to = cert.valid_to().unwrap_or("".to_string());
-    dict.insert("ExpirationDateString".to_string(), create_python_string(&valid_to));
-
-    let private_options = cert.private_key().map_err(|_| {
-        PyRuntimeError::new_err("Could not get the private key.")
-    })?;
+/// Store names and locations are case insensitive, so they are cached under their lower case names.
+fn cache_key(store: &str, user: &str, query: &str) -> CacheKey {
+    CacheKey::new(&user.to_lowercase(), &store.to_lowercase(), query)
+}
 
-    // NOTE: The private key may be correct, but until this is tested on a live system, I am guessing that this is the expected output.
-    dict.insert("cert".to_string(), create_python_bytes(&private_options.as_slice()));
+/// Watches the store for changes while its search is cached. Without notifications, the cache relies on its TTL.
+fn change_detector(store: &str, user: &str) -> Option<Box<dyn ChangeDetector>> {
+    StoreChangeNotifier::open(store, user).ok().map(|notifier| Box::new(notifier) as Box<dyn ChangeDetector>)
+}
 
-    Ok(dict)
+/// Checks the validity period of a certificate, certificates whose validity cannot be checked are skipped.
+fn is_time_valid(cert: &CertContext) -> bool {
+    cert.is_time_valid().unwrap_or(false)
 }
 
-/// Helper function to create a Python string from a Rust string
-fn create_python_string(value: &str) -> Py<PyAny> {
-    Python::attach(|py| {
-        PyString::new(py, value).into()
-    })
+/// Checks the host criterion of the search functions, certificates that cannot be parsed never match a host.
+fn is_valid_for_host(cert: &CertContext, host: Option<&str>) -> bool {
+    match host {
+        Some(host) => Certificate::from_der(cert.der())
+            .and_then(|certificate| certificate.matches_host(host))
+            .unwrap_or(false),
+        None => true,
+    }
 }
 
-/// Helper function to create a Python bytes object from a Rust byte slice
-fn create_python_bytes(value: &[u8]) -> Py<PyAny> {
-    Python::attach(|py| {
-        PyBytes::new(py, value).into()
-    })
+/// Checks the purpose criterion of the search functions, including the purposes set in the certificate properties.
+fn is_valid_for_purpose(cert: &CertContext, purpose: Option<&ObjectIdentifier>) -> bool {
+    if purpose.is_none() {
+        return true;
+    }
+    Certificate::from_der(cert.der())
+        .and_then(|certificate| allows_purpose(&certificate, || cert.enhanced_key_usage_property(), purpose))
+        .unwrap_or(false)
 }
@@ -0,0 +1,180 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::ffi::CString;
+use std::io::{Result, Error, ErrorKind};
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::ptr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::watch::ChangeSignal;
+
+
+const EVENTS: u32 = libc::IN_CREATE | libc::IN_DELETE | libc::IN_MODIFY | libc::IN_CLOSE_WRITE | libc::IN_ATTRIB
+    | libc::IN_MOVED_FROM | libc::IN_MOVED_TO | libc::IN_DELETE_SELF | libc::IN_MOVE_SELF;
+
+/// Signals changes to a directory, or to the directory holding a file, with inotify.
+///
+/// The kernel drops the watch when the directory is deleted, and a watch would follow a renamed directory away
+/// from the store's path, so until a directory is back at the path it is looked for every [`Inotify::RETRY`].
+pub struct Inotify {
+    fd: libc::c_int,
+    directory: CString,
+    /// The watch descriptor, `None` while there is no directory at the path.
+    watch: Option<libc::c_int>,
+}
+
+impl Drop for Inotify {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+impl Inotify {
+    /// How often a deleted or renamed directory is looked for.
+    pub const RETRY: Duration = Duration::from_millis(100);
+
+    /// Watches a directory, or the directory of a file so that a file replaced by a rename is noticed too.
+    pub fn watch_path(path: &Path) -> Result<Inotify> {
+        let directory = if path.is_dir() {
+            path
+        } else {
+            path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."))
+        };
+        let directory = CString::new(directory.as_os_str().as_bytes())
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "The path contains a NUL byte"))?;
+
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(Error::last_os_error());
+        }
+        let mut inotify = Inotify { fd, directory, watch: None };
+
+        match inotify.add_watch()? {
+            true => Ok(inotify),
+            false => Err(Error::new(ErrorKind::NotFound, format!("{} does not exist", inotify.directory.to_string_lossy()))),
+        }
+    }
+
+    /// Watches the directory at the path, returning whether there is one.
+    fn add_watch(&mut self) -> Result<bool> {
+        let watch = unsafe { libc::inotify_add_watch(self.fd, self.directory.as_ptr(), EVENTS) };
+        if watch >= 0 {
+            self.watch = Some(watch);
+            return Ok(true);
+        }
+
+        let err = Error::last_os_error();
+        match err.kind() {
+            ErrorKind::NotFound => Ok(false),
+            _ => Err(err),
+        }
+    }
+
+    /// Reads the queued events. They are discarded, as the watcher rereads the whole store anyway, apart from the
+    /// ones telling that the directory itself is gone.
+    fn drain(&mut self) -> Result<()> {
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = unsafe { libc::read(self.fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len()) };
+            if read > 0 {
+                self.handle_events(&buffer[..read as usize]);
+                continue;
+            }
+            if read == 0 {
+                return Ok(());
+            }
+
+            let err = Error::last_os_error();
+            return match err.kind() {
+                ErrorKind::WouldBlock => Ok(()),
+                ErrorKind::Interrupted => continue,
+                _ => Err(err),
+            };
+        }
+    }
+
+    fn handle_events(&mut self, events: &[u8]) {
+        let header = mem::size_of::<libc::inotify_event>();
+        let mut offset = 0;
+        while offset + header <= events.len() {
+            // The events are packed one after the other, each followed by its name.
+            let event = unsafe { ptr::read_unaligned(events[offset..].as_ptr() as *const libc::inotify_event) };
+            offset += header + event.len as usize;
+            if Some(event.wd) != self.watch {
+                continue;
+            }
+
+            if event.mask & libc::IN_IGNORED != 0 {
+                self.watch = None;
+            } else if event.mask & libc::IN_MOVE_SELF != 0 {
+                unsafe {
+                    libc::inotify_rm_watch(self.fd, event.wd);
+                }
+                self.watch = None;
+            }
+        }
+    }
+
+    /// Waits for a directory to be back at the path, which is a change as its certificates are back too.
+    fn wait_for_directory(&mut self, timeout: Duration) -> Result<bool> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.add_watch()? {
+                return Ok(true);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(false);
+            }
+            thread::sleep(Inotify::RETRY.min(deadline - now));
+        }
+    }
+}
+
+impl ChangeSignal for Inotify {
+    fn wait(&mut self, timeout: Duration) -> Result<bool> {
+        if self.watch.is_none() {
+            return self.wait_for_directory(timeout);
+        }
+
+        let mut poll = libc::pollfd { fd: self.fd, events: libc::POLLIN, revents: 0 };
+        let millis = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+
+        let ready = unsafe { libc::poll(&mut poll, 1, millis) };
+        match ready {
+            0 => Ok(false),
+            ready if ready > 0 => {
+                self.drain()?;
+                Ok(true)
+            },
+            _ => {
+                let err = Error::last_os_error();
+                // A signal arriving while waiting is not a change, the caller simply waits again.
+                if err.kind() == ErrorKind::Interrupted { Ok(false) } else { Err(err) }
+            },
+        }
+    }
+}
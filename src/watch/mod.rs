@@ -0,0 +1,378 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::certificate::Certificate;
+use crate::store::StoreEntry;
+use crate::store::path::{PathFingerprint, open_path};
+
+#[cfg(target_os = "linux")]
+pub mod inotify;
+
+
+/// How a certificate changed between two snapshots of a store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// The certificate is still there, but its friendly name or private key changed.
+    Modified,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Modified => "modified",
+        })
+    }
+}
+
+/// A certificate as seen by a snapshot of a store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotEntry {
+    pub certificate: Certificate,
+    pub friendly_name: String,
+    pub has_private_key: bool,
+    pub key_provider: Option<String>,
+}
+
+/// A change to a store. Removed certificates are described as they were last seen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoreEvent {
+    pub kind: ChangeKind,
+    pub thumbprint: String,
+    pub entry: SnapshotEntry,
+}
+
+/// The certificates of a store at one point in time, by thumbprint.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoreSnapshot(BTreeMap<String, SnapshotEntry>);
+
+impl StoreSnapshot {
+    /// Reads the certificates of a store without exporting any key. Certificates that cannot be parsed are left out.
+    pub fn from_entries<I: IntoIterator<Item = Box<dyn StoreEntry>>>(entries: I) -> StoreSnapshot {
+        let mut snapshot = BTreeMap::new();
+        for entry in entries {
+            if let Ok(certificate) = Certificate::from_der(entry.der()) {
+                let entry = SnapshotEntry {
+                    friendly_name: entry.friendly_name(),
+                    has_private_key: entry.has_private_key(),
+                    key_provider: entry.key_provider(),
+                    certificate,
+                };
+                snapshot.insert(entry.certificate.thumbprint(), entry);
+            }
+        }
+        StoreSnapshot(snapshot)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, thumbprint: &str) -> Option<&SnapshotEntry> {
+        self.0.get(&thumbprint.to_uppercase())
+    }
+
+    pub fn thumbprints(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// The changes from this snapshot to a newer one: removals first, then additions and modifications, each
+    /// ordered by thumbprint.
+    pub fn diff(&self, newer: &StoreSnapshot) -> Vec<StoreEvent> {
+        let event = |kind, thumbprint: &String, entry: &SnapshotEntry| {
+            StoreEvent { kind, thumbprint: thumbprint.clone(), entry: entry.clone() }
+        };
+
+        let removed = self.0.iter()
+            .filter(|(thumbprint, _)| !newer.0.contains_key(*thumbprint))
+            .map(|(thumbprint, entry)| event(ChangeKind::Removed, thumbprint, entry));
+
+        let changed = newer.0.iter().filter_map(|(thumbprint, entry)| match self.0.get(thumbprint) {
+            None => Some(event(ChangeKind::Added, thumbprint, entry)),
+            Some(old) if old != entry => Some(event(ChangeKind::Modified, thumbprint, entry)),
+            Some(_) => None,
+        });
+
+        removed.chain(changed).collect()
+    }
+}
+
+/// Tells a [`StoreWatcher`] that its store may have changed.
+pub trait ChangeSignal: Send {
+    /// Waits up to `timeout` for a possible change, returning whether there was one.
+    fn wait(&mut self, timeout: Duration) -> Result<bool>;
+}
+
+/// Signals a change when the files of a file based store change, checking every `interval`.
+///
+/// Used where the platform offers no notifications for the path.
+pub struct PollingSignal {
+    path: PathBuf,
+    fingerprint: Option<PathFingerprint>,
+    interval: Duration,
+}
+
+impl PollingSignal {
+    pub fn new<P: AsRef<Path>>(path: P, interval: Duration) -> PollingSignal {
+        let path = path.as_ref().to_path_buf();
+        let fingerprint = PathFingerprint::of(&path).ok();
+        PollingSignal { path, fingerprint, interval }
+    }
+}
+
+impl ChangeSignal for PollingSignal {
+    fn wait(&mut self, timeout: Duration) -> Result<bool> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let current = PathFingerprint::of(&self.path).ok();
+            if current != self.fingerprint {
+                self.fingerprint = current;
+                return Ok(true);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(false);
+            }
+            thread::sleep(self.interval.min(deadline - now));
+        }
+    }
+}
+
+/// Reads the current snapshot of the watched store.
+pub type SnapshotLoader = Box<dyn FnMut() -> Result<StoreSnapshot> + Send>;
+
+/// Turns the change signals of a store into added, removed and modified events, by comparing snapshots of the store.
+pub struct StoreWatcher {
+    load: SnapshotLoader,
+    signal: Box<dyn ChangeSignal>,
+    snapshot: StoreSnapshot,
+    pending: VecDeque<StoreEvent>,
+    settle: Duration,
+}
+
+impl StoreWatcher {
+    /// How long a store has to stay quiet after a change before it is read again, so a file that is still being
+    /// written or a batch of changes is seen as a whole.
+    pub const SETTLE: Duration = Duration::from_millis(50);
+
+    /// Starts watching, taking the first snapshot right away.
+    pub fn new(mut load: SnapshotLoader, signal: Box<dyn ChangeSignal>) -> Result<StoreWatcher> {
+        let snapshot = load()?;
+        Ok(StoreWatcher { load, signal, snapshot, pending: VecDeque::new(), settle: StoreWatcher::SETTLE })
+    }
+
+    /// Watches a certificate file, PKCS#12 file or directory of certificates, see [`open_path`].
+    pub fn path<P: AsRef<Path>>(path: P, password: &str) -> Result<StoreWatcher> {
+        let path = path.as_ref().to_path_buf();
+
+        #[cfg(target_os = "linux")]
+        let signal: Box<dyn ChangeSignal> = Box::new(inotify::Inotify::watch_path(&path)?);
+        #[cfg(not(target_os = "linux"))]
+        let signal: Box<dyn ChangeSignal> = Box::new(PollingSignal::new(&path, Duration::from_millis(500)));
+
+        let password = password.to_string();
+        let load = move || {
+            // A store that disappeared is empty, so its certificates are reported as removed.
+            if !path.exists() {
+                return Ok(StoreSnapshot::default());
+            }
+            Ok(StoreSnapshot::from_entries(open_path(&path, &password)?.entries()))
+        };
+        StoreWatcher::new(Box::new(load), signal)
+    }
+
+    /// Watches a Windows system store of the current user or the local machine.
+    #[cfg(windows)]
+    pub fn windows(store: &str, user: &str) -> Result<StoreWatcher> {
+        use crate::windows_store::cert_store::CertStore;
+        use crate::windows_store::change_notify::StoreChangeNotifier;
+
+        let signal = StoreChangeNotifier::open(store, user)?;
+        let (store, user) = (store.to_string(), user.to_string());
+        let load = move || {
+            let entries = CertStore::open_system(&store, &user)?.into_entries();
+            Ok(StoreSnapshot::from_entries(entries))
+        };
+        StoreWatcher::new(Box::new(load), Box::new(signal))
+    }
+
+    pub fn snapshot(&self) -> &StoreSnapshot {
+        &self.snapshot
+    }
+
+    /// Waits up to `timeout` for the next change, returning the events it caused. A change that leaves the
+    /// certificates as they were returns no events.
+    pub fn poll(&mut self, timeout: Duration) -> Result<Vec<StoreEvent>> {
+        if !self.pending.is_empty() {
+            return Ok(self.pending.drain(..).collect());
+        }
+
+        if !self.signal.wait(timeout)? {
+            return Ok(Vec::new());
+        }
+
+        // Let a burst of changes finish, but keep up with a store that never stays quiet.
+        let deadline = Instant::now() + self.settle * 20;
+        while Instant::now() < deadline && self.signal.wait(self.settle)? {}
+
+        let snapshot = (self.load)()?;
+        let events = self.snapshot.diff(&snapshot);
+        self.snapshot = snapshot;
+        Ok(events)
+    }
+
+    /// Blocks until the store changes, returning the next event, or `None` when there was none within `timeout`.
+    pub fn next_event(&mut self, timeout: Duration) -> Result<Option<StoreEvent>> {
+        let deadline = Instant::now() + timeout;
+        while self.pending.is_empty() {
+            // A change that left the certificates as they were is no event, so keep waiting.
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            let events = self.poll(deadline - now)?;
+            self.pending.extend(events);
+        }
+        Ok(self.pending.pop_front())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::store::memory::MemoryStore;
+    use crate::cert_builder::CertificateBuilder;
+    use crate::cert_builder::test_support::sign;
+
+    fn certificate(subject: &str) -> Certificate {
+        sign(CertificateBuilder::new(subject).unwrap(), None).certificate
+    }
+
+    #[test]
+    fn diffs_snapshots_by_thumbprint() {
+        let kept = certificate("CN=kept");
+        let removed = certificate("CN=removed");
+        let added = certificate("CN=added");
+        let renamed = sign(CertificateBuilder::new("CN=renamed").unwrap(), None);
+        let (renamed, renamed_key) = (renamed.certificate, renamed.key);
+
+        let mut before = MemoryStore::new();
+        before.add_certificate(kept.clone(), "kept");
+        before.add_certificate(removed.clone(), "removed");
+        before.add_certificate(renamed.clone(), "renamed");
+
+        let mut after = MemoryStore::new();
+        after.add_certificate(kept.clone(), "kept");
+        after.add_certificate(added.clone(), "added");
+        after.add_certificate_with_key(renamed.clone(), renamed_key, "renamed", true);
+
+        let before = StoreSnapshot::from_entries(before.entries());
+        let after = StoreSnapshot::from_entries(after.entries());
+        assert!(before.diff(&before).is_empty());
+
+        let events: Vec<_> = before.diff(&after).into_iter().map(|event| (event.kind, event.thumbprint)).collect();
+        let mut expected = vec![(ChangeKind::Added, added.thumbprint()), (ChangeKind::Modified, renamed.thumbprint())];
+        expected.sort_by(|a, b| a.1.cmp(&b.1));
+        expected.insert(0, (ChangeKind::Removed, removed.thumbprint()));
+        assert_eq!(events, expected);
+
+        assert!(after.get(&renamed.thumbprint().to_lowercase()).unwrap().has_private_key);
+    }
+
+    #[test]
+    fn watches_a_directory() {
+        let dir = std::env::temp_dir().join(format!("py_cert_store-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let first = certificate("CN=first");
+        let second = certificate("CN=second");
+        fs::write(dir.join("first.crt"), first.to_pem().unwrap()).unwrap();
+
+        let mut watcher = StoreWatcher::path(&dir, "").unwrap();
+        assert_eq!(watcher.snapshot().len(), 1);
+        assert!(watcher.poll(Duration::from_millis(50)).unwrap().is_empty());
+
+        fs::write(dir.join("second.crt"), second.to_pem().unwrap()).unwrap();
+        let event = watcher.next_event(Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!((event.kind, event.thumbprint), (ChangeKind::Added, second.thumbprint()));
+
+        fs::remove_file(dir.join("first.crt")).unwrap();
+        let event = watcher.next_event(Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!((event.kind, event.thumbprint), (ChangeKind::Removed, first.thumbprint()));
+
+        // Writing the same certificate again changes nothing.
+        fs::write(dir.join("second.crt"), second.to_pem().unwrap()).unwrap();
+        assert!(watcher.poll(Duration::from_millis(500)).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+        let event = watcher.next_event(Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!(event.kind, ChangeKind::Removed);
+        assert!(watcher.snapshot().is_empty());
+
+        // A directory created again at the path is watched again.
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("first.crt"), first.to_pem().unwrap()).unwrap();
+        let event = watcher.next_event(Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!((event.kind, event.thumbprint), (ChangeKind::Added, first.thumbprint()));
+        fs::write(dir.join("second.crt"), second.to_pem().unwrap()).unwrap();
+        let event = watcher.next_event(Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!((event.kind, event.thumbprint), (ChangeKind::Added, second.thumbprint()));
+
+        // So is one renamed away and replaced, instead of the renamed one.
+        let moved = dir.with_extension("moved");
+        fs::rename(&dir, &moved).unwrap();
+        assert_eq!(watcher.poll(Duration::from_secs(5)).unwrap().len(), 2);
+        fs::remove_file(moved.join("first.crt")).unwrap();
+        assert!(watcher.poll(Duration::from_millis(300)).unwrap().is_empty());
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("first.crt"), first.to_pem().unwrap()).unwrap();
+        let event = watcher.next_event(Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!((event.kind, event.thumbprint), (ChangeKind::Added, first.thumbprint()));
+
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&moved).unwrap();
+    }
+
+    #[test]
+    fn polls_for_changes() {
+        let path = std::env::temp_dir().join(format!("py_cert_store-poll-{}.crt", std::process::id()));
+        let mut signal = PollingSignal::new(&path, Duration::from_millis(10));
+        assert!(!signal.wait(Duration::from_millis(30)).unwrap());
+
+        fs::write(&path, "changed").unwrap();
+        assert!(signal.wait(Duration::from_millis(30)).unwrap());
+        assert!(!signal.wait(Duration::ZERO).unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::types::PyBytes;
+
+use crate::watch::{StoreEvent as Event, StoreWatcher};
+
+
+/// How long a blocking wait releases the GIL before checking for signals such as Ctrl+C.
+const WAIT_SLICE: Duration = Duration::from_millis(200);
+
+#[pyclass(name = "StoreEvent", module = "py_cert_store", frozen)]
+/// A certificate added to, removed from or modified in a watched store.
+pub struct StoreEvent(Event);
+
+#[pymethods]
+impl StoreEvent {
+    /// "added", "removed" or "modified".
+    #[getter]
+    fn kind(&self) -> String {
+        self.0.kind.to_string()
+    }
+
+    #[getter]
+    fn thumbprint(&self) -> &str {
+        &self.0.thumbprint
+    }
+
+    #[getter]
+    fn friendly_name(&self) -> &str {
+        &self.0.entry.friendly_name
+    }
+
+    #[getter]
+    fn has_private_key(&self) -> bool {
+        self.0.entry.has_private_key
+    }
+
+    #[getter]
+    fn key_provider(&self) -> Option<&str> {
+        self.0.entry.key_provider.as_deref()
+    }
+
+    #[getter]
+    fn subject(&self) -> String {
+        self.0.entry.certificate.subject().to_dotnet()
+    }
+
+    #[getter]
+    fn not_after(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from(self.0.entry.certificate.not_after())
+    }
+
+    #[getter]
+    fn certificate<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.0.entry.certificate.as_der())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("StoreEvent(kind={:?}, subject={:?}, thumbprint={:?})", self.kind(), self.subject(), self.0.thumbprint)
+    }
+}
+
+#[pyclass(name = "StoreWatch", module = "py_cert_store", frozen)]
+/// Watches a store, yielding a `StoreEvent` for every certificate added, removed or modified.
+///
+/// Iterating blocks until the next change. The watch stops when `close()` is called or the `with` block ends.
+pub struct StoreWatch {
+    watcher: Mutex<Option<StoreWatcher>>,
+    closed: AtomicBool,
+}
+
+impl StoreWatch {
+    fn new(watcher: StoreWatcher) -> StoreWatch {
+        StoreWatch { watcher: Mutex::new(Some(watcher)), closed: AtomicBool::new(false) }
+    }
+
+    /// Waits up to one slice for events, without the GIL. Returns `None` once the watch is closed.
+    fn wait_slice(&self, py: Python<'_>, timeout: Duration, all: bool) -> PyResult<Option<Vec<Event>>> {
+        py.detach(|| {
+            let mut watcher = self.watcher.lock()
+                .map_err(|_| PyRuntimeError::new_err("The store watch was poisoned by an earlier panic."))?;
+            let watcher = match watcher.as_mut() {
+                Some(watcher) if !self.closed.load(Ordering::SeqCst) => watcher,
+                _ => return Ok(None),
+            };
+
+            let events = if all {
+                watcher.poll(timeout)?
+            } else {
+                watcher.next_event(timeout)?.into_iter().collect()
+            };
+            Ok(Some(events))
+        })
+    }
+
+    /// Waits for events, up to `timeout` or until the watch is closed, checking for signals in between.
+    fn wait(&self, py: Python<'_>, timeout: Option<Duration>, all: bool) -> PyResult<Option<Vec<Event>>> {
+        let mut remaining = timeout;
+        loop {
+            let slice = remaining.map_or(WAIT_SLICE, |remaining| remaining.min(WAIT_SLICE));
+            match self.wait_slice(py, slice, all)? {
+                Some(events) if events.is_empty() => {},
+                result => return Ok(result),
+            }
+
+            py.check_signals()?;
+            remaining = remaining.map(|remaining| remaining.saturating_sub(slice));
+            if remaining.is_some_and(|remaining| remaining.is_zero()) {
+                return Ok(Some(Vec::new()));
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl StoreWatch {
+    /// Waits up to `timeout` seconds (forever when None) for the store to change, returning the events.
+    /// Returns an empty list when nothing changed in time or the watch is closed.
+    #[pyo3(signature = (timeout=None))]
+    fn poll(&self, py: Python<'_>, timeout: Option<f64>) -> PyResult<Vec<StoreEvent>> {
+        let timeout = timeout
+            .map(|timeout| Duration::try_from_secs_f64(timeout).map_err(|_| PyValueError::new_err("timeout must be a positive number of seconds")))
+            .transpose()?;
+        let events = self.wait(py, timeout, true)?.unwrap_or_default();
+        Ok(events.into_iter().map(StoreEvent).collect())
+    }
+
+    /// The thumbprints of the certificates currently in the store.
+    #[getter]
+    fn thumbprints(&self) -> PyResult<Vec<String>> {
+        let watcher = self.watcher.lock()
+            .map_err(|_| PyRuntimeError::new_err("The store watch was poisoned by an earlier panic."))?;
+        Ok(watcher.as_ref().map(|watcher| watcher.snapshot().thumbprints().map(str::to_string).collect()).unwrap_or_default())
+    }
+
+    /// Stops the watch. A thread waiting for events stops within a fraction of a second.
+    fn close(&self) -> PyResult<()> {
+        self.closed.store(true, Ordering::SeqCst);
+        let mut watcher = self.watcher.lock()
+            .map_err(|_| PyRuntimeError::new_err("The store watch was poisoned by an earlier panic."))?;
+        *watcher = None;
+        Ok(())
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<StoreEvent>> {
+        let events = self.wait(py, None, false)?;
+        Ok(events.and_then(|events| events.into_iter().next()).map(StoreEvent))
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, _args: &Bound<'_, pyo3::types::PyTuple>) -> PyResult<bool> {
+        self.close()?;
+        Ok(false)
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (path, password=""))]
+/// Watch a certificate file, PKCS#12 file or directory of certificates for changes.
+pub fn watch_path(py: Python<'_>, path: PathBuf, password: &str) -> PyResult<StoreWatch> {
+    let watcher = py.detach(|| StoreWatcher::path(&path, password))?;
+    Ok(StoreWatch::new(watcher))
+}
+
+#[cfg(windows)]
+#[pyfunction]
+#[pyo3(signature = (store="My", user="CurrentUser"))]
+/// Watch a store of the Windows Certificate Store for changes, such as certificates renewed by auto-enrollment.
+pub fn watch_windows_store(py: Python<'_>, store: &str, user: &str) -> PyResult<StoreWatch> {
+    let watcher = py.detach(|| StoreWatcher::windows(store, user))?;
+    Ok(StoreWatch::new(watcher))
+}
@@ -17,14 +17,19 @@
 #[deny(clippy::expect_used)]
 #[deny(clippy::panic)]
 
-use std::io::{Result, Error};
+use std::io::{Result, Error, ErrorKind};
 use std::os::windows::ffi::OsStringExt;
 use std::ptr;
 use std::ffi::OsString;
+use der::asn1::ObjectIdentifier;
 use windows_sys::Win32::Security::Cryptography;
 use windows_sys::Win32::System::Time;
 use windows_sys::Win32::Foundation::{SYSTEMTIME, FILETIME};
 
+use crate::certificate::name::DistinguishedName;
+use crate::keys::storage::{KeyStorage, decode_dword, decode_ui_policy};
+use crate::store::properties::{PropertyId, PropertyValue};
+
 
 #[derive(Debug)]
 pub struct CertContext(*const Cryptography::CERT_CONTEXT);
@@ -39,6 +44,9 @@ impl Clone for CertContext {
 
 inner_impl!(CertContext, *const Cryptography::CERT_CONTEXT);
 
+// Certificate contexts are reference counted by CryptoAPI and may be used from any thread.
+unsafe impl Send for CertContext {}
+
 
 impl CertContext {
     fn get_context_string(&self, prop:u32) -> Result<String> {
@@ -75,78 +83,12 @@ impl CertContext {
         return Ok(OsString::from_wide(&buf[..amt-1]).to_string_lossy().to_string());
     }
 
-    /// Retrieves the name string from the certificate context.
-    /// This function is a helper for `get_name_string` and retrieves a specific name element based on the provided OID and property.
-    /// 
-    /// The reason for this helper function is do to how the win32 CERT_NAME_RDN_TYPE is not able to pull the proceeding elements (C=, S=, L=, O=, OU=, CN=) in a single call.
-    fn get_name_element(&self, attribut_oid:*const u8, prop:u32) -> Result<String> {
-        let ret = unsafe {
-            Cryptography::CertGetNameStringW(
-                self.0,
-                Cryptography::CERT_NAME_ATTR_TYPE,
-                prop,
-                attribut_oid as *const std::ffi::c_void,
-                ptr::null_mut(),
-                0,
-            )
-        };
-
-        if ret == 0 {
-            return Err(Error::last_os_error());
-        }
-
-        let len = ret as usize;
-        let mut buf = vec![0u16; len];
-
-        // Retrieve the name string
-        let ret = unsafe {
-            Cryptography::CertGetNameStringW(
-                self.0,
-                Cryptography::CERT_NAME_ATTR_TYPE,
-                prop,
-                attribut_oid as *const std::ffi::c_void,
-                buf.as_mut_ptr(),
-                len as u32,
-            )
+    /// Decodes a name blob of the certificate info (`Subject` or `Issuer`).
+    fn get_name(&self, blob: Cryptography::CRYPT_INTEGER_BLOB) -> Result<DistinguishedName> {
+        let der = unsafe {
+            std::slice::from_raw_parts(blob.pbData, blob.cbData as usize)
         };
-
-        if ret == 0 {
-            return Err(Error::last_os_error());
-        }
-
-        // Convert the wide string to a Rust String
-        let name = OsString::from_wide(&buf[..len - 1]).to_string_lossy().to_string();
-        return Ok(name);
-    }
-
-    /// Retrieves the entire name string from the certificate context.
-    /// This function attempts to clone the equivalent C# code to get the name string.
-    /// It retrieves the name elements (C=, S=, L=, O=, OU=, CN=) and concatenates them into a single string.
-    fn get_name_string(&self, prop: u32) -> Result<String> {
-        // TODO: Check that these are all the elements that are printed by the equivalent C# code
-        let elements = [
-            ("C=", Cryptography::szOID_COUNTRY_NAME),
-            ("S=", Cryptography::szOID_STATE_OR_PROVINCE_NAME),
-            ("L=", Cryptography::szOID_LOCALITY_NAME),
-            ("O=", Cryptography::szOID_ORGANIZATION_NAME),
-            ("OU=", Cryptography::szOID_ORGANIZATIONAL_UNIT_NAME),
-            ("CN=", Cryptography::szOID_COMMON_NAME),
-        ];
-
-        let mut output_string = String::new();
-
-        for &(prefix, oid) in &elements {
-            let name_element = self.get_name_element(oid, prop).unwrap_or(String::new());
-            if !name_element.is_empty() {
-                if !output_string.is_empty(){
-                    output_string.push_str(", ");
-                }
-                output_string.push_str(prefix);
-                output_string.push_str(&name_element);
-            }
-        }
-
-        Ok(output_string)
+        DistinguishedName::from_der(der)
     }
 
     fn get_date_string(&self, time_val:FILETIME) -> Result<String> {
@@ -191,6 +133,72 @@ impl CertContext {
         return Ok(output);
     }
 
+    /// The DER encoding of the certificate.
+    pub fn der(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts((*self.0).pbCertEncoded, (*self.0).cbCertEncoded as usize)
+        }
+    }
+
+    /// Reads a property of the certificate, or `None` when it is not set.
+    pub fn property(&self, id: PropertyId) -> Result<Option<PropertyValue>> {
+        let mut len = 0;
+        let ret = unsafe {
+            Cryptography::CertGetCertificateContextProperty(
+                self.0,
+                id.prop_id(),
+                ptr::null_mut(),
+                &mut len
+            )
+        };
+
+        if ret == 0 {
+            return Ok(None);
+        }
+
+        if len == 0 {
+            // Flags such as CERT_ARCHIVED_PROP_ID have no data.
+            return PropertyValue::decode(id, &[], 0).map(Some);
+        }
+
+        // Structures such as CRYPT_KEY_PROV_INFO are followed by the strings they point to, so keep it 8 byte aligned.
+        let mut buf = vec![0u64; (len as usize).div_ceil(8)];
+        let ret = unsafe {
+            Cryptography::CertGetCertificateContextProperty(
+                self.0,
+                id.prop_id(),
+                buf.as_mut_ptr() as *mut std::ffi::c_void,
+                &mut len
+            )
+        };
+
+        if ret == 0 {
+            return Err(Error::last_os_error());
+        }
+
+        let blob = unsafe {
+            std::slice::from_raw_parts(buf.as_ptr() as *const u8, len as usize)
+        };
+        PropertyValue::decode(id, blob, buf.as_ptr() as u64).map(Some)
+    }
+
+    /// The name of the key storage provider holding the private key, or `None` when there is no private key.
+    pub fn key_provider(&self) -> Result<Option<String>> {
+        match self.property(PropertyId::KeyProviderInfo)? {
+            Some(PropertyValue::KeyProviderInfo(info)) => Ok(Some(info.provider_name)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The purposes set with "Enable only the following purposes" or "Disable all purposes" in the certificate
+    /// properties, or `None` when the certificate's own ExtendedKeyUsage applies.
+    pub fn enhanced_key_usage_property(&self) -> Result<Option<Vec<ObjectIdentifier>>> {
+        match self.property(PropertyId::EnhancedKeyUsage)? {
+            Some(PropertyValue::EnhancedKeyUsage(purposes)) => Ok(Some(purposes)),
+            _ => Ok(None),
+        }
+    }
+
     pub fn friendly_name(&self) -> Result<String> {
         self.get_context_string(Cryptography::CERT_FRIENDLY_NAME_PROP_ID)
     }
@@ -215,14 +223,26 @@ impl CertContext {
         self.get_date_string(file_time)
     }
 
-    /// Pulls the Issuer of the certificate.
+    /// Pulls the Issuer of the certificate, formatted like .NET's `X509Certificate2.Issuer`.
     pub fn issuer(&self) -> Result<String> {
-        self.get_name_string(Cryptography::CERT_NAME_ISSUER_FLAG)
+        let blob = unsafe {
+            (*self.0).pCertInfo.as_ref()
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "certificate has no CERT_INFO"))?
+                .Issuer
+        };
+
+        Ok(self.get_name(blob)?.to_dotnet())
     }
 
-    /// Pulls the Name of the certificate.
+    /// Pulls the Name of the certificate, formatted like .NET's `X509Certificate2.Subject`.
     pub fn name(&self) -> Result<String> {
-        self.get_name_string(0)
+        let blob = unsafe {
+            (*self.0).pCertInfo.as_ref()
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "certificate has no CERT_INFO"))?
+                .Subject
+        };
+
+        Ok(self.get_name(blob)?.to_dotnet())
     }
 
     /// Pulls the private key from the certificate in PKCS#12 format.
@@ -427,6 +447,102 @@ impl CertContext {
         return Ok(false);
     }
 
+    /// Describes the private key without exporting it, or `None` when there is no private key.
+    ///
+    /// The key is opened silently, so a key that needs a PIN or consent to open only reports its provider.
+    pub fn key_storage(&self) -> Result<Option<KeyStorage>> {
+        let Some(provider) = self.key_provider()? else {
+            return Ok(None);
+        };
+
+        let mut key_handle: Cryptography::HCRYPTPROV_OR_NCRYPT_KEY_HANDLE = 0;
+        let mut key_spec = 0;
+        let mut free_key = 0;
+        let ret = unsafe {
+            Cryptography::CryptAcquireCertificatePrivateKey(
+                self.0,
+                Cryptography::CRYPT_ACQUIRE_CACHE_FLAG | Cryptography::CRYPT_ACQUIRE_ALLOW_NCRYPT_KEY_FLAG | Cryptography::CRYPT_ACQUIRE_SILENT_FLAG,
+                ptr::null_mut(),
+                &mut key_handle,
+                &mut key_spec,
+                &mut free_key,
+            )
+        };
+
+        if ret == 0 {
+            return Ok(Some(KeyStorage::provider(Some(provider))));
+        }
+
+        let is_ncrypt = key_spec == Cryptography::CERT_NCRYPT_KEY_SPEC;
+        // Cached handles belong to the certificate, the others are released once the key is described.
+        let _handle = scopeguard::guard(key_handle, |handle| {
+            if free_key != 0 {
+                unsafe {
+                    if is_ncrypt {
+                        Cryptography::NCryptFreeObject(handle as Cryptography::NCRYPT_HANDLE);
+                    } else {
+                        Cryptography::CryptReleaseContext(handle, 0);
+                    }
+                }
+            }
+        });
+
+        if is_ncrypt {
+            let property = |name| self.ncrypt_key_property(key_handle as Cryptography::NCRYPT_HANDLE, name);
+            return Ok(Some(KeyStorage {
+                provider: Some(provider),
+                export_policy: property(Cryptography::NCRYPT_EXPORT_POLICY_PROPERTY).and_then(|blob| decode_dword(&blob).ok()),
+                ui_policy: property(Cryptography::NCRYPT_UI_POLICY_PROPERTY).and_then(|blob| decode_ui_policy(&blob).ok()),
+                implementation: property(Cryptography::NCRYPT_IMPL_TYPE_PROPERTY).and_then(|blob| decode_dword(&blob).ok()),
+            }));
+        }
+
+        let mut user_key = 0;
+        let permissions = match unsafe { Cryptography::CryptGetUserKey(key_handle, key_spec, &mut user_key) } {
+            0 => None,
+            _ => {
+                let mut permissions = [0u8; 4];
+                let mut len = permissions.len() as u32;
+                let ret = unsafe {
+                    Cryptography::CryptGetKeyParam(user_key, Cryptography::KP_PERMISSIONS, permissions.as_mut_ptr(), &mut len, 0)
+                };
+                unsafe { Cryptography::CryptDestroyKey(user_key) };
+                (ret != 0).then(|| u32::from_le_bytes(permissions))
+            },
+        };
+
+        let mut implementation = [0u8; 4];
+        let mut len = implementation.len() as u32;
+        let ret = unsafe {
+            Cryptography::CryptGetProvParam(key_handle, Cryptography::PP_IMPTYPE, implementation.as_mut_ptr(), &mut len, 0)
+        };
+        let implementation = (ret != 0).then(|| u32::from_le_bytes(implementation));
+
+        Ok(Some(KeyStorage::from_csp(Some(provider), permissions, implementation)))
+    }
+
+    /// Reads a property of an NCrypt key, or `None` when the provider does not report it.
+    fn ncrypt_key_property(&self, key: Cryptography::NCRYPT_HANDLE, name: windows_sys::core::PCWSTR) -> Option<Vec<u8>> {
+        let mut len = 0;
+        let ret = unsafe {
+            Cryptography::NCryptGetProperty(key, name, ptr::null_mut(), 0, &mut len, Cryptography::NCRYPT_SILENT_FLAG)
+        };
+        if ret != 0 {
+            return None;
+        }
+
+        let mut buf = vec![0u8; len as usize];
+        let ret = unsafe {
+            Cryptography::NCryptGetProperty(key, name, buf.as_mut_ptr(), len, &mut len, Cryptography::NCRYPT_SILENT_FLAG)
+        };
+        if ret != 0 {
+            return None;
+        }
+
+        buf.truncate(len as usize);
+        Some(buf)
+    }
+
     pub fn has_extension_with_property(&self, extension_oid:*const u8, extension_value:Option<&str>) -> Result<bool> {
         let key_usage = unsafe {
             Cryptography::CertFindExtension(
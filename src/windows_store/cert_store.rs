@@ -17,12 +17,12 @@
 #[deny(clippy::expect_used)]
 #[deny(clippy::panic)]
 
-use std::io::{Result, Error};
+use std::io::{Result, Error, ErrorKind};
 use std::os::windows::ffi::OsStrExt;
 use std::ptr;
-use std::mem;
 use std::ffi::OsStr;
 // use pyo3::prelude::*;
+use windows_sys::Win32::Foundation;
 use windows_sys::Win32::Security::Cryptography;
 
 use crate::windows_store::cert_context::CertContext;
@@ -50,27 +50,24 @@ impl <'a> Iterator for CertIter<'a> {
     type Item = CertContext;
 
     fn next(&mut self) -> Option<CertContext> {
-        unsafe {
-            let cur = self.cur.take().map(|p| {
-                let ptr = p.as_inner();
-                mem::forget(p);
-                ptr
-            });
-            let cur = cur.unwrap_or(ptr::null_mut());
-            let next = Cryptography::CertEnumCertificatesInStore(self.store.0, cur);
+        let next = self.store.next_cert(self.cur.take())?;
+        self.cur = Some(next.clone());
+        Some(next)
+    }
+}
 
-            if next.is_null() {
-                self.cur = None;
-                return None;
-            } else {
-                let next = CertContext::from_inner(next);
-                self.cur = Some(next.clone());
-                Some(next)
-            }
+impl <'a> Drop for CertIter<'a> {
+    fn drop(&mut self) {
+        // A search that stops early, such as a cancelled one, still holds the duplicate kept to continue it.
+        if let Some(cur) = self.cur.take() {
+            cur.close();
         }
     }
 }
 
+// Certificate stores and contexts are reference counted by CryptoAPI and may be used from any thread.
+unsafe impl Send for CertStore {}
+
 impl CertStore {
     pub fn open_current_user(store:&str) -> Result<CertStore> {
         unsafe {
@@ -116,6 +113,139 @@ impl CertStore {
         }
     }
 
+    /// Opens a system store of the current user (`CurrentUser`) or the local machine (`LocalMachine`).
+    pub fn open_system(store: &str, user: &str) -> Result<CertStore> {
+        match user.to_lowercase().as_str() {
+            "currentuser" => CertStore::open_current_user(store),
+            "localmachine" => CertStore::open_local_machine(store),
+            _ => Err(Error::new(ErrorKind::InvalidInput, "Invalid user parameter. Use 'CurrentUser' or 'LocalMachine'.")),
+        }
+    }
+
+    /// Opens a serialized store file (.sst), a PKCS#7 bundle (.p7b) or a single encoded certificate as a read only store.
+    pub fn open_file(path:&str) -> Result<CertStore> {
+        unsafe {
+            let data = OsStr::new(path)
+                .encode_wide()
+                .chain(Some(0))
+                .collect::<Vec<_>>();
+            let store = Cryptography::CertOpenStore(
+                Cryptography::CERT_STORE_PROV_FILENAME_W,
+                Cryptography::X509_ASN_ENCODING | Cryptography::PKCS_7_ASN_ENCODING,
+                Cryptography::HCRYPTPROV_LEGACY::default(),
+                Cryptography::CERT_STORE_OPEN_EXISTING_FLAG | Cryptography::CERT_STORE_READONLY_FLAG,
+                data.as_ptr() as *mut _
+            );
+            if !store.is_null() {
+                Ok(CertStore(store))
+            } else {
+                Err(Error::last_os_error())
+            }
+        }
+    }
+
+    /// Adds an encoded certificate, replacing an existing copy of it.
+    pub fn add_encoded_certificate(&self, der: &[u8]) -> Result<()> {
+        unsafe {
+            let added = Cryptography::CertAddEncodedCertificateToStore(
+                self.0,
+                Cryptography::X509_ASN_ENCODING,
+                der.as_ptr(),
+                der.len() as u32,
+                Cryptography::CERT_STORE_ADD_REPLACE_EXISTING,
+                ptr::null_mut()
+            );
+            if added != 0 {
+                Ok(())
+            } else {
+                Err(Error::last_os_error())
+            }
+        }
+    }
+
+    /// Imports the certificates and keys of a PKCS#12 file, replacing existing copies, and returns how many
+    /// certificates were added. Keys are persisted as exportable in the user or machine key set.
+    pub fn import_pkcs12(&self, pfx: &[u8], password: &str, machine_keys: bool) -> Result<usize> {
+        let password = OsStr::new(password)
+            .encode_wide()
+            .chain(Some(0))
+            .collect::<Vec<_>>();
+        let blob = Cryptography::CRYPT_INTEGER_BLOB {
+            cbData: pfx.len() as u32,
+            pbData: pfx.as_ptr() as *mut _,
+        };
+        let key_set = if machine_keys {
+            Cryptography::CRYPT_MACHINE_KEYSET
+        } else {
+            Cryptography::CRYPT_USER_KEYSET
+        };
+
+        let imported = unsafe {
+            let imported = Cryptography::PFXImportCertStore(&blob, password.as_ptr(), Cryptography::CRYPT_EXPORTABLE | key_set);
+            if imported.is_null() {
+                return Err(Error::last_os_error());
+            }
+            CertStore(imported)
+        };
+
+        let mut count = 0;
+        let mut cur = None;
+        while let Some(cert) = imported.next_cert(cur.take()) {
+            let added = unsafe {
+                Cryptography::CertAddCertificateContextToStore(
+                    self.0,
+                    cert.as_inner(),
+                    Cryptography::CERT_STORE_ADD_REPLACE_EXISTING,
+                    ptr::null_mut()
+                )
+            };
+            if added == 0 {
+                let err = Error::last_os_error();
+                cert.close();
+                return Err(err);
+            }
+            count += 1;
+            cur = Some(cert);
+        }
+        Ok(count)
+    }
+
+    /// Signals `event` the next time the store changes, in this or another process.
+    pub fn notify_change(&self, event: Foundation::HANDLE) -> Result<()> {
+        self.control(Cryptography::CERT_STORE_CTRL_NOTIFY_CHANGE, event)
+    }
+
+    /// Reloads the store after a change was signalled, and signals `event` again on the next change.
+    pub fn resync(&self, event: Foundation::HANDLE) -> Result<()> {
+        self.control(Cryptography::CERT_STORE_CTRL_RESYNC, event)
+    }
+
+    fn control(&self, control: u32, event: Foundation::HANDLE) -> Result<()> {
+        unsafe {
+            let done = Cryptography::CertControlStore(self.0, 0, control, &event as *const Foundation::HANDLE as *const _);
+            if done != 0 {
+                Ok(())
+            } else {
+                Err(Error::last_os_error())
+            }
+        }
+    }
+
+    /// Continues an enumeration after `cur`, which is freed by the call. Starts from the first certificate when
+    /// `cur` is `None`.
+    pub(crate) fn next_cert(&self, cur: Option<CertContext>) -> Option<CertContext> {
+        unsafe {
+            let cur = cur.map_or(ptr::null(), |p| p.as_inner());
+            let next = Cryptography::CertEnumCertificatesInStore(self.0, cur);
+
+            if next.is_null() {
+                None
+            } else {
+                Some(CertContext::from_inner(next))
+            }
+        }
+    }
+
     pub fn certs<'a>(&'a self) -> CertIter<'a> {
         CertIter {
             store: self,
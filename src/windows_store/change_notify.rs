@@ -0,0 +1,96 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error};
+use std::ptr;
+use std::time::Duration;
+use windows_sys::Win32::Foundation;
+use windows_sys::Win32::System::Threading;
+
+use crate::cache::ChangeDetector;
+use crate::watch::ChangeSignal;
+use crate::windows_store::cert_store::CertStore;
+
+
+/// Waits for changes to a system store, made by this or any other process (such as auto-enrollment).
+pub struct StoreChangeNotifier {
+    store: CertStore,
+    event: Foundation::HANDLE,
+    changed: bool,
+}
+
+// The event handle may be waited on from any thread.
+unsafe impl Send for StoreChangeNotifier {}
+
+impl Drop for StoreChangeNotifier {
+    fn drop(&mut self) {
+        unsafe {
+            Foundation::CloseHandle(self.event);
+        }
+    }
+}
+
+impl StoreChangeNotifier {
+    /// Starts watching an open store.
+    pub fn new(store: CertStore) -> Result<StoreChangeNotifier> {
+        unsafe {
+            let event = Threading::CreateEventW(ptr::null(), 0, 0, ptr::null());
+            if event.is_null() {
+                return Err(Error::last_os_error());
+            }
+
+            let notifier = StoreChangeNotifier { store, event, changed: false };
+            notifier.store.notify_change(event)?;
+            Ok(notifier)
+        }
+    }
+
+    /// Opens a system store of the current user or the local machine and starts watching it.
+    pub fn open(store: &str, user: &str) -> Result<StoreChangeNotifier> {
+        StoreChangeNotifier::new(CertStore::open_system(store, user)?)
+    }
+
+    /// Waits up to `timeout` for the store to change, returning whether it did. The store is resynchronized after a
+    /// change, so the next call waits for the change after that one.
+    pub fn wait(&self, timeout: Duration) -> Result<bool> {
+        let millis = u32::try_from(timeout.as_millis()).unwrap_or(Threading::INFINITE - 1);
+        match unsafe { Threading::WaitForSingleObject(self.event, millis) } {
+            Foundation::WAIT_OBJECT_0 => {
+                self.store.resync(self.event)?;
+                Ok(true)
+            },
+            Foundation::WAIT_TIMEOUT => Ok(false),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+}
+
+impl ChangeDetector for StoreChangeNotifier {
+    fn has_changed(&mut self) -> bool {
+        // Once changed, the cached search stays stale even though the store was resynchronized.
+        self.changed = self.changed || self.wait(Duration::ZERO).unwrap_or(true);
+        self.changed
+    }
+}
+
+impl ChangeSignal for StoreChangeNotifier {
+    fn wait(&mut self, timeout: Duration) -> Result<bool> {
+        StoreChangeNotifier::wait(self, timeout)
+    }
+}
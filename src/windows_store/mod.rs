@@ -39,6 +39,8 @@ macro_rules! inner_impl {
 
 pub mod cert_context;
 pub mod cert_store;
+pub mod change_notify;
+pub mod store_entry;
 
 trait Inner<T> {
     unsafe fn from_inner(t: T) -> Self;
@@ -0,0 +1,111 @@
+// Copyright 2025 Niky H. (Unwarymold9171)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+
+use std::io::{Result, Error, ErrorKind};
+
+use crate::keys::storage::KeyStorage;
+use crate::store::{StoreEntry, StoreIter};
+use crate::store::properties::{PropertyId, PropertyValue};
+use crate::windows_store::cert_context::CertContext;
+use crate::windows_store::cert_store::CertStore;
+
+
+/// A certificate enumerated from a Windows store, freed when dropped.
+pub struct WindowsEntry(CertContext);
+
+impl WindowsEntry {
+    /// Takes ownership of a certificate context, which is freed with the entry.
+    pub(crate) fn new(cert: CertContext) -> WindowsEntry {
+        WindowsEntry(cert)
+    }
+}
+
+impl Drop for WindowsEntry {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+impl StoreEntry for WindowsEntry {
+    fn der(&self) -> &[u8] {
+        self.0.der()
+    }
+
+    fn friendly_name(&self) -> String {
+        self.0.friendly_name().unwrap_or_default()
+    }
+
+    fn has_private_key(&self) -> bool {
+        self.key_provider().is_some()
+    }
+
+    fn key_provider(&self) -> Option<String> {
+        self.0.key_provider().ok().flatten()
+    }
+
+    fn key_storage(&self) -> Result<Option<KeyStorage>> {
+        self.0.key_storage()
+    }
+
+    fn export_pkcs12(&self) -> Result<Vec<u8>> {
+        if !self.has_private_key() {
+            return Err(Error::new(ErrorKind::NotFound, "The certificate has no private key"));
+        }
+        if !self.0.is_exportable()? {
+            return Err(Error::new(ErrorKind::PermissionDenied, "The private key is not exportable"));
+        }
+        self.0.private_key()
+    }
+
+    fn property(&self, id: PropertyId) -> Result<Option<PropertyValue>> {
+        self.0.property(id)
+    }
+}
+
+/// Enumerates a store it owns, so the store stays open exactly as long as the enumeration.
+struct CertIntoIter {
+    store: CertStore,
+    cur: Option<CertContext>,
+}
+
+impl Iterator for CertIntoIter {
+    type Item = Box<dyn StoreEntry>;
+
+    fn next(&mut self) -> Option<Box<dyn StoreEntry>> {
+        let next = self.store.next_cert(self.cur.take())?;
+        self.cur = Some(next.clone());
+        Some(Box::new(WindowsEntry(next)))
+    }
+}
+
+impl Drop for CertIntoIter {
+    fn drop(&mut self) {
+        // The duplicate kept to continue the enumeration is only freed by the next enumeration call.
+        if let Some(cur) = self.cur.take() {
+            cur.close();
+        }
+    }
+}
+
+impl CertStore {
+    /// Turns the store into a lazy enumeration of its certificates.
+    pub fn into_entries(self) -> StoreIter {
+        Box::new(CertIntoIter { store: self, cur: None })
+    }
+}
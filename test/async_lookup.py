@@ -0,0 +1,85 @@
+"""Checks the asyncio API against a slow fake store, so it runs on Linux without a Windows certificate store.
+
+The fake store waits before each key export, standing in for a smart card or a key protection prompt. It is only
+built with the `test-support` feature, e.g. `maturin develop --features test-support`.
+"""
+
+import asyncio
+import time
+from concurrent.futures import ThreadPoolExecutor
+
+import py_cert_store
+from py_cert_store import py_cert_store as native
+
+if not hasattr(native, "_find_slow_certificates"):
+    raise SystemExit("test/async_lookup.py needs py_cert_store built with the test-support feature")
+
+EXPORT_DELAY = 0.2
+ENTRIES = 10
+
+pfx = py_cert_store.generate_certificate(
+    "CN=async.example.com", key_type="p256", friendly_name="Async Test", password="pass1234"
+)["cert"]
+
+
+def slow_search(**kwargs):
+    return py_cert_store._run_async(native._find_slow_certificates, [pfx] * ENTRIES, password="pass1234", export_delay=EXPORT_DELAY, **kwargs)
+
+
+async def heartbeat(ticks):
+    while True:
+        ticks.append(time.perf_counter())
+        await asyncio.sleep(0.01)
+
+
+async def main():
+    # A single worker, so a search that kept running after being cancelled would hold up the next one.
+    py_cert_store.set_async_executor(ThreadPoolExecutor(max_workers=1))
+
+    ticks = []
+    beat = asyncio.create_task(heartbeat(ticks))
+
+    started = time.perf_counter()
+    found = await slow_search(selection="first")
+    search_time = time.perf_counter() - started
+    longest_pause = max(b - a for a, b in zip(ticks, ticks[1:]))
+
+    task = asyncio.create_task(slow_search())
+    await asyncio.sleep(EXPORT_DELAY * 1.5)
+    task.cancel()
+    try:
+        await task
+        cancelled = False
+    except asyncio.CancelledError:
+        cancelled = True
+
+    # The next search waits for the cancelled one to stop, which should take at most one more export instead of the
+    # rest of the search.
+    started = time.perf_counter()
+    await py_cert_store.find_certificates_async([pfx], password="pass1234")
+    after_cancel = time.perf_counter() - started
+
+    token = py_cert_store.CancellationToken()
+    token.cancel()
+    try:
+        await py_cert_store.find_certificates_async([pfx], password="pass1234", cancel=token)
+        token_raised = False
+    except py_cert_store.CertSearchCancelled:
+        token_raised = True
+
+    beat.cancel()
+    py_cert_store.set_async_executor(None)
+
+    return {
+        "Search returned the certificate": len(found) == 1 and found[0]["FriendlyName"] == "Async Test",
+        "Search waited for every export": search_time >= EXPORT_DELAY * ENTRIES,
+        "Event loop kept running": longest_pause < EXPORT_DELAY,
+        "Task was cancelled": cancelled,
+        "Cancelled search stopped early": after_cancel < search_time / 2,
+        "Cancelled token raised": token_raised,
+    }
+
+
+pass_fail = asyncio.run(main())
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
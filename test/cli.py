@@ -0,0 +1,53 @@
+"""Runs the py-cert-store command line tool through `python -m py_cert_store` against a directory of certificates."""
+
+import csv
+import io
+import json
+import subprocess
+import sys
+import tempfile
+from pathlib import Path
+
+import py_cert_store
+
+
+def cli(*args):
+    result = subprocess.run([sys.executable, "-m", "py_cert_store", *args], capture_output=True, text=True)
+    return result.returncode, result.stdout, result.stderr
+
+
+with tempfile.TemporaryDirectory() as directory:
+    directory = Path(directory)
+    server = py_cert_store.generate_certificate(
+        "CN=localhost", key_type="p256", alt_names=["DNS:localhost"], key_usage=["digital_signature"],
+    )
+    (directory / "server.pem").write_text(server["Certificate"] + server["PrivateKey"])
+    other = py_cert_store.generate_certificate("CN=other.example.com", key_type="p256")
+    (directory / "other.crt").write_text(other["Certificate"])
+
+    list_code, list_out, _ = cli("--format", "json", "list", "--path", str(directory))
+    listed = json.loads(list_out)
+    thumbprint = next(row["Thumbprint"] for row in listed if row["Subject"] == "CN=localhost")
+
+    csv_code, csv_out, _ = cli("--format", "csv", "find", "--path", str(directory), "--host", "localhost")
+    found = list(csv.DictReader(io.StringIO(csv_out)))
+
+    out_file = directory / "export.p12"
+    export_code, _, _ = cli("export", "--path", str(directory), thumbprint, "--out", str(out_file))
+    exported = py_cert_store.find_certificates_in_path(out_file) if out_file.exists() else []
+
+    chain_code, chain_out, _ = cli("verify-chain", "--path", str(directory), thumbprint)
+    missing_code, _, missing_err = cli("show", "--path", str(directory), "00")
+    usage_code, _, usage_err = cli("list", "--bogus")
+
+pass_fail = {
+    "List as JSON": list_code == 0 and len(listed) == 2,
+    "Find as CSV": csv_code == 0 and [row["Thumbprint"] for row in found] == [thumbprint] and found[0]["Selected"] == "yes",
+    "Export PKCS#12": export_code == 0 and [cert["Name"] for cert in exported] == ["CN=localhost"],
+    "Self-signed chain is trusted from the directory": chain_code == 0 and " ok" in chain_out,
+    "Unknown thumbprint fails": missing_code == 1 and "No certificate with thumbprint" in missing_err,
+    "Invalid arguments": usage_code == 2 and "python -m py_cert_store" in usage_err,
+}
+
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
@@ -0,0 +1,65 @@
+"""Checks that the store searches release the GIL while they enumerate and export certificates.
+
+A portable store is searched from several threads while a heartbeat thread counts how often it gets to run. If the
+searches held the GIL, the heartbeat would stall for as long as the searches take.
+"""
+
+import threading
+import time
+
+import py_cert_store
+
+THREADS = 4
+SEARCHES = 3
+
+pfx = py_cert_store.generate_certificate(
+    "CN=concurrency.example.com", key_type="p256", friendly_name="Concurrency Test", password="pass1234"
+)["cert"]
+
+
+def search(results):
+    for _ in range(SEARCHES):
+        found = py_cert_store.find_certificates([pfx, pfx], password="pass1234", selection="first")
+        results.append(found[0]["Name"])
+
+
+def heartbeat(stop, ticks):
+    while not stop.is_set():
+        ticks.append(time.perf_counter())
+        time.sleep(0.001)
+
+
+# Time one search on its own, a search holding the GIL would stall the heartbeat for about this long.
+single_started = time.perf_counter()
+search([])
+single_search = (time.perf_counter() - single_started) / SEARCHES
+
+results = []
+ticks = []
+stop = threading.Event()
+
+beat = threading.Thread(target=heartbeat, args=(stop, ticks))
+beat.start()
+
+started = time.perf_counter()
+workers = [threading.Thread(target=search, args=(results,)) for _ in range(THREADS)]
+for worker in workers:
+    worker.start()
+for worker in workers:
+    worker.join()
+elapsed = time.perf_counter() - started
+
+stop.set()
+beat.join()
+
+longest_pause = max((b - a for a, b in zip(ticks, ticks[1:])), default=elapsed)
+
+pass_fail = {
+    "All searches finished": len(results) == THREADS * SEARCHES,
+    "Same certificate every time": len(set(results)) == 1,
+    "Heartbeat kept running": longest_pause < single_search / 2,
+}
+
+print(f"{THREADS * SEARCHES} searches in {elapsed:.2f}s, {len(ticks)} heartbeats, longest pause {longest_pause * 1000:.1f}ms")
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
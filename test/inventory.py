@@ -0,0 +1,51 @@
+"""Takes snapshots of a PEM directory and diffs them, along with the fixture snapshots in test/snapshots."""
+
+import json
+import pathlib
+import sys
+import tempfile
+
+import py_cert_store
+
+fixtures = pathlib.Path(__file__).parent / "snapshots"
+previous = json.loads((fixtures / "previous.json").read_text())
+current = (fixtures / "current.json").read_text()
+diff = py_cert_store.diff_snapshots(previous, current)
+short_window = py_cert_store.diff_snapshots(previous, current, expiring_days=1)
+
+kept = py_cert_store.generate_certificate("CN=kept.example.com", key_type="p256")
+old = py_cert_store.generate_certificate("CN=renewed.example.com", key_type="p256", valid_days=10)
+new = py_cert_store.generate_certificate("CN=renewed.example.com", key_type="p256", valid_days=365)
+
+with tempfile.TemporaryDirectory() as directory:
+    directory = pathlib.Path(directory)
+    (directory / "kept.pem").write_text(kept["Certificate"] + kept["PrivateKey"])
+    (directory / "renewed.pem").write_text(old["Certificate"])
+    before = py_cert_store.snapshot_stores(paths=[str(directory)], host="build01")
+    (directory / "renewed.pem").write_text(new["Certificate"])
+    after = py_cert_store.snapshot_stores(paths=[str(directory)])
+    local_diff = py_cert_store.diff_snapshots(json.dumps(before), after)
+
+try:
+    py_cert_store.snapshot_stores(stores=[("CurrentUser", "My")])
+    windows_stores = sys.platform == "win32"
+except OSError:
+    windows_stores = sys.platform != "win32"
+
+common_names = lambda certificates: [cert["Subject"]["CommonName"] for cert in certificates]
+
+pass_fail = {
+    "Added": common_names(diff["Added"]) == ["new.example.com"],
+    "Removed only from stores read twice": common_names(diff["Removed"]) == ["old.example.com"],
+    "Renewed": [(r["Previous"]["SerialNumber"] != r["Current"]["SerialNumber"], r["Current"]["Subject"]["CommonName"]) for r in diff["Renewed"]] == [(True, "web.example.com")],
+    "Expiring": common_names(diff["Expiring"]) == ["api.example.com"] and short_window["Expiring"] == [],
+    "Snapshot": before["Host"] == "build01" and before["Stores"] == [{"Kind": "file", "Location": None, "Store": None, "Path": str(directory)}],
+    "No key material": all("Pkcs12" not in cert for cert in before["Certificates"]),
+    "Private keys": sorted(cert["Key"]["HasPrivateKey"] for cert in before["Certificates"]) == [False, True],
+    "Snapshot diff": local_diff["Added"] == [] and local_diff["Removed"] == [] and len(local_diff["Renewed"]) == 1,
+    "Renewal is not expiring": local_diff["Expiring"] == [],
+    "Windows stores": windows_stores,
+}
+
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
@@ -0,0 +1,99 @@
+"""Reads the keytool generated keystores in test/jks and writes keystores that keytool can read back.
+
+The keystores hold "server" (an RSA key with a chain of two), "root" (the CA, trusted) and "ec client" (a P-256 key),
+protected with "changeit".
+"""
+
+import os
+import pathlib
+import shutil
+import subprocess
+import tempfile
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.serialization import pkcs12
+
+import py_cert_store
+
+FIXTURES = pathlib.Path(__file__).parent / "jks"
+
+
+def read(name, **kwargs):
+    return {entry["Alias"]: entry for entry in py_cert_store.read_java_keystore((FIXTURES / name).read_bytes(), **kwargs)}
+
+
+def keytool_aliases(data, password, store_type):
+    """The aliases and entry types `keytool -list` shows, or None when keytool is not installed."""
+    if shutil.which("keytool") is None:
+        return None
+    with tempfile.TemporaryDirectory() as directory:
+        path = os.path.join(directory, "keystore")
+        with open(path, "wb") as file:
+            file.write(data)
+        output = subprocess.run(
+            ["keytool", "-list", "-keystore", path, "-storepass", password, "-storetype", store_type],
+            check=True, capture_output=True, text=True,
+        ).stdout
+    return {line.split(",")[0]: line.split(",")[-2].strip() for line in output.splitlines() if line.count(",") >= 2}
+
+
+jks = read("keystore.jks", password="changeit")
+jceks = read("keystore.jceks", password="changeit")
+listed = read("keystore.jks")
+
+try:
+    read("keystore.jks", password="wrong")
+    wrong_password = False
+except PermissionError:
+    wrong_password = True
+
+server = jks["server"]
+leaf = x509.load_der_x509_certificate(server["Certificate"])
+ca = x509.load_der_x509_certificate(jks["root"]["Certificate"])
+
+# A truststore from the CA, and a keystore from the certificates with a key found by a search
+truststore = py_cert_store.write_java_keystore([jks["root"]["Certificate"]], "truststore pass")
+found = py_cert_store.find_certificates([(FIXTURES / "keystore.jceks").read_bytes()], password="changeit")
+keystore = py_cert_store.write_java_keystore(found, "keystore pass", format="jceks", key_password="key pass")
+written = {entry["Alias"]: entry for entry in py_cert_store.read_java_keystore(keystore, "keystore pass", key_password="key pass")}
+trusted_only = py_cert_store.write_java_keystore(found, "changeit", include_private_keys=False)
+duplicates = py_cert_store.read_java_keystore(py_cert_store.write_java_keystore([server["Certificate"], server["Certificate"]], "changeit"))
+
+# A PKCS#12 file with the CA stored before the leaf
+ca_first = pkcs12.serialize_key_and_certificates(None, None, None, [ca, leaf], serialization.NoEncryption())
+ca_first = py_cert_store.read_java_keystore(py_cert_store.write_java_keystore([ca_first], "changeit", include_private_keys=False), "changeit")
+
+with py_cert_store.iter_certificates([server["cert"]]) as certificates:
+    from_iterator = py_cert_store.read_java_keystore(py_cert_store.write_java_keystore(list(certificates), "changeit"), "changeit")
+
+keytool = {
+    "truststore": keytool_aliases(truststore, "truststore pass", "JKS"),
+    "keystore": keytool_aliases(keystore, "keystore pass", "JCEKS"),
+}
+
+pass_fail = {
+    "Aliases": set(jks) == {"server", "root", "ec client"} and set(jceks) == set(jks),
+    "Entry types": server["EntryType"] == "PrivateKeyEntry" and jks["root"]["EntryType"] == "trustedCertEntry",
+    "Chain": len(server["Chain"]) == 2 and leaf.issuer == ca.subject and server["Chain"][1] == jks["root"]["Certificate"],
+    "Private keys": server["HasPrivateKey"] and jceks["ec client"]["HasPrivateKey"] and "cert" not in jks["root"],
+    "Same certificates": all(jks[alias]["Chain"] == jceks[alias]["Chain"] for alias in jks),
+    "Without password": not any(entry["HasPrivateKey"] for entry in listed.values()) and set(listed) == set(jks),
+    "Wrong password": wrong_password,
+    "Search": sorted(certificate["FriendlyName"] for certificate in found) == ["ec client", "server"],
+    "Written key entries": set(written) == {"server", "ec client"} and all(entry["HasPrivateKey"] for entry in written.values()),
+    "Trusted only": all(entry["EntryType"] == "trustedCertEntry" for entry in py_cert_store.read_java_keystore(trusted_only, "changeit")),
+    "Unique aliases": len(duplicates) == 2,
+    "CA first": [entry["Certificate"] for entry in ca_first] == [server["Certificate"]],
+    "StoreCertificate": [(entry["Alias"], entry["EntryType"]) for entry in from_iterator] == [
+        ("server", "PrivateKeyEntry"), (ca.fingerprint(hashes.SHA1()).hex(), "trustedCertEntry"),
+    ],
+    "keytool": keytool["truststore"] is None or (
+        len(keytool["truststore"]) == 1 and list(keytool["truststore"].values()) == ["trustedCertEntry"]
+        and keytool["keystore"] == {"server": "PrivateKeyEntry", "ec client": "PrivateKeyEntry"}
+    ),
+}
+
+print("keytool: ", keytool)
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
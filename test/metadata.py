@@ -0,0 +1,42 @@
+"""Serializes certificate metadata from search results and store iterators, checked against the bundled schema."""
+
+import base64
+import json
+
+import py_cert_store
+
+generated = py_cert_store.generate_certificate("CN=inventory.example.com", key_type="p256", alt_names=["DNS:inventory.example.com"])
+results = py_cert_store.find_certificates([generated["cert"]])
+
+schema = json.loads(py_cert_store.metadata_schema())
+inventory = json.loads(py_cert_store.metadata_to_json(results, indent=2))
+single = py_cert_store.certificate_metadata(results[0])
+with_key = json.loads(py_cert_store.metadata_to_json(results[0], include_private_key=True))
+from_pem = py_cert_store.certificate_metadata(generated["Certificate"], include_certificate=True)
+
+with py_cert_store.iter_certificates([generated["cert"]]) as certificates:
+    stored = next(certificates)
+    stored_json = json.loads(stored.to_json())
+    stored_yaml = stored.to_yaml()
+    stored_key = stored.key_metadata()
+
+required = schema["$defs"]["CertificateMetadata"]["required"]
+
+pass_fail = {
+    "Inventory": inventory["SchemaVersion"] == 1 and len(inventory["Certificates"]) == 1,
+    "Required fields": all(field in single for field in required),
+    "No key material by default": "Pkcs12" not in single and "Certificate" not in single,
+    "Search result location": single["Store"] == {"Kind": "memory", "Location": None, "Store": None, "Path": None},
+    "Key material when asked": base64.b64decode(with_key["Pkcs12"]) == results[0]["cert"],
+    "Names and extensions": single["Subject"]["CommonName"] == "inventory.example.com" and single["AltNames"] == ["DNS:inventory.example.com"],
+    "Private key": single["Key"]["HasPrivateKey"] and single["Key"]["Curve"] == "P-256",
+    "Certificate PEM": from_pem["Certificate"].startswith("-----BEGIN CERTIFICATE-----") and not from_pem["Key"]["HasPrivateKey"],
+    "StoreCertificate.to_json": stored_json["Thumbprint"] == single["Thumbprint"] and "Pkcs12" not in stored_json,
+    "StoreCertificate.to_yaml": "Thumbprint: " + single["Thumbprint"] in stored_yaml,
+    "Key metadata": stored_key["Algorithm"] == "ECDSA" and stored_key["Size"] == 256 and stored_key["HasPrivateKey"]
+        and stored_key["Exportable"] is True and stored_key["HardwareBacked"] is False and stored_key["UiProtected"] is False,
+    "Search result key": results[0]["Key"] == stored_key and stored_json["Key"] == stored_key,
+}
+
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
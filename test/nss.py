@@ -0,0 +1,45 @@
+"""Reads the NSS database in test/nss, written by NSS itself with the password "pass1234"."""
+
+import json
+import pathlib
+import sys
+
+import py_cert_store
+
+database = str(pathlib.Path(__file__).parent / "nss")
+schema = json.loads(py_cert_store.metadata_schema())
+
+certificates = {cert["FriendlyName"]: cert for cert in py_cert_store.read_nss_database(database, password="pass1234")}
+locked = py_cert_store.read_nss_database(f"sql:{database}")
+found = py_cert_store.find_nss_certificates(database, password="pass1234", host="ec.example.com")
+snapshot = py_cert_store.snapshot_stores(nss_databases=[database], password="pass1234")
+
+try:
+    py_cert_store.read_nss_database(database, password="wrong")
+    wrong_password = False
+except PermissionError:
+    wrong_password = True
+
+try:
+    py_cert_store.find_nss_certificates(database)
+    without_keys = False
+except py_cert_store.CertNotExportable:
+    without_keys = True
+
+pass_fail = {
+    "Certificates": sorted(certificates) == ["NSS Test CA", "blocked peer", "ec client", "rsa client"],
+    "Trust flags": [certificates[name]["NssTrust"] for name in ("NSS Test CA", "blocked peer", "ec client")] == ["CT,C,C", "p,p,p", "u,u,u"],
+    "Private keys": certificates["rsa client"]["Key"] == {"Algorithm": "RSA", "Size": 2048, "Curve": None, "HasPrivateKey": True, "Provider": "NSS Certificate DB", "HardwareBacked": False, "Exportable": True, "UiProtected": False},
+    "Store location": certificates["ec client"]["Store"] == {"Kind": "nss", "Location": None, "Store": None, "Path": database},
+    "Metadata fields": all(field in schema["$defs"]["CertificateMetadata"]["properties"] for field in certificates["ec client"]),
+    "Keys need the password": len(locked) == 4 and not any(cert["Key"]["HasPrivateKey"] for cert in locked),
+    "Wrong password": wrong_password,
+    "Search": [(cert["FriendlyName"], cert["NssTrust"], cert["KeyProvider"]) for cert in found] == [("ec client", "u,u,u", "NSS Certificate DB")],
+    "Search result key": py_cert_store.certificate_metadata(found[0], include_private_key=True)["Key"]["HasPrivateKey"],
+    "Search without keys": without_keys,
+    "Snapshot": len(snapshot["Certificates"]) == 4 and snapshot["Stores"][0]["Kind"] == "nss",
+    "Default databases": isinstance(py_cert_store.list_nss_databases(), list),
+}
+
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
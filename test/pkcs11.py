@@ -0,0 +1,131 @@
+"""Reads certificates from a PKCS#11 token and signs through it.
+
+With SoftHSMv2 (softhsm2-util and OpenSC's pkcs11-tool) installed, a token is created and filled with an RSA and an
+ECDSA certificate. Otherwise the NSS softoken serves a copy of the database in test/nss as a token.
+"""
+
+import os
+import pathlib
+import shutil
+import subprocess
+import sys
+import tempfile
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.serialization import Encoding
+from cryptography.hazmat.primitives.asymmetric import ec, padding, rsa
+
+import py_cert_store
+
+SOFTHSM_MODULES = ["/usr/lib/softhsm/libsofthsm2.so", "/usr/lib/x86_64-linux-gnu/softhsm/libsofthsm2.so", "/usr/lib64/pkcs11/libsofthsm2.so", "/usr/local/lib/softhsm/libsofthsm2.so"]
+SOFTOKEN_MODULES = ["/usr/lib/x86_64-linux-gnu/libsoftokn3.so", "/usr/lib/aarch64-linux-gnu/libsoftokn3.so", "/usr/lib64/libsoftokn3.so", "/usr/lib/libsoftokn3.so"]
+
+directory = tempfile.mkdtemp()
+
+
+def first_file(paths):
+    return next((path for path in paths if os.path.isfile(path)), None)
+
+
+def softhsm_token():
+    """Creates a SoftHSMv2 token holding "rsa client" and "ec client", and returns how to open it."""
+    module = first_file(SOFTHSM_MODULES)
+    if module is None or shutil.which("softhsm2-util") is None or shutil.which("pkcs11-tool") is None:
+        return None
+
+    os.makedirs(os.path.join(directory, "tokens"))
+    config = os.path.join(directory, "softhsm2.conf")
+    with open(config, "w") as file:
+        file.write(f"directories.tokendir = {directory}/tokens\nobjectstore.backend = file\n")
+    os.environ["SOFTHSM2_CONF"] = config
+
+    subprocess.run(["softhsm2-util", "--init-token", "--free", "--label", "py_cert_store", "--pin", "1234", "--so-pin", "5678"], check=True, capture_output=True)
+    for index, (label, key_type) in enumerate([("rsa client", "rsa2048"), ("ec client", "p256")], start=1):
+        generated = py_cert_store.generate_certificate(f"CN={label}", key_type=key_type)
+        key = os.path.join(directory, f"{index}.key")
+        certificate = os.path.join(directory, f"{index}.der")
+        with open(key, "w") as file:
+            file.write(generated["PrivateKey"])
+        with open(certificate, "wb") as file:
+            file.write(x509.load_pem_x509_certificate(generated["Certificate"].encode()).public_bytes(Encoding.DER))
+        subprocess.run(["softhsm2-util", "--import", key, "--token", "py_cert_store", "--label", label, "--id", f"{index:02x}", "--pin", "1234"], check=True, capture_output=True)
+        subprocess.run([
+            "pkcs11-tool", "--module", module, "--login", "--pin", "1234", "--token-label", "py_cert_store",
+            "--write-object", certificate, "--type", "cert", "--id", f"{index:02x}", "--label", label,
+        ], check=True, capture_output=True)
+
+    return {"module": module, "pin": "1234", "token_label": "py_cert_store", "parameters": None, "certificates": 2}
+
+
+def softoken_token():
+    """Serves a copy of test/nss through the NSS softoken, with its PIN "pass1234"."""
+    module = first_file(SOFTOKEN_MODULES)
+    if module is None:
+        return None
+
+    for name in ("cert9.db", "key4.db"):
+        shutil.copy(pathlib.Path(__file__).parent / "nss" / name, directory)
+    parameters = f"configdir='sql:{directory}' certPrefix='' keyPrefix='' secmod='secmod.db' flags=readOnly"
+    return {"module": module, "pin": "pass1234", "token_label": "NSS Certificate DB", "parameters": parameters, "certificates": 4}
+
+
+def verify(certificate, signature, data):
+    public_key = x509.load_der_x509_certificate(certificate).public_key()
+    try:
+        if isinstance(public_key, rsa.RSAPublicKey):
+            public_key.verify(signature, data, padding.PKCS1v15(), hashes.SHA256())
+        else:
+            public_key.verify(signature, data, ec.ECDSA(hashes.SHA256()))
+        return True
+    except Exception:
+        return False
+
+
+token = softhsm_token() or softoken_token()
+if token is None:
+    print("Neither SoftHSMv2 nor the NSS softoken is installed, skipping the PKCS#11 tests")
+    sys.exit(0)
+print("Module: ", token["module"])
+
+tokens = py_cert_store.list_pkcs11_tokens(token["module"], token["parameters"])
+
+with py_cert_store.open_pkcs11_token(token["module"], pin=token["pin"], token_label=token["token_label"], parameters=token["parameters"]) as session:
+    certificates = {certificate.friendly_name: certificate for certificate in session.certificates()}
+    metadata = session.metadata()
+    signatures = {name: verify(certificates[name].certificate, session.sign(certificates[name].thumbprint, b"signed on the token"), b"signed on the token") for name in ("rsa client", "ec client")}
+
+    try:
+        certificates["rsa client"].export_pfx()
+        exportable = True
+    except py_cert_store.CertNotExportable:
+        exportable = False
+
+    try:
+        session.sign("00" * 20, b"data")
+        unknown_thumbprint = False
+    except py_cert_store.CertNotFound:
+        unknown_thumbprint = True
+
+try:
+    py_cert_store.open_pkcs11_token(token["module"], pin="wrong pin", token_label=token["token_label"], parameters=token["parameters"])
+    wrong_pin = False
+except PermissionError:
+    wrong_pin = True
+
+pass_fail = {
+    "Tokens": token["token_label"] in [entry["Label"] for entry in tokens],
+    "Certificates": len(certificates) == token["certificates"],
+    "Paired keys": certificates["rsa client"].has_private_key and certificates["ec client"].has_private_key,
+    "Key provider": certificates["ec client"].key_provider == token["token_label"],
+    "Not exportable": not exportable,
+    "Signatures": all(signatures.values()),
+    "Unknown thumbprint": unknown_thumbprint,
+    "Store location": metadata[0]["Store"]["Kind"] == "pkcs11" and metadata[0]["Store"]["Store"] == token["token_label"],
+    "Closed": session.closed,
+    "Wrong PIN": wrong_pin,
+}
+
+shutil.rmtree(directory, ignore_errors=True)
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
@@ -0,0 +1,50 @@
+"""Filters generated certificates by the purpose their Extended Key Usage allows.
+
+The purposes a Windows store assigns to a certificate are covered by the Rust unit tests of certificate::purpose.
+"""
+
+import py_cert_store
+
+
+def generate(name, extended_key_usage):
+    return py_cert_store.generate_certificate(f"CN={name}", key_type="p256", extended_key_usage=extended_key_usage)
+
+
+certificates = {
+    "client": generate("client", ["client_auth"]),
+    "signing": generate("signing", ["code_signing", "document_signing"]),
+    "mail": generate("mail", ["email_protection", "client_auth"]),
+    "logon": generate("logon", ["smart_card_logon", "client_auth"]),
+    "any": generate("any", ["2.5.29.37.0"]),
+    "unrestricted": generate("unrestricted", None),
+}
+pfx = [certificate["cert"] for certificate in certificates.values()]
+
+
+def found(purpose):
+    return sorted(result["Name"].removeprefix("CN=") for result in py_cert_store.find_certificates(pfx, purpose=purpose))
+
+
+def allows(name, purpose):
+    return py_cert_store.certificate_allows_purpose(certificates[name]["Certificate"].encode(), purpose)
+
+
+try:
+    py_cert_store.find_certificates(pfx, purpose="teleportation")
+    unknown_purpose = False
+except ValueError:
+    unknown_purpose = True
+
+pass_fail = {
+    "No purpose": len(found(None)) == len(certificates),
+    "Client auth": found("client_auth") == ["any", "client", "logon", "mail", "unrestricted"],
+    "Code signing": found("code_signing") == ["any", "signing", "unrestricted"],
+    "S/MIME alias": found("smime") == ["any", "mail", "unrestricted"],
+    "Smart card logon": found("smart_card_logon") == ["any", "logon", "unrestricted"],
+    "Document signing OID": found("1.3.6.1.4.1.311.10.3.12") == ["any", "signing", "unrestricted"],
+    "Allows purpose": allows("client", "client-auth") and not allows("client", "server_auth") and allows("unrestricted", "ocsp_signing"),
+    "Unknown purpose": unknown_purpose,
+}
+
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
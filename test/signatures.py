@@ -0,0 +1,82 @@
+"""Verifies the OpenSSL signatures in test/signatures with the public keys of certificates.
+
+ec_pss.crt is issued by rsa.crt with an RSA-PSS signature, see test/signatures/README.md.
+"""
+
+import os
+import shutil
+import subprocess
+import tempfile
+import pathlib
+
+import py_cert_store
+
+FIXTURES = pathlib.Path(__file__).parent / "signatures"
+
+
+def fixture(name):
+    return (FIXTURES / name).read_bytes()
+
+
+def openssl_signature(key, data, *options):
+    """A signature made by `openssl dgst` now, or None when openssl is not installed."""
+    if shutil.which("openssl") is None:
+        return None
+    with tempfile.TemporaryDirectory() as directory:
+        path = os.path.join(directory, "data")
+        with open(path, "wb") as file:
+            file.write(data)
+        return subprocess.run(["openssl", "dgst", *options, "-sign", str(FIXTURES / key), path], check=True, capture_output=True).stdout
+
+
+message = fixture("message.txt")
+rsa, ec, ed25519 = fixture("rsa.crt"), fixture("ec_pss.crt"), fixture("ed25519.crt")
+
+
+def verifies(certificate, signature, **options):
+    return py_cert_store.certificate_verifies_signature(certificate, message, fixture(signature), **options)
+
+
+try:
+    verifies(ec, "ecdsa_sha384.sig", hash_alg="sha384", padding="pss")
+    padding_for_ec = True
+except ValueError:
+    padding_for_ec = False
+
+try:
+    verifies(rsa, "rsa_pkcs1_sha256.sig", hash_alg="md5")
+    unknown_hash = True
+except ValueError:
+    unknown_hash = False
+
+with py_cert_store.iter_certificates([rsa, ec]) as certificates:
+    leaf, ca = sorted(certificates, key=lambda certificate: certificate.subject)
+    stored = {
+        "verify": ca.verify(message, fixture("rsa_pss_sha256.sig"), padding="pss")
+            and leaf.verify(message, fixture("ecdsa_sha384.sig"), hash_alg="sha384")
+            and not leaf.verify(b"tampered", fixture("ecdsa_sha384.sig"), hash_alg="sha384"),
+        "is_signed_by": leaf.is_signed_by(ca) and leaf.is_signed_by(rsa) and not ca.is_signed_by(fixture("../certs/ca_certificate.crt")),
+    }
+
+live_salt = openssl_signature("rsa.key", b"live data", "-sha384", "-sigopt", "rsa_padding_mode:pss", "-sigopt", "rsa_pss_saltlen:digest")
+
+pass_fail = {
+    "RSA PKCS#1 v1.5": verifies(rsa, "rsa_pkcs1_sha256.sig") and verifies(rsa, "rsa_pkcs1_sha256.sig", padding="pkcs1v15"),
+    "RSA-PSS": verifies(rsa, "rsa_pss_sha256.sig", padding="pss") and verifies(rsa, "rsa_pss_max_sha512.sig", hash_alg="sha512", padding="pss")
+        and verifies(rsa, "rsa_pss_salt20_sha256.sig", padding="pss"),
+    "ECDSA": verifies(ec, "ecdsa_sha384.sig", hash_alg="sha384"),
+    "Ed25519": verifies(ed25519, "ed25519.sig"),
+    "Wrong padding": not verifies(rsa, "rsa_pss_sha256.sig"),
+    "Wrong hash": not verifies(ec, "ecdsa_sha384.sig"),
+    "Wrong key": not verifies(ed25519, "rsa_pkcs1_sha256.sig"),
+    "Padding for ECDSA": not padding_for_ec,
+    "Unknown hash": not unknown_hash,
+    "Issued by": py_cert_store.certificate_issued_by(ec, rsa) and py_cert_store.certificate_issued_by(ed25519, ed25519)
+        and not py_cert_store.certificate_issued_by(rsa, fixture("../certs/ca_certificate.crt")),
+    "StoreCertificate.verify": stored["verify"],
+    "StoreCertificate.is_signed_by": stored["is_signed_by"],
+    "openssl": live_salt is None or py_cert_store.certificate_verifies_signature(rsa, b"live data", live_salt, hash_alg="sha384", padding="pss"),
+}
+
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))
@@ -0,0 +1,59 @@
+"""Checks the store watch API against a directory of certificates, so it runs without a Windows certificate store."""
+
+import tempfile
+import threading
+import time
+from pathlib import Path
+
+import py_cert_store
+
+
+def certificate(subject):
+    return py_cert_store.generate_certificate(subject, key_type="p256")
+
+
+with tempfile.TemporaryDirectory() as directory:
+    directory = Path(directory)
+    first = certificate("CN=first.example.com")
+    (directory / "first.crt").write_text(first["Certificate"])
+
+    # Iterator: each change of the directory is reported by thumbprint.
+    with py_cert_store.watch_path(directory) as watch:
+        initial = watch.thumbprints
+        events = iter(watch)
+
+        second = certificate("CN=second.example.com")
+        (directory / "second.pem").write_text(second["Certificate"] + second["PrivateKey"])
+        added = next(events)
+
+        (directory / "first.crt").unlink()
+        removed = next(events)
+
+        nothing = watch.poll(timeout=0.2)
+
+    # Callback: events are delivered on a background thread until the watch is closed.
+    received = []
+    delivered = threading.Event()
+    watch = py_cert_store.watch_path(directory)
+
+    def callback(event):
+        received.append(event)
+        delivered.set()
+
+    thread = py_cert_store.on_store_change(watch, callback)
+    (directory / "second.pem").write_text(second["Certificate"])
+    delivered.wait(timeout=5)
+    watch.close()
+    thread.join(timeout=5)
+
+pass_fail = {
+    "Initial snapshot": len(initial) == 1,
+    "Added event": added.kind == "added" and added.subject == "CN=second.example.com" and added.has_private_key,
+    "Removed event": removed.kind == "removed" and removed.subject == "CN=first.example.com",
+    "No event without a change": nothing == [],
+    "Callback received modified event": [event.kind for event in received] == ["modified"] and not received[0].has_private_key,
+    "Callback thread stopped": not thread.is_alive(),
+}
+
+print("Pass/Fail: ", pass_fail)
+print("All tests pass: ", all(pass_fail.values()))